@@ -0,0 +1,268 @@
+use crate::btc::get_bt_containers;
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::c_void;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result, anyhow};
+use windows::{
+    Win32::{
+        Foundation::PROPERTYKEY,
+        Media::Audio::{
+            AudioSessionStateActive, DEVICE_STATE_ACTIVE, EDataFlow, ERole, IAudioSessionManager2,
+            IMMDevice, IMMDeviceEnumerator, MMDeviceEnumerator, eCapture, eCommunications,
+            eConsole, eMultimedia, eRender,
+        },
+        System::Com::{
+            CLSCTX_ALL, COINIT_MULTITHREADED, CoCreateInstance, CoInitializeEx, CoTaskMemFree,
+            STGM_READ,
+            StructuredStorage::{PropVariantToGUID, PropVariantToUInt32},
+        },
+        UI::Shell::PropertiesSystem::IPropertyStore,
+    },
+    core::{GUID, HRESULT, IUnknown, IUnknown_Vtbl, Interface, PCWSTR},
+};
+
+/// 设备所属容器的 ID（`PKEY_Device_ContainerId`），蓝牙音频端点与其蓝牙设备相同
+#[allow(non_upper_case_globals)]
+const PKEY_Device_ContainerId: PROPERTYKEY = PROPERTYKEY {
+    fmtid: GUID::from_u128(0x8C7ED206_3F8A_4827_B3AB_AE9E1FAEFC6C),
+    pid: 2,
+};
+
+/// 端点的外形（`PKEY_AudioEndpoint_FormFactor`），取值为 `EndpointFormFactor`
+#[allow(non_upper_case_globals)]
+const PKEY_AudioEndpoint_FormFactor: PROPERTYKEY = PROPERTYKEY {
+    fmtid: GUID::from_u128(0x1DA5D803_D492_4EDD_8C23_E0C0FFEE7F0E),
+    pid: 0,
+};
+
+/// `EndpointFormFactor::Headset`，蓝牙耳机的免提（HFP）播放端点
+const HEADSET_FORM_FACTOR: u32 = 5;
+
+/// 系统声音设置使用的 `PolicyConfigClient`，没有公开文档，但自 Windows 7 起保持不变
+const CLSID_POLICY_CONFIG_CLIENT: GUID = GUID::from_u128(0x870AF99C_171D_4F9E_AF0D_E63DF40C2BC9);
+
+const IID_POLICY_CONFIG: GUID = GUID::from_u128(0xF8679F50_850A_41CF_9C72_430F290290C8);
+
+/// `IPolicyConfig` 的虚表，只调用 `SetDefaultEndpoint`，之前的 10 个方法仅用于占位
+#[repr(C)]
+struct PolicyConfigVtbl {
+    base: IUnknown_Vtbl,
+    unused: [usize; 10],
+    set_default_endpoint:
+        unsafe extern "system" fn(this: *mut c_void, device_id: PCWSTR, role: ERole) -> HRESULT,
+}
+
+/// 上次读取时各播放端点的 Container ID 及当时对应的蓝牙设备地址，端点变化时才重新枚举蓝牙设备
+static CONTAINERS: Mutex<Option<(HashSet<u128>, HashMap<u128, u64>)>> = Mutex::new(None);
+
+/// 蓝牙音频设备当前使用的配置文件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioProfile {
+    /// 立体声（A2DP），音质较好
+    Stereo,
+    /// 免提（HFP），通话时使用，音质较差且更耗电
+    HandsFree,
+}
+
+/// 蓝牙设备的播放端点及当前的默认播放设备
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AudioEndpoints {
+    /// 蓝牙设备地址对应的播放端点 ID
+    pub endpoints: HashMap<u64, String>,
+    /// 默认播放设备对应的蓝牙设备地址，默认设备不是蓝牙设备时为 `None`
+    pub default: Option<u64>,
+    /// 蓝牙音频设备当前的配置文件
+    pub profiles: HashMap<u64, AudioProfile>,
+}
+
+/// 一个播放或录音端点
+struct AudioEndpoint {
+    id: String,
+    container_id: u128,
+    render: bool,
+    /// 免提（HFP）端点
+    hands_free: bool,
+    device: IMMDevice,
+}
+
+/// 通过 `IMMDeviceEnumerator` 读取已启用的播放及录音端点，按 Container ID 对应到蓝牙设备
+pub fn get_audio_endpoints() -> Result<AudioEndpoints> {
+    let enumerator = device_enumerator()?;
+
+    let mut audio_endpoints = audio_endpoints(&enumerator, eRender)?;
+    audio_endpoints.extend(audio_endpoints(&enumerator, eCapture)?);
+
+    // 没有任何播放设备时没有默认端点
+    let default_id = unsafe { enumerator.GetDefaultAudioEndpoint(eRender, eConsole) }
+        .ok()
+        .map(|device| endpoint_id(&device))
+        .transpose()?;
+
+    let containers = bt_containers(
+        audio_endpoints
+            .iter()
+            .map(|endpoint| endpoint.container_id)
+            .collect(),
+    )?;
+    let mut devices: HashMap<u64, Vec<AudioEndpoint>> = HashMap::new();
+    for endpoint in audio_endpoints {
+        if let Some(&address) = containers.get(&endpoint.container_id) {
+            devices.entry(address).or_default().push(endpoint);
+        }
+    }
+
+    let mut endpoints = HashMap::new();
+    let mut profiles = HashMap::new();
+    let mut default = None;
+    for (address, device_endpoints) in devices {
+        // 同一设备有立体声及免提两个播放端点时，优先使用默认端点，其次为立体声端点
+        let render_endpoint = device_endpoints
+            .iter()
+            .filter(|endpoint| endpoint.render)
+            .max_by_key(|endpoint| {
+                (
+                    default_id.as_ref() == Some(&endpoint.id),
+                    !endpoint.hands_free,
+                )
+            });
+        if let Some(endpoint) = render_endpoint {
+            if default_id.as_ref() == Some(&endpoint.id) {
+                default = Some(address);
+            }
+            endpoints.insert(address, endpoint.id.clone());
+        }
+
+        profiles.insert(address, audio_profile(&device_endpoints));
+    }
+
+    Ok(AudioEndpoints {
+        endpoints,
+        default,
+        profiles,
+    })
+}
+
+/// 免提端点正在使用（如通话或语音输入）时为免提模式，否则有立体声端点时为立体声模式
+fn audio_profile(endpoints: &[AudioEndpoint]) -> AudioProfile {
+    let hands_free_active = endpoints.iter().any(|endpoint| {
+        endpoint.hands_free && has_active_session(&endpoint.device).unwrap_or(false)
+    });
+    let has_stereo = endpoints.iter().any(|endpoint| !endpoint.hands_free);
+    if !hands_free_active && has_stereo {
+        AudioProfile::Stereo
+    } else {
+        AudioProfile::HandsFree
+    }
+}
+
+/// 已启用的播放或录音端点，没有 Container ID 的虚拟声卡除外。
+/// 蓝牙设备的录音端点只有免提，播放端点中外形为耳麦的为免提
+fn audio_endpoints(
+    enumerator: &IMMDeviceEnumerator,
+    flow: EDataFlow,
+) -> Result<Vec<AudioEndpoint>> {
+    let collection = unsafe { enumerator.EnumAudioEndpoints(flow, DEVICE_STATE_ACTIVE) }
+        .context("Failed to enumerate audio endpoints")?;
+    let count = unsafe { collection.GetCount() }?;
+    let render = flow == eRender;
+
+    let mut endpoints = Vec::new();
+    for i in 0..count {
+        let device = unsafe { collection.Item(i) }?;
+        let store = unsafe { device.OpenPropertyStore(STGM_READ) }?;
+        let Ok(container_id) = container_id(&store) else {
+            continue;
+        };
+        endpoints.push(AudioEndpoint {
+            id: endpoint_id(&device)?,
+            container_id,
+            render,
+            hands_free: !render || form_factor(&store).ok() == Some(HEADSET_FORM_FACTOR),
+            device,
+        });
+    }
+
+    Ok(endpoints)
+}
+
+/// 端点上是否有正在播放或录音的音频会话
+fn has_active_session(device: &IMMDevice) -> Result<bool> {
+    let manager: IAudioSessionManager2 = unsafe { device.Activate(CLSCTX_ALL, None) }?;
+    let sessions = unsafe { manager.GetSessionEnumerator() }?;
+    for i in 0..unsafe { sessions.GetCount() }? {
+        let state = unsafe { sessions.GetSession(i)?.GetState() }?;
+        if state == AudioSessionStateActive {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// 与系统声音设置相同，将端点设为所有用途的默认播放设备
+pub fn set_default_audio_endpoint(endpoint_id: &str) -> Result<()> {
+    let unknown: IUnknown = unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        CoCreateInstance(&CLSID_POLICY_CONFIG_CLIENT, None, CLSCTX_ALL)
+    }
+    .context("Failed to create the audio policy config")?;
+
+    let mut policy_config = std::ptr::null_mut();
+    unsafe { unknown.query(&IID_POLICY_CONFIG, &mut policy_config) }
+        .ok()
+        .context("Failed to query the audio policy config")?;
+    // 查询得到的引用在 Drop 时释放
+    let policy_config = unsafe { IUnknown::from_raw(policy_config) };
+
+    let endpoint_id = endpoint_id.encode_utf16().chain([0]).collect::<Vec<u16>>();
+    let vtbl = unsafe { &**(policy_config.as_raw() as *const *const PolicyConfigVtbl) };
+    for role in [eConsole, eMultimedia, eCommunications] {
+        unsafe {
+            (vtbl.set_default_endpoint)(policy_config.as_raw(), PCWSTR(endpoint_id.as_ptr()), role)
+        }
+        .ok()
+        .map_err(|e| anyhow!("Failed to set the default audio endpoint - {e}"))?;
+    }
+
+    Ok(())
+}
+
+fn device_enumerator() -> Result<IMMDeviceEnumerator> {
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+    }
+    .context("Failed to create the audio device enumerator")
+}
+
+fn endpoint_id(device: &IMMDevice) -> Result<String> {
+    let id = unsafe { device.GetId() }?;
+    let endpoint_id = unsafe { id.to_string() };
+    unsafe { CoTaskMemFree(Some(id.0 as _)) };
+    Ok(endpoint_id?)
+}
+
+fn container_id(store: &IPropertyStore) -> Result<u128> {
+    let value = unsafe { store.GetValue(&PKEY_Device_ContainerId) }?;
+    Ok(unsafe { PropVariantToGUID(&value) }?.to_u128())
+}
+
+fn form_factor(store: &IPropertyStore) -> Result<u32> {
+    let value = unsafe { store.GetValue(&PKEY_AudioEndpoint_FormFactor) }?;
+    Ok(unsafe { PropVariantToUInt32(&value) }?)
+}
+
+/// 播放端点的 Container ID 与上次相同时沿用上次的蓝牙设备地址，避免每次都枚举 PnP 设备
+fn bt_containers(endpoint_containers: HashSet<u128>) -> Result<HashMap<u128, u64>> {
+    let mut cached = CONTAINERS.lock().unwrap();
+    if let Some((last_endpoint_containers, containers)) = cached.as_ref()
+        && *last_endpoint_containers == endpoint_containers
+    {
+        return Ok(containers.clone());
+    }
+
+    let containers = get_bt_containers()?;
+    *cached = Some((endpoint_containers, containers.clone()));
+    Ok(containers)
+}
@@ -0,0 +1,649 @@
+use crate::{
+    companion::get_companion_battery,
+    info::{
+        BluetoothInfo, BluetoothScan, BluetoothType, ChargeState, DeviceCategory, DeviceError,
+        PowerStatus,
+    },
+    retry::RetryPolicy,
+};
+
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{LazyLock, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, anyhow};
+use log::warn;
+use tokio::sync::mpsc::UnboundedSender;
+use windows::{
+    Devices::Bluetooth::{
+        Advertisement::{
+            BluetoothLEAdvertisement, BluetoothLEAdvertisementDataTypes,
+            BluetoothLEAdvertisementReceivedEventArgs, BluetoothLEAdvertisementWatcher,
+            BluetoothLEScanningMode,
+        },
+        BluetoothCacheMode, BluetoothConnectionStatus, BluetoothLEConnectionPhyInfo,
+        BluetoothLEDevice,
+        GenericAttributeProfile::{
+            GattCharacteristic, GattCharacteristicUuids, GattDeviceService, GattServiceUuids,
+            GattSession,
+        },
+    },
+    Devices::Enumeration::DeviceInformation,
+    Foundation::TypedEventHandler,
+    Storage::Streams::DataReader,
+    core::{GUID, Ref},
+};
+
+pub fn find_ble_devices() -> Result<Vec<BluetoothLEDevice>> {
+    let ble_aqs_filter = BluetoothLEDevice::GetDeviceSelectorFromPairingState(true)?;
+
+    let ble_devices_info = DeviceInformation::FindAllAsyncAqsFilter(&ble_aqs_filter)?
+        .get()
+        .with_context(|| "Faled to find Bluetooth Low Energy from all devices")?;
+
+    let ble_devices = ble_devices_info
+        .into_iter()
+        .filter_map(|device_info| {
+            BluetoothLEDevice::FromIdAsync(&device_info.Id().ok()?)
+                .ok()?
+                .get()
+                .ok()
+        })
+        .collect::<Vec<_>>();
+
+    Ok(ble_devices)
+}
+
+/// 刚配对或刚重新连接的设备可能短暂查找失败
+const LOOKUP_RETRY: RetryPolicy =
+    RetryPolicy::new(3, Duration::from_millis(200), Duration::from_secs(1));
+/// GATT 读取偶尔失败（如设备正忙），需在 `BLE_READ_TIMEOUT` 内完成，只重试一次
+const GATT_RETRY: RetryPolicy =
+    RetryPolicy::new(2, Duration::from_millis(300), Duration::from_millis(300));
+
+pub fn find_ble_device(address: u64) -> Result<BluetoothLEDevice> {
+    LOOKUP_RETRY.retry("BLE lookup", || {
+        BluetoothLEDevice::FromBluetoothAddressAsync(address)?
+            .get()
+            .map_err(|e| anyhow!("Failed to find ble ({address}) - {e}"))
+    })
+}
+
+/// BLE 连接使用的物理层
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlePhy {
+    Le1M,
+    Le2M,
+    Coded,
+}
+
+/// 已连接 BLE 设备的连接参数，系统不支持（Windows 11 之前）或读取失败的项为 `None`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BleConnectionInfo {
+    /// 连接间隔（毫秒）
+    pub interval_ms: Option<f64>,
+    /// 外设可跳过的连接事件数
+    pub latency: Option<u16>,
+    /// 监督超时（毫秒）
+    pub timeout_ms: Option<u32>,
+    /// 发送及接收的物理层
+    pub phy: Option<(BlePhy, BlePhy)>,
+    /// GATT 会话的最大 PDU 大小（ATT MTU）
+    pub mtu: Option<u16>,
+}
+
+/// 读取已连接 BLE 设备的连接间隔、物理层及 MTU，用于排查响应迟缓的外设
+pub fn get_ble_connection_info(address: u64) -> Result<BleConnectionInfo> {
+    let ble_device = find_ble_device(address)?;
+
+    // 连接间隔以 1.25 毫秒、监督超时以 10 毫秒为单位
+    let parameters = ble_device.GetConnectionParameters().ok();
+    let interval_ms = parameters
+        .as_ref()
+        .and_then(|parameters| parameters.ConnectionInterval().ok())
+        .map(|interval| f64::from(interval) * 1.25);
+    let latency = parameters
+        .as_ref()
+        .and_then(|parameters| parameters.ConnectionLatency().ok());
+    let timeout_ms = parameters
+        .as_ref()
+        .and_then(|parameters| parameters.LinkTimeout().ok())
+        .map(|timeout| u32::from(timeout) * 10);
+
+    let phy = ble_device.GetConnectionPhy().ok().and_then(|phy| {
+        Some((
+            ble_phy(&phy.TransmitInfo().ok()?)?,
+            ble_phy(&phy.ReceiveInfo().ok()?)?,
+        ))
+    });
+
+    let mtu = ble_device
+        .BluetoothDeviceId()
+        .and_then(|device_id| GattSession::FromDeviceIdAsync(&device_id)?.get())
+        .and_then(|session| session.MaxPduSize())
+        .ok();
+
+    Ok(BleConnectionInfo {
+        interval_ms,
+        latency,
+        timeout_ms,
+        phy,
+        mtu,
+    })
+}
+
+fn ble_phy(info: &BluetoothLEConnectionPhyInfo) -> Option<BlePhy> {
+    if info.IsUncoded2MPhy().ok()? {
+        Some(BlePhy::Le2M)
+    } else if info.IsCodedPhy().ok()? {
+        Some(BlePhy::Coded)
+    } else if info.IsUncoded1MPhy().ok()? {
+        Some(BlePhy::Le1M)
+    } else {
+        None
+    }
+}
+
+/// Battery Service 1.1 新增的特征，Windows 未提供对应的 UUID 常量
+const BATTERY_LEVEL_STATUS_UUID: GUID = GUID::from_u128(0x00002BED_0000_1000_8000_00805F9B34FB);
+const BATTERY_ENERGY_STATUS_UUID: GUID = GUID::from_u128(0x00002BF0_0000_1000_8000_00805F9B34FB);
+
+/// 已知电量在该时间内有更新时不主动读取 GATT，启动时由配置 `[ble]` 设置一次。
+/// 未设置时（如命令行）每次都读取
+static GATT_READ_MAX_AGE: OnceLock<Duration> = OnceLock::new();
+
+pub fn init_gatt_read_policy(max_data_age_minutes: u64) {
+    let _ = GATT_READ_MAX_AGE.set(Duration::from_secs(max_data_age_minutes * 60));
+}
+
+/// 最近一次获得的电量，来自 GATT 读取、电量通知或广播
+#[derive(Debug, Clone, Copy)]
+struct BleReading {
+    battery: u8,
+    power: Option<PowerStatus>,
+    updated: Instant,
+}
+
+static BLE_READINGS: LazyLock<Mutex<HashMap</* address */ u64, BleReading>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 单个设备读取电量的超时时间，避免一个不可达的设备拖慢整次刷新
+const BLE_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 仍在读取的设备，超时的线程结束前不再为同一设备创建线程，线程数量不超过设备数量
+static BLE_READS_IN_FLIGHT: LazyLock<Mutex<HashSet</* address */ u64>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// 线程结束（包括 panic）时移出 `BLE_READS_IN_FLIGHT`
+struct InFlightRead(u64);
+
+impl Drop for InFlightRead {
+    fn drop(&mut self) {
+        BLE_READS_IN_FLIGHT.lock().unwrap().remove(&self.0);
+    }
+}
+
+/// 每读取到一个设备调用一次 `on_device`
+pub fn get_ble_info(
+    ble_devices: &[BluetoothLEDevice],
+    on_device: &dyn Fn(&BluetoothInfo),
+) -> Result<BluetoothScan> {
+    let mut scan = BluetoothScan::default();
+
+    // 每个设备在独立线程中并发读取，超时的设备留待下次刷新
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut pending: HashMap</* address */ u64, /* name */ String> = HashMap::new();
+    for ble_device in ble_devices {
+        let address = ble_device.BluetoothAddress().unwrap_or_default();
+        let name = ble_device
+            .Name()
+            .map(|name| name.to_string())
+            .unwrap_or_default();
+
+        // 上次超时的读取仍未结束，本次跳过
+        if !BLE_READS_IN_FLIGHT.lock().unwrap().insert(address) {
+            scan.errors.push(DeviceError {
+                name,
+                address,
+                error: "The previous read has not finished".to_owned(),
+            });
+            continue;
+        }
+        pending.insert(address, name);
+
+        let ble_device = ble_device.clone();
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            let in_flight = InFlightRead(address);
+            let r_ble_info = process_ble_device(&ble_device);
+            drop(in_flight);
+            let _ = tx.send((address, r_ble_info));
+        });
+    }
+    drop(tx);
+
+    let deadline = Instant::now() + BLE_READ_TIMEOUT;
+
+    while !pending.is_empty() {
+        match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok((address, r_ble_info)) => {
+                let name = pending.remove(&address).unwrap_or_default();
+                match r_ble_info {
+                    Ok(bt_info) => {
+                        on_device(&bt_info);
+                        scan.devices.insert(bt_info);
+                    }
+                    Err(e) => {
+                        warn!("{e}");
+                        scan.errors.push(DeviceError {
+                            name,
+                            address,
+                            error: e.to_string(),
+                        });
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                warn!(
+                    "Timed out reading the battery of {} BLE device(s)",
+                    pending.len()
+                );
+                scan.errors
+                    .extend(pending.drain().map(|(address, name)| DeviceError {
+                        name,
+                        address,
+                        error: format!("Timed out after {BLE_READ_TIMEOUT:?}"),
+                    }));
+                break;
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    Ok(scan)
+}
+
+pub fn process_ble_device(ble_device: &BluetoothLEDevice) -> Result<BluetoothInfo> {
+    let name = ble_device.Name()?.to_string();
+
+    let status = ble_device
+        .ConnectionStatus()
+        .map(|status| status == BluetoothConnectionStatus::Connected)
+        .with_context(|| format!("Failed to get BLE connected status: {name}"))?;
+
+    let address = ble_device.BluetoothAddress()?;
+
+    // 读取 GATT 会唤醒设备建立连接，电量通知、广播或上次读取的数据足够新时直接使用；
+    // 未连接的设备只读取 Windows 缓存的值
+    let (battery, power) = match get_recent_reading(address) {
+        Some(reading) => (reading.battery, reading.power),
+        None => {
+            let cache_mode = match status {
+                true => BluetoothCacheMode::Uncached,
+                false => BluetoothCacheMode::Cached,
+            };
+            // 没有电量服务的设备（如手机）使用配套命令的电量
+            let battery = match GATT_RETRY.retry("GATT battery read", || {
+                get_ble_battery_level(ble_device, cache_mode)
+            }) {
+                Ok(battery) => battery,
+                Err(e) => get_companion_battery(&name, address)
+                    .ok_or_else(|| anyhow!("Failed to get '{name}'BLE Battery Level: {e}"))?,
+            };
+            let power = get_ble_power_status(ble_device, cache_mode);
+            if status {
+                record_ble_reading(address, battery, power);
+            }
+            (battery, power)
+        }
+    };
+
+    let category = ble_device
+        .Appearance()
+        .and_then(|appearance| appearance.RawValue())
+        .map_or(DeviceCategory::Other, DeviceCategory::from_appearance);
+
+    Ok(BluetoothInfo {
+        name,
+        battery,
+        status,
+        address,
+        r#type: BluetoothType::LowEnergy,
+        category,
+        power,
+        components: None,
+    })
+}
+
+pub fn get_ble_battery_level(
+    ble_device: &BluetoothLEDevice,
+    cache_mode: BluetoothCacheMode,
+) -> Result<u8> {
+    let battery_gatt_char = get_ble_battery_gatt_char(ble_device, cache_mode)?;
+
+    let buffer = battery_gatt_char
+        .ReadValueWithCacheModeAsync(cache_mode)?
+        .get()?
+        .Value()?;
+    let reader = DataReader::FromBuffer(&buffer)?;
+    reader
+        .ReadByte()
+        .map_err(|e| anyhow!("Failed to read byte: {e}"))
+}
+
+/// 电量服务（0x180F）
+fn get_ble_battery_service(
+    ble_device: &BluetoothLEDevice,
+    cache_mode: BluetoothCacheMode,
+) -> Result<GattDeviceService> {
+    // 0000180F-0000-1000-8000-00805F9B34FB
+    let battery_services_uuid: GUID = GattServiceUuids::Battery()?;
+
+    let battery_gatt_services = ble_device
+        .GetGattServicesForUuidWithCacheModeAsync(battery_services_uuid, cache_mode)?
+        .get()?
+        .Services()
+        .map_err(|e| anyhow!("Failed to get BLE Battery Gatt Services: {e}"))?;
+
+    // 手机蓝牙无电量服务
+    battery_gatt_services
+        .into_iter()
+        .next()
+        .ok_or(anyhow!("Failed to get BLE Battery Gatt Service"))
+}
+
+/// 电量服务（0x180F）下的电量特征（0x2A19）
+pub fn get_ble_battery_gatt_char(
+    ble_device: &BluetoothLEDevice,
+    cache_mode: BluetoothCacheMode,
+) -> Result<GattCharacteristic> {
+    // 00002A19-0000-1000-8000-00805F9B34FB
+    let battery_level_uuid: GUID = GattCharacteristicUuids::BatteryLevel()?;
+
+    let battery_gatt_service = get_ble_battery_service(ble_device, cache_mode)?;
+
+    let battery_gatt_chars = battery_gatt_service
+        .GetCharacteristicsForUuidWithCacheModeAsync(battery_level_uuid, cache_mode)?
+        .get()?
+        .Characteristics()
+        .map_err(|e| anyhow!("Failed to get BLE Battery Gatt Characteristics: {e}"))?;
+
+    let battery_gatt_char = battery_gatt_chars
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Failed to get BLE Battery Gatt Characteristic"))?;
+
+    match battery_gatt_char.Uuid()? == battery_level_uuid {
+        true => Ok(battery_gatt_char),
+        false => Err(anyhow!(
+            "Failed to match BLE level UUID:\n{:?}:\n{battery_level_uuid:?}",
+            battery_gatt_char.Uuid()?
+        )),
+    }
+}
+
+/// 电量服务中的 Battery Level Status 及 Battery Energy Status，设备均不支持时为 `None`
+pub fn get_ble_power_status(
+    ble_device: &BluetoothLEDevice,
+    cache_mode: BluetoothCacheMode,
+) -> Option<PowerStatus> {
+    let battery_gatt_service = get_ble_battery_service(ble_device, cache_mode).ok()?;
+    let level_status =
+        read_battery_characteristic(&battery_gatt_service, BATTERY_LEVEL_STATUS_UUID, cache_mode)
+            .ok();
+    let energy_status = read_battery_characteristic(
+        &battery_gatt_service,
+        BATTERY_ENERGY_STATUS_UUID,
+        cache_mode,
+    )
+    .ok();
+
+    if level_status.is_none() && energy_status.is_none() {
+        return None;
+    }
+
+    let mut power = level_status
+        .as_deref()
+        .and_then(parse_battery_level_status)
+        .unwrap_or_default();
+    if let Some(energy_status) = energy_status.as_deref() {
+        (power.available_energy, power.charge_rate) = parse_battery_energy_status(energy_status);
+    }
+    Some(power)
+}
+
+fn read_battery_characteristic(
+    battery_gatt_service: &GattDeviceService,
+    uuid: GUID,
+    cache_mode: BluetoothCacheMode,
+) -> Result<Vec<u8>> {
+    let battery_gatt_char = battery_gatt_service
+        .GetCharacteristicsForUuidWithCacheModeAsync(uuid, cache_mode)?
+        .get()?
+        .Characteristics()?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("BLE Battery Gatt Characteristic {uuid:?} is not supported"))?;
+
+    let buffer = battery_gatt_char
+        .ReadValueWithCacheModeAsync(cache_mode)?
+        .get()?
+        .Value()?;
+    let reader = DataReader::FromBuffer(&buffer)?;
+    let mut value = vec![0; reader.UnconsumedBufferLength()? as usize];
+    reader.ReadBytes(&mut value)?;
+    Ok(value)
+}
+
+/// Flags (u8) + Power State (u16)，之后的可选字段不需要。
+/// Power State：bit 0 电池存在，bit 1-2 有线电源，bit 3-4 无线电源（1 为已连接），
+/// bit 5-6 充电状态（1 充电中，2、3 放电中）
+fn parse_battery_level_status(value: &[u8]) -> Option<PowerStatus> {
+    let &[_flags, low, high, ..] = value else {
+        return None;
+    };
+    let power_state = u16::from_le_bytes([low, high]);
+
+    let charge_state = match (power_state >> 5) & 0b11 {
+        1 => ChargeState::Charging,
+        2 | 3 => ChargeState::Discharging,
+        _ => ChargeState::Unknown,
+    };
+
+    Some(PowerStatus {
+        battery_present: power_state & 1 == 1,
+        external_power: (power_state >> 1) & 0b11 == 1 || (power_state >> 3) & 0b11 == 1,
+        charge_state,
+        ..Default::default()
+    })
+}
+
+/// Flags (u8) 后依次为标志位对应的 SFLOAT 字段：外部电源功率 (W)、电压 (V)、
+/// 剩余能量 (kWh)、剩余容量 (kWh)、充电功率 (W)、上次充满时的能量 (kWh)。
+/// 返回（剩余能量 mWh，充电功率 mW）
+fn parse_battery_energy_status(value: &[u8]) -> (Option<u32>, Option<i32>) {
+    let Some((&flags, mut fields)) = value.split_first() else {
+        return (None, None);
+    };
+
+    let mut available_energy = None;
+    let mut charge_rate = None;
+    for bit in 0..6 {
+        if flags & (1 << bit) == 0 {
+            continue;
+        }
+        let Some((field, rest)) = fields.split_first_chunk::<2>() else {
+            break;
+        };
+        fields = rest;
+
+        let field = parse_sfloat(u16::from_le_bytes(*field));
+        match bit {
+            2 => available_energy = field.map(|kwh| (kwh * 1_000_000.0).round() as u32),
+            4 => charge_rate = field.map(|watt| (watt * 1000.0).round() as i32),
+            _ => (),
+        }
+    }
+
+    (available_energy, charge_rate)
+}
+
+/// IEEE 11073 SFLOAT：高 4 位为指数，低 12 位为尾数，均为有符号数。
+/// 0x07FE-0x0802 为 NaN、无穷大等特殊值
+fn parse_sfloat(raw: u16) -> Option<f64> {
+    if (0x07FE..=0x0802).contains(&raw) {
+        return None;
+    }
+    let mantissa = (((raw & 0x0FFF) as i16) << 4) >> 4;
+    let exponent = (raw as i16) >> 12;
+    Some(f64::from(mantissa) * 10f64.powi(i32::from(exponent)))
+}
+
+/// 未超过 `GATT_READ_MAX_AGE` 的电量
+fn get_recent_reading(address: u64) -> Option<BleReading> {
+    let max_age = GATT_READ_MAX_AGE.get().copied().unwrap_or_default();
+    BLE_READINGS
+        .lock()
+        .unwrap()
+        .get(&address)
+        .filter(|reading| reading.updated.elapsed() < max_age)
+        .copied()
+}
+
+fn record_ble_reading(address: u64, battery: u8, power: Option<PowerStatus>) {
+    let reading = BleReading {
+        battery,
+        power,
+        updated: Instant::now(),
+    };
+    BLE_READINGS.lock().unwrap().insert(address, reading);
+}
+
+/// 记录电量通知或广播中的电量，保留上次读取的电源状态，返回电量是否变化
+pub fn record_ble_battery(address: u64, battery: u8) -> bool {
+    let mut readings = BLE_READINGS.lock().unwrap();
+    let previous = readings.get(&address).copied();
+    readings.insert(
+        address,
+        BleReading {
+            battery,
+            power: previous.and_then(|reading| reading.power),
+            updated: Instant::now(),
+        },
+    );
+    previous.is_none_or(|reading| reading.battery != battery)
+}
+
+/// 被动扫描 BLE 广播，从电量服务（0x180F）的 Service Data 中获取电量，无需建立 GATT 连接。
+/// 电量变化时发送 (地址, 电量)，Drop 时停止扫描并注销事件
+pub struct AdvertisementWatcher {
+    watcher: BluetoothLEAdvertisementWatcher,
+    token: i64,
+}
+
+impl AdvertisementWatcher {
+    pub fn start(tx: UnboundedSender<(/* address */ u64, /* battery */ u8)>) -> Result<Self> {
+        let watcher = BluetoothLEAdvertisementWatcher::new()?;
+        // 被动扫描不发送扫描请求，不会影响设备
+        watcher.SetScanningMode(BluetoothLEScanningMode::Passive)?;
+
+        let token = watcher.Received(&TypedEventHandler::new(
+            move |_watcher: Ref<BluetoothLEAdvertisementWatcher>,
+                  args: Ref<BluetoothLEAdvertisementReceivedEventArgs>| {
+                if let Some(args) = args.as_ref()
+                    && let Some(battery) = parse_advertised_battery(&args.Advertisement()?)
+                {
+                    let address = args.BluetoothAddress()?;
+                    if record_ble_battery(address, battery) {
+                        let _ = tx.send((address, battery));
+                    }
+                }
+                Ok(())
+            },
+        ))?;
+
+        watcher.Start()?;
+
+        Ok(AdvertisementWatcher { watcher, token })
+    }
+}
+
+impl Drop for AdvertisementWatcher {
+    fn drop(&mut self) {
+        let _ = self.watcher.Stop();
+        let _ = self.watcher.RemoveReceived(self.token);
+    }
+}
+
+/// Service Data - 16-bit UUID：UUID（小端，0x180F）后的第一个字节为电量
+fn parse_advertised_battery(advertisement: &BluetoothLEAdvertisement) -> Option<u8> {
+    let service_data_type = BluetoothLEAdvertisementDataTypes::ServiceData16BitUuids().ok()?;
+    advertisement
+        .GetSectionsByType(service_data_type)
+        .ok()?
+        .into_iter()
+        .find_map(|section| {
+            let reader = DataReader::FromBuffer(&section.Data().ok()?).ok()?;
+            let mut data = vec![0; reader.UnconsumedBufferLength().ok()? as usize];
+            reader.ReadBytes(&mut data).ok()?;
+            match data.as_slice() {
+                [0x0F, 0x18, battery, ..] if *battery <= 100 => Some(*battery),
+                _ => None,
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sfloat_values() {
+        assert_eq!(parse_sfloat(0x0064), Some(100.0));
+        assert_eq!(parse_sfloat(0x0FFF), Some(-1.0));
+        let value = parse_sfloat(0xF00A).unwrap();
+        assert!((value - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sfloat_special_values() {
+        for raw in [0x07FE, 0x07FF, 0x0800, 0x0801, 0x0802] {
+            assert_eq!(parse_sfloat(raw), None);
+        }
+    }
+
+    #[test]
+    fn battery_level_status_power_state() {
+        // 电池存在、有线电源已连接、充电中
+        let status = parse_battery_level_status(&[0, 0x23, 0x00]).unwrap();
+        assert!(status.battery_present);
+        assert!(status.external_power);
+        assert_eq!(status.charge_state, ChargeState::Charging);
+
+        // 电池存在、无外部电源、放电中
+        let status = parse_battery_level_status(&[0, 0x41, 0x00, 0xFF]).unwrap();
+        assert!(status.battery_present);
+        assert!(!status.external_power);
+        assert_eq!(status.charge_state, ChargeState::Discharging);
+
+        assert!(parse_battery_level_status(&[0, 0x01]).is_none());
+    }
+
+    #[test]
+    fn battery_energy_status_fields() {
+        // 剩余能量 0.05 kWh、充电功率 12 W
+        assert_eq!(
+            parse_battery_energy_status(&[0x14, 0x05, 0xE0, 0x0C, 0x00]),
+            (Some(50_000), Some(12_000))
+        );
+        // 前面有外部电源功率字段时依次跳过
+        assert_eq!(
+            parse_battery_energy_status(&[0x15, 0x64, 0x00, 0x05, 0xE0, 0x0C, 0x00]),
+            (Some(50_000), Some(12_000))
+        );
+        // 字段不完整
+        assert_eq!(parse_battery_energy_status(&[0x04, 0x05]), (None, None));
+        assert_eq!(parse_battery_energy_status(&[]), (None, None));
+    }
+}
@@ -0,0 +1,403 @@
+use crate::{
+    companion::get_companion_battery,
+    galaxy_buds::get_galaxy_buds_battery,
+    info::{BluetoothInfo, BluetoothScan, BluetoothType, DeviceCategory, DeviceError},
+    pnp::{get_cached_pnp_instances, get_devnode_battery, set_cached_pnp_instances},
+    retry::RetryPolicy,
+};
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+use log::warn;
+use windows::Devices::{
+    Bluetooth::{BluetoothConnectionStatus, BluetoothDevice},
+    Enumeration::DeviceInformation,
+};
+use windows_pnp::{
+    DeviceInstanceIdFilter, PnpDeviceNodeInfo, PnpDevicePropertyValue, PnpEnumerator,
+};
+use windows_sys::{
+    Wdk::Devices::Bluetooth::DEVPKEY_Bluetooth_DeviceAddress,
+    Win32::{
+        Devices::{
+            DeviceAndDriverInstallation::GUID_DEVCLASS_SYSTEM,
+            Properties::{DEVPKEY_Device_Manufacturer, DEVPKEY_Device_Model},
+        },
+        Foundation::DEVPROPKEY,
+    },
+};
+
+#[allow(non_upper_case_globals)]
+pub const DEVPKEY_Bluetooth_Battery: DEVPROPKEY = DEVPROPKEY {
+    fmtid: windows_sys::core::GUID::from_u128(0x104EA319_6EE2_4701_BD47_8DDBF425BBE5),
+    pid: 2,
+};
+pub const BT_INSTANCE_ID: &str = "BTHENUM\\";
+/// 免提音频设备（HFP）节点的枚举器。只通过 HFP 指示器（AT+IPHONEACCEV、HFP 1.7 电量指示器）
+/// 报告电量的耳机，电量由免提驱动写入该节点而不是 BTHENUM 节点
+pub const HFP_ENUMERATOR_ID: &str = "BTHHFENUM";
+
+pub struct PnpDeviceInfo {
+    pub address: u64,
+    pub battery: u8,
+    pub instance_id: String,
+}
+
+pub fn find_btc_devices() -> Result<Vec<BluetoothDevice>> {
+    let btc_aqs_filter = BluetoothDevice::GetDeviceSelectorFromPairingState(true)?;
+
+    let btc_devices_info = DeviceInformation::FindAllAsyncAqsFilter(&btc_aqs_filter)?
+        .get()
+        .with_context(|| "Faled to find Bluetooth Classic from all devices")?;
+
+    let btc_devices = btc_devices_info
+        .into_iter()
+        .filter_map(|device_info| {
+            BluetoothDevice::FromIdAsync(&device_info.Id().ok()?)
+                .ok()?
+                .get()
+                .ok()
+        })
+        .collect::<Vec<_>>();
+
+    Ok(btc_devices)
+}
+
+/// 枚举 PnP 设备可能出错（如蓝牙栈初始化尚未完成）
+const PNP_RETRY: RetryPolicy = RetryPolicy::new(2, Duration::from_secs(2), Duration::from_secs(2));
+/// 刚配对或刚重新连接的设备可能短暂查找失败
+const LOOKUP_RETRY: RetryPolicy =
+    RetryPolicy::new(3, Duration::from_millis(200), Duration::from_secs(1));
+
+pub fn find_btc_device(address: u64) -> Result<BluetoothDevice> {
+    LOOKUP_RETRY.retry("Bluetooth Classic lookup", || {
+        BluetoothDevice::FromBluetoothAddressAsync(address)?
+            .get()
+            .map_err(|e| anyhow!("Failed to find btc ({address}) - {e}"))
+    })
+}
+
+/// 每读取到一个设备调用一次 `on_device`
+pub fn get_btc_info(
+    btc_devices: &[BluetoothDevice],
+    on_device: &dyn Fn(&BluetoothInfo),
+) -> Result<BluetoothScan> {
+    let pnp_devices_info = PNP_RETRY
+        .retry("PnP enumeration", get_pnp_devices_info)
+        .map_err(|e| anyhow!("Failed to enumerate the pnp devices: {e}"))?;
+
+    let mut scan = BluetoothScan::default();
+
+    for btc_device in btc_devices {
+        match process_btc_device(btc_device, &pnp_devices_info) {
+            Ok(bt_info) => {
+                on_device(&bt_info);
+                scan.devices.insert(bt_info);
+            }
+            Err(e) => {
+                warn!("{e}");
+                scan.errors.push(DeviceError {
+                    name: btc_device
+                        .Name()
+                        .map(|name| name.to_string().trim().to_owned())
+                        .unwrap_or_default(),
+                    address: btc_device.BluetoothAddress().unwrap_or_default(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(scan)
+}
+
+pub fn process_btc_device(
+    btc_device: &BluetoothDevice,
+    pnp_devices_info: &HashMap<u64, PnpDeviceInfo>,
+) -> Result<BluetoothInfo> {
+    let btc_name = btc_device.Name()?.to_string().trim().to_owned();
+
+    let btc_address = btc_device.BluetoothAddress()?;
+
+    // 没有电量属性的设备（如手机）使用配套命令的电量，此时没有可读取的 PnP 实例
+    let (pnp_instance_id, btc_battery) = match pnp_devices_info.get(&btc_address) {
+        Some(i) => (i.instance_id.clone(), i.battery),
+        None => get_companion_battery(&btc_name, btc_address)
+            .map(|battery| (String::new(), battery))
+            .ok_or_else(|| {
+                anyhow!("No matching Bluetooth Classic Device in Pnp device: {btc_name}")
+            })?,
+    };
+
+    let btc_status = btc_device.ConnectionStatus()? == BluetoothConnectionStatus::Connected;
+
+    let btc_components = if btc_status {
+        get_galaxy_buds_battery(btc_device)
+    } else {
+        None
+    };
+
+    let btc_category = btc_device
+        .ClassOfDevice()
+        .and_then(|class| class.RawValue())
+        .map_or(DeviceCategory::Other, DeviceCategory::from_class_of_device);
+
+    Ok(BluetoothInfo {
+        name: btc_name,
+        battery: btc_battery,
+        status: btc_status,
+        address: btc_address,
+        r#type: BluetoothType::Classic(pnp_instance_id),
+        category: btc_category,
+        power: None,
+        components: btc_components,
+    })
+}
+
+pub fn get_pnp_devices_info() -> Result<HashMap<u64, PnpDeviceInfo>> {
+    // 已知设备实例时只读取其电量属性，无需重新枚举
+    if let Some(instances) = get_cached_pnp_instances() {
+        match get_cached_pnp_devices_info(&instances) {
+            Ok(pnp_devices_info) => return Ok(pnp_devices_info),
+            Err(e) => warn!("Failed to read cached PnP devices, enumerating again: {e}"),
+        }
+    }
+
+    let mut pnp_devices_info: HashMap<u64, PnpDeviceInfo> = HashMap::new();
+    let mut instances: HashMap<String, u64> = HashMap::new();
+    // 同一设备的所有节点有相同的 Container ID，用于找到 HFP 节点对应的地址
+    let mut containers: HashMap</* Container ID */ u128, u64> = HashMap::new();
+
+    let bt_devices_info = get_pnp_bt_devices()?;
+
+    for bt_device_info in bt_devices_info {
+        let container_id = bt_device_info
+            .base_container_id
+            .as_ref()
+            .map(|container_id| container_id.as_u128());
+
+        if let Some(mut props) = bt_device_info.device_instance_properties {
+            let battery = props
+                .remove(&DEVPKEY_Bluetooth_Battery.into())
+                .and_then(|value| match value {
+                    PnpDevicePropertyValue::Byte(v) => Some(v),
+                    _ => None,
+                });
+
+            let address = props
+                .remove(&DEVPKEY_Bluetooth_DeviceAddress.into())
+                .and_then(|value| match value {
+                    PnpDevicePropertyValue::String(v) => u64::from_str_radix(&v, 16).ok(),
+                    _ => None,
+                });
+
+            // 暂无电量属性的实例（如未连接）同样缓存，连接后即可直接读取
+            if let Some(address) = address {
+                instances.insert(bt_device_info.device_instance_id.clone(), address);
+                if let Some(container_id) = container_id {
+                    containers.insert(container_id, address);
+                }
+            }
+
+            if let (Some(address), Some(battery)) = (address, battery) {
+                pnp_devices_info.insert(
+                    address,
+                    PnpDeviceInfo {
+                        address,
+                        battery,
+                        instance_id: bt_device_info.device_instance_id,
+                    },
+                );
+            }
+        }
+    }
+
+    // HFP 节点的电量只用于 BTHENUM 节点没有电量的设备
+    match get_hfp_devices_info(&containers) {
+        Ok(hfp_devices_info) => {
+            for hfp_device_info in hfp_devices_info {
+                instances.insert(hfp_device_info.instance_id.clone(), hfp_device_info.address);
+                pnp_devices_info
+                    .entry(hfp_device_info.address)
+                    .or_insert(hfp_device_info);
+            }
+        }
+        Err(e) => warn!("{e}"),
+    }
+
+    set_cached_pnp_instances(instances);
+
+    Ok(pnp_devices_info)
+}
+
+/// 经典蓝牙设备的 Container ID 及地址，同一设备的音频端点等节点有相同的 Container ID
+pub fn get_bt_containers() -> Result<HashMap</* Container ID */ u128, u64>> {
+    let containers = get_pnp_bt_devices()?
+        .into_iter()
+        .filter_map(|bt_device_info| {
+            let container_id = bt_device_info.base_container_id.as_ref()?.as_u128();
+            let address = bt_device_info
+                .device_instance_properties?
+                .remove(&DEVPKEY_Bluetooth_DeviceAddress.into())
+                .and_then(|value| match value {
+                    PnpDevicePropertyValue::String(v) => u64::from_str_radix(&v, 16).ok(),
+                    _ => None,
+                })?;
+            Some((container_id, address))
+        })
+        .collect();
+
+    Ok(containers)
+}
+
+/// 免提音频设备节点上的电量，按 Container ID 对应到 BTHENUM 节点的地址
+fn get_hfp_devices_info(containers: &HashMap<u128, u64>) -> Result<Vec<PnpDeviceInfo>> {
+    let hfp_devices =
+        PnpEnumerator::enumerate_present_devices_by_pnp_enumerator_id(HFP_ENUMERATOR_ID)
+            .map_err(|e| anyhow!("Failed to enumerate HFP devices - {e:?}"))?;
+
+    let hfp_devices_info = hfp_devices
+        .into_iter()
+        .filter_map(|hfp_device| {
+            let address = *containers.get(&hfp_device.base_container_id.as_ref()?.as_u128())?;
+            let battery = hfp_device
+                .device_instance_properties?
+                .remove(&DEVPKEY_Bluetooth_Battery.into())
+                .and_then(|value| match value {
+                    PnpDevicePropertyValue::Byte(v) => Some(v),
+                    _ => None,
+                })?;
+
+            Some(PnpDeviceInfo {
+                address,
+                battery,
+                instance_id: hfp_device.device_instance_id,
+            })
+        })
+        .collect();
+
+    Ok(hfp_devices_info)
+}
+
+fn get_cached_pnp_devices_info(
+    instances: &HashMap<String, u64>,
+) -> Result<HashMap<u64, PnpDeviceInfo>> {
+    let mut pnp_devices_info: HashMap<u64, PnpDeviceInfo> = HashMap::new();
+
+    for (instance_id, &address) in instances {
+        if let Some(battery) = get_devnode_battery(instance_id)? {
+            let pnp_device_info = PnpDeviceInfo {
+                address,
+                battery,
+                instance_id: instance_id.clone(),
+            };
+            // 与完整枚举一致，BTHENUM 节点的电量优先于 HFP 节点
+            if instance_id.starts_with(HFP_ENUMERATOR_ID) {
+                pnp_devices_info.entry(address).or_insert(pnp_device_info);
+            } else {
+                pnp_devices_info.insert(address, pnp_device_info);
+            }
+        }
+    }
+
+    Ok(pnp_devices_info)
+}
+
+pub fn get_pnp_device_info(device_instance_id: &str) -> Result<PnpDeviceInfo> {
+    let cached_address =
+        get_cached_pnp_instances().and_then(|instances| instances.get(device_instance_id).copied());
+
+    if let Some(address) = cached_address
+        && let Ok(Some(battery)) = get_devnode_battery(device_instance_id)
+    {
+        return Ok(PnpDeviceInfo {
+            address,
+            battery,
+            instance_id: device_instance_id.to_owned(),
+        });
+    }
+
+    // HFP 节点没有地址属性，需完整枚举才能对应到设备
+    if device_instance_id.starts_with(HFP_ENUMERATOR_ID) {
+        return get_pnp_devices_info()?
+            .into_values()
+            .find(|pnp_info| pnp_info.instance_id == device_instance_id)
+            .ok_or_else(|| {
+                anyhow!("Failed to get battery for HFP device instance ID: {device_instance_id}")
+            });
+    }
+
+    let bt_device_info = get_pnp_bt_device(device_instance_id)?;
+
+    if let Some(mut props) = bt_device_info.device_instance_properties {
+        let battery =
+            props
+                .remove(&DEVPKEY_Bluetooth_Battery.into())
+                .and_then(|value| match value {
+                    PnpDevicePropertyValue::Byte(v) => Some(v),
+                    _ => None,
+                });
+
+        let address = props
+            .remove(&DEVPKEY_Bluetooth_DeviceAddress.into())
+            .and_then(|value| match value {
+                PnpDevicePropertyValue::String(v) => u64::from_str_radix(&v, 16).ok(),
+                _ => None,
+            });
+
+        if let (Some(address), Some(battery)) = (address, battery) {
+            return Ok(PnpDeviceInfo {
+                address,
+                battery,
+                instance_id: bt_device_info.device_instance_id,
+            });
+        }
+    }
+
+    Err(anyhow!(
+        "Failed to get address or battery for device instance ID: {device_instance_id}"
+    ))
+}
+
+/// 设备节点上由驱动提供的制造商及型号属性，不包括用户可修改的设备名称
+pub fn get_device_model(device_instance_id: &str) -> Result<(Option<String>, Option<String>)> {
+    let mut props = get_pnp_bt_device(device_instance_id)?
+        .device_instance_properties
+        .unwrap_or_default();
+    let mut string_property = |key: DEVPROPKEY| {
+        props.remove(&key.into()).and_then(|value| match value {
+            PnpDevicePropertyValue::String(v) if !v.trim().is_empty() => Some(v.trim().to_owned()),
+            _ => None,
+        })
+    };
+
+    Ok((
+        string_property(DEVPKEY_Device_Manufacturer),
+        string_property(DEVPKEY_Device_Model),
+    ))
+}
+
+fn get_pnp_bt_devices() -> Result<Vec<PnpDeviceNodeInfo>> {
+    PnpEnumerator::enumerate_present_devices_and_filter_device_instance_id_by_device_setup_class(
+        GUID_DEVCLASS_SYSTEM,
+        DeviceInstanceIdFilter::Contains(BT_INSTANCE_ID.to_owned()),
+    )
+    .map_err(|e| anyhow!("Failed to enumerate pnp devices - {e:?}"))
+}
+
+fn get_pnp_bt_device(device_instance_id: &str) -> Result<PnpDeviceNodeInfo> {
+    PnpEnumerator::enumerate_present_devices_and_filter_device_instance_id_by_device_setup_class(
+        GUID_DEVCLASS_SYSTEM,
+        DeviceInstanceIdFilter::Eq(device_instance_id.to_owned()),
+    )
+    .map_err(|e| {
+        anyhow!("Failed to enumerate the instance ID ({device_instance_id}) device - {e:?}")
+    })
+    .and_then(|d| {
+        d.into_iter().next().ok_or_else(|| {
+            anyhow!("No Pnp device found for the instance ID ({device_instance_id})")
+        })
+    })
+}
@@ -0,0 +1,112 @@
+use crate::info::format_address;
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::os::windows::process::CommandExt;
+use std::process::Stdio;
+use std::sync::{LazyLock, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, anyhow};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/// 配套命令的超时时间，超时后结束进程
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+/// 手机电量变化缓慢，结果（包括失败）在该时间内复用，避免每次刷新都执行命令
+const CACHE_MAX_AGE: Duration = Duration::from_secs(5 * 60);
+
+/// 没有电量服务的设备（如手机）通过配套命令获取电量，
+/// 如 `adb -s 192.168.1.20:5555 shell dumpsys battery`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompanionOptions {
+    /// 设备名称或地址
+    pub device: String,
+    /// 由 cmd 执行，输出中的 `level: 85` 或第一个 0-100 的数字作为电量
+    pub command: String,
+}
+
+/// 配置中的 `[[companions]]`，启动时设置一次
+static COMPANIONS: OnceLock<Vec<CompanionOptions>> = OnceLock::new();
+
+static COMPANION_BATTERY: LazyLock<Mutex<HashMap</* address */ u64, (Option<u8>, Instant)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub fn init_companions(companions: Vec<CompanionOptions>) {
+    let _ = COMPANIONS.set(companions);
+}
+
+/// 设备没有电量服务或电量属性时，执行其配套命令获取电量，未配置时为 `None`
+pub fn get_companion_battery(name: &str, address: u64) -> Option<u8> {
+    let companion = COMPANIONS.get()?.iter().find(|companion| {
+        companion.device == name
+            || companion
+                .device
+                .eq_ignore_ascii_case(&format_address(address))
+    })?;
+
+    if let Some((battery, updated)) = COMPANION_BATTERY.lock().unwrap().get(&address)
+        && updated.elapsed() < CACHE_MAX_AGE
+    {
+        return *battery;
+    }
+
+    let battery = run_companion_command(&companion.command)
+        .inspect(|battery| info!("[{name}]: Companion battery -> {battery}"))
+        .inspect_err(|e| warn!("Failed to get the companion battery of {name} - {e}"))
+        .ok();
+    COMPANION_BATTERY
+        .lock()
+        .unwrap()
+        .insert(address, (battery, Instant::now()));
+    battery
+}
+
+fn run_companion_command(command_line: &str) -> Result<u8> {
+    let mut child = std::process::Command::new("cmd")
+        .arg("/C")
+        .raw_arg(command_line)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn()
+        .with_context(|| format!("Failed to run `{command_line}`"))?;
+
+    let deadline = Instant::now() + COMMAND_TIMEOUT;
+    while child.try_wait()?.is_none() {
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            return Err(anyhow!("`{command_line}` timed out"));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    let mut output = String::new();
+    if let Some(mut stdout) = child.stdout.take() {
+        stdout.read_to_string(&mut output)?;
+    }
+
+    parse_battery(&output).ok_or_else(|| anyhow!("No battery level in the output: {output:?}"))
+}
+
+/// `adb shell dumpsys battery` 输出中的 `level: 85`，其他命令取第一个 0-100 的数字
+fn parse_battery(output: &str) -> Option<u8> {
+    let percent = |number: &str| {
+        number
+            .trim()
+            .parse::<u8>()
+            .ok()
+            .filter(|battery| *battery <= 100)
+    };
+
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("level:").and_then(percent))
+        .or_else(|| {
+            output
+                .split(|c: char| !c.is_ascii_digit())
+                .find_map(percent)
+        })
+}
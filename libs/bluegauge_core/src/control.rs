@@ -0,0 +1,175 @@
+use crate::{
+    ble::find_ble_device,
+    info::{BluetoothInfo, BluetoothType},
+};
+
+use anyhow::{Result, anyhow};
+use windows::{
+    Devices::Bluetooth::{BluetoothCacheMode, GenericAttributeProfile::GattCommunicationStatus},
+    Win32::{
+        Devices::Bluetooth::{
+            AVRemoteControlServiceClassID_UUID16, AudioSinkServiceClassID_UUID16,
+            BLUETOOTH_ADDRESS, BLUETOOTH_ADDRESS_0, BLUETOOTH_DEVICE_INFO,
+            BLUETOOTH_FIND_RADIO_PARAMS, BLUETOOTH_SERVICE_DISABLE, BLUETOOTH_SERVICE_ENABLE,
+            BluetoothFindFirstRadio, BluetoothFindRadioClose, BluetoothSetServiceState,
+            HandsfreeServiceClassID_UUID16, HeadsetServiceClassID_UUID16,
+        },
+        Foundation::{CloseHandle, ERROR_SUCCESS, HANDLE},
+    },
+    core::GUID,
+};
+
+/// 连接或断开经典蓝牙设备时启用或停用的音频服务（A2DP、HFP、HSP、AVRCP）
+const AUDIO_SERVICES: [u32; 4] = [
+    AudioSinkServiceClassID_UUID16,
+    HandsfreeServiceClassID_UUID16,
+    HeadsetServiceClassID_UUID16,
+    AVRemoteControlServiceClassID_UUID16,
+];
+
+/// 16 位服务 UUID 对应的蓝牙基础 UUID：`0000XXXX-0000-1000-8000-00805F9B34FB`
+fn service_guid(uuid16: u32) -> GUID {
+    GUID::from_values(
+        uuid16,
+        0x0000,
+        0x1000,
+        [0x80, 0x00, 0x00, 0x80, 0x5F, 0x9B, 0x34, 0xFB],
+    )
+}
+
+/// 第一个本地蓝牙适配器的句柄，Drop 时关闭
+struct RadioHandle(HANDLE);
+
+impl RadioHandle {
+    fn open() -> Result<Self> {
+        let params = BLUETOOTH_FIND_RADIO_PARAMS {
+            dwSize: std::mem::size_of::<BLUETOOTH_FIND_RADIO_PARAMS>() as u32,
+        };
+        let mut radio = HANDLE::default();
+        let find = unsafe { BluetoothFindFirstRadio(&params, &mut radio) }
+            .map_err(|e| anyhow!("Failed to find a Bluetooth radio - {e}"))?;
+        let _ = unsafe { BluetoothFindRadioClose(find) };
+        Ok(RadioHandle(radio))
+    }
+}
+
+impl Drop for RadioHandle {
+    fn drop(&mut self) {
+        let _ = unsafe { CloseHandle(self.0) };
+    }
+}
+
+fn device_info(address: u64) -> BLUETOOTH_DEVICE_INFO {
+    BLUETOOTH_DEVICE_INFO {
+        dwSize: std::mem::size_of::<BLUETOOTH_DEVICE_INFO>() as u32,
+        Address: BLUETOOTH_ADDRESS {
+            Anonymous: BLUETOOTH_ADDRESS_0 { ullLong: address },
+        },
+        ..Default::default()
+    }
+}
+
+/// 连接已配对但未连接的设备。
+/// 同样没有公开接口，经典蓝牙通过重新启用音频服务让系统尝试连接，设备不在范围内时不会生效；
+/// BLE 设备由使用它的程序连接
+pub fn connect_device(info: &BluetoothInfo) -> Result<()> {
+    if info.r#type == BluetoothType::LowEnergy {
+        return Err(anyhow!("Connecting Bluetooth LE devices is not supported"));
+    }
+
+    let radio = RadioHandle::open()?;
+    let device_info = device_info(info.address);
+
+    let enabled_services = AUDIO_SERVICES
+        .into_iter()
+        .map(service_guid)
+        .filter(|service| {
+            let result = unsafe {
+                BluetoothSetServiceState(
+                    Some(radio.0),
+                    &device_info,
+                    service,
+                    BLUETOOTH_SERVICE_ENABLE,
+                )
+            };
+            result == ERROR_SUCCESS.0
+        })
+        .count();
+
+    if enabled_services == 0 {
+        return Err(anyhow!("{} has no audio service to connect", info.name));
+    }
+
+    Ok(())
+}
+
+/// 重新连接意外断开的设备：经典蓝牙同 [`connect_device`]；
+/// BLE 设备不使用缓存读取 GATT 服务，系统会为此建立连接，设备不在范围内时返回错误
+pub fn reconnect_device(info: &BluetoothInfo) -> Result<()> {
+    if info.r#type != BluetoothType::LowEnergy {
+        return connect_device(info);
+    }
+
+    let ble_device = find_ble_device(info.address)?;
+    let status = ble_device
+        .GetGattServicesWithCacheModeAsync(BluetoothCacheMode::Uncached)?
+        .get()?
+        .Status()?;
+
+    if status != GattCommunicationStatus::Success {
+        return Err(anyhow!(
+            "Failed to reach {} over GATT - {status:?}",
+            info.name
+        ));
+    }
+
+    Ok(())
+}
+
+/// 断开设备的连接但保留配对。
+/// Windows 没有断开连接的公开接口，经典蓝牙通过停用其音频服务强制断开，随即重新启用以便下次连接；
+/// BLE 设备在所有程序释放后才会断开，无法主动断开
+pub fn disconnect_device(info: &BluetoothInfo) -> Result<()> {
+    if info.r#type == BluetoothType::LowEnergy {
+        return Err(anyhow!(
+            "Disconnecting Bluetooth LE devices is not supported"
+        ));
+    }
+
+    let radio = RadioHandle::open()?;
+    let device_info = device_info(info.address);
+
+    // 设备未提供的服务会返回错误，只需重新启用成功停用的服务
+    let disabled_services = AUDIO_SERVICES
+        .into_iter()
+        .map(service_guid)
+        .filter(|service| {
+            let result = unsafe {
+                BluetoothSetServiceState(
+                    Some(radio.0),
+                    &device_info,
+                    service,
+                    BLUETOOTH_SERVICE_DISABLE,
+                )
+            };
+            result == ERROR_SUCCESS.0
+        })
+        .collect::<Vec<_>>();
+
+    if disabled_services.is_empty() {
+        return Err(anyhow!("{} has no audio service to disconnect", info.name));
+    }
+
+    for service in &disabled_services {
+        unsafe {
+            BluetoothSetServiceState(
+                Some(radio.0),
+                &device_info,
+                service,
+                BLUETOOTH_SERVICE_ENABLE,
+            )
+        };
+    }
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use crate::info::BluetoothInfo;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// 每个订阅者最多积压的事件数，超出时丢弃其最旧的事件，下次接收时得到 `Lagged`
+const CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum DeviceEvent {
+    /// 电量变化
+    BatteryChanged {
+        info: BluetoothInfo,
+        old_battery: u8,
+    },
+    /// 连接或断开，新的连接状态为 `info.status`
+    ConnectionChanged { info: BluetoothInfo },
+    /// 新配对的设备
+    Added { info: BluetoothInfo },
+    /// 已取消配对的设备，`info` 为移除前最后的信息
+    Removed { info: BluetoothInfo },
+    /// 同一地址的设备名称变化，如驱动更新后
+    Renamed {
+        info: BluetoothInfo,
+        old_name: String,
+    },
+}
+
+impl DeviceEvent {
+    pub fn info(&self) -> &BluetoothInfo {
+        match self {
+            Self::BatteryChanged { info, .. }
+            | Self::ConnectionChanged { info }
+            | Self::Added { info }
+            | Self::Removed { info }
+            | Self::Renamed { info, .. } => info,
+        }
+    }
+}
+
+/// 设备事件流：按地址记录每个设备上次发布的信息，由前后状态得到各设备的转换并广播给所有订阅者。
+/// 发布不会因订阅者处理过慢而阻塞，积压过多的订阅者会丢弃最旧的事件
+pub struct DeviceEvents {
+    sender: broadcast::Sender<DeviceEvent>,
+    /// 上次发布的设备信息，为 `None` 时下次发布只记录而不产生事件
+    devices: Mutex<Option<HashMap</* address */ u64, BluetoothInfo>>>,
+}
+
+impl Default for DeviceEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeviceEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        DeviceEvents {
+            sender,
+            devices: Mutex::new(None),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DeviceEvent> {
+        self.sender.subscribe()
+    }
+
+    /// 以设备信息作为之后比较的基准而不产生事件，用于首次枚举完成时
+    pub fn reset(&self, devices: &HashSet<BluetoothInfo>) {
+        *self.devices.lock().unwrap() = Some(by_address(devices));
+    }
+
+    /// 与上次的设备信息比较，广播并返回其中的变化。
+    /// 此前没有设备信息时只作为基准，返回 `None`。
+    /// `unreadable` 为本次读取失败的设备地址，这些设备仍已配对，保留上次的信息而不视为移除
+    pub fn publish(
+        &self,
+        devices: &HashSet<BluetoothInfo>,
+        unreadable: &[u64],
+    ) -> Option<Vec<DeviceEvent>> {
+        let mut devices = by_address(devices);
+        let mut last_devices = self.devices.lock().unwrap();
+        let events = last_devices.as_ref().map(|last_devices| {
+            for address in unreadable {
+                if let Some(info) = last_devices.get(address) {
+                    devices.entry(*address).or_insert_with(|| info.clone());
+                }
+            }
+            diff_devices(last_devices, &devices)
+        });
+        *last_devices = Some(devices);
+
+        for event in events.iter().flatten() {
+            // 没有订阅者时发送失败，事件仍通过返回值交给调用者
+            let _ = self.sender.send(event.clone());
+        }
+
+        events
+    }
+}
+
+fn by_address(devices: &HashSet<BluetoothInfo>) -> HashMap<u64, BluetoothInfo> {
+    devices
+        .iter()
+        .map(|info| (info.address, info.clone()))
+        .collect()
+}
+
+/// 按地址逐个设备比较：添加为只在新信息中的地址，移除为只在上次信息中的地址，各产生一个事件；
+/// 两侧都有的设备由前后信息得到转换
+fn diff_devices(
+    old: &HashMap<u64, BluetoothInfo>,
+    new: &HashMap<u64, BluetoothInfo>,
+) -> Vec<DeviceEvent> {
+    let mut events = Vec::new();
+
+    for (address, info) in new {
+        match old.get(address) {
+            Some(old_info) => transitions(old_info, info, &mut events),
+            None => events.push(DeviceEvent::Added { info: info.clone() }),
+        }
+    }
+
+    events.extend(
+        old.iter()
+            .filter(|(address, _)| !new.contains_key(address))
+            .map(|(_, info)| DeviceEvent::Removed { info: info.clone() }),
+    );
+
+    events
+}
+
+/// 同一设备的名称、连接状态及电量变化，依次产生对应的事件
+fn transitions(old: &BluetoothInfo, new: &BluetoothInfo, events: &mut Vec<DeviceEvent>) {
+    if new.name != old.name {
+        events.push(DeviceEvent::Renamed {
+            info: new.clone(),
+            old_name: old.name.clone(),
+        });
+    }
+
+    if new.status != old.status {
+        events.push(DeviceEvent::ConnectionChanged { info: new.clone() });
+    }
+
+    if new.battery != old.battery {
+        events.push(DeviceEvent::BatteryChanged {
+            info: new.clone(),
+            old_battery: old.battery,
+        });
+    }
+}
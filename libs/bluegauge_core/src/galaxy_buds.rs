@@ -0,0 +1,229 @@
+use crate::info::ComponentBattery;
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use log::warn;
+use windows::{
+    Devices::Bluetooth::{
+        BluetoothCacheMode, BluetoothDevice,
+        Rfcomm::{RfcommDeviceService, RfcommServiceId},
+    },
+    Networking::Sockets::StreamSocket,
+    Storage::Streams::{DataReader, InputStreamOptions},
+    core::GUID,
+};
+
+/// Galaxy Buds（初代）的 SPP 服务
+const BUDS_SERVICE_UUID: GUID = GUID::from_u128(0x00001102_0000_1000_8000_00805F9B34FD);
+/// Galaxy Buds+ 及之后型号的 SPP 服务
+const BUDS_PLUS_SERVICE_UUID: GUID = GUID::from_u128(0x2E73A4AD_332D_41FC_90E2_16BEF06523F2);
+
+/// 电量等状态变化时耳机主动发送
+const MSG_STATUS_UPDATED: u8 = 0x60;
+/// 建立连接后耳机主动发送的完整状态
+const MSG_EXTENDED_STATUS_UPDATED: u8 = 0x61;
+
+/// 等待状态消息的超时时间，超时后关闭连接
+const READ_TIMEOUT: Duration = Duration::from_secs(3);
+/// 每次读取都需建立 RFCOMM 连接，结果（包括失败）在该时间内复用
+const CACHE_MAX_AGE: Duration = Duration::from_secs(5 * 60);
+
+/// 配置中的 `[protocols] galaxy_buds`，启动时设置一次
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+static COMPONENT_BATTERY: LazyLock<
+    Mutex<HashMap</* address */ u64, (Option<ComponentBattery>, Instant)>>,
+> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub fn init_galaxy_buds(enabled: bool) {
+    let _ = ENABLED.set(enabled);
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Protocol {
+    Buds,
+    BudsPlus,
+}
+
+impl Protocol {
+    fn service_uuid(self) -> GUID {
+        match self {
+            Protocol::Buds => BUDS_SERVICE_UUID,
+            Protocol::BudsPlus => BUDS_PLUS_SERVICE_UUID,
+        }
+    }
+
+    fn start_of_message(self) -> u8 {
+        match self {
+            Protocol::Buds => 0xFE,
+            Protocol::BudsPlus => 0xFD,
+        }
+    }
+
+    fn end_of_message(self) -> u8 {
+        match self {
+            Protocol::Buds => 0xEE,
+            Protocol::BudsPlus => 0xDD,
+        }
+    }
+
+    /// 两字节头部中的数据长度（消息 ID + 负载 + CRC）。
+    /// 初代为 (类型, 长度)，之后的型号为小端 u16，低 10 位为长度
+    fn data_size(self, header: [u8; 2]) -> usize {
+        match self {
+            Protocol::Buds => header[1] as usize,
+            Protocol::BudsPlus => (u16::from_le_bytes(header) & 0x3FF) as usize,
+        }
+    }
+}
+
+/// 已连接的 Galaxy Buds 的左右耳及充电盒电量，未启用或不是 Galaxy Buds 时为 `None`
+pub fn get_galaxy_buds_battery(device: &BluetoothDevice) -> Option<ComponentBattery> {
+    if !ENABLED.get().copied().unwrap_or(false) {
+        return None;
+    }
+    // 先按名称筛选，避免对每个设备查询 RFCOMM 服务
+    let name = device.Name().ok()?.to_string();
+    if !name.contains("Buds") {
+        return None;
+    }
+
+    let address = device.BluetoothAddress().ok()?;
+    if let Some((components, updated)) = COMPONENT_BATTERY.lock().unwrap().get(&address)
+        && updated.elapsed() < CACHE_MAX_AGE
+    {
+        return *components;
+    }
+
+    let components = read_components(device)
+        .inspect_err(|e| warn!("Failed to read Galaxy Buds battery ({name}) - {e}"))
+        .ok();
+    COMPONENT_BATTERY
+        .lock()
+        .unwrap()
+        .insert(address, (components, Instant::now()));
+    components
+}
+
+fn read_components(device: &BluetoothDevice) -> Result<ComponentBattery> {
+    for protocol in [Protocol::BudsPlus, Protocol::Buds] {
+        let service_id = RfcommServiceId::FromUuid(protocol.service_uuid())?;
+        let services = device
+            .GetRfcommServicesForIdWithCacheModeAsync(&service_id, BluetoothCacheMode::Cached)?
+            .get()?
+            .Services()?;
+
+        if let Some(service) = services.into_iter().next() {
+            return read_status(&service, protocol);
+        }
+    }
+
+    Err(anyhow!("No Galaxy Buds RFCOMM service"))
+}
+
+/// 连接后等待耳机主动发送的状态消息，超时时关闭连接以结束等待中的读取
+fn read_status(service: &RfcommDeviceService, protocol: Protocol) -> Result<ComponentBattery> {
+    let host_name = service.ConnectionHostName()?;
+    let service_name = service.ConnectionServiceName()?;
+    let socket = StreamSocket::new()?;
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let reader_socket = socket.clone();
+    std::thread::spawn(move || {
+        let result = reader_socket
+            .ConnectAsync(&host_name, &service_name)
+            .and_then(|connect| connect.get())
+            .map_err(|e| anyhow!("Failed to connect - {e}"))
+            .and_then(|_| receive_status(&reader_socket, protocol));
+        let _ = tx.send(result);
+    });
+
+    let result = rx
+        .recv_timeout(READ_TIMEOUT)
+        .map_err(|_| anyhow!("Timed out waiting for the status message"));
+    let _ = socket.Close();
+    result?
+}
+
+fn receive_status(socket: &StreamSocket, protocol: Protocol) -> Result<ComponentBattery> {
+    let reader = DataReader::CreateDataReader(&socket.InputStream()?)?;
+    reader.SetInputStreamOptions(InputStreamOptions::Partial)?;
+
+    let mut buffer = Vec::new();
+    loop {
+        let loaded = reader.LoadAsync(1024)?.get()?;
+        if loaded == 0 {
+            return Err(anyhow!("Connection closed by the device"));
+        }
+        let mut chunk = vec![0; loaded as usize];
+        reader.ReadBytes(&mut chunk)?;
+        buffer.extend_from_slice(&chunk);
+
+        if let Some(components) = parse_frames(&mut buffer, protocol) {
+            return Ok(components);
+        }
+    }
+}
+
+/// 取出缓冲区中的完整帧并返回最后一条状态消息，不完整的帧留待下次读取。
+/// 帧格式：SOM (1) + 头部 (2) + 消息 ID (1) + 负载 + CRC (2) + EOM (1)
+fn parse_frames(buffer: &mut Vec<u8>, protocol: Protocol) -> Option<ComponentBattery> {
+    let mut components = None;
+
+    while let Some(start) = buffer
+        .iter()
+        .position(|byte| *byte == protocol.start_of_message())
+    {
+        buffer.drain(..start);
+        let Some(&[_, header_low, header_high, ..]) = buffer.get(..3) else {
+            break;
+        };
+        let size = protocol.data_size([header_low, header_high]);
+        let frame_len = 3 + size + 1;
+        if buffer.len() < frame_len {
+            break;
+        }
+
+        // 结束标志不符时该 SOM 不是帧的开头，跳过后继续查找
+        if size < 3 || buffer[frame_len - 1] != protocol.end_of_message() {
+            buffer.drain(..1);
+            continue;
+        }
+
+        let frame = buffer.drain(..frame_len).collect::<Vec<_>>();
+        let (id, payload) = (frame[3], &frame[4..frame_len - 3]);
+        components = parse_status(id, payload, protocol).or(components);
+    }
+
+    components
+}
+
+/// 状态消息中左右耳电量的位置在扩展状态中后移一位（多了耳机类型），
+/// 只有 Buds+ 及之后的型号报告充电盒电量
+fn parse_status(id: u8, payload: &[u8], protocol: Protocol) -> Option<ComponentBattery> {
+    let (left, right, case) = match id {
+        MSG_STATUS_UPDATED => (1, 2, 6),
+        MSG_EXTENDED_STATUS_UPDATED => (2, 3, 7),
+        _ => return None,
+    };
+
+    // 耳机未连接或充电盒状态未知时为 0 或超过 100
+    let battery = |index: usize| {
+        payload
+            .get(index)
+            .copied()
+            .filter(|battery| (1..=100).contains(battery))
+    };
+
+    Some(ComponentBattery {
+        left: battery(left),
+        right: battery(right),
+        case: match protocol {
+            Protocol::Buds => None,
+            Protocol::BudsPlus => battery(case),
+        },
+    })
+}
@@ -0,0 +1,379 @@
+use crate::{
+    ble::{find_ble_device, find_ble_devices, get_ble_info, process_ble_device},
+    btc::{find_btc_device, find_btc_devices, get_btc_info, get_pnp_device_info},
+    companion::get_companion_battery,
+    galaxy_buds::get_galaxy_buds_battery,
+};
+
+use std::{collections::HashSet, sync::OnceLock};
+
+use anyhow::{Result, anyhow};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use windows::Devices::Bluetooth::{BluetoothConnectionStatus, BluetoothDevice, BluetoothLEDevice};
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum BluetoothType {
+    Classic(/* Instance ID */ String),
+    LowEnergy,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct BluetoothInfo {
+    pub name: String,
+    pub battery: u8,
+    pub status: bool,
+    pub address: u64,
+    pub r#type: BluetoothType,
+    pub category: DeviceCategory,
+    /// 支持 Battery Service 1.1 的 BLE 设备提供的电源状态
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power: Option<PowerStatus>,
+    /// 真无线耳机通过私有协议报告的左右耳及充电盒电量
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<ComponentBattery>,
+}
+
+/// 各部分的电量（%），未报告的部分为 `None`
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub struct ComponentBattery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub left: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub right: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub case: Option<u8>,
+}
+
+/// Battery Level Status（0x2BED）及 Battery Energy Status（0x2BF0）中的电源信息
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub struct PowerStatus {
+    pub battery_present: bool,
+    /// 已连接有线或无线外部电源
+    pub external_power: bool,
+    pub charge_state: ChargeState,
+    /// 剩余能量（mWh）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub available_energy: Option<u32>,
+    /// 充电功率（mW），放电时为负数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub charge_rate: Option<i32>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChargeState {
+    #[default]
+    Unknown,
+    Charging,
+    Discharging,
+}
+
+/// 读取失败的设备及原因
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct DeviceError {
+    pub name: String,
+    pub address: u64,
+    pub error: String,
+}
+
+/// 一次枚举的结果：部分设备读取失败时，其余设备照常返回
+#[derive(Clone, Debug, Default)]
+pub struct BluetoothScan {
+    pub devices: HashSet<BluetoothInfo>,
+    pub errors: Vec<DeviceError>,
+}
+
+impl DeviceError {
+    pub fn matches(&self, device: &str) -> bool {
+        self.name == device || device.eq_ignore_ascii_case(&format_address(self.address))
+    }
+}
+
+impl BluetoothScan {
+    fn merge(mut self, other: BluetoothScan) -> Self {
+        self.devices.extend(other.devices);
+        self.errors.extend(other.errors);
+        self
+    }
+}
+
+/// 设备类别，经典蓝牙取自 Class of Device，BLE 取自 GAP Appearance
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceCategory {
+    Audio,
+    Mouse,
+    Keyboard,
+    Gamepad,
+    Phone,
+    #[default]
+    Other,
+}
+
+impl DeviceCategory {
+    /// Class of Device：bit 8-12 为主类别，bit 2-7 为次类别
+    pub fn from_class_of_device(raw: u32) -> Self {
+        let minor = (raw >> 2) & 0x3F;
+        match (raw >> 8) & 0x1F {
+            0x02 => Self::Phone,
+            0x04 => Self::Audio,
+            // 外设：次类别高两位为键盘、指点设备，低四位为设备类型（1 游戏杆，2 游戏手柄）
+            0x05 if minor & 0x10 != 0 => Self::Keyboard,
+            0x05 if minor & 0x20 != 0 => Self::Mouse,
+            0x05 if matches!(minor & 0x0F, 0x01 | 0x02) => Self::Gamepad,
+            _ => Self::Other,
+        }
+    }
+
+    /// GAP Appearance：高 10 位为类别，低 6 位为子类别
+    pub fn from_appearance(raw: u16) -> Self {
+        match (raw >> 6, raw & 0x3F) {
+            (0x001, _) => Self::Phone,
+            (0x00F, 0x01) => Self::Keyboard,
+            (0x00F, 0x02) => Self::Mouse,
+            (0x00F, 0x03 | 0x04) => Self::Gamepad,
+            // 音频接收设备、可穿戴音频设备（耳机）
+            (0x021 | 0x025, _) => Self::Audio,
+            _ => Self::Other,
+        }
+    }
+
+    /// 托盘提示及菜单中的类别图标，未知类别不显示
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Audio => "🎧",
+            Self::Mouse => "🖱️",
+            Self::Keyboard => "⌨️",
+            Self::Gamepad => "🎮",
+            Self::Phone => "📱",
+            Self::Other => "",
+        }
+    }
+}
+
+/// 以 `AA:BB:CC:DD:EE:FF` 格式显示蓝牙地址
+pub fn format_address(address: u64) -> String {
+    (0..6)
+        .rev()
+        .map(|i| format!("{:02X}", (address >> (i * 8)) & 0xFF))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// 设备电量的校准：按分段线性映射后再加上偏移
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationOptions {
+    /// 设备名称或地址
+    pub device: String,
+    /// 上报电量到实际电量的分段线性映射，如 `[[0, 0], [50, 40], [100, 90]]`
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub curve: Vec<[u8; 2]>,
+    /// 映射后再加上的偏移，如设备总是偏高 10% 时设为 -10
+    #[serde(default)]
+    pub offset: i8,
+}
+
+impl CalibrationOptions {
+    pub fn apply(&self, battery: u8) -> u8 {
+        let mapped = self.map_curve(battery);
+        (mapped as i16 + self.offset as i16).clamp(0, 100) as u8
+    }
+
+    /// 两端以外沿用端点的值，点需按上报电量升序排列
+    fn map_curve(&self, battery: u8) -> u8 {
+        let Some(index) = self
+            .curve
+            .iter()
+            .position(|[reported, _]| *reported >= battery)
+        else {
+            return self.curve.last().map_or(battery, |[_, actual]| *actual);
+        };
+        if index == 0 {
+            return self.curve[0][1];
+        }
+
+        let [x0, y0] = self.curve[index - 1].map(f32::from);
+        let [x1, y1] = self.curve[index].map(f32::from);
+        (y0 + (f32::from(battery) - x0) * (y1 - y0) / (x1 - x0)).round() as u8
+    }
+}
+
+/// 配置中的 `[[calibrations]]`，启动时设置一次
+static CALIBRATIONS: OnceLock<Vec<CalibrationOptions>> = OnceLock::new();
+
+pub fn init_calibrations(calibrations: Vec<CalibrationOptions>) {
+    let _ = CALIBRATIONS.set(calibrations);
+}
+
+/// 按设备的校准修正上报的电量，没有对应校准时原样返回
+pub fn calibrate_battery(info: &BluetoothInfo, battery: u8) -> u8 {
+    CALIBRATIONS
+        .get()
+        .and_then(|calibrations| {
+            calibrations
+                .iter()
+                .find(|calibration| info.matches(&calibration.device))
+        })
+        .map_or(battery, |calibration| calibration.apply(battery))
+}
+
+impl BluetoothInfo {
+    /// 配置中以设备名称或地址（`AA:BB:CC:DD:EE:FF`）指定设备
+    pub fn matches(&self, device: &str) -> bool {
+        self.name == device || device.eq_ignore_ascii_case(&format_address(self.address))
+    }
+
+    /// 按配置校准电量
+    pub fn calibrated(mut self) -> Self {
+        self.battery = calibrate_battery(&self, self.battery);
+        self
+    }
+}
+
+pub fn find_bluetooth_devices() -> Result<(Vec<BluetoothDevice>, Vec<BluetoothLEDevice>)> {
+    let bt_devices = find_btc_devices()?;
+    let ble_devices = find_ble_devices()?;
+    Ok((bt_devices, ble_devices))
+}
+
+/// 读取所有设备信息，电量按配置校准，每读取到一个设备调用一次 `on_device`
+pub fn get_bluetooth_info(
+    bt_devices: (&[BluetoothDevice], &[BluetoothLEDevice]),
+    on_device: &dyn Fn(&BluetoothInfo),
+) -> Result<BluetoothScan> {
+    let on_device = |info: &BluetoothInfo| on_device(&info.clone().calibrated());
+    read_bluetooth_info(bt_devices, &on_device).map(|scan| BluetoothScan {
+        devices: scan
+            .devices
+            .into_iter()
+            .map(BluetoothInfo::calibrated)
+            .collect(),
+        ..scan
+    })
+}
+
+fn read_bluetooth_info(
+    bt_devices: (&[BluetoothDevice], &[BluetoothLEDevice]),
+    on_device: &dyn Fn(&BluetoothInfo),
+) -> Result<BluetoothScan> {
+    let btc_devices = bt_devices.0;
+    let ble_devices = bt_devices.1;
+    match (btc_devices.len(), ble_devices.len()) {
+        (0, 0) => Err(anyhow!(
+            "No Classic Bluetooth and Bluetooth LE devices found"
+        )),
+        (0, _) => {
+            let ble_result = get_ble_info(ble_devices, on_device);
+            info!("{ble_result:#?}");
+
+            ble_result.or_else(|e| {
+                warn!("Failed to get BLE info: {e}");
+                Ok(BluetoothScan::default())
+            })
+        },
+        (_, 0) => {
+            let btc_result = get_btc_info(btc_devices, on_device);
+            info!("{btc_result:#?}");
+
+            btc_result.or_else(|e| {
+                warn!("Failed to get BTC info: {e}");
+                Ok(BluetoothScan::default())
+            })
+        },
+        (_, _) => {
+            let btc_result = get_btc_info(btc_devices, on_device);
+            let ble_result = get_ble_info(ble_devices, on_device);
+
+            info!("{btc_result:#?}");
+            info!("{ble_result:#?}");
+
+            match (btc_result, ble_result) {
+                (Ok(btc_scan), Ok(ble_scan)) => Ok(btc_scan.merge(ble_scan)),
+                (Ok(btc_scan), Err(e)) => {
+                    warn!("Failed to get BLE info: {e}");
+                    Ok(btc_scan)
+                }
+                (Err(e), Ok(ble_scan)) => {
+                    warn!("Failed to get BTC info: {e}");
+                    Ok(ble_scan)
+                }
+                (Err(btc_err), Err(ble_err)) => Err(anyhow!(
+                    "Failed to get both BTC and BLE info: {btc_err} | {ble_err}"
+                )),
+            }
+        }
+    }
+}
+
+/// 只重新读取单个设备：经典蓝牙读取其 PnP 电量属性，BLE 读取 GATT 电量特征
+pub fn get_single_bluetooth_info(device: &BluetoothInfo) -> Result<BluetoothInfo> {
+    match &device.r#type {
+        BluetoothType::Classic(instance_id) => {
+            let btc_device = find_btc_device(device.address)?;
+            let status = btc_device.ConnectionStatus()? == BluetoothConnectionStatus::Connected;
+            // 未连接的设备没有电量属性，沿用上次（已校准）的电量
+            let battery = get_pnp_device_info(instance_id)
+                .map(|pnp_info| pnp_info.battery)
+                .ok()
+                .or_else(|| get_companion_battery(&device.name, device.address))
+                .map_or(device.battery, |battery| calibrate_battery(device, battery));
+
+            Ok(BluetoothInfo {
+                name: btc_device.Name()?.to_string().trim().to_owned(),
+                battery,
+                status,
+                address: device.address,
+                r#type: device.r#type.clone(),
+                category: device.category,
+                power: device.power,
+                components: if status {
+                    get_galaxy_buds_battery(&btc_device)
+                } else {
+                    None
+                },
+            })
+        }
+        BluetoothType::LowEnergy => {
+            process_ble_device(&find_ble_device(device.address)?).map(BluetoothInfo::calibrated)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calibration(curve: &[[u8; 2]], offset: i8) -> CalibrationOptions {
+        CalibrationOptions {
+            device: String::new(),
+            curve: curve.to_vec(),
+            offset,
+        }
+    }
+
+    #[test]
+    fn calibration_interpolates_curve() {
+        let calibration = calibration(&[[0, 0], [50, 40], [100, 90]], 0);
+        assert_eq!(calibration.apply(0), 0);
+        assert_eq!(calibration.apply(25), 20);
+        assert_eq!(calibration.apply(50), 40);
+        assert_eq!(calibration.apply(75), 65);
+        assert_eq!(calibration.apply(100), 90);
+    }
+
+    #[test]
+    fn calibration_keeps_end_points_outside_curve() {
+        let calibration = calibration(&[[20, 10], [80, 90]], 0);
+        assert_eq!(calibration.apply(5), 10);
+        assert_eq!(calibration.apply(95), 90);
+    }
+
+    #[test]
+    fn calibration_offset_is_clamped() {
+        assert_eq!(calibration(&[], -10).apply(50), 40);
+        assert_eq!(calibration(&[], -10).apply(5), 0);
+        assert_eq!(calibration(&[], 10).apply(95), 100);
+    }
+}
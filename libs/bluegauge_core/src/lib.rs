@@ -0,0 +1,25 @@
+//! BlueGauge 的蓝牙部分：枚举已配对的经典蓝牙及 BLE 设备、读取电量、订阅连接状态与电量变化，
+//! 以及连接、断开设备、切换默认播放设备和开关蓝牙适配器。不依赖托盘、通知等界面，可供其他程序使用：
+//!
+//! - 枚举：[`provider::BluetoothProvider::get_devices`]
+//! - 读取单个设备：[`provider::BluetoothProvider::get_device`]
+//! - 订阅变化：[`provider::BluetoothProvider::watch_device`]
+//! - 比较前后的设备信息并广播电量、连接、添加及移除事件：[`events::DeviceEvents`]
+//!
+//! 校准、配套命令及 Galaxy Buds 协议默认关闭，由 [`info::init_calibrations`]、
+//! [`companion::init_companions`] 及 [`galaxy_buds::init_galaxy_buds`] 在启动时设置一次
+
+#![cfg(target_os = "windows")]
+
+pub mod audio;
+pub mod ble;
+pub mod btc;
+pub mod companion;
+pub mod control;
+pub mod events;
+pub mod galaxy_buds;
+pub mod info;
+pub mod pnp;
+pub mod provider;
+pub mod radio;
+pub mod retry;
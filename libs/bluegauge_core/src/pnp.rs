@@ -0,0 +1,226 @@
+use crate::btc::{BT_INSTANCE_ID, DEVPKEY_Bluetooth_Battery, HFP_ENUMERATOR_ID};
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use anyhow::{Result, anyhow};
+use log::info;
+use tokio::sync::mpsc::UnboundedSender;
+use windows::{
+    Devices::Enumeration::{
+        DeviceInformation, DeviceInformationKind, DeviceInformationUpdate, DeviceWatcher,
+    },
+    Foundation::TypedEventHandler,
+    core::{HSTRING, Ref},
+};
+use windows_collections::IIterable;
+use windows_sys::Win32::Devices::{
+    DeviceAndDriverInstallation::{
+        CM_Get_DevNode_PropertyW, CM_LOCATE_DEVNODE_NORMAL, CM_Locate_DevNodeW, CM_NOTIFY_ACTION,
+        CM_NOTIFY_ACTION_DEVICEINSTANCEENUMERATED, CM_NOTIFY_ACTION_DEVICEINSTANCEREMOVED,
+        CM_NOTIFY_ACTION_DEVICEINSTANCESTARTED, CM_NOTIFY_EVENT_DATA, CM_NOTIFY_FILTER,
+        CM_NOTIFY_FILTER_FLAG_ALL_DEVICE_INSTANCES, CM_NOTIFY_FILTER_TYPE_DEVICEINSTANCE,
+        CM_Register_Notification, CM_Unregister_Notification, CR_NO_SUCH_VALUE, CR_SUCCESS,
+        HCMNOTIFICATION,
+    },
+    Properties::DEVPROPTYPE,
+};
+
+/// 已知的 BTHENUM 设备实例：实例 ID → 蓝牙地址。
+/// 为 `None` 时需要完整枚举 PnP 设备，设备实例增删时失效
+static PNP_INSTANCES: Mutex<Option<HashMap</* Instance ID */ String, /* address */ u64>>> =
+    Mutex::new(None);
+
+pub fn get_cached_pnp_instances() -> Option<HashMap<String, u64>> {
+    PNP_INSTANCES.lock().unwrap().clone()
+}
+
+pub fn set_cached_pnp_instances(instances: HashMap<String, u64>) {
+    *PNP_INSTANCES.lock().unwrap() = Some(instances);
+}
+
+pub fn invalidate_pnp_cache() {
+    *PNP_INSTANCES.lock().unwrap() = None;
+}
+
+/// 直接读取单个设备节点的电量属性，设备没有电量属性时返回 `None`
+pub fn get_devnode_battery(instance_id: &str) -> Result<Option<u8>> {
+    let instance_id_wide = instance_id.encode_utf16().chain([0]).collect::<Vec<u16>>();
+
+    let mut devinst = 0u32;
+    let result = unsafe {
+        CM_Locate_DevNodeW(
+            &mut devinst,
+            instance_id_wide.as_ptr(),
+            CM_LOCATE_DEVNODE_NORMAL,
+        )
+    };
+    if result != CR_SUCCESS {
+        return Err(anyhow!(
+            "Failed to locate the devnode ({instance_id}) - CONFIGRET {result}"
+        ));
+    }
+
+    let mut property_type: DEVPROPTYPE = 0;
+    let mut battery = 0u8;
+    let mut size = std::mem::size_of::<u8>() as u32;
+    let result = unsafe {
+        CM_Get_DevNode_PropertyW(
+            devinst,
+            &DEVPKEY_Bluetooth_Battery,
+            &mut property_type,
+            &mut battery,
+            &mut size,
+            0,
+        )
+    };
+
+    match result {
+        CR_SUCCESS => Ok(Some(battery)),
+        CR_NO_SUCH_VALUE => Ok(None),
+        _ => Err(anyhow!(
+            "Failed to get the battery of the devnode ({instance_id}) - CONFIGRET {result}"
+        )),
+    }
+}
+
+/// 电量属性的规范名称（`DEVPKEY_Bluetooth_Battery`），供 WinRT 设备枚举使用
+const BATTERY_PROPERTY: &str = "{104EA319-6EE2-4701-BD47-8DDBF425BBE5} 2";
+
+/// BTHENUM 或免提音频设备实例枚举、启动或移除时使实例缓存失效，
+/// 并发送枚举/启动的实例 ID（重新连接的设备此时才有电量属性），Drop 时注销
+pub struct PnpNotification {
+    handle: HCMNOTIFICATION,
+    context: *mut UnboundedSender<String>,
+}
+
+impl PnpNotification {
+    pub fn register(tx: UnboundedSender<String>) -> Result<Self> {
+        let filter = CM_NOTIFY_FILTER {
+            cbSize: std::mem::size_of::<CM_NOTIFY_FILTER>() as u32,
+            Flags: CM_NOTIFY_FILTER_FLAG_ALL_DEVICE_INSTANCES,
+            FilterType: CM_NOTIFY_FILTER_TYPE_DEVICEINSTANCE,
+            ..Default::default()
+        };
+
+        let context = Box::into_raw(Box::new(tx));
+        let mut handle: HCMNOTIFICATION = std::ptr::null_mut();
+        let result = unsafe {
+            CM_Register_Notification(
+                &filter,
+                context as *const core::ffi::c_void,
+                Some(on_device_instance_changed),
+                &mut handle,
+            )
+        };
+
+        if result != CR_SUCCESS {
+            drop(unsafe { Box::from_raw(context) });
+            return Err(anyhow!(
+                "Failed to register PnP notifications - CONFIGRET {result}"
+            ));
+        }
+
+        Ok(PnpNotification { handle, context })
+    }
+}
+
+impl Drop for PnpNotification {
+    fn drop(&mut self) {
+        // 注销后不会再有回调，此时才能释放上下文
+        unsafe {
+            CM_Unregister_Notification(self.handle);
+            drop(Box::from_raw(self.context));
+        }
+    }
+}
+
+unsafe extern "system" fn on_device_instance_changed(
+    _notify: HCMNOTIFICATION,
+    context: *const core::ffi::c_void,
+    action: CM_NOTIFY_ACTION,
+    event_data: *const CM_NOTIFY_EVENT_DATA,
+    _event_data_size: u32,
+) -> u32 {
+    if !matches!(
+        action,
+        CM_NOTIFY_ACTION_DEVICEINSTANCEENUMERATED
+            | CM_NOTIFY_ACTION_DEVICEINSTANCESTARTED
+            | CM_NOTIFY_ACTION_DEVICEINSTANCEREMOVED
+    ) || event_data.is_null()
+    {
+        return 0;
+    }
+
+    let instance_id = unsafe { read_instance_id(event_data) };
+    if instance_id.starts_with(BT_INSTANCE_ID) || instance_id.starts_with(HFP_ENUMERATOR_ID) {
+        info!("PnP device instance changed: {instance_id}");
+        invalidate_pnp_cache();
+
+        if action != CM_NOTIFY_ACTION_DEVICEINSTANCEREMOVED {
+            let tx = unsafe { &*(context as *const UnboundedSender<String>) };
+            let _ = tx.send(instance_id);
+        }
+    }
+
+    0 // ERROR_SUCCESS
+}
+
+/// `InstanceId` 是以 0 结尾的变长数组
+unsafe fn read_instance_id(event_data: *const CM_NOTIFY_EVENT_DATA) -> String {
+    let ptr =
+        unsafe { std::ptr::addr_of!((*event_data).u.DeviceInstance.InstanceId) as *const u16 };
+    let len = (0..).take_while(|&i| unsafe { *ptr.add(i) } != 0).count();
+    String::from_utf16_lossy(unsafe { std::slice::from_raw_parts(ptr, len) })
+}
+
+/// 监听 BTHENUM 设备节点的电量属性变化，发送变化的实例 ID，Drop 时停止监听并注销事件
+pub struct BatteryPropertyWatcher {
+    watcher: DeviceWatcher,
+    tokens: [i64; 2],
+}
+
+impl BatteryPropertyWatcher {
+    pub fn start(tx: UnboundedSender<String>) -> Result<Self> {
+        let aqs_filter = HSTRING::from(r#"System.Devices.DeviceInstanceId:~<"BTHENUM""#);
+        let properties = IIterable::<HSTRING>::from(vec![HSTRING::from(BATTERY_PROPERTY)]);
+        let watcher = DeviceInformation::CreateWatcherWithKindAqsFilterAndAdditionalProperties(
+            &aqs_filter,
+            &properties,
+            DeviceInformationKind::Device,
+        )?;
+
+        // 需同时注册 Added 才会收到 Updated 事件
+        let added_token = watcher.Added(&TypedEventHandler::new(
+            |_watcher: Ref<DeviceWatcher>, _device_info: Ref<DeviceInformation>| Ok(()),
+        ))?;
+
+        let battery_property = HSTRING::from(BATTERY_PROPERTY);
+        let updated_token = watcher.Updated(&TypedEventHandler::new(
+            move |_watcher: Ref<DeviceWatcher>, update: Ref<DeviceInformationUpdate>| {
+                if let Some(update) = update.as_ref()
+                    && update.Properties()?.HasKey(&battery_property)?
+                {
+                    let _ = tx.send(update.Id()?.to_string());
+                }
+                Ok(())
+            },
+        ))?;
+
+        watcher.Start()?;
+
+        Ok(BatteryPropertyWatcher {
+            watcher,
+            tokens: [added_token, updated_token],
+        })
+    }
+}
+
+impl Drop for BatteryPropertyWatcher {
+    fn drop(&mut self) {
+        let [added_token, updated_token] = self.tokens;
+        let _ = self.watcher.Stop();
+        let _ = self.watcher.RemoveAdded(added_token);
+        let _ = self.watcher.RemoveUpdated(updated_token);
+    }
+}
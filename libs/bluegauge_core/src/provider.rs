@@ -0,0 +1,531 @@
+use crate::{
+    ble::{find_ble_device, get_ble_battery_gatt_char, record_ble_battery},
+    btc::find_btc_device,
+    info::{
+        BluetoothInfo, BluetoothScan, BluetoothType, DeviceCategory, find_bluetooth_devices,
+        get_bluetooth_info, get_single_bluetooth_info,
+    },
+};
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Result, anyhow};
+use log::warn;
+use tokio::sync::mpsc::UnboundedSender;
+use windows::{
+    Devices::Bluetooth::{
+        BluetoothCacheMode, BluetoothConnectionStatus, BluetoothDevice, BluetoothLEDevice,
+        GenericAttributeProfile::{
+            GattCharacteristic, GattCharacteristicProperties,
+            GattClientCharacteristicConfigurationDescriptorValue, GattCommunicationStatus,
+            GattValueChangedEventArgs,
+        },
+    },
+    Foundation::TypedEventHandler,
+    Storage::Streams::DataReader,
+    core::Ref,
+};
+
+/// 单个设备的电量或连接状态变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceUpdate {
+    BatteryLevel(u8),
+    ConnectionStatus(bool),
+}
+
+/// 设备信息的来源：枚举、单个设备读取及变化订阅。
+/// 更新引擎只通过该接口访问设备，便于替换为模拟实现
+pub trait BluetoothProvider: Send + Sync {
+    /// 枚举所有已配对设备，同时返回读取失败的设备
+    fn get_devices(&self) -> Result<BluetoothScan> {
+        self.get_devices_progressively(&|_| ())
+    }
+
+    /// 与 `get_devices` 相同，每读取到一个设备调用一次 `on_device`，用于启动时逐个显示设备
+    fn get_devices_progressively(
+        &self,
+        on_device: &dyn Fn(&BluetoothInfo),
+    ) -> Result<BluetoothScan>;
+
+    /// 只重新读取单个设备
+    fn get_device(&self, device: &BluetoothInfo) -> Result<BluetoothInfo>;
+
+    /// 订阅单个设备的连接状态与电量变化，返回值 Drop 时注销事件，
+    /// `unsubscribe` 还会关闭设备端的通知
+    fn watch_device(
+        &self,
+        device: &BluetoothInfo,
+        tx: UnboundedSender<DeviceUpdate>,
+    ) -> Result<Box<dyn DeviceSubscription>>;
+
+    /// 模拟设备不需要监听系统的配对、PnP 及适配器事件
+    fn is_simulated(&self) -> bool {
+        false
+    }
+}
+
+/// `watch_device` 返回的订阅。Drop 只注销事件而不访问设备，可在任意线程中进行
+pub trait DeviceSubscription: Send + 'static {
+    /// 关闭设备端的通知（如 GATT 电量通知）后注销事件，由调用者决定等待的时间
+    fn unsubscribe(self: Box<Self>) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(async move { drop(self) })
+    }
+}
+
+/// WinRT（连接状态、BLE GATT 电量）与 PnP（经典蓝牙电量）
+pub struct WindowsBluetoothProvider;
+
+impl BluetoothProvider for WindowsBluetoothProvider {
+    fn get_devices_progressively(
+        &self,
+        on_device: &dyn Fn(&BluetoothInfo),
+    ) -> Result<BluetoothScan> {
+        let bluetooth_devices = find_bluetooth_devices()
+            .map_err(|e| anyhow!("Failed to find bluetooth devices - {e}"))?;
+
+        get_bluetooth_info((&bluetooth_devices.0, &bluetooth_devices.1), on_device)
+            .map_err(|e| anyhow!("Failed to get bluetooth devices info - {e}"))
+    }
+
+    fn get_device(&self, device: &BluetoothInfo) -> Result<BluetoothInfo> {
+        get_single_bluetooth_info(device)
+    }
+
+    fn watch_device(
+        &self,
+        device: &BluetoothInfo,
+        tx: UnboundedSender<DeviceUpdate>,
+    ) -> Result<Box<dyn DeviceSubscription>> {
+        let subscription = match device.r#type {
+            BluetoothType::Classic(_) => Subscription::classic(device.address, tx)?,
+            BluetoothType::LowEnergy => Subscription::low_energy(device.address, tx)?,
+        };
+        Ok(Box::new(subscription))
+    }
+}
+
+/// 持有 WinRT 事件注册，Drop 时注销，`unsubscribe` 时先关闭 GATT 电量通知
+enum Subscription {
+    Classic {
+        device: BluetoothDevice,
+        connection_token: i64,
+    },
+    LowEnergy {
+        device: BluetoothLEDevice,
+        connection_token: i64,
+        battery: Option<(GattCharacteristic, i64)>,
+    },
+}
+
+impl Subscription {
+    fn classic(address: u64, tx: UnboundedSender<DeviceUpdate>) -> Result<Self> {
+        let device = find_btc_device(address)?;
+
+        let handler = TypedEventHandler::new(move |sender: Ref<BluetoothDevice>, _args| {
+            if let Some(btc) = sender.as_ref() {
+                let status = btc.ConnectionStatus()? == BluetoothConnectionStatus::Connected;
+                let _ = tx.send(DeviceUpdate::ConnectionStatus(status));
+            }
+            Ok(())
+        });
+        let connection_token = device.ConnectionStatusChanged(&handler)?;
+
+        Ok(Subscription::Classic {
+            device,
+            connection_token,
+        })
+    }
+
+    fn low_energy(address: u64, tx: UnboundedSender<DeviceUpdate>) -> Result<Self> {
+        let device = find_ble_device(address)?;
+
+        let tx_status = tx.clone();
+        let handler = TypedEventHandler::new(move |sender: Ref<BluetoothLEDevice>, _args| {
+            if let Some(ble) = sender.as_ref() {
+                let status = ble.ConnectionStatus()? == BluetoothConnectionStatus::Connected;
+                let _ = tx_status.send(DeviceUpdate::ConnectionStatus(status));
+            }
+            Ok(())
+        });
+        let connection_token = device.ConnectionStatusChanged(&handler)?;
+
+        // 不支持通知的设备只依赖定时全量刷新获取电量
+        let battery = subscribe_battery_level(&device, tx)
+            .inspect_err(|e| warn!("Failed to subscribe to BLE battery notifications - {e}"))
+            .ok();
+
+        Ok(Subscription::LowEnergy {
+            device,
+            connection_token,
+            battery,
+        })
+    }
+}
+
+impl DeviceSubscription for Subscription {
+    fn unsubscribe(self: Box<Self>) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(async move {
+            let Subscription::LowEnergy {
+                device,
+                battery: Some((battery_gatt_char, _)),
+                ..
+            } = self.as_ref()
+            else {
+                return;
+            };
+
+            // 未连接时写入会触发重新连接，设备重新连接后订阅本就失效
+            let connected = device
+                .ConnectionStatus()
+                .is_ok_and(|status| status == BluetoothConnectionStatus::Connected);
+            if !connected {
+                return;
+            }
+
+            let result = match battery_gatt_char
+                .WriteClientCharacteristicConfigurationDescriptorAsync(
+                    GattClientCharacteristicConfigurationDescriptorValue::None,
+                ) {
+                Ok(operation) => operation.await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                warn!("Failed to disable BLE battery notifications - {e}");
+            }
+        })
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        match self {
+            Subscription::Classic {
+                device,
+                connection_token,
+            } => {
+                let _ = device.RemoveConnectionStatusChanged(*connection_token);
+            }
+            Subscription::LowEnergy {
+                device,
+                connection_token,
+                battery,
+            } => {
+                let _ = device.RemoveConnectionStatusChanged(*connection_token);
+                if let Some((battery_gatt_char, battery_token)) = battery {
+                    let _ = battery_gatt_char.RemoveValueChanged(*battery_token);
+                }
+            }
+        }
+    }
+}
+
+fn subscribe_battery_level(
+    device: &BluetoothLEDevice,
+    tx: UnboundedSender<DeviceUpdate>,
+) -> Result<(GattCharacteristic, i64)> {
+    let battery_gatt_char = get_ble_battery_gatt_char(device, BluetoothCacheMode::Uncached)?;
+    let address = device.BluetoothAddress()?;
+
+    if !battery_gatt_char
+        .CharacteristicProperties()?
+        .contains(GattCharacteristicProperties::Notify)
+    {
+        return Err(anyhow!("Battery level does not support notifications"));
+    }
+
+    let handler = TypedEventHandler::new(move |_, args: Ref<GattValueChangedEventArgs>| {
+        if let Ok(args) = args.ok() {
+            let value = args.CharacteristicValue()?;
+            let reader = DataReader::FromBuffer(&value)?;
+            let battery = reader.ReadByte()?;
+            // 通知的电量可代替下次刷新时的 GATT 读取
+            record_ble_battery(address, battery);
+            let _ = tx.send(DeviceUpdate::BatteryLevel(battery));
+        }
+        Ok(())
+    });
+    let battery_token = battery_gatt_char.ValueChanged(&handler)?;
+
+    let status = battery_gatt_char
+        .WriteClientCharacteristicConfigurationDescriptorAsync(
+            GattClientCharacteristicConfigurationDescriptorValue::Notify,
+        )?
+        .get()?;
+
+    if status != GattCommunicationStatus::Success {
+        let _ = battery_gatt_char.RemoveValueChanged(battery_token);
+        return Err(anyhow!(
+            "Failed to enable battery level notifications - {status:?}"
+        ));
+    }
+
+    Ok((battery_gatt_char, battery_token))
+}
+
+/// 已连接的 BLE 模拟设备，模拟模式及各模块的测试共用，其余字段按需覆盖
+pub fn mock_device(name: &str, address: u64, battery: u8) -> BluetoothInfo {
+    BluetoothInfo {
+        name: name.to_owned(),
+        battery,
+        status: true,
+        address,
+        r#type: BluetoothType::LowEnergy,
+        category: DeviceCategory::Other,
+        power: None,
+        components: None,
+    }
+}
+
+/// 可编程的模拟设备：通过 `set_*` 修改设备并推送给订阅者，
+/// 用于在没有真实硬件时驱动完整的更新流程
+#[derive(Clone, Default)]
+pub struct MockBluetoothProvider {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Default)]
+struct MockState {
+    devices: HashMap</* address */ u64, BluetoothInfo>,
+    watchers: Vec<(
+        /* watch id */ u64,
+        /* address */ u64,
+        UnboundedSender<DeviceUpdate>,
+    )>,
+    next_watch_id: u64,
+    /// 设置后枚举返回该错误，模拟蓝牙栈未就绪
+    error: Option<String>,
+}
+
+impl MockBluetoothProvider {
+    pub fn new(devices: impl IntoIterator<Item = BluetoothInfo>) -> Self {
+        let provider = MockBluetoothProvider::default();
+        provider.state.lock().unwrap().devices = devices
+            .into_iter()
+            .map(|device| (device.address, device))
+            .collect();
+        provider
+    }
+
+    pub fn add_device(&self, device: BluetoothInfo) {
+        self.state
+            .lock()
+            .unwrap()
+            .devices
+            .insert(device.address, device);
+    }
+
+    pub fn remove_device(&self, address: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.devices.remove(&address);
+        state
+            .watchers
+            .retain(|(_, watch_address, _)| *watch_address != address);
+    }
+
+    pub fn set_battery(&self, address: u64, battery: u8) {
+        self.update_device(address, DeviceUpdate::BatteryLevel(battery));
+    }
+
+    pub fn set_connected(&self, address: u64, status: bool) {
+        self.update_device(address, DeviceUpdate::ConnectionStatus(status));
+    }
+
+    pub fn set_error(&self, error: Option<String>) {
+        self.state.lock().unwrap().error = error;
+    }
+
+    fn update_device(&self, address: u64, update: DeviceUpdate) {
+        let mut state = self.state.lock().unwrap();
+        let Some(device) = state.devices.get_mut(&address) else {
+            return;
+        };
+
+        match update {
+            DeviceUpdate::BatteryLevel(battery) => device.battery = battery,
+            DeviceUpdate::ConnectionStatus(status) => device.status = status,
+        }
+
+        // 顺便移除已取消订阅（接收端已关闭）的发送端
+        state
+            .watchers
+            .retain(|(_, watch_address, tx)| *watch_address != address || tx.send(update).is_ok());
+    }
+}
+
+impl BluetoothProvider for MockBluetoothProvider {
+    fn get_devices_progressively(
+        &self,
+        on_device: &dyn Fn(&BluetoothInfo),
+    ) -> Result<BluetoothScan> {
+        let state = self.state.lock().unwrap();
+        match &state.error {
+            Some(error) => Err(anyhow!("{error}")),
+            None => {
+                state.devices.values().for_each(on_device);
+                Ok(BluetoothScan {
+                    devices: state.devices.values().cloned().collect(),
+                    errors: Vec::new(),
+                })
+            }
+        }
+    }
+
+    fn get_device(&self, device: &BluetoothInfo) -> Result<BluetoothInfo> {
+        self.state
+            .lock()
+            .unwrap()
+            .devices
+            .get(&device.address)
+            .cloned()
+            .ok_or_else(|| anyhow!("Device {} not found", device.address))
+    }
+
+    fn watch_device(
+        &self,
+        device: &BluetoothInfo,
+        tx: UnboundedSender<DeviceUpdate>,
+    ) -> Result<Box<dyn DeviceSubscription>> {
+        let mut state = self.state.lock().unwrap();
+        if !state.devices.contains_key(&device.address) {
+            return Err(anyhow!("Device {} not found", device.address));
+        }
+
+        let watch_id = state.next_watch_id;
+        state.next_watch_id += 1;
+        state.watchers.push((watch_id, device.address, tx));
+
+        Ok(Box::new(MockSubscription {
+            state: Arc::clone(&self.state),
+            watch_id,
+        }))
+    }
+
+    fn is_simulated(&self) -> bool {
+        true
+    }
+}
+
+struct MockSubscription {
+    state: Arc<Mutex<MockState>>,
+    watch_id: u64,
+}
+
+impl DeviceSubscription for MockSubscription {}
+
+impl Drop for MockSubscription {
+    fn drop(&mut self) {
+        self.state
+            .lock()
+            .unwrap()
+            .watchers
+            .retain(|(watch_id, ..)| *watch_id != self.watch_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{DeviceEvent, DeviceEvents};
+
+    use std::collections::HashSet;
+
+    fn scan(provider: &MockBluetoothProvider) -> HashSet<BluetoothInfo> {
+        provider.get_devices().unwrap().devices
+    }
+
+    /// 事件类型及地址，按地址排序
+    fn summarize(events: &[DeviceEvent]) -> Vec<(&'static str, u64)> {
+        let mut events = events
+            .iter()
+            .map(|event| {
+                let kind = match event {
+                    DeviceEvent::BatteryChanged { .. } => "battery_changed",
+                    DeviceEvent::ConnectionChanged { .. } => "connection_changed",
+                    DeviceEvent::Added { .. } => "added",
+                    DeviceEvent::Removed { .. } => "removed",
+                    DeviceEvent::Renamed { .. } => "renamed",
+                };
+                (kind, event.info().address)
+            })
+            .collect::<Vec<_>>();
+        events.sort_by_key(|(_, address)| *address);
+        events
+    }
+
+    #[test]
+    fn mock_changes_become_device_events() {
+        let provider =
+            MockBluetoothProvider::new([mock_device("Buds", 1, 80), mock_device("Mouse", 2, 50)]);
+        let device_events = DeviceEvents::new();
+        let mut rx = device_events.subscribe();
+
+        // 首次发布只作为基准
+        assert!(device_events.publish(&scan(&provider), &[]).is_none());
+
+        provider.set_battery(1, 75);
+        provider.set_connected(2, false);
+        provider.add_device(mock_device("Keyboard", 3, 90));
+        let events = device_events.publish(&scan(&provider), &[]).unwrap();
+        assert_eq!(
+            summarize(&events),
+            [
+                ("battery_changed", 1),
+                ("connection_changed", 2),
+                ("added", 3),
+            ]
+        );
+
+        let mut received = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            received.push(event);
+        }
+        assert_eq!(summarize(&received), summarize(&events));
+
+        // 没有变化时不产生事件
+        assert!(
+            device_events
+                .publish(&scan(&provider), &[])
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn unreadable_devices_are_not_removed() {
+        let provider =
+            MockBluetoothProvider::new([mock_device("Buds", 1, 80), mock_device("Mouse", 2, 50)]);
+        let device_events = DeviceEvents::new();
+        device_events.reset(&scan(&provider));
+
+        provider.remove_device(2);
+        assert!(
+            device_events
+                .publish(&scan(&provider), &[2])
+                .unwrap()
+                .is_empty()
+        );
+
+        provider.remove_device(1);
+        let events = device_events.publish(&scan(&provider), &[]).unwrap();
+        assert_eq!(summarize(&events), [("removed", 1), ("removed", 2)]);
+    }
+
+    #[test]
+    fn mock_watchers_receive_updates_until_dropped() {
+        let provider = MockBluetoothProvider::new([mock_device("Buds", 1, 80)]);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let subscription = provider
+            .watch_device(&mock_device("Buds", 1, 80), tx)
+            .unwrap();
+
+        provider.set_battery(1, 70);
+        assert_eq!(rx.try_recv(), Ok(DeviceUpdate::BatteryLevel(70)));
+
+        drop(subscription);
+        provider.set_connected(1, false);
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Result, anyhow};
+use tokio::sync::mpsc::UnboundedSender;
+use windows::{
+    Devices::Bluetooth::BluetoothAdapter,
+    Devices::Enumeration::{DeviceInformation, DeviceInformationUpdate, DeviceWatcher},
+    Devices::Radios::{Radio, RadioAccessStatus, RadioKind, RadioState},
+    Foundation::TypedEventHandler,
+    core::{IInspectable, Ref},
+};
+
+/// 蓝牙适配器状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BluetoothRadioState {
+    On,
+    /// 已关闭，可通过菜单重新打开
+    Off,
+    /// 没有适配器，或适配器已被禁用
+    Unavailable,
+}
+
+fn find_bluetooth_radio() -> Result<Option<Radio>> {
+    let radios = Radio::GetRadiosAsync()?
+        .get()
+        .map_err(|e| anyhow!("Failed to get radios - {e}"))?;
+
+    Ok(radios
+        .into_iter()
+        .find(|radio| radio.Kind().is_ok_and(|kind| kind == RadioKind::Bluetooth)))
+}
+
+pub fn get_bluetooth_radio_state() -> Result<BluetoothRadioState> {
+    let Some(radio) = find_bluetooth_radio()? else {
+        return Ok(BluetoothRadioState::Unavailable);
+    };
+
+    let state = match radio.State()? {
+        RadioState::Off => BluetoothRadioState::Off,
+        RadioState::Disabled => BluetoothRadioState::Unavailable,
+        // 状态未知时按已打开处理，不影响正常更新
+        _ => BluetoothRadioState::On,
+    };
+
+    Ok(state)
+}
+
+/// 默认蓝牙适配器的名称、地址及支持的功能
+#[derive(Debug, Clone)]
+pub struct AdapterInfo {
+    pub name: String,
+    pub address: u64,
+    pub classic: bool,
+    pub low_energy: bool,
+    pub peripheral_role: bool,
+    pub secure_connections: bool,
+}
+
+pub fn get_adapter_info() -> Result<AdapterInfo> {
+    let adapter = BluetoothAdapter::GetDefaultAsync()?
+        .get()
+        .map_err(|e| anyhow!("No Bluetooth adapter found - {e}"))?;
+    let name = DeviceInformation::CreateFromIdAsync(&adapter.DeviceId()?)?
+        .get()?
+        .Name()?
+        .to_string();
+
+    Ok(AdapterInfo {
+        name,
+        address: adapter.BluetoothAddress()?,
+        classic: adapter.IsClassicSupported()?,
+        low_energy: adapter.IsLowEnergySupported()?,
+        peripheral_role: adapter.IsPeripheralRoleSupported()?,
+        secure_connections: adapter.AreLowEnergySecureConnectionsSupported()?,
+    })
+}
+
+pub fn set_bluetooth_radio_state(on: bool) -> Result<()> {
+    let access_status = Radio::RequestAccessAsync()?.get()?;
+    if access_status != RadioAccessStatus::Allowed {
+        return Err(anyhow!(
+            "Access to the Bluetooth radio was denied - {access_status:?}"
+        ));
+    }
+
+    let radio = find_bluetooth_radio()?.ok_or_else(|| anyhow!("No Bluetooth adapter found"))?;
+    let state = if on { RadioState::On } else { RadioState::Off };
+    let access_status = radio.SetStateAsync(state)?.get()?;
+    if access_status != RadioAccessStatus::Allowed {
+        return Err(anyhow!(
+            "Failed to set the Bluetooth radio state to {state:?} - {access_status:?}"
+        ));
+    }
+
+    Ok(())
+}
+
+/// 监听蓝牙适配器的出现、移除及开关，发生变化时发送信号，Drop 时停止监听并注销事件
+pub struct RadioWatcher {
+    watcher: DeviceWatcher,
+    tokens: [i64; 2],
+    /// 已订阅 `StateChanged` 的蓝牙适配器
+    radios: Arc<Mutex<Vec<(Radio, i64)>>>,
+}
+
+impl RadioWatcher {
+    pub fn start(tx: UnboundedSender<()>) -> Result<Self> {
+        let watcher = DeviceInformation::CreateWatcherAqsFilter(&Radio::GetDeviceSelector()?)?;
+        let radios = Arc::new(Mutex::new(Vec::new()));
+
+        let tx_added = tx.clone();
+        let added_radios = Arc::clone(&radios);
+        let added_token = watcher.Added(&TypedEventHandler::new(
+            move |_watcher: Ref<DeviceWatcher>, device_info: Ref<DeviceInformation>| {
+                if let Some(device_info) = device_info.as_ref() {
+                    let radio = Radio::FromIdAsync(&device_info.Id()?)?.get()?;
+                    if radio.Kind()? != RadioKind::Bluetooth {
+                        return Ok(());
+                    }
+
+                    let tx_state = tx_added.clone();
+                    let state_token = radio.StateChanged(&TypedEventHandler::new(
+                        move |_radio: Ref<Radio>, _args: Ref<IInspectable>| {
+                            let _ = tx_state.send(());
+                            Ok(())
+                        },
+                    ))?;
+                    added_radios.lock().unwrap().push((radio, state_token));
+
+                    let _ = tx_added.send(());
+                }
+                Ok(())
+            },
+        ))?;
+
+        let removed_token = watcher.Removed(&TypedEventHandler::new(
+            move |_watcher: Ref<DeviceWatcher>, _update: Ref<DeviceInformationUpdate>| {
+                let _ = tx.send(());
+                Ok(())
+            },
+        ))?;
+
+        watcher.Start()?;
+
+        Ok(RadioWatcher {
+            watcher,
+            tokens: [added_token, removed_token],
+            radios,
+        })
+    }
+}
+
+impl Drop for RadioWatcher {
+    fn drop(&mut self) {
+        let [added_token, removed_token] = self.tokens;
+        let _ = self.watcher.Stop();
+        let _ = self.watcher.RemoveAdded(added_token);
+        let _ = self.watcher.RemoveRemoved(removed_token);
+
+        for (radio, state_token) in self.radios.lock().unwrap().drain(..) {
+            let _ = radio.RemoveStateChanged(state_token);
+        }
+    }
+}
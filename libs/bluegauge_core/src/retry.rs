@@ -0,0 +1,78 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use anyhow::Result;
+use log::warn;
+
+/// 失败后的重试策略：等待时间从 `min_delay` 开始每次翻倍，不超过 `max_delay`，
+/// 并加入 ±25% 的随机抖动，避免多个设备或线程同时重试
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// 包括第一次在内的最多尝试次数
+    pub max_attempts: u32,
+    pub min_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub const fn new(max_attempts: u32, min_delay: Duration, max_delay: Duration) -> Self {
+        RetryPolicy {
+            max_attempts,
+            min_delay,
+            max_delay,
+        }
+    }
+
+    /// 第 `attempt` 次（从 1 开始）失败后的等待时间
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .min_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        delay.mul_f64(0.75 + 0.5 * random_fraction())
+    }
+
+    /// 在当前线程中重试，只用于可以阻塞的线程（如 `spawn_blocking`）
+    pub fn retry<T>(&self, operation: &str, mut f: impl FnMut() -> Result<T>) -> Result<T> {
+        let mut attempt = 1;
+        loop {
+            match f() {
+                Err(e) if attempt < self.max_attempts => {
+                    let delay = self.delay(attempt);
+                    warn!("{operation} attempt {attempt} failed, retrying in {delay:?} - {e}");
+                    std::thread::sleep(delay);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// 异步重试，等待期间不占用线程
+    pub async fn retry_async<T, F, Fut>(&self, operation: &str, mut f: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match f().await {
+                Err(e) if attempt < self.max_attempts => {
+                    let delay = self.delay(attempt);
+                    warn!("{operation} attempt {attempt} failed, retrying in {delay:?} - {e}");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// [0, 1) 的随机数，`RandomState` 每次创建时使用不同的随机种子
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u8(0);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
@@ -0,0 +1,70 @@
+use crate::bluetooth::audio::{AudioEndpoints, AudioProfile, get_audio_endpoints};
+
+use std::sync::{LazyLock, RwLock};
+use std::time::Duration;
+
+use log::warn;
+use tokio::runtime::Handle;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 最近一次读取的蓝牙播放端点，菜单及托盘提示从这里读取而不在主线程调用 COM
+static AUDIO_ENDPOINTS: LazyLock<RwLock<AudioEndpoints>> =
+    LazyLock::new(|| RwLock::new(AudioEndpoints::default()));
+
+/// 是否为当前的默认播放设备
+pub fn is_default_audio(address: u64) -> bool {
+    AUDIO_ENDPOINTS.read().unwrap().default == Some(address)
+}
+
+/// 蓝牙设备的播放端点 ID，不是音频设备或未连接时为 `None`
+pub fn audio_endpoint(address: u64) -> Option<String> {
+    AUDIO_ENDPOINTS
+        .read()
+        .unwrap()
+        .endpoints
+        .get(&address)
+        .cloned()
+}
+
+/// 音频设备当前的配置文件（立体声或免提），不是音频设备或未连接时为 `None`
+pub fn audio_profile(address: u64) -> Option<AudioProfile> {
+    AUDIO_ENDPOINTS
+        .read()
+        .unwrap()
+        .profiles
+        .get(&address)
+        .copied()
+}
+
+/// 定时读取音频端点，默认播放设备、蓝牙播放端点或其配置文件变化时调用 `on_change`
+pub fn watch_audio_endpoints(runtime: &Handle, on_change: impl Fn() + Send + 'static) {
+    runtime.spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        let mut warned = false;
+        loop {
+            interval.tick().await;
+
+            let endpoints = match tokio::task::spawn_blocking(get_audio_endpoints).await {
+                Ok(Ok(endpoints)) => endpoints,
+                // 没有音频服务等情况下每次都会失败，只记录一次
+                Ok(Err(e)) if !warned => {
+                    warn!("Failed to read audio endpoints - {e}");
+                    warned = true;
+                    continue;
+                }
+                _ => continue,
+            };
+
+            let changed = {
+                let mut audio_endpoints = AUDIO_ENDPOINTS.write().unwrap();
+                let changed = *audio_endpoints != endpoints;
+                *audio_endpoints = endpoints;
+                changed
+            };
+            if changed {
+                on_change();
+            }
+        }
+    });
+}
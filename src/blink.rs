@@ -0,0 +1,63 @@
+use crate::{bluetooth::info::BluetoothInfo, config::BlinkOptions};
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use tray_icon::Icon;
+
+/// 闪烁时与正常图标交替显示的透明图标
+pub fn blank_icon() -> Result<Icon> {
+    Icon::from_rgba(vec![0; 16 * 16 * 4], 16, 16)
+        .map_err(|e| anyhow!("Failed to create the blank icon - {e}"))
+}
+
+/// 是否有已连接设备的电量低于临界值
+pub fn has_critical_device(
+    options: &BlinkOptions,
+    bluetooth_devices_info: &HashSet<BluetoothInfo>,
+) -> bool {
+    options.enabled
+        && bluetooth_devices_info
+            .iter()
+            .any(|info| info.status && info.battery < options.critical_battery)
+}
+
+/// 托盘图标的闪烁状态，由事件循环在 `next_toggle` 时切换
+#[derive(Debug, Default)]
+pub struct IconBlinker {
+    /// 正在闪烁时为下一次切换的时间
+    next_toggle: Option<Instant>,
+    /// 当前显示的是透明图标
+    hidden: bool,
+}
+
+impl IconBlinker {
+    /// 开始或停止闪烁，停止时图标正处于隐藏状态则返回 `true`，需要恢复正常图标
+    pub fn set_blinking(&mut self, blinking: bool, interval: Duration) -> bool {
+        match (blinking, self.next_toggle) {
+            (true, None) => {
+                self.next_toggle = Some(Instant::now() + interval);
+                false
+            }
+            (false, Some(_)) => {
+                self.next_toggle = None;
+                std::mem::take(&mut self.hidden)
+            }
+            _ => false,
+        }
+    }
+
+    /// 到达切换时间时切换显示状态，返回切换后是否隐藏
+    pub fn toggle(&mut self, interval: Duration) -> Option<bool> {
+        let now = Instant::now();
+        self.next_toggle.filter(|next_toggle| now >= *next_toggle)?;
+        self.next_toggle = Some(now + interval);
+        self.hidden = !self.hidden;
+        Some(self.hidden)
+    }
+
+    pub fn next_toggle(&self) -> Option<Instant> {
+        self.next_toggle
+    }
+}
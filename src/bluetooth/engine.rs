@@ -0,0 +1,1372 @@
+use crate::{
+    UserEvent,
+    bluetooth::{
+        ble::{AdvertisementWatcher, process_ble_device},
+        btc::{get_pnp_device_info, get_pnp_devices_info, process_btc_device},
+        info::{BluetoothInfo, BluetoothType, calibrate_battery, set_device_errors},
+        pnp::{BatteryPropertyWatcher, PnpNotification},
+        provider::{BluetoothProvider, DeviceSubscription, DeviceUpdate},
+        radio::{BluetoothRadioState, RadioWatcher, get_bluetooth_radio_state},
+    },
+    config::Config,
+    notify::app_notify,
+};
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, AtomicU64, Ordering},
+};
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use bluegauge_core::retry::RetryPolicy;
+use log::{info, warn};
+use tokio::{
+    runtime::Handle,
+    sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
+    task::JoinHandle,
+    time::Instant,
+};
+use windows::{
+    Devices::Bluetooth::{BluetoothDevice, BluetoothLEDevice},
+    Devices::Enumeration::{DeviceInformation, DeviceInformationUpdate, DeviceWatcher},
+    Foundation::TypedEventHandler,
+    core::{HSTRING, IInspectable, Ref},
+};
+use winit::event_loop::EventLoopProxy;
+
+/// 更新引擎产生的事件，统一经由一个通道转发到事件循环
+#[derive(Debug)]
+pub enum EngineEvent {
+    /// 请求全量刷新
+    Refresh { force: bool },
+    /// 全量枚举完成，`generation` 为开始枚举时的设备列表版本
+    BluetoothInfoReady {
+        result: Result<HashSet<BluetoothInfo>>,
+        force: bool,
+        generation: u64,
+    },
+    /// 单个设备的连接状态或电量发生变化
+    DeviceChanged(BluetoothInfo),
+    /// 单独重新读取的设备，`generation` 为开始读取时的设备列表版本
+    DeviceRefreshed {
+        info: BluetoothInfo,
+        generation: u64,
+    },
+    /// 新配对的设备
+    DeviceAdded(BluetoothInfo),
+    /// 已取消配对的设备
+    DeviceRemoved(/* address */ u64),
+    /// 蓝牙适配器打开、关闭、出现或移除
+    RadioStateChanged(BluetoothRadioState),
+    /// 定时暂停已到期
+    PauseExpired,
+    /// 首次枚举中读取到的单个设备
+    InitialDevice(BluetoothInfo),
+    /// 启动时的首次枚举完成（含重试），`generation` 为开始枚举时的设备列表版本
+    Initialized {
+        result: Result<HashSet<BluetoothInfo>>,
+        generation: u64,
+    },
+    /// 后台线程中修改了显示相关的设置，需要重建托盘
+    RedrawTray,
+    /// 看门狗定时检查设备监听
+    CheckWatchers,
+}
+
+/// 统一的更新引擎：
+/// - 连接状态：所有设备的 `ConnectionStatusChanged` 事件
+/// - BLE 电量：GATT 电量特征通知，未连接的设备为广播中的电量（被动扫描）
+/// - 经典蓝牙电量：PnP 设备通知及电量属性变化事件（不可用时托盘图标来源设备轮询 PnP 属性）
+/// - 配对/取消配对：`DeviceWatcher`
+/// - 蓝牙适配器开关：`Radio::StateChanged`
+/// - 其余情况：按 `update_interval` 全量刷新
+pub struct UpdateEngine {
+    /// 应用共用的运行时
+    runtime: Handle,
+    /// 枚举、读取及监听设备的来源
+    provider: Arc<dyn BluetoothProvider>,
+    _pairing_watcher: Option<PairingWatcher>,
+    _pnp_notification: Option<PnpNotification>,
+    _radio_watcher: Option<RadioWatcher>,
+    _advertisement_watcher: Option<AdvertisementWatcher>,
+    battery_property_watcher: Option<BatteryPropertyWatcher>,
+    tx: UnboundedSender<EngineEvent>,
+    /// 系统睡眠时暂停定时刷新
+    suspended: Arc<AtomicBool>,
+    /// 用户暂停监控时停止刷新、监听，并丢弃设备事件
+    paused: Arc<AtomicBool>,
+    /// 设备列表的版本，配对或取消配对时递增。
+    /// 此前开始的枚举或读取可能包含已移除的设备（或缺少新设备），其结果被丢弃
+    generation: Arc<AtomicU64>,
+    resume_timer: Option<JoinHandle<()>>,
+    /// 定时全量刷新，Drop 时取消，其余任务随发送端释放而结束
+    poll_task: JoinHandle<()>,
+    watchdog_task: JoinHandle<()>,
+    /// 与事件循环共享的最新设备信息，监听任务据此判断是否有变化
+    bluetooth_info: Arc<Mutex<HashSet<BluetoothInfo>>>,
+    watchers: WatcherRegistry,
+}
+
+/// 首次枚举最多尝试 8 次，间隔 2 秒起翻倍，最长 60 秒（共约 4 分钟）
+const INIT_RETRY: RetryPolicy =
+    RetryPolicy::new(8, Duration::from_secs(2), Duration::from_secs(60));
+
+/// 退出时等待设备监听关闭设备端通知的最长时间
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 看门狗检查设备监听的间隔
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(60);
+/// 超过该时间仍未完成订阅时视为卡住
+const WATCH_SUBSCRIBE_TIMEOUT: Duration = Duration::from_secs(2 * 60);
+/// 轮询电量的监听超过该时间没有完成轮询时视为卡住
+const WATCH_POLL_TIMEOUT: Duration = Duration::from_secs(2 * 60);
+/// 已连接的 BLE 设备超过该时间没有任何 GATT 通知时重新订阅
+const WATCH_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// 监听最近一次完成订阅、收到设备事件或完成轮询的时间，订阅完成前为 `None`
+type Heartbeat = Arc<Mutex<Option<Instant>>>;
+
+fn beat(heartbeat: &Heartbeat) {
+    *heartbeat.lock().unwrap() = Some(Instant::now());
+}
+
+/// 监听任务完成订阅后放入其中。取消监听时取出并等待 `unsubscribe` 关闭设备端的通知；
+/// 任务自行结束时仍在其中的订阅随之 Drop，只注销事件
+#[derive(Clone, Default)]
+struct SubscriptionSlot(Arc<Mutex<Option<Box<dyn DeviceSubscription>>>>);
+
+impl SubscriptionSlot {
+    fn set(&self, subscription: Box<dyn DeviceSubscription>) {
+        *self.0.lock().unwrap() = Some(subscription);
+    }
+
+    fn take(&self) -> Option<Box<dyn DeviceSubscription>> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+/// 监听任务持有，任务结束或被取消时释放仍未取出的订阅
+struct SubscriptionGuard(SubscriptionSlot);
+
+/// `DeviceWatch` 与其监听任务共享的状态
+#[derive(Clone, Default)]
+struct WatchShared {
+    /// `abort` 无法中断 `spawn_blocking` 中的阻塞调用，阻塞调用开始前检查此标志
+    cancelled: Arc<AtomicBool>,
+    heartbeat: Heartbeat,
+    subscription: SubscriptionSlot,
+}
+
+impl Drop for SubscriptionGuard {
+    fn drop(&mut self) {
+        drop(self.0.take());
+    }
+}
+
+/// 看门狗重建监听的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WatchFailure {
+    /// 任务已退出（出错或 panic）
+    Exited,
+    /// 订阅长时间没有完成
+    Stuck,
+    /// 轮询电量长时间没有完成
+    Stalled,
+    /// 已连接的 BLE 设备长时间没有通知，订阅可能已失效，也可能只是电量没有变化，不发送通知
+    Idle,
+}
+
+impl WatchFailure {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Exited => "exited",
+            Self::Stuck => "is stuck subscribing",
+            Self::Stalled => "stopped polling",
+            Self::Idle => "has been idle",
+        }
+    }
+}
+
+struct DeviceWatch {
+    handle: JoinHandle<()>,
+    poll_battery: bool,
+    started: Instant,
+    shared: WatchShared,
+    /// 由看门狗重建，重建后仍未完成订阅时不再重复通知
+    restarted: bool,
+}
+
+impl DeviceWatch {
+    /// 停止任务，已完成订阅时在后台关闭设备端的通知，返回该后台任务
+    fn cancel(&self, runtime: &Handle) -> Option<JoinHandle<()>> {
+        self.shared.cancelled.store(true, Ordering::SeqCst);
+        let subscription = self.shared.subscription.take();
+        self.handle.abort();
+        subscription.map(|subscription| runtime.spawn(subscription.unsubscribe()))
+    }
+
+    /// 根据任务状态及心跳判断监听是否失效，`device` 为设备的最新信息
+    fn failure(&self, device: &BluetoothInfo) -> Option<WatchFailure> {
+        if self.handle.is_finished() {
+            return Some(WatchFailure::Exited);
+        }
+
+        let heartbeat = *self.shared.heartbeat.lock().unwrap();
+        match heartbeat {
+            None => {
+                (self.started.elapsed() > WATCH_SUBSCRIBE_TIMEOUT).then_some(WatchFailure::Stuck)
+            }
+            Some(beat) if self.poll_battery => {
+                (beat.elapsed() > WATCH_POLL_TIMEOUT).then_some(WatchFailure::Stalled)
+            }
+            Some(beat) if device.status && matches!(device.r#type, BluetoothType::LowEnergy) => {
+                (beat.elapsed() > WATCH_IDLE_TIMEOUT).then_some(WatchFailure::Idle)
+            }
+            Some(_) => None,
+        }
+    }
+}
+
+/// 按地址登记的设备监听，替换、移除或 Drop 时取消对应的监听
+struct WatcherRegistry {
+    runtime: Handle,
+    watches: HashMap</* address */ u64, DeviceWatch>,
+}
+
+impl WatcherRegistry {
+    fn new(runtime: Handle) -> Self {
+        WatcherRegistry {
+            runtime,
+            watches: HashMap::new(),
+        }
+    }
+
+    fn contains(&self, address: u64) -> bool {
+        self.watches.contains_key(&address)
+    }
+
+    fn get(&self, address: u64) -> Option<&DeviceWatch> {
+        self.watches.get(&address)
+    }
+
+    /// 已有同一设备的监听时先取消
+    fn insert(&mut self, address: u64, watch: DeviceWatch) {
+        if let Some(previous) = self.watches.insert(address, watch) {
+            previous.cancel(&self.runtime);
+        }
+    }
+
+    fn cancel(&mut self, address: u64) {
+        if let Some(watch) = self.watches.remove(&address) {
+            watch.cancel(&self.runtime);
+        }
+    }
+
+    /// 取消不满足条件的监听
+    fn retain(&mut self, mut keep: impl FnMut(u64, &DeviceWatch) -> bool) {
+        self.watches.retain(|address, watch| {
+            let keep = keep(*address, watch);
+            if !keep {
+                watch.cancel(&self.runtime);
+            }
+            keep
+        });
+    }
+
+    fn cancel_all(&mut self) {
+        for (_, watch) in self.watches.drain() {
+            watch.cancel(&self.runtime);
+        }
+    }
+
+    /// 取消所有监听并返回关闭设备端通知的任务，用于等待其完成
+    fn cancel_all_and_take(&mut self) -> Vec<JoinHandle<()>> {
+        self.watches
+            .drain()
+            .filter_map(|(_, watch)| watch.cancel(&self.runtime))
+            .collect()
+    }
+}
+
+impl Drop for WatcherRegistry {
+    fn drop(&mut self) {
+        self.cancel_all();
+    }
+}
+
+impl UpdateEngine {
+    pub fn start(
+        runtime: Handle,
+        config: Arc<Config>,
+        provider: Arc<dyn BluetoothProvider>,
+        bluetooth_info: Arc<Mutex<HashSet<BluetoothInfo>>>,
+        proxy: EventLoopProxy<UserEvent>,
+    ) -> Self {
+        let (tx, rx) = unbounded_channel();
+
+        let paused = Arc::new(AtomicBool::new(false));
+        let generation = Arc::new(AtomicU64::new(0));
+        runtime.spawn(forward_events(
+            rx,
+            Arc::clone(&paused),
+            Arc::clone(&generation),
+            config.smoothing_options.hysteresis,
+            Arc::clone(&bluetooth_info),
+            proxy,
+        ));
+        let suspended = Arc::new(AtomicBool::new(false));
+        let poll_task = runtime.spawn(poll_loop(
+            config,
+            Arc::clone(&provider),
+            Arc::clone(&bluetooth_info),
+            Arc::clone(&suspended),
+            Arc::clone(&paused),
+            Arc::clone(&generation),
+            tx.clone(),
+        ));
+        let watchdog_task = runtime.spawn(watchdog_loop(Arc::clone(&suspended), tx.clone()));
+        let watchers = WatcherRegistry::new(runtime.clone());
+
+        let mut engine = UpdateEngine {
+            runtime,
+            provider,
+            _pairing_watcher: None,
+            _pnp_notification: None,
+            _radio_watcher: None,
+            _advertisement_watcher: None,
+            battery_property_watcher: None,
+            tx,
+            suspended,
+            paused,
+            generation,
+            resume_timer: None,
+            poll_task,
+            watchdog_task,
+            bluetooth_info,
+            watchers,
+        };
+
+        // 模拟设备的变化全部来自提供者本身
+        if !engine.provider.is_simulated() {
+            engine.watch_system_events();
+        }
+
+        engine
+    }
+
+    /// 监听配对变化、PnP 电量、BLE 广播电量及蓝牙适配器开关
+    fn watch_system_events(&mut self) {
+        let (pairing_tx, pairing_rx) = unbounded_channel();
+        self._pairing_watcher = PairingWatcher::start(pairing_tx)
+            .inspect_err(|e| warn!("Failed to watch Bluetooth pairing changes - {e}"))
+            .ok();
+        self.runtime.spawn(handle_pairing_events(
+            pairing_rx,
+            Arc::clone(&self.generation),
+            self.tx.clone(),
+        ));
+
+        let (pnp_tx, pnp_rx) = unbounded_channel();
+        self._pnp_notification = PnpNotification::register(pnp_tx.clone())
+            .inspect_err(|e| warn!("{e}"))
+            .ok();
+        self.battery_property_watcher = BatteryPropertyWatcher::start(pnp_tx)
+            .inspect_err(|e| warn!("Failed to watch PnP battery changes - {e}"))
+            .ok();
+        self.runtime.spawn(handle_pnp_events(
+            pnp_rx,
+            Arc::clone(&self.bluetooth_info),
+            self.tx.clone(),
+        ));
+
+        let (advertisement_tx, advertisement_rx) = unbounded_channel();
+        self._advertisement_watcher = AdvertisementWatcher::start(advertisement_tx)
+            .inspect_err(|e| warn!("Failed to watch BLE advertisements - {e}"))
+            .ok();
+        self.runtime.spawn(handle_advertisement_events(
+            advertisement_rx,
+            Arc::clone(&self.bluetooth_info),
+            self.tx.clone(),
+        ));
+
+        let (radio_tx, radio_rx) = unbounded_channel();
+        self._radio_watcher = RadioWatcher::start(radio_tx)
+            .inspect_err(|e| warn!("Failed to watch the Bluetooth radio - {e}"))
+            .ok();
+        self.runtime
+            .spawn(handle_radio_events(radio_rx, self.tx.clone()));
+    }
+
+    /// 在后台枚举所有设备，完成后发送 `UserEvent::BluetoothInfoReady`
+    pub fn refresh(&self, force: bool) {
+        let tx = self.tx.clone();
+        let provider = Arc::clone(&self.provider);
+        let generation = self.generation.load(Ordering::SeqCst);
+        self.runtime.spawn(async move {
+            let result = enumerate_bluetooth_info(provider).await;
+            let _ = tx.send(EngineEvent::BluetoothInfoReady {
+                result,
+                force,
+                generation,
+            });
+        });
+    }
+
+    /// 等待 `delay` 后进行首次枚举，失败时按指数退避重试，完成后发送 `UserEvent::Initialized`。
+    /// 枚举期间每读取到一个设备发送 `UserEvent::InitialDevice`，托盘无需等待所有设备
+    pub fn initialize(&self, delay: Duration) {
+        let tx = self.tx.clone();
+        let provider = Arc::clone(&self.provider);
+        let device_list_generation = Arc::clone(&self.generation);
+        self.runtime.spawn(async move {
+            tokio::time::sleep(delay).await;
+
+            let generation = device_list_generation.load(Ordering::SeqCst);
+            let result = INIT_RETRY
+                .retry_async("Initialization", || {
+                    enumerate_bluetooth_info_progressively(Arc::clone(&provider), tx.clone())
+                })
+                .await;
+
+            let _ = tx.send(EngineEvent::Initialized { result, generation });
+        });
+    }
+
+    pub fn set_suspended(&self, suspended: bool) {
+        self.suspended.store(suspended, Ordering::SeqCst);
+    }
+
+    /// 在后台只重新读取单个设备，完成后发送 `UserEvent::UpdateTrayForBluetooth`
+    pub fn refresh_device(&self, device: BluetoothInfo) {
+        let tx = self.tx.clone();
+        let provider = Arc::clone(&self.provider);
+        let generation = self.generation.load(Ordering::SeqCst);
+        self.runtime.spawn(async move {
+            let name = device.name.clone();
+            let result = tokio::task::spawn_blocking(move || provider.get_device(&device));
+            let result = match result.await {
+                Ok(result) => result,
+                Err(e) => Err(e.into()),
+            };
+
+            match result {
+                Ok(info) => {
+                    let _ = tx.send(EngineEvent::DeviceRefreshed { info, generation });
+                }
+                Err(e) => app_notify(format!("Failed to refresh {name} - {e}")),
+            }
+        });
+    }
+
+    /// 暂停监控，`duration` 为 `None` 时直到手动恢复
+    pub fn pause(&mut self, duration: Option<Duration>) {
+        self.paused.store(true, Ordering::SeqCst);
+
+        self.watchers.cancel_all();
+
+        if let Some(resume_timer) = self.resume_timer.take() {
+            resume_timer.abort();
+        }
+        if let Some(duration) = duration {
+            let tx = self.tx.clone();
+            self.resume_timer = Some(self.runtime.spawn(async move {
+                tokio::time::sleep(duration).await;
+                let _ = tx.send(EngineEvent::PauseExpired);
+            }));
+        }
+    }
+
+    pub fn resume(&mut self) {
+        self.paused.store(false, Ordering::SeqCst);
+
+        if let Some(resume_timer) = self.resume_timer.take() {
+            resume_timer.abort();
+        }
+    }
+
+    /// 同步需要监听的设备：为新设备创建监听，取消已移除设备的监听，已退出的监听由看门狗重建。
+    /// `poll_battery_address` 为需要轮询 PnP 电量的经典蓝牙设备（托盘图标来源），
+    /// 已能收到电量属性变化事件时不再轮询
+    pub fn watch_devices(
+        &mut self,
+        devices: &HashSet<BluetoothInfo>,
+        poll_battery_address: Option<u64>,
+    ) {
+        if self.paused.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let poll_battery_address = poll_battery_address
+            .filter(|_| self.battery_property_watcher.is_none() && !self.provider.is_simulated());
+
+        let devices = devices
+            .iter()
+            .map(|device| (device.address, device))
+            .collect::<HashMap<_, _>>();
+
+        self.watchers.retain(|address, watch| {
+            devices.contains_key(&address)
+                && watch.poll_battery == (poll_battery_address == Some(address))
+        });
+
+        for (address, device) in devices {
+            if !self.watchers.contains(address) {
+                self.watch(device.clone(), poll_battery_address == Some(address));
+            }
+        }
+    }
+
+    /// 监听单个设备，已在监听的设备会先取消再重新监听
+    pub fn watch(&mut self, device: BluetoothInfo, poll_battery: bool) {
+        self.spawn_watch(device, poll_battery, false);
+    }
+
+    /// 看门狗：重建已退出、订阅或轮询卡住、以及长时间没有通知的监听。
+    /// 返回需要通知的设备名称，空闲重建及重建后仍失败的设备不再通知
+    pub fn restart_dead_watchers(&mut self, devices: &HashSet<BluetoothInfo>) -> Vec<String> {
+        if self.paused.load(Ordering::SeqCst) {
+            return Vec::new();
+        }
+
+        let mut dead = Vec::new();
+        for device in devices {
+            let Some(watch) = self.watchers.get(device.address) else {
+                continue;
+            };
+            let Some(failure) = watch.failure(device) else {
+                continue;
+            };
+            warn!(
+                "[{}]: Device watch {}, restarting",
+                device.name,
+                failure.as_str()
+            );
+            let failed_again = watch.restarted && watch.shared.heartbeat.lock().unwrap().is_none();
+            let notify = failure != WatchFailure::Idle && !failed_again;
+            dead.push((device.clone(), watch.poll_battery, notify));
+        }
+
+        let mut names = Vec::new();
+        for (device, poll_battery, notify) in dead {
+            if notify {
+                names.push(device.name.clone());
+            }
+            self.spawn_watch(device, poll_battery, true);
+        }
+        names
+    }
+
+    fn spawn_watch(&mut self, device: BluetoothInfo, poll_battery: bool, restarted: bool) {
+        self.unwatch(device.address);
+
+        let address = device.address;
+        let tx = self.tx.clone();
+        let provider = Arc::clone(&self.provider);
+        let bluetooth_info = Arc::clone(&self.bluetooth_info);
+        let shared = WatchShared::default();
+        let watch_shared = shared.clone();
+        let handle = self.runtime.spawn(async move {
+            let name = device.name.clone();
+            info!("[{name}]: Starting the device watch...");
+            if let Err(e) = watch_device(
+                provider,
+                device,
+                poll_battery,
+                bluetooth_info,
+                tx,
+                watch_shared,
+            )
+            .await
+            {
+                warn!("[{name}]: Failed to watch device - {e}");
+            }
+        });
+
+        self.watchers.insert(
+            address,
+            DeviceWatch {
+                handle,
+                poll_battery,
+                started: Instant::now(),
+                shared,
+                restarted,
+            },
+        );
+    }
+
+    /// 取消任务时会释放其持有的事件注册
+    pub fn unwatch(&mut self, address: u64) {
+        self.watchers.cancel(address);
+    }
+
+    /// 退出前停止定时任务及所有监听，并等待各订阅关闭 GATT 通知，最多等待 `SHUTDOWN_TIMEOUT`。
+    /// 不能在运行时的线程中调用
+    pub fn shutdown(&mut self) {
+        self.paused.store(true, Ordering::SeqCst);
+        self.poll_task.abort();
+        self.watchdog_task.abort();
+        if let Some(resume_timer) = self.resume_timer.take() {
+            resume_timer.abort();
+        }
+
+        let unsubscribes = self.watchers.cancel_all_and_take();
+        let count = unsubscribes.len();
+        let finished = self
+            .runtime
+            .block_on(tokio::time::timeout(SHUTDOWN_TIMEOUT, async {
+                for unsubscribe in unsubscribes {
+                    let _ = unsubscribe.await;
+                }
+            }));
+        match finished {
+            Ok(()) => info!("Unsubscribed {count} device watches"),
+            Err(_) => warn!("Timed out unsubscribing {count} device watches"),
+        }
+    }
+}
+
+impl Drop for UpdateEngine {
+    fn drop(&mut self) {
+        self.poll_task.abort();
+        self.watchdog_task.abort();
+        if let Some(resume_timer) = self.resume_timer.take() {
+            resume_timer.abort();
+        }
+    }
+}
+
+/// 多个设备同时变化（如唤醒后）时，合并该时间内的设备变化，只刷新一次托盘
+const DEVICE_CHANGE_COALESCE_WINDOW: Duration = Duration::from_millis(200);
+
+async fn forward_events(
+    mut rx: UnboundedReceiver<EngineEvent>,
+    paused: Arc<AtomicBool>,
+    generation: Arc<AtomicU64>,
+    hysteresis: u8,
+    bluetooth_info: Arc<Mutex<HashSet<BluetoothInfo>>>,
+    proxy: EventLoopProxy<UserEvent>,
+) {
+    // 等待合并的设备变化，窗口从第一个变化开始计时，不因后续变化而延长
+    let mut pending_changes: Vec<BluetoothInfo> = Vec::new();
+    let mut flush_deadline = None;
+
+    loop {
+        let event = tokio::select! {
+            event = rx.recv() => match event {
+                Some(event) => event,
+                None => break,
+            },
+            _ = tokio::time::sleep_until(flush_deadline.unwrap_or_else(Instant::now)),
+                if flush_deadline.is_some() =>
+            {
+                flush_deadline = None;
+                let changes = std::mem::take(&mut pending_changes);
+                // 事件循环已退出
+                if proxy.send_event(UserEvent::UpdateTrayForBluetooth(changes)).is_err() {
+                    break;
+                }
+                continue;
+            }
+        };
+
+        // 设备列表变化前开始的读取结果已过期：单个设备的结果直接丢弃，
+        // 全量枚举的结果改为重新枚举，以免重新加入已移除的设备
+        let is_superseded = |started: u64| started != generation.load(Ordering::SeqCst);
+        let event = match event {
+            EngineEvent::BluetoothInfoReady {
+                result: Ok(_),
+                force,
+                generation: started,
+            } if is_superseded(started) => {
+                info!("Device list changed during the refresh, refreshing again");
+                EngineEvent::Refresh { force }
+            }
+            EngineEvent::Initialized {
+                result: Ok(_),
+                generation: started,
+            } if is_superseded(started) => {
+                info!("Device list changed during initialization, refreshing again");
+                EngineEvent::Refresh { force: true }
+            }
+            EngineEvent::DeviceRefreshed {
+                info,
+                generation: started,
+            } => {
+                if is_superseded(started) {
+                    info!(
+                        "[{}]: Device list changed, discarding the refresh",
+                        info.name
+                    );
+                    continue;
+                }
+                EngineEvent::DeviceChanged(info)
+            }
+            event => event,
+        };
+
+        // 暂停期间不更新设备信息，也不发送通知（初始化结果仍需结束“正在初始化”状态）
+        if paused.load(Ordering::SeqCst)
+            && !matches!(
+                event,
+                EngineEvent::RadioStateChanged(_)
+                    | EngineEvent::PauseExpired
+                    | EngineEvent::Initialized { .. }
+                    | EngineEvent::RedrawTray
+            )
+        {
+            continue;
+        }
+
+        let user_event = match event {
+            EngineEvent::Refresh { force } => UserEvent::UpdateTray(force),
+            EngineEvent::BluetoothInfoReady { result, force, .. } => {
+                let result = result.map(|infos| {
+                    let current = bluetooth_info.lock().unwrap();
+                    infos
+                        .into_iter()
+                        .map(|info| smooth_battery(&current, info, hysteresis))
+                        .collect()
+                });
+                UserEvent::BluetoothInfoReady(result, force)
+            }
+            EngineEvent::DeviceChanged(info) => {
+                let current = bluetooth_info.lock().unwrap().clone();
+                let smoothed = smooth_battery(&current, info.clone(), hysteresis);
+                // 窗口内同一设备只保留最后的变化，变回当前显示时不再刷新
+                pending_changes.retain(|pending| pending.address != info.address);
+                // 只有电量的小幅波动，平滑后与当前显示相同
+                if smoothed != info && current.contains(&smoothed) {
+                    if pending_changes.is_empty() {
+                        flush_deadline = None;
+                    }
+                    continue;
+                }
+                pending_changes.push(smoothed);
+                flush_deadline
+                    .get_or_insert_with(|| Instant::now() + DEVICE_CHANGE_COALESCE_WINDOW);
+                continue;
+            }
+            EngineEvent::DeviceAdded(info) => UserEvent::AddBluetoothInfo(info),
+            EngineEvent::DeviceRemoved(address) => UserEvent::RemoveBluetoothInfo(address),
+            EngineEvent::RadioStateChanged(state) => UserEvent::RadioStateChanged(state),
+            EngineEvent::PauseExpired => UserEvent::ResumeMonitoring,
+            EngineEvent::InitialDevice(info) => UserEvent::InitialDevice(info),
+            EngineEvent::Initialized { result, .. } => UserEvent::Initialized(result),
+            // 已在上面转换为 `DeviceChanged`
+            EngineEvent::DeviceRefreshed { .. } => continue,
+            EngineEvent::RedrawTray => UserEvent::RedrawTray,
+            EngineEvent::CheckWatchers => UserEvent::CheckWatchers,
+        };
+
+        // 其余事件前先发送已合并的设备变化，保持事件顺序
+        if !pending_changes.is_empty() {
+            flush_deadline = None;
+            let changes = std::mem::take(&mut pending_changes);
+            if proxy
+                .send_event(UserEvent::UpdateTrayForBluetooth(changes))
+                .is_err()
+            {
+                break;
+            }
+        }
+
+        // 事件循环已退出
+        if proxy.send_event(user_event).is_err() {
+            break;
+        }
+    }
+}
+
+/// 连接状态不变且电量变化小于 `hysteresis` 时沿用当前显示的电量，充满或耗尽时立即更新
+fn smooth_battery(
+    current: &HashSet<BluetoothInfo>,
+    mut info: BluetoothInfo,
+    hysteresis: u8,
+) -> BluetoothInfo {
+    if let Some(shown) = current.iter().find(|i| i.address == info.address)
+        && shown.status == info.status
+        && shown.battery.abs_diff(info.battery) < hysteresis
+        && !matches!(info.battery, 0 | 100)
+    {
+        info.battery = shown.battery;
+    }
+    info
+}
+
+async fn poll_loop(
+    config: Arc<Config>,
+    provider: Arc<dyn BluetoothProvider>,
+    bluetooth_info: Arc<Mutex<HashSet<BluetoothInfo>>>,
+    suspended: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    device_list_generation: Arc<AtomicU64>,
+    tx: UnboundedSender<EngineEvent>,
+) {
+    let mut last_bluetooth_info = None;
+    let mut stable_rounds = 0;
+
+    loop {
+        // 切换配置方案时不会读到新旧混合的刷新间隔
+        let update_interval = {
+            let _options = config.read_options();
+            if config.get_adaptive_update_interval() {
+                let current_bluetooth_info = bluetooth_info.lock().unwrap().clone();
+                if last_bluetooth_info.as_ref() == Some(&current_bluetooth_info) {
+                    stable_rounds += 1;
+                } else {
+                    stable_rounds = 0;
+                }
+
+                let update_interval =
+                    adaptive_update_interval(&config, &current_bluetooth_info, stable_rounds);
+                last_bluetooth_info = Some(current_bluetooth_info);
+                update_interval
+            } else {
+                config.get_update_interval()
+            }
+        };
+        let mut force = false;
+
+        for _ in 0..update_interval {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            if config.force_update.swap(false, Ordering::SeqCst) {
+                force = true;
+                break;
+            }
+            if config.refresh_tray.swap(false, Ordering::SeqCst)
+                && tx.send(EngineEvent::RedrawTray).is_err()
+            {
+                return;
+            }
+        }
+
+        if suspended.load(Ordering::SeqCst) || paused.load(Ordering::SeqCst) {
+            continue;
+        }
+
+        let generation = device_list_generation.load(Ordering::SeqCst);
+        let result = enumerate_bluetooth_info(Arc::clone(&provider)).await;
+        if tx
+            .send(EngineEvent::BluetoothInfoReady {
+                result,
+                force,
+                generation,
+            })
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+/// 定时请求事件循环检查设备监听；睡眠期间设备断开、任务停止，唤醒后跳过一次以免误判
+async fn watchdog_loop(suspended: Arc<AtomicBool>, tx: UnboundedSender<EngineEvent>) {
+    let mut interval = tokio::time::interval(WATCHDOG_INTERVAL);
+    let mut skip_next = false;
+    loop {
+        interval.tick().await;
+
+        if suspended.load(Ordering::SeqCst) {
+            skip_next = true;
+            continue;
+        }
+        if std::mem::take(&mut skip_next) {
+            continue;
+        }
+
+        if tx.send(EngineEvent::CheckWatchers).is_err() {
+            break;
+        }
+    }
+}
+
+const ADAPTIVE_MIN_INTERVAL: u64 = 30;
+const ADAPTIVE_MAX_INTERVAL: u64 = 1800;
+
+/// 智能调整模式下的全量刷新间隔（秒），以 `update_interval` 为基准：
+/// - 刚发生变化：缩短到 30 秒
+/// - 已连接的设备低电量：不超过 1 分钟
+/// - 所有设备均未连接且持续无变化：每轮翻倍，最长 30 分钟
+fn adaptive_update_interval(
+    config: &Config,
+    bluetooth_info: &HashSet<BluetoothInfo>,
+    stable_rounds: u32,
+) -> u64 {
+    let update_interval = config.get_update_interval();
+    let low_battery = config.get_low_battery();
+
+    let recently_changed = stable_rounds == 0;
+    let has_low_battery = bluetooth_info
+        .iter()
+        .any(|info| info.status && info.battery <= low_battery);
+    let all_disconnected = bluetooth_info.iter().all(|info| !info.status);
+
+    if recently_changed {
+        update_interval.min(ADAPTIVE_MIN_INTERVAL)
+    } else if has_low_battery {
+        update_interval.min(60)
+    } else if all_disconnected {
+        update_interval
+            .saturating_mul(1 << stable_rounds.min(6))
+            .clamp(update_interval, ADAPTIVE_MAX_INTERVAL.max(update_interval))
+    } else {
+        update_interval
+    }
+}
+
+/// 处理 PnP 通知及电量属性变化：重新读取该实例的电量，与现有设备信息不同时发送
+async fn handle_pnp_events(
+    mut rx: UnboundedReceiver</* Instance ID */ String>,
+    bluetooth_info: Arc<Mutex<HashSet<BluetoothInfo>>>,
+    tx: UnboundedSender<EngineEvent>,
+) {
+    while let Some(instance_id) = rx.recv().await {
+        let pnp_info = tokio::task::spawn_blocking(move || get_pnp_device_info(&instance_id));
+        // 同一设备有多个 BTHENUM 实例，只有其中之一带有电量属性
+        let Ok(Ok(pnp_info)) = pnp_info.await else {
+            continue;
+        };
+
+        let changed_info = bluetooth_info
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|i| i.address == pnp_info.address)
+            .and_then(|i| {
+                let battery = calibrate_battery(i, pnp_info.battery);
+                (i.battery != battery).then(|| BluetoothInfo {
+                    battery,
+                    ..i.clone()
+                })
+            });
+
+        if let Some(info) = changed_info {
+            info!("[{}]: Battery -> {}", info.name, info.battery);
+            if tx.send(EngineEvent::DeviceChanged(info)).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// 广播中的电量只更新已知的未连接 BLE 设备，已连接的设备由 GATT 通知更新
+async fn handle_advertisement_events(
+    mut rx: UnboundedReceiver<(/* address */ u64, /* battery */ u8)>,
+    bluetooth_info: Arc<Mutex<HashSet<BluetoothInfo>>>,
+    tx: UnboundedSender<EngineEvent>,
+) {
+    while let Some((address, battery)) = rx.recv().await {
+        let changed_info = bluetooth_info
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|i| i.address == address && i.r#type == BluetoothType::LowEnergy && !i.status)
+            .and_then(|i| {
+                let battery = calibrate_battery(i, battery);
+                (i.battery != battery).then(|| BluetoothInfo {
+                    battery,
+                    ..i.clone()
+                })
+            });
+
+        if let Some(info) = changed_info {
+            info!("[{}]: Advertised battery -> {}", info.name, info.battery);
+            if tx.send(EngineEvent::DeviceChanged(info)).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// 收到适配器变化信号后重新读取适配器状态，只在状态改变时发送
+async fn handle_radio_events(mut rx: UnboundedReceiver<()>, tx: UnboundedSender<EngineEvent>) {
+    let mut last_state = None;
+
+    while rx.recv().await.is_some() {
+        let state = match tokio::task::spawn_blocking(get_bluetooth_radio_state).await {
+            Ok(Ok(state)) => state,
+            Ok(Err(e)) => {
+                warn!("Failed to get the Bluetooth radio state - {e}");
+                continue;
+            }
+            Err(e) => {
+                warn!("{e}");
+                continue;
+            }
+        };
+
+        if last_state.replace(state) == Some(state) {
+            continue;
+        }
+
+        info!("Bluetooth radio state -> {state:?}");
+        if tx.send(EngineEvent::RadioStateChanged(state)).is_err() {
+            break;
+        }
+    }
+}
+
+/// 枚举设备及读取 GATT 可能耗时数秒，放在阻塞线程池中执行。
+/// 读取失败的设备留给托盘菜单显示
+async fn enumerate_bluetooth_info(
+    provider: Arc<dyn BluetoothProvider>,
+) -> Result<HashSet<BluetoothInfo>> {
+    let scan = tokio::task::spawn_blocking(move || provider.get_devices()).await??;
+    set_device_errors(scan.errors);
+    Ok(scan.devices)
+}
+
+async fn enumerate_bluetooth_info_progressively(
+    provider: Arc<dyn BluetoothProvider>,
+    tx: UnboundedSender<EngineEvent>,
+) -> Result<HashSet<BluetoothInfo>> {
+    let scan = tokio::task::spawn_blocking(move || {
+        provider.get_devices_progressively(&|info| {
+            let _ = tx.send(EngineEvent::InitialDevice(info.clone()));
+        })
+    })
+    .await??;
+    set_device_errors(scan.errors);
+    Ok(scan.devices)
+}
+
+async fn watch_device(
+    provider: Arc<dyn BluetoothProvider>,
+    mut device: BluetoothInfo,
+    poll_battery: bool,
+    bluetooth_info: Arc<Mutex<HashSet<BluetoothInfo>>>,
+    tx: UnboundedSender<EngineEvent>,
+    shared: WatchShared,
+) -> Result<()> {
+    let WatchShared {
+        cancelled,
+        heartbeat,
+        subscription,
+    } = shared;
+    let (update_tx, mut update_rx) = unbounded_channel();
+
+    // 阻塞线程繁忙时订阅可能在监听取消后才开始，此时不再订阅
+    let watched_device = device.clone();
+    let subscribe_cancelled = Arc::clone(&cancelled);
+    let device_subscription = tokio::task::spawn_blocking(move || {
+        if subscribe_cancelled.load(Ordering::SeqCst) {
+            return Err(anyhow!("Device watch cancelled"));
+        }
+        provider.watch_device(&watched_device, update_tx)
+    })
+    .await??;
+    subscription.set(device_subscription);
+    let _subscription = SubscriptionGuard(subscription);
+    beat(&heartbeat);
+
+    loop {
+        let update = match &device.r#type {
+            BluetoothType::Classic(instance_id) => tokio::select! {
+                update = update_rx.recv() => update,
+                _ = tokio::time::sleep(pnp_poll_interval(&device)), if poll_battery => {
+                    let instance_id = instance_id.clone();
+                    let poll_cancelled = Arc::clone(&cancelled);
+                    let pnp_info = tokio::task::spawn_blocking(move || {
+                        if poll_cancelled.load(Ordering::SeqCst) {
+                            return Err(anyhow!("Device watch cancelled"));
+                        }
+                        get_pnp_device_info(&instance_id)
+                    });
+                    let pnp_info = pnp_info.await?;
+                    beat(&heartbeat);
+                    match pnp_info {
+                        Ok(pnp_info) => Some(DeviceUpdate::BatteryLevel(pnp_info.battery)),
+                        Err(e) => {
+                            warn!("[{}]: {e}", device.name);
+                            continue;
+                        }
+                    }
+                }
+            },
+            BluetoothType::LowEnergy => update_rx.recv().await,
+        };
+
+        let Some(update) = update else {
+            return Err(anyhow!("Device event channel closed"));
+        };
+        beat(&heartbeat);
+
+        // 全量刷新也会更新设备信息，以共享的最新信息为准
+        if let Some(latest) = bluetooth_info
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|i| i.address == device.address)
+        {
+            device = latest.clone();
+        }
+
+        let mut new_info = device.clone();
+        match update {
+            DeviceUpdate::BatteryLevel(battery) => {
+                new_info.battery = calibrate_battery(&device, battery)
+            }
+            DeviceUpdate::ConnectionStatus(status) => new_info.status = status,
+        }
+
+        if new_info != device {
+            info!(
+                "[{}]: Status -> {}, Battery -> {}",
+                new_info.name, new_info.status, new_info.battery
+            );
+            tx.send(EngineEvent::DeviceChanged(new_info.clone()))?;
+            device = new_info;
+        }
+    }
+}
+
+fn pnp_poll_interval(device: &BluetoothInfo) -> Duration {
+    match device {
+        _ if !device.status => Duration::from_secs(5), // 未连接
+        _ if device.battery <= 30 => Duration::from_secs(7), // 低电量
+        _ => Duration::from_secs(10),                  // 已连接且电量充足
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum DeviceKind {
+    Classic,
+    LowEnergy,
+}
+
+#[derive(Debug)]
+enum PairingEvent {
+    Added { id: String, kind: DeviceKind },
+    Removed { id: String },
+    EnumerationCompleted(DeviceKind),
+}
+
+/// 监听已配对设备列表的变化，Drop 时停止监听并注销事件
+struct PairingWatcher {
+    watchers: Vec<(DeviceWatcher, [i64; 3])>,
+}
+
+impl PairingWatcher {
+    fn start(tx: UnboundedSender<PairingEvent>) -> Result<Self> {
+        let mut pairing_watcher = PairingWatcher {
+            watchers: Vec::new(),
+        };
+
+        let selectors = [
+            (
+                BluetoothDevice::GetDeviceSelectorFromPairingState(true)?,
+                DeviceKind::Classic,
+            ),
+            (
+                BluetoothLEDevice::GetDeviceSelectorFromPairingState(true)?,
+                DeviceKind::LowEnergy,
+            ),
+        ];
+
+        for (selector, kind) in selectors {
+            let watcher = DeviceInformation::CreateWatcherAqsFilter(&selector)?;
+
+            let tx_added = tx.clone();
+            let added_token = watcher.Added(&TypedEventHandler::new(
+                move |_watcher: Ref<DeviceWatcher>, device_info: Ref<DeviceInformation>| {
+                    if let Some(device_info) = device_info.as_ref() {
+                        let id = device_info.Id()?.to_string();
+                        let _ = tx_added.send(PairingEvent::Added { id, kind });
+                    }
+                    Ok(())
+                },
+            ))?;
+
+            let tx_removed = tx.clone();
+            let removed_token = watcher.Removed(&TypedEventHandler::new(
+                move |_watcher: Ref<DeviceWatcher>, update: Ref<DeviceInformationUpdate>| {
+                    if let Some(update) = update.as_ref() {
+                        let id = update.Id()?.to_string();
+                        let _ = tx_removed.send(PairingEvent::Removed { id });
+                    }
+                    Ok(())
+                },
+            ))?;
+
+            let tx_completed = tx.clone();
+            let completed_token = watcher.EnumerationCompleted(&TypedEventHandler::new(
+                move |_watcher: Ref<DeviceWatcher>, _args: Ref<IInspectable>| {
+                    let _ = tx_completed.send(PairingEvent::EnumerationCompleted(kind));
+                    Ok(())
+                },
+            ))?;
+
+            pairing_watcher.watchers.push((
+                watcher.clone(),
+                [added_token, removed_token, completed_token],
+            ));
+
+            watcher.Start()?;
+        }
+
+        Ok(pairing_watcher)
+    }
+}
+
+impl Drop for PairingWatcher {
+    fn drop(&mut self) {
+        for (watcher, [added_token, removed_token, completed_token]) in &self.watchers {
+            let _ = watcher.Stop();
+            let _ = watcher.RemoveAdded(*added_token);
+            let _ = watcher.RemoveRemoved(*removed_token);
+            let _ = watcher.RemoveEnumerationCompleted(*completed_token);
+        }
+    }
+}
+
+async fn handle_pairing_events(
+    mut rx: UnboundedReceiver<PairingEvent>,
+    generation: Arc<AtomicU64>,
+    tx: UnboundedSender<EngineEvent>,
+) {
+    // 初次枚举完成前的 Added 事件对应已配对的设备，只记录其地址
+    let mut enumeration_completed = HashSet::new();
+    let mut paired_devices: HashMap</* Device ID */ String, /* address */ u64> = HashMap::new();
+
+    while let Some(event) = rx.recv().await {
+        let engine_event = match event {
+            PairingEvent::EnumerationCompleted(kind) => {
+                enumeration_completed.insert(kind);
+                continue;
+            }
+            PairingEvent::Added { id, kind } => {
+                let is_new = enumeration_completed.contains(&kind);
+                let device_id = id.clone();
+                let read_result = tokio::task::spawn_blocking(move || {
+                    read_paired_device(&device_id, kind, is_new)
+                });
+                let read_result = match read_result.await {
+                    Ok(read_result) => read_result,
+                    Err(e) => Err(e.into()),
+                };
+
+                match read_result {
+                    Ok((address, info)) => {
+                        paired_devices.insert(id, address);
+                        match info {
+                            Some(info) => EngineEvent::DeviceAdded(info),
+                            None if is_new => EngineEvent::Refresh { force: true },
+                            None => continue,
+                        }
+                    }
+                    Err(e) => {
+                        warn!("Failed to read paired device ({id}) - {e}");
+                        continue;
+                    }
+                }
+            }
+            PairingEvent::Removed { id } => match paired_devices.remove(&id) {
+                Some(address) => EngineEvent::DeviceRemoved(address),
+                None => continue,
+            },
+        };
+
+        // 先于事件递增，事件之后到达的旧结果均会被丢弃
+        if matches!(
+            engine_event,
+            EngineEvent::DeviceAdded(_) | EngineEvent::DeviceRemoved(_)
+        ) {
+            generation.fetch_add(1, Ordering::SeqCst);
+        }
+
+        if tx.send(engine_event).is_err() {
+            break;
+        }
+    }
+}
+
+/// 返回设备地址，新配对的设备同时读取其信息（电量已校准，读取失败时为 `None`，等待全量刷新）
+fn read_paired_device(
+    id: &str,
+    kind: DeviceKind,
+    is_new: bool,
+) -> Result<(u64, Option<BluetoothInfo>)> {
+    let id = HSTRING::from(id);
+
+    match kind {
+        DeviceKind::Classic => {
+            let btc_device = BluetoothDevice::FromIdAsync(&id)?.get()?;
+            let address = btc_device.BluetoothAddress()?;
+            let info = is_new
+                .then(|| process_btc_device(&btc_device, &get_pnp_devices_info()?))
+                .transpose()
+                .inspect_err(|e| warn!("{e}"))
+                .ok()
+                .flatten();
+            Ok((address, info.map(BluetoothInfo::calibrated)))
+        }
+        DeviceKind::LowEnergy => {
+            let ble_device = BluetoothLEDevice::FromIdAsync(&id)?.get()?;
+            let address = ble_device.BluetoothAddress()?;
+            let info = is_new
+                .then(|| process_ble_device(&ble_device))
+                .transpose()
+                .inspect_err(|e| warn!("{e}"))
+                .ok()
+                .flatten();
+            Ok((address, info.map(BluetoothInfo::calibrated)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bluetooth::provider::mock_device, config::test_config};
+
+    fn device(address: u64, battery: u8, status: bool) -> BluetoothInfo {
+        BluetoothInfo {
+            status,
+            ..mock_device(&format!("Device {address}"), address, battery)
+        }
+    }
+
+    #[test]
+    fn small_battery_changes_are_smoothed() {
+        let current = HashSet::from([device(1, 50, true)]);
+        let smoothed = |battery, status| smooth_battery(&current, device(1, battery, status), 3);
+
+        assert_eq!(smoothed(52, true).battery, 50);
+        assert_eq!(smoothed(48, true).battery, 50);
+        assert_eq!(smoothed(53, true).battery, 53);
+        // 连接状态变化时立即更新
+        assert_eq!(smoothed(52, false).battery, 52);
+        assert_eq!(smooth_battery(&current, device(2, 52, true), 3).battery, 52);
+    }
+
+    #[test]
+    fn full_and_empty_are_not_smoothed() {
+        let current = HashSet::from([device(1, 99, true), device(2, 1, true)]);
+        assert_eq!(
+            smooth_battery(&current, device(1, 100, true), 3).battery,
+            100
+        );
+        assert_eq!(smooth_battery(&current, device(2, 0, true), 3).battery, 0);
+    }
+
+    #[test]
+    fn adaptive_interval_follows_device_state() {
+        let config = test_config("adaptive_update_interval");
+        config
+            .tray_options
+            .update_interval
+            .store(300, Ordering::Relaxed);
+        let connected = HashSet::from([device(1, 80, true)]);
+        let low_battery = HashSet::from([device(1, 10, true)]);
+        let disconnected = HashSet::from([device(1, 80, false)]);
+
+        assert_eq!(adaptive_update_interval(&config, &connected, 0), 30);
+        assert_eq!(adaptive_update_interval(&config, &connected, 3), 300);
+        assert_eq!(adaptive_update_interval(&config, &low_battery, 3), 60);
+        assert_eq!(adaptive_update_interval(&config, &disconnected, 1), 600);
+        assert_eq!(adaptive_update_interval(&config, &disconnected, 2), 1200);
+        assert_eq!(adaptive_update_interval(&config, &disconnected, 10), 1800);
+    }
+}
@@ -1,209 +1,507 @@
 use crate::{
-    bluetooth::{
-        ble::{find_ble_devices, get_ble_info},
-        btc::{find_btc_devices, get_btc_info},
-    },
-    config::Config,
-    language::{Language, Localization},
-    notify::{app_notify, notify},
+    bluetooth::events::DeviceEvent,
+    command::run_commands,
+    config::{BatteryDisplay, CommandOptions, Config, SpeechOptions},
+    debounce::{ConnectionOutcome, debounce_connection},
+    language::Localization,
+    notify::{NotifyEvent, notify},
+    speech::speak_event,
 };
 
 use std::{
-    collections::HashSet,
-    sync::{Arc, Mutex},
+    collections::{HashMap, HashSet},
+    sync::{Arc, LazyLock, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
-use anyhow::{Result, anyhow};
-use log::{info, warn};
-use windows::Devices::Bluetooth::{BluetoothDevice, BluetoothLEDevice};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug)]
-pub enum BluetoothType {
-    Classic(/* Instance ID */ String),
-    LowEnergy,
+pub use bluegauge_core::info::*;
+
+/// 最近一次枚举中读取失败的设备，显示在托盘菜单中
+static DEVICE_ERRORS: Mutex<Vec<DeviceError>> = Mutex::new(Vec::new());
+
+pub fn get_device_errors() -> Vec<DeviceError> {
+    DEVICE_ERRORS.lock().unwrap().clone()
+}
+
+pub fn set_device_errors(errors: Vec<DeviceError>) {
+    *DEVICE_ERRORS.lock().unwrap() = errors;
+}
+
+/// 各设备最后一次报告数据的时间（Unix 时间戳，秒）。
+/// 未连接的设备不会更新电量，其时间停留在最后一次连接时
+static LAST_UPDATED: LazyLock<Mutex<HashMap</* address */ u64, u64>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// 记录已连接设备的最新数据时间
+pub fn record_last_updated(bluetooth_devices_info: &HashSet<BluetoothInfo>) {
+    let now = now_timestamp();
+    let mut last_updated = LAST_UPDATED.lock().unwrap();
+    for info in bluetooth_devices_info.iter().filter(|info| info.status) {
+        last_updated.insert(info.address, now);
+    }
+}
+
+/// 启动时以电量历史中最后一次连接的时间补全尚未记录的设备
+pub fn seed_last_updated(timestamps: HashMap</* address */ u64, u64>) {
+    let mut last_updated = LAST_UPDATED.lock().unwrap();
+    for (address, timestamp) in timestamps {
+        last_updated.entry(address).or_insert(timestamp);
+    }
+}
+
+/// 设备最后一次报告数据的时间，没有记录时为 `None`
+pub fn get_last_updated(address: u64) -> Option<u64> {
+    LAST_UPDATED.lock().unwrap().get(&address).copied()
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug)]
-pub struct BluetoothInfo {
-    pub name: String,
+/// 设备数据距今的秒数，没有记录时为 `None`
+pub fn get_data_age(address: u64) -> Option<u64> {
+    Some(now_timestamp().saturating_sub(get_last_updated(address)?))
+}
+
+/// 设备断开连接前最后的电量及断开的时间（Unix 时间戳，秒）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LastSeen {
     pub battery: u8,
-    pub status: bool,
-    pub address: u64,
-    pub r#type: BluetoothType,
-}
-
-pub fn find_bluetooth_devices() -> Result<(Vec<BluetoothDevice>, Vec<BluetoothLEDevice>)> {
-    let bt_devices = find_btc_devices()?;
-    let ble_devices = find_ble_devices()?;
-    Ok((bt_devices, ble_devices))
-}
-
-pub fn get_bluetooth_info(
-    bt_devices: (&[BluetoothDevice], &[BluetoothLEDevice]),
-) -> Result<HashSet<BluetoothInfo>> {
-    let btc_devices = bt_devices.0;
-    let ble_devices = bt_devices.1;
-    match (btc_devices.len(), ble_devices.len()) {
-        (0, 0) => Err(anyhow!(
-            "No Classic Bluetooth and Bluetooth LE devices found"
-        )),
-        (0, _) => {
-            let ble_result = get_ble_info(ble_devices);
-            info!("{ble_result:#?}");
-
-            ble_result.or_else(|e| {
-                app_notify(format!("Warning: Failed to get BLE info: {e}"));
-                Ok(HashSet::new())
-            })
-        },
-        (_, 0) => {
-            let btc_result = get_btc_info(btc_devices);
-            info!("{btc_result:#?}");
+    pub disconnected_at: u64,
+}
+
+/// 未连接设备的 `LastSeen`，重新连接时清除
+static LAST_SEEN: LazyLock<Mutex<HashMap</* address */ u64, LastSeen>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
 
-            btc_result.or_else(|e| {
-                app_notify(format!("Warning: Failed to get BTC info: {e}"));
-                Ok(HashSet::new())
+/// 由连接变化记录断开时的电量：断开的同时电量变化（如归零）时取变化前的电量
+pub fn record_last_seen(events: &[DeviceEvent]) {
+    let now = now_timestamp();
+    let mut last_seen = LAST_SEEN.lock().unwrap();
+    for event in events {
+        let DeviceEvent::ConnectionChanged { info } = event else {
+            continue;
+        };
+        if info.status {
+            last_seen.remove(&info.address);
+            continue;
+        }
+        let battery = events
+            .iter()
+            .find_map(|event| match event {
+                DeviceEvent::BatteryChanged {
+                    info: new,
+                    old_battery,
+                } if new.address == info.address => Some(*old_battery),
+                _ => None,
             })
-        },
-        (_, _) => {
-            let btc_result = get_btc_info(btc_devices);
-            let ble_result = get_ble_info(ble_devices);
+            .unwrap_or(info.battery);
+        last_seen.insert(
+            info.address,
+            LastSeen {
+                battery,
+                disconnected_at: now,
+            },
+        );
+    }
+}
 
-            info!("{btc_result:#?}");
-            info!("{ble_result:#?}");
+/// 启动时恢复上次退出时保存的记录
+pub fn seed_last_seen(records: HashMap</* address */ u64, LastSeen>) {
+    LAST_SEEN.lock().unwrap().extend(records);
+}
 
-            match (btc_result, ble_result) {
-                (Ok(btc_info), Ok(ble_info)) => {
-                    let combined_info = btc_info.into_iter().chain(ble_info).collect();
-                    Ok(combined_info)
-                }
-                (Ok(btc_info), Err(e)) => {
-                    warn!("Failed to get BLE info: {e}");
-                    Ok(btc_info)
-                }
-                (Err(e), Ok(ble_info)) => {
-                    warn!("Failed to get BTC info: {e}");
-                    Ok(ble_info)
-                }
-                (Err(btc_err), Err(ble_err)) => Err(anyhow!(
-                    "Failed to get both BTC and BLE info: {btc_err} | {ble_err}"
-                )),
-            }
+/// 未连接设备断开前最后的电量及断开的时间，没有记录时为 `None`
+pub fn get_last_seen(address: u64) -> Option<LastSeen> {
+    LAST_SEEN.lock().unwrap().get(&address).copied()
+}
+
+/// 设备断开距今的秒数
+pub fn last_seen_age(last_seen: &LastSeen) -> u64 {
+    now_timestamp().saturating_sub(last_seen.disconnected_at)
+}
+
+/// 电量下降时越过的最低阶梯，如 `[50, 30]` 中 55% → 28% 返回 30
+pub fn crossed_milestone(steps: &[u8], old_battery: u8, new_battery: u8) -> Option<u8> {
+    steps
+        .iter()
+        .copied()
+        .filter(|step| old_battery >= *step && new_battery < *step)
+        .min()
+}
+
+/// 断开、重新连接的通知及朗读设置，去抖时在窗口结束后使用
+#[derive(Clone)]
+struct ConnectionAlerts {
+    mute: bool,
+    disconnection: bool,
+    reconnection: bool,
+    flap_summary: bool,
+    speech: SpeechOptions,
+}
+
+impl ConnectionAlerts {
+    fn announce(&self, info: &BluetoothInfo) {
+        let loc = Localization::current();
+        let (event, enabled, title) = if info.status {
+            (
+                NotifyEvent::Reconnection,
+                self.reconnection,
+                loc.bluetooth_device_reconnected,
+            )
+        } else {
+            (
+                NotifyEvent::Disconnection,
+                self.disconnection,
+                loc.bluetooth_device_disconnected,
+            )
+        };
+
+        if self.speech.shows_notification(event, enabled) {
+            notify(
+                event,
+                title,
+                format!("{}: {}", loc.device_name, info.name),
+                self.mute,
+                info.address,
+            );
         }
+        if self.speech.speaks(event) {
+            speak_event(event, info);
+        }
+    }
+
+    /// 与断开、重新连接的通知同组，替换之前的连接通知
+    fn announce_unstable(&self, info: &BluetoothInfo) {
+        let loc = Localization::current();
+        notify(
+            NotifyEvent::Reconnection,
+            loc.connection_unstable,
+            format!("{}: {}", loc.device_name, info.name),
+            self.mute,
+            info.address,
+        );
     }
 }
 
-pub fn compare_bt_info_to_send_notifications(
+/// 第一次低于低电量阈值时的通知、朗读及命令
+struct LowBatteryAlerts {
+    low_battery: u8,
+    battery_display: BatteryDisplay,
+    mute: bool,
+    speech: SpeechOptions,
+    commands: Vec<CommandOptions>,
+}
+
+impl LowBatteryAlerts {
+    fn new(config: &Config) -> Self {
+        LowBatteryAlerts {
+            low_battery: config.get_low_battery(),
+            battery_display: config.get_battery_display(),
+            mute: config.get_mute(),
+            speech: config.speech_options.clone(),
+            commands: config.commands.clone(),
+        }
+    }
+
+    /// `alert` 为假时只执行命令
+    fn announce(&self, info: &BluetoothInfo, alert: bool) {
+        let loc = Localization::current();
+        if alert
+            && self
+                .speech
+                .shows_notification(NotifyEvent::LowBattery, true)
+        {
+            let title = format!("{} {}%", loc.bluetooth_battery_below, self.low_battery);
+            let text = format!(
+                "{}: {}",
+                info.name,
+                self.battery_display.format(info.battery)
+            );
+            notify(
+                NotifyEvent::LowBattery,
+                title,
+                text,
+                self.mute,
+                info.address,
+            );
+        }
+        if alert && self.speech.speaks(NotifyEvent::LowBattery) {
+            speak_event(NotifyEvent::LowBattery, info);
+        }
+        run_commands(&self.commands, NotifyEvent::LowBattery, info);
+    }
+}
+
+/// 首次枚举时已低于阈值的已连接设备，同样按低电量通知一次，
+/// 之后电量回升到阈值以上才会再次通知
+pub fn send_initial_low_battery_notifications(
+    config: &Config,
+    notified_low_battery_devices: Arc<Mutex<HashSet<u64>>>,
+    bluetooth_devices_info: &HashSet<BluetoothInfo>,
+) {
+    let low_battery_alerts = LowBatteryAlerts::new(config);
+    let low_devices = bluetooth_devices_info
+        .iter()
+        .filter(|info| info.status && info.battery < low_battery_alerts.low_battery)
+        .map(|info| (info.clone(), config.notifies_device(info)))
+        .collect::<Vec<_>>();
+    if low_devices.is_empty() {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        let mut notified_low_battery_devices = notified_low_battery_devices.lock().unwrap();
+        for (info, alert) in &low_devices {
+            if notified_low_battery_devices.insert(info.address) {
+                low_battery_alerts.announce(info, *alert);
+            }
+        }
+    });
+}
+
+/// 隐藏的设备及只通知固定的设备时未固定的设备不弹出通知、不朗读，命令照常执行
+fn silenced_devices(config: &Config, events: &[DeviceEvent]) -> HashSet</* address */ u64> {
+    events
+        .iter()
+        .map(DeviceEvent::info)
+        .filter(|info| !config.notifies_device(info))
+        .map(|info| info.address)
+        .collect()
+}
+
+/// 按设备事件在后台线程中发送通知、朗读并执行命令，Webhook 单独订阅设备事件
+pub fn send_notifications(
     config: &Config,
     notified_low_battery_devices: Arc<Mutex<HashSet<u64>>>,
-    old_bt_info: Arc<Mutex<HashSet<BluetoothInfo>>>,
-    new_bt_info: &HashSet<BluetoothInfo>,
-) -> Option<Result<()>> {
-    let mut old_bt_info = old_bt_info.lock().unwrap();
-
-    let change_old_bt_info = old_bt_info
-        .difference(new_bt_info)
-        .cloned()
-        .collect::<HashSet<_>>();
-    let change_new_bt_info = new_bt_info
-        .difference(&old_bt_info)
-        .cloned()
-        .collect::<HashSet<_>>();
-
-    if change_old_bt_info == change_new_bt_info {
-        return None;
+    events: Vec<DeviceEvent>,
+) {
+    if events.is_empty() {
+        return;
     }
 
-    let low_battery = config.get_low_battery();
+    let low_battery_alerts = LowBatteryAlerts::new(config);
+    let low_battery = low_battery_alerts.low_battery;
     let mute = config.get_mute();
-    let disconnection = config.get_disconnection();
-    let reconnection = config.get_reconnection();
+    let connection_alerts = ConnectionAlerts {
+        mute,
+        disconnection: config.get_disconnection(),
+        reconnection: config.get_reconnection(),
+        flap_summary: config.get_flap_summary(),
+        speech: config.speech_options.clone(),
+    };
+    let flap_window = config.get_flap_window();
+    let milestone_steps = config.get_milestone_steps();
     let added = config.get_added();
     let removed = config.get_removed();
+    let renamed = config.get_renamed();
+    let commands = config.commands.clone();
+    let speech = config.speech_options.clone();
+    let silenced = silenced_devices(config, &events);
 
     std::thread::spawn(move || {
-        let language = Language::get_system_language();
-        let loc = Localization::get(language);
+        let loc = Localization::current();
 
         let mut notified_low_battery_devices = notified_low_battery_devices.lock().unwrap();
 
-        for old in &change_old_bt_info {
-            for new in &change_new_bt_info {
-                // 低电量 / 重新连接 / 断开连接 的同一设备
-                if old.address == new.address {
-                    if new.battery != old.battery {
-                        let is_low = new.battery < low_battery;
-                        let was_low = notified_low_battery_devices.contains(&new.address);
-                        match (was_low, is_low) {
-                            (false, true) => {
-                                // 第一次进入低电量
-                                let title =
-                                    format!("{} {low_battery}%", loc.bluetooth_battery_below);
-                                let text = format!("{}: {}%", new.name, new.battery);
-                                notify(title, text, mute);
-                                notified_low_battery_devices.insert(new.address);
-                            }
-                            (true, false) => {
-                                // 电量回升，允许下次低电量时再次通知
-                                notified_low_battery_devices.remove(&new.address);
-                            }
-                            _ => (),
+        for event in &events {
+            let alert = !silenced.contains(&event.info().address);
+            match event {
+                DeviceEvent::BatteryChanged {
+                    info: new,
+                    old_battery,
+                } => {
+                    let is_low = new.battery < low_battery;
+                    let was_low = notified_low_battery_devices.contains(&new.address);
+                    match (was_low, is_low) {
+                        (false, true) => {
+                            // 第一次进入低电量
+                            low_battery_alerts.announce(new, alert);
+                            notified_low_battery_devices.insert(new.address);
+                        }
+                        (true, false) => {
+                            // 电量回升，允许下次低电量时再次通知
+                            notified_low_battery_devices.remove(&new.address);
                         }
+                        _ => (),
                     }
 
-                    if new.status != old.status {
-                        if disconnection && !new.status {
-                            notify(
-                                loc.bluetooth_device_disconnected,
-                                format!("{}: {}", loc.device_name, new.name),
-                                mute,
+                    if let Some(step) =
+                        crossed_milestone(&milestone_steps, *old_battery, new.battery)
+                    {
+                        if alert && speech.shows_notification(NotifyEvent::Milestone, true) {
+                            let title = format!("{} {step}%", loc.bluetooth_battery_below);
+                            let text = format!(
+                                "{}: {}",
+                                new.name,
+                                low_battery_alerts.battery_display.format(new.battery)
                             );
+                            notify(NotifyEvent::Milestone, title, text, mute, new.address);
                         }
-
-                        if reconnection && new.status {
-                            notify(
-                                loc.bluetooth_device_reconnected,
-                                format!("{}: {}", loc.device_name, new.name),
-                                mute,
-                            );
+                        if alert && speech.speaks(NotifyEvent::Milestone) {
+                            speak_event(NotifyEvent::Milestone, new);
                         }
+                        run_commands(&commands, NotifyEvent::Milestone, new);
                     }
-
-                    continue;
                 }
+                DeviceEvent::ConnectionChanged { info: new } => {
+                    let event = if new.status {
+                        NotifyEvent::Reconnection
+                    } else {
+                        NotifyEvent::Disconnection
+                    };
 
-                // 新添加设备
-                if added {
-                    let added_devices = change_new_bt_info
-                        .difference(&change_old_bt_info)
-                        .collect::<HashSet<_>>();
-                    if !added_devices.is_empty() {
+                    // 命令不去抖，供自动化获取每次变化
+                    if alert && flap_window.is_zero() {
+                        connection_alerts.announce(new);
+                    } else if alert {
+                        let connection_alerts = connection_alerts.clone();
+                        debounce_connection(new, flap_window, move |outcome| match outcome {
+                            ConnectionOutcome::Changed(info) => connection_alerts.announce(&info),
+                            ConnectionOutcome::Unstable { info, .. }
+                                if connection_alerts.flap_summary =>
+                            {
+                                connection_alerts.announce_unstable(&info)
+                            }
+                            ConnectionOutcome::Unstable { info, changed } => {
+                                if changed {
+                                    connection_alerts.announce(&info);
+                                }
+                            }
+                        });
+                    }
+                    run_commands(&commands, event, new);
+                }
+                DeviceEvent::Added { info: new } => {
+                    if alert && speech.shows_notification(NotifyEvent::Added, added) {
                         notify(
+                            NotifyEvent::Added,
                             loc.new_bluetooth_device_add,
                             format!("{}: {}", loc.device_name, new.name),
                             mute,
+                            new.address,
                         );
                     }
+                    if alert && speech.speaks(NotifyEvent::Added) {
+                        speak_event(NotifyEvent::Added, new);
+                    }
+                    run_commands(&commands, NotifyEvent::Added, new);
                 }
-
-                // 移除设备
-                if removed {
-                    let removed_devices = change_old_bt_info
-                        .difference(&change_new_bt_info)
-                        .collect::<HashSet<_>>();
-                    if !removed_devices.is_empty() {
+                DeviceEvent::Removed { info: old } => {
+                    if alert && speech.shows_notification(NotifyEvent::Removed, removed) {
                         notify(
+                            NotifyEvent::Removed,
                             loc.old_bluetooth_device_removed,
                             format!("{}: {}", loc.device_name, old.name),
                             mute,
+                            old.address,
+                        );
+                    }
+                    if alert && speech.speaks(NotifyEvent::Removed) {
+                        speak_event(NotifyEvent::Removed, old);
+                    }
+                    run_commands(&commands, NotifyEvent::Removed, old);
+                }
+                DeviceEvent::Renamed {
+                    info: new,
+                    old_name,
+                } => {
+                    if alert && speech.shows_notification(NotifyEvent::Renamed, renamed) {
+                        notify(
+                            NotifyEvent::Renamed,
+                            loc.bluetooth_device_renamed,
+                            format!("{}: {old_name} → {}", loc.device_name, new.name),
+                            mute,
+                            new.address,
                         );
                     }
+                    if alert && speech.speaks(NotifyEvent::Renamed) {
+                        speak_event(NotifyEvent::Renamed, new);
+                    }
+                    run_commands(&commands, NotifyEvent::Renamed, new);
                 }
             }
         }
     });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bluetooth::{
+            events::DeviceEvents,
+            provider::{BluetoothProvider, MockBluetoothProvider, mock_device},
+        },
+        config::test_config,
+    };
+
+    use std::sync::atomic::Ordering;
+
+    fn mock_events(provider: &MockBluetoothProvider, change: impl FnOnce()) -> Vec<DeviceEvent> {
+        let device_events = DeviceEvents::new();
+        device_events.reset(&provider.get_devices().unwrap().devices);
+        change();
+        device_events
+            .publish(&provider.get_devices().unwrap().devices, &[])
+            .unwrap()
+    }
 
-    *old_bt_info = new_bt_info.clone();
+    #[test]
+    fn hidden_and_unpinned_devices_are_silenced() {
+        let config = test_config("hidden_and_unpinned_devices_are_silenced");
+        let provider = MockBluetoothProvider::new([
+            mock_device("Buds", 1, 80),
+            mock_device("Mouse", 2, 50),
+            mock_device("Keyboard", 3, 90),
+        ]);
+        let events = mock_events(&provider, || {
+            provider.set_battery(1, 70);
+            provider.set_battery(2, 40);
+            provider.set_connected(3, false);
+        });
+        assert!(silenced_devices(&config, &events).is_empty());
 
-    Some(Ok(()))
+        config
+            .hidden_devices
+            .lock()
+            .unwrap()
+            .push("Mouse".to_owned());
+        assert_eq!(silenced_devices(&config, &events), HashSet::from([2]));
+
+        config
+            .pinned_devices
+            .lock()
+            .unwrap()
+            .push("00:00:00:00:00:03".to_owned());
+        config
+            .notify_options
+            .pinned_only
+            .store(true, Ordering::Relaxed);
+        assert_eq!(silenced_devices(&config, &events), HashSet::from([1, 2]));
+    }
+
+    #[test]
+    fn mock_battery_drain_crosses_milestones() {
+        let provider = MockBluetoothProvider::new([mock_device("Buds", 1, 55)]);
+        let events = mock_events(&provider, || provider.set_battery(1, 28));
+
+        let [DeviceEvent::BatteryChanged { info, old_battery }] = events.as_slice() else {
+            panic!("expected a battery change");
+        };
+        assert_eq!(
+            crossed_milestone(&[50, 30], *old_battery, info.battery),
+            Some(30)
+        );
+        assert_eq!(crossed_milestone(&[20], *old_battery, info.battery), None);
+        // 电量上升时不通知
+        assert_eq!(
+            crossed_milestone(&[50, 30], info.battery, *old_battery),
+            None
+        );
+    }
 }
@@ -1,4 +1,8 @@
-pub mod ble;
-pub mod btc;
+pub use bluegauge_core::{
+    audio, ble, btc, companion, control, events, galaxy_buds, pnp, provider, radio,
+};
+
+pub mod engine;
 pub mod info;
-pub mod listen;
+pub mod simulate;
+pub mod snapshot;
@@ -0,0 +1,120 @@
+use crate::bluetooth::{
+    info::{BluetoothInfo, BluetoothType, DeviceCategory},
+    provider::{MockBluetoothProvider, mock_device},
+};
+
+use std::time::Duration;
+
+use log::info;
+
+/// 以模拟设备启动，不访问真实的蓝牙硬件
+pub const SIMULATE_ARG: &str = "--simulate";
+
+/// 每轮推进一次脚本
+const TICK: Duration = Duration::from_secs(5);
+
+const EARBUDS: u64 = 0x5100_0000_0001;
+const MOUSE: u64 = 0x5100_0000_0002;
+const KEYBOARD: u64 = 0x5100_0000_0003;
+const HEADSET: u64 = 0x5100_0000_0004;
+
+pub fn is_simulating() -> bool {
+    std::env::args().any(|arg| arg == SIMULATE_ARG)
+}
+
+fn simulated_device(
+    name: &str,
+    address: u64,
+    battery: u8,
+    classic: bool,
+    category: DeviceCategory,
+) -> BluetoothInfo {
+    BluetoothInfo {
+        r#type: if classic {
+            BluetoothType::Classic(format!("SIMULATED\\{address:012X}"))
+        } else {
+            BluetoothType::LowEnergy
+        },
+        category,
+        ..mock_device(name, address, battery)
+    }
+}
+
+/// 启动后第一轮之前枚举失败（模拟蓝牙栈未就绪），之后按脚本变化：
+/// - 耳机：每轮放电 1%，降到 5% 后充满
+/// - 鼠标：每轮放电 3%，降到 0% 后充满
+/// - 键盘：每 6 轮断开或重新连接
+/// - 头戴耳机：每 12 轮取消配对或重新配对
+pub fn start_simulation() -> MockBluetoothProvider {
+    let headset = simulated_device(
+        "Simulated Headset",
+        HEADSET,
+        60,
+        true,
+        DeviceCategory::Audio,
+    );
+    let provider = MockBluetoothProvider::new([
+        simulated_device(
+            "Simulated Earbuds",
+            EARBUDS,
+            100,
+            true,
+            DeviceCategory::Audio,
+        ),
+        simulated_device("Simulated Mouse", MOUSE, 45, false, DeviceCategory::Mouse),
+        simulated_device(
+            "Simulated Keyboard",
+            KEYBOARD,
+            80,
+            false,
+            DeviceCategory::Keyboard,
+        ),
+        headset.clone(),
+    ]);
+    provider.set_error(Some("Simulated Bluetooth stack is not ready".to_owned()));
+
+    info!("Starting the Bluetooth simulation");
+
+    let script_provider = provider.clone();
+    std::thread::spawn(move || {
+        let provider = script_provider;
+        let mut earbuds_battery = 100u8;
+        let mut mouse_battery = 45u8;
+        let mut keyboard_connected = true;
+        let mut headset_paired = true;
+
+        for tick in 1u32.. {
+            std::thread::sleep(TICK);
+
+            if tick == 1 {
+                provider.set_error(None);
+            }
+
+            earbuds_battery = match earbuds_battery {
+                0..=5 => 100,
+                battery => battery - 1,
+            };
+            provider.set_battery(EARBUDS, earbuds_battery);
+
+            mouse_battery = mouse_battery.checked_sub(3).unwrap_or(100);
+            provider.set_battery(MOUSE, mouse_battery);
+
+            if tick % 6 == 0 {
+                keyboard_connected = !keyboard_connected;
+                provider.set_connected(KEYBOARD, keyboard_connected);
+            }
+
+            // 配对变化只能由全量刷新发现
+            if tick % 12 == 0 {
+                headset_paired = !headset_paired;
+                if headset_paired {
+                    provider.add_device(headset.clone());
+                } else {
+                    provider.remove_device(HEADSET);
+                }
+            }
+        }
+    });
+
+    provider
+}
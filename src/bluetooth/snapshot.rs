@@ -0,0 +1,113 @@
+use crate::{
+    bluetooth::info::{
+        BluetoothInfo, LastSeen, get_last_seen, get_last_updated, seed_last_seen, seed_last_updated,
+    },
+    config::Config,
+};
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+const SNAPSHOT_FILE_NAME: &str = "BlueGauge.last-known.json";
+
+/// 保存的设备信息及其最后一次报告数据的时间（Unix 时间戳，秒），
+/// 未连接的设备另外保存断开前最后的电量
+#[derive(Serialize, Deserialize)]
+struct DeviceSnapshot {
+    info: BluetoothInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    updated: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_seen: Option<LastSeen>,
+    /// 已发送过低电量通知，重启后电量回升前不再重复通知
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    low_battery_notified: bool,
+}
+
+/// 上次退出时保存的设备信息
+#[derive(Default)]
+pub struct LastKnown {
+    pub devices: HashSet<BluetoothInfo>,
+    /// 已发送过低电量通知的设备地址
+    pub low_battery_notified: HashSet<u64>,
+}
+
+fn snapshot_path(config: &Config) -> PathBuf {
+    config.config_path.with_file_name(SNAPSHOT_FILE_NAME)
+}
+
+/// 读取上次退出时保存的设备信息，并以保存的时间补全各设备的数据时间。
+/// 没有保存或读取失败时为空，此时启动时仍同步枚举设备
+pub fn load_last_known(config: &Config) -> LastKnown {
+    let path = snapshot_path(config);
+    if !path.exists() {
+        return LastKnown::default();
+    }
+
+    match read_snapshots(&path) {
+        Ok(snapshots) => {
+            info!(
+                "Loaded {} last known devices from {path:?}",
+                snapshots.len()
+            );
+            seed_last_updated(
+                snapshots
+                    .iter()
+                    .filter_map(|snapshot| Some((snapshot.info.address, snapshot.updated?)))
+                    .collect(),
+            );
+            seed_last_seen(
+                snapshots
+                    .iter()
+                    .filter_map(|snapshot| Some((snapshot.info.address, snapshot.last_seen?)))
+                    .collect(),
+            );
+            LastKnown {
+                low_battery_notified: snapshots
+                    .iter()
+                    .filter(|snapshot| snapshot.low_battery_notified)
+                    .map(|snapshot| snapshot.info.address)
+                    .collect(),
+                devices: snapshots
+                    .into_iter()
+                    .map(|snapshot| snapshot.info)
+                    .collect(),
+            }
+        }
+        Err(e) => {
+            warn!("Failed to load the last known devices - {e}");
+            LastKnown::default()
+        }
+    }
+}
+
+/// 退出时保存当前设备信息及已发送的低电量通知，下次启动时在首次枚举完成前显示
+pub fn save_last_known(
+    config: &Config,
+    bluetooth_devices_info: &HashSet<BluetoothInfo>,
+    low_battery_notified: &HashSet<u64>,
+) -> Result<()> {
+    let snapshots = bluetooth_devices_info
+        .iter()
+        .map(|info| DeviceSnapshot {
+            info: info.clone(),
+            updated: get_last_updated(info.address),
+            last_seen: get_last_seen(info.address).filter(|_| !info.status),
+            low_battery_notified: low_battery_notified.contains(&info.address),
+        })
+        .collect::<Vec<_>>();
+
+    let path = snapshot_path(config);
+    std::fs::write(&path, serde_json::to_string_pretty(&snapshots)?)
+        .with_context(|| format!("Failed to write {path:?}"))
+}
+
+fn read_snapshots(path: &Path) -> Result<Vec<DeviceSnapshot>> {
+    let contents =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {path:?}"))?;
+    serde_json::from_str(&contents).with_context(|| format!("Failed to parse {path:?}"))
+}
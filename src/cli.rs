@@ -0,0 +1,278 @@
+use crate::{
+    bluetooth::{
+        companion::init_companions,
+        galaxy_buds::init_galaxy_buds,
+        info::{BluetoothInfo, init_calibrations},
+        provider::{BluetoothProvider, WindowsBluetoothProvider},
+        simulate::SIMULATE_ARG,
+    },
+    config::Config,
+    startup::STARTUP_ARG,
+};
+
+use std::collections::HashSet;
+use std::sync::atomic::Ordering;
+
+use anyhow::{Result, anyhow};
+use windows::Win32::System::Console::{ATTACH_PARENT_PROCESS, AttachConsole};
+
+pub use bluegauge_core::info::format_address;
+
+const HELP: &str = "\
+Usage: BlueGauge [--portable] [--minimized] [--simulate] [<command>] [--json]
+
+Commands:
+  list                      List paired Bluetooth devices and their battery
+  get <address>             Show a single device (AA:BB:CC:DD:EE:FF or decimal address)
+  set <option> <value>      Change an option in BlueGauge.toml
+  help                      Show this help
+
+Options for `set`:
+  update-interval <seconds>
+  low-battery <0-100>
+  mute | disconnection | reconnection | added | removed | renamed | milestones <true|false>
+  pinned-only <true|false>
+  show-disconnected | truncate-name | prefix-battery <true|false>
+
+Without a command, BlueGauge starts in the system tray.
+With --simulate, it shows scripted fake devices instead of real ones.";
+
+pub enum Command {
+    List,
+    Get(u64),
+    Set { option: String, value: String },
+    Help,
+}
+
+pub struct Cli {
+    command: Command,
+    json: bool,
+}
+
+impl Cli {
+    /// 解析命令行参数，没有子命令时返回 `None`（托盘模式）。
+    /// 有子命令时先连接到父进程的控制台，解析错误也能输出
+    pub fn parse() -> Option<Result<Self>> {
+        let args = std::env::args()
+            .skip(1)
+            .filter(|arg| arg != "--portable" && arg != STARTUP_ARG && arg != SIMULATE_ARG)
+            .collect::<Vec<_>>();
+
+        let json = args.iter().any(|arg| arg == "--json");
+        let mut args = args.into_iter().filter(|arg| arg != "--json");
+
+        let command = args.next()?;
+        attach_console();
+
+        let command = match command.as_str() {
+            "list" => Ok(Command::List),
+            "get" => args
+                .next()
+                .ok_or_else(|| anyhow!("Missing <address> for `get`"))
+                .and_then(|address| parse_address(&address))
+                .map(Command::Get),
+            "set" => match (args.next(), args.next()) {
+                (Some(option), Some(value)) => Ok(Command::Set { option, value }),
+                _ => Err(anyhow!("Usage: BlueGauge set <option> <value>")),
+            },
+            "help" | "--help" | "-h" => Ok(Command::Help),
+            other => Err(anyhow!("Unknown command: {other}\n\n{HELP}")),
+        };
+
+        Some(command.map(|command| Cli { command, json }))
+    }
+
+    pub fn run(self) -> Result<()> {
+        match self.command {
+            Command::List => {
+                let mut infos = query_bluetooth_info()?.into_iter().collect::<Vec<_>>();
+                infos.sort_by(|a, b| a.name.cmp(&b.name));
+                self.print_infos(&infos)
+            }
+            Command::Get(address) => {
+                let info = query_bluetooth_info()?
+                    .into_iter()
+                    .find(|i| i.address == address)
+                    .ok_or_else(|| {
+                        anyhow!("No device found with address {}", format_address(address))
+                    })?;
+                self.print_infos(&[info])
+            }
+            Command::Set { option, value } => {
+                let config = Config::open()?;
+                set_option(&config, &option, &value)?;
+                config.save();
+                println!("{option} = {value}");
+                Ok(())
+            }
+            Command::Help => {
+                println!("{HELP}");
+                Ok(())
+            }
+        }
+    }
+
+    fn print_infos(&self, infos: &[BluetoothInfo]) -> Result<()> {
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(infos)?);
+        } else {
+            for info in infos {
+                let status = if info.status {
+                    "connected"
+                } else {
+                    "disconnected"
+                };
+                println!(
+                    "{:3}%  {}  {:<12}  {}",
+                    info.battery,
+                    format_address(info.address),
+                    status,
+                    info.name
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 以 GUI 子系统编译时没有控制台，需附加到父进程（cmd/PowerShell）的控制台才能输出
+fn attach_console() {
+    let _ = unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+}
+
+fn query_bluetooth_info() -> Result<HashSet<BluetoothInfo>> {
+    let config = Config::open()?;
+    init_calibrations(config.calibrations);
+    init_galaxy_buds(config.protocol_options.galaxy_buds);
+    init_companions(config.companions);
+
+    let scan = WindowsBluetoothProvider.get_devices()?;
+    for error in &scan.errors {
+        eprintln!(
+            "Failed to read {} ({}): {}",
+            error.name,
+            format_address(error.address),
+            error.error
+        );
+    }
+    Ok(scan.devices)
+}
+
+fn set_option(config: &Config, option: &str, value: &str) -> Result<()> {
+    let parse_bool = || {
+        value
+            .parse::<bool>()
+            .map_err(|_| anyhow!("Expected `true` or `false` for {option}, got {value}"))
+    };
+
+    match option {
+        "update-interval" => {
+            let update_interval = value
+                .parse::<u64>()
+                .map_err(|_| anyhow!("Expected seconds for {option}, got {value}"))?;
+            config
+                .tray_options
+                .update_interval
+                .store(update_interval.max(1), Ordering::Relaxed);
+        }
+        "low-battery" => {
+            let low_battery = value
+                .parse::<u8>()
+                .ok()
+                .filter(|b| *b <= 100)
+                .ok_or_else(|| anyhow!("Expected 0-100 for {option}, got {value}"))?;
+            config
+                .notify_options
+                .low_battery
+                .store(low_battery, Ordering::Relaxed);
+        }
+        "mute" | "disconnection" | "reconnection" | "added" | "removed" | "renamed"
+        | "milestones" => {
+            config.notify_options.update(option, parse_bool()?);
+        }
+        "pinned-only" => {
+            config.notify_options.update("pinned_only", parse_bool()?);
+        }
+        "show-disconnected" | "truncate-name" | "prefix-battery" => {
+            config
+                .tray_options
+                .update(&option.replace('-', "_"), parse_bool()?);
+        }
+        _ => return Err(anyhow!("Unknown option: {option}\n\n{HELP}")),
+    }
+
+    Ok(())
+}
+
+/// 蓝牙地址为 48 位
+const MAX_ADDRESS: u64 = 0xFFFF_FFFF_FFFF;
+
+/// `AA:BB:CC:DD:EE:FF`（6 组两位十六进制数）或不超过 48 位的十进制地址（配置文件中使用的格式）
+pub fn parse_address(address: &str) -> Result<u64> {
+    let invalid = || anyhow!("Invalid Bluetooth address: {address}");
+
+    if address.contains(':') {
+        let octets = address.split(':').collect::<Vec<_>>();
+        let valid = octets.len() == 6
+            && octets
+                .iter()
+                .all(|octet| octet.len() == 2 && octet.chars().all(|c| c.is_ascii_hexdigit()));
+        if !valid {
+            return Err(invalid());
+        }
+        u64::from_str_radix(&octets.concat(), 16).map_err(|_| invalid())
+    } else if !address.is_empty() && address.chars().all(|c| c.is_ascii_digit()) {
+        address
+            .parse::<u64>()
+            .ok()
+            .filter(|address| *address <= MAX_ADDRESS)
+            .ok_or_else(invalid)
+    } else {
+        Err(invalid())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addresses_in_both_formats() {
+        assert_eq!(
+            parse_address("AA:BB:CC:DD:EE:FF").unwrap(),
+            0xAABB_CCDD_EEFF
+        );
+        assert_eq!(
+            parse_address("aa:bb:cc:dd:ee:ff").unwrap(),
+            0xAABB_CCDD_EEFF
+        );
+        assert_eq!(parse_address("187723572702975").unwrap(), 0xAABB_CCDD_EEFF);
+        assert_eq!(parse_address("281474976710655").unwrap(), MAX_ADDRESS);
+        assert_eq!(format_address(0xAABB_CCDD_EEFF), "AA:BB:CC:DD:EE:FF");
+    }
+
+    #[test]
+    fn invalid_addresses() {
+        assert!(parse_address("AA:BB:CC:DD:EE:GG").is_err());
+        assert!(parse_address("headphones").is_err());
+        assert!(parse_address("").is_err());
+    }
+
+    #[test]
+    fn addresses_must_have_six_octets_or_fit_in_48_bits() {
+        for address in [
+            "1:2",
+            "AA:BB:CC:DD:EE",
+            "AA:BB:CC:DD:EE:FF:00",
+            "A:BB:CC:DD:EE:FF",
+            "AAA:BB:CC:DD:EE:F",
+            "AA:BB:CC:DD:EE:+F",
+            "AA::CC:DD:EE:FF",
+            "281474976710656",
+            "18446744073709551615",
+            "+5",
+        ] {
+            assert!(parse_address(address).is_err(), "{address}");
+        }
+    }
+}
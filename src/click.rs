@@ -0,0 +1,67 @@
+use crate::config::{ClickAction, ClickOptions};
+
+use std::time::{Duration, Instant};
+
+use tray_icon::{MouseButton, MouseButtonState, TrayIconEvent};
+use windows::Win32::UI::Input::KeyboardAndMouse::GetDoubleClickTime;
+
+/// 托盘图标的点击状态。设置了双击操作时，左键单击等到双击间隔结束后才执行，
+/// 避免双击时同时触发单击操作
+#[derive(Debug, Default)]
+pub struct TrayClicks {
+    /// 等待中的左键单击的执行时间
+    pending_single: Option<Instant>,
+}
+
+impl TrayClicks {
+    /// 返回需要立即执行的操作。
+    /// 按下时处理，双击时系统以双击消息代替第二次按下
+    pub fn handle(&mut self, options: &ClickOptions, event: &TrayIconEvent) -> Option<ClickAction> {
+        match event {
+            TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Down,
+                ..
+            } => {
+                // 菜单由托盘在按下时打开
+                if matches!(options.single, ClickAction::None | ClickAction::Menu) {
+                    return None;
+                }
+                if options.double == ClickAction::None {
+                    return Some(options.single);
+                }
+                self.pending_single = Some(Instant::now() + double_click_time());
+                None
+            }
+            TrayIconEvent::DoubleClick {
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.pending_single = None;
+                Some(options.double)
+            }
+            TrayIconEvent::Click {
+                button: MouseButton::Middle,
+                button_state: MouseButtonState::Down,
+                ..
+            } => Some(options.middle),
+            _ => None,
+        }
+    }
+
+    /// 双击间隔结束时返回等待中的单击操作
+    pub fn take_due(&mut self, options: &ClickOptions) -> Option<ClickAction> {
+        let now = Instant::now();
+        self.pending_single.filter(|due| now >= *due)?;
+        self.pending_single = None;
+        Some(options.single)
+    }
+
+    pub fn next_due(&self) -> Option<Instant> {
+        self.pending_single
+    }
+}
+
+fn double_click_time() -> Duration {
+    Duration::from_millis(unsafe { GetDoubleClickTime() } as u64)
+}
@@ -0,0 +1,45 @@
+use anyhow::{Result, anyhow};
+use windows::Win32::{
+    Foundation::{GlobalFree, HANDLE},
+    System::{
+        DataExchange::{CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData},
+        Memory::{GMEM_MOVEABLE, GlobalAlloc, GlobalLock, GlobalUnlock},
+    },
+};
+
+const CF_UNICODETEXT: u32 = 13;
+
+/// 以 UTF-16 文本替换剪贴板内容
+pub fn set_clipboard_text(text: &str) -> Result<()> {
+    let text = text.encode_utf16().chain([0]).collect::<Vec<u16>>();
+
+    unsafe {
+        OpenClipboard(None).map_err(|e| anyhow!("Failed to open the clipboard - {e}"))?;
+        let result = write_clipboard_text(&text);
+        let _ = CloseClipboard();
+        result
+    }
+}
+
+unsafe fn write_clipboard_text(text: &[u16]) -> Result<()> {
+    unsafe {
+        EmptyClipboard()?;
+
+        let memory = GlobalAlloc(GMEM_MOVEABLE, std::mem::size_of_val(text))?;
+        let buffer = GlobalLock(memory) as *mut u16;
+        if buffer.is_null() {
+            let _ = GlobalFree(Some(memory));
+            return Err(anyhow!("Failed to lock the clipboard memory"));
+        }
+        std::ptr::copy_nonoverlapping(text.as_ptr(), buffer, text.len());
+        let _ = GlobalUnlock(memory);
+
+        // 设置成功后内存归系统所有
+        if let Err(e) = SetClipboardData(CF_UNICODETEXT, Some(HANDLE(memory.0))) {
+            let _ = GlobalFree(Some(memory));
+            return Err(anyhow!("Failed to set the clipboard data - {e}"));
+        }
+    }
+
+    Ok(())
+}
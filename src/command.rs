@@ -0,0 +1,65 @@
+use crate::{
+    bluetooth::info::BluetoothInfo, cli::format_address, config::CommandOptions,
+    notify::NotifyEvent,
+};
+
+use std::os::windows::process::CommandExt;
+
+use log::{info, warn};
+
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/// 根据事件及设备过滤条件，在后台执行所有匹配的命令
+pub fn run_commands(commands: &[CommandOptions], event: NotifyEvent, info: &BluetoothInfo) {
+    commands
+        .iter()
+        .filter(|command| command.events.is_empty() || command.events.contains(&event))
+        .filter(|command| {
+            command.devices.is_empty() || command.devices.iter().any(|device| info.matches(device))
+        })
+        .for_each(|command| {
+            let command_line = render_command(&command.command, event, info);
+            info!("Running command for {}: {command_line}", event.as_str());
+
+            // 同时以环境变量提供原始值，供脚本自行读取
+            let result = std::process::Command::new("cmd")
+                .arg("/C")
+                .raw_arg(&command_line)
+                .env("BLUEGAUGE_EVENT", event.as_str())
+                .env("BLUEGAUGE_NAME", &info.name)
+                .env("BLUEGAUGE_BATTERY", info.battery.to_string())
+                .env("BLUEGAUGE_ADDRESS", format_address(info.address))
+                .creation_flags(CREATE_NO_WINDOW)
+                .spawn();
+
+            if let Err(e) = result {
+                warn!("Failed to run command `{command_line}`: {e}");
+            }
+        });
+}
+
+/// 设备名称可由他人任意设置，去掉 cmd 的特殊字符以免被当作命令执行
+fn render_command(template: &str, event: NotifyEvent, info: &BluetoothInfo) -> String {
+    let name = info
+        .name
+        .chars()
+        .filter(|c| {
+            !matches!(
+                c,
+                '"' | '&' | '|' | '<' | '>' | '^' | '%' | '!' | '\r' | '\n'
+            )
+        })
+        .collect::<String>();
+    let status = if info.status {
+        "connected"
+    } else {
+        "disconnected"
+    };
+
+    template
+        .replace("{event}", event.as_str())
+        .replace("{name}", &name)
+        .replace("{battery}", &info.battery.to_string())
+        .replace("{address}", &format_address(info.address))
+        .replace("{status}", status)
+}
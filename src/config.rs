@@ -1,13 +1,23 @@
-use std::collections::HashMap;
+use crate::{
+    bluetooth::info::{BluetoothInfo, DeviceCategory, DeviceError, get_device_errors},
+    language::Localization,
+    legacy::{find_legacy_config, import_legacy_config},
+    notify::NotifyEvent,
+};
+
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::env;
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock, RwLockReadGuard};
+use std::time::Duration;
 
-use anyhow::{Result, anyhow};
-use log::warn;
+use anyhow::{Context, Result, anyhow};
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
 
+pub use bluegauge_core::{companion::CompanionOptions, info::CalibrationOptions};
+
 #[derive(Debug, Serialize, Deserialize)]
 struct ConfigToml {
     #[serde(rename = "tray")]
@@ -19,22 +29,266 @@ struct ConfigToml {
     #[serde(default)]
     #[serde(rename = "device_aliases")]
     device_aliases: HashMap<String, String>,
+
+    /// 不在托盘菜单及提示中显示的设备，名称或地址
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    hidden_devices: Vec<String>,
+
+    /// 固定的设备，名称或地址；开启 `pinned_only` 时只为这些设备发送通知
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pinned_devices: Vec<String>,
+
+    /// 保持连接的设备，名称或地址；意外断开时自动尝试重新连接
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    keep_connected_devices: Vec<String>,
+
+    #[serde(default)]
+    #[serde(rename = "export")]
+    export_options: ExportOptions,
+
+    #[serde(default)]
+    #[serde(rename = "http_api")]
+    http_api_options: HttpApiOptions,
+
+    #[serde(default)]
+    #[serde(rename = "history")]
+    history_options: HistoryOptions,
+
+    #[serde(default)]
+    #[serde(rename = "named_pipe")]
+    named_pipe_options: NamedPipeOptions,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    webhooks: Vec<WebhookOptions>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    commands: Vec<CommandOptions>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    rules: Vec<RuleOptions>,
+
+    #[serde(default)]
+    #[serde(rename = "startup")]
+    startup_options: StartupOptions,
+
+    #[serde(default)]
+    #[serde(rename = "update")]
+    update_options: UpdateOptions,
+
+    #[serde(default)]
+    #[serde(rename = "network")]
+    network_options: NetworkOptions,
+
+    #[serde(default)]
+    #[serde(rename = "language")]
+    language_options: LanguageOptions,
+
+    #[serde(default)]
+    #[serde(rename = "hotkey")]
+    hotkey_options: HotkeyOptions,
+
+    #[serde(default)]
+    #[serde(rename = "speech")]
+    speech_options: SpeechOptions,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    calibrations: Vec<CalibrationOptions>,
+
+    #[serde(default)]
+    #[serde(rename = "smoothing")]
+    smoothing_options: SmoothingOptions,
+
+    #[serde(default)]
+    #[serde(rename = "blink")]
+    blink_options: BlinkOptions,
+
+    #[serde(default)]
+    #[serde(rename = "click")]
+    click_options: ClickOptions,
+
+    /// 当前使用的配置方案
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    active_profile: Option<String>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    profiles: Vec<Profile>,
+
+    #[serde(default)]
+    #[serde(rename = "ble")]
+    ble_options: BleOptions,
+
+    #[serde(default)]
+    #[serde(rename = "protocols")]
+    protocol_options: ProtocolOptions,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    companions: Vec<CompanionOptions>,
+
+    #[serde(default)]
+    #[serde(rename = "low_battery_icon")]
+    low_battery_icon_options: LowBatteryIconOptions,
+
+    #[serde(default)]
+    #[serde(rename = "telemetry")]
+    telemetry_options: TelemetryOptions,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct TrayOptionsToml {
     update_interval: u64,
+    /// 根据设备状态自动调整更新间隔
+    #[serde(default)]
+    adaptive_update_interval: bool,
     #[serde(rename = "tooltip")]
     tray_tooltip: TrayTooltipToml,
     #[serde(rename = "icon")]
     tray_icon_source: TrayIconSource,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct TrayTooltipToml {
     show_disconnected: bool,
     truncate_name: bool,
     prefix_battery: bool,
+    /// 裁剪后的最大显示宽度（CJK 全角字符占 2）
+    #[serde(default = "default_truncate_length")]
+    truncate_length: u8,
+    #[serde(default)]
+    truncate_strategy: TruncateStrategy,
+    /// 去除 "LE-"、"(2)" 等常见前后缀
+    #[serde(default)]
+    strip_affixes: bool,
+    /// 显示设备数据距今多久（已连接时最后一次读取）
+    #[serde(default)]
+    show_last_updated: bool,
+    /// 设备超过该分钟数未报告数据时视为过期，0 为不处理
+    #[serde(default)]
+    stale_minutes: u64,
+    #[serde(default)]
+    stale_policy: StalePolicy,
+    /// 只显示某一类设备
+    #[serde(default)]
+    device_filter: DeviceFilter,
+    /// 托盘提示、菜单及通知中电量的显示方式
+    #[serde(default)]
+    battery_display: BatteryDisplay,
+    /// 托盘菜单中不显示设备列表
+    #[serde(default)]
+    hide_device_list: bool,
+    /// 不显示托盘提示
+    #[serde(default)]
+    hide_tooltip: bool,
+    /// 悬停时以自绘的浮窗代替托盘提示
+    #[serde(default)]
+    rich_flyout: bool,
+    /// 每行末尾以方块字符显示电量条
+    #[serde(default)]
+    battery_bar: bool,
+    /// 首行显示已连接设备数及电量最低的设备
+    #[serde(default)]
+    summary_header: bool,
+    /// 在当前的默认播放设备后显示 🎧
+    #[serde(default)]
+    show_default_audio: bool,
+}
+
+fn default_truncate_length() -> u8 {
+    10
+}
+
+/// 过期数据在托盘提示中的显示方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StalePolicy {
+    /// 以灰色状态图标显示
+    #[default]
+    Dim,
+    /// 不显示电量
+    HideBattery,
+    /// 不显示该设备
+    Hide,
+}
+
+/// 托盘提示及设备菜单中显示的设备类别
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceFilter {
+    #[default]
+    All,
+    /// 耳机、音箱
+    Audio,
+    /// 鼠标、键盘、手柄
+    Input,
+}
+
+impl DeviceFilter {
+    pub fn matches(self, category: DeviceCategory) -> bool {
+        match self {
+            DeviceFilter::All => true,
+            DeviceFilter::Audio => category == DeviceCategory::Audio,
+            DeviceFilter::Input => matches!(
+                category,
+                DeviceCategory::Mouse | DeviceCategory::Keyboard | DeviceCategory::Gamepad
+            ),
+        }
+    }
+}
+
+/// 电量的显示方式，部分设备只以 10% 为单位报告电量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatteryDisplay {
+    #[default]
+    Exact,
+    /// 四舍五入到 5%
+    Round5,
+    /// 四舍五入到 10%
+    Round10,
+    /// 高（60% 及以上）、中（30% 及以上）、低（10% 及以上）、严重
+    Verbal,
+}
+
+impl BatteryDisplay {
+    /// 按显示方式格式化电量，如 "64%"、"65%"、"60%" 或 "中"
+    pub fn format(self, battery: u8) -> String {
+        let round = |step: u16| (u16::from(battery) + step / 2) / step * step;
+        match self {
+            BatteryDisplay::Exact => format!("{battery}%"),
+            BatteryDisplay::Round5 => format!("{}%", round(5)),
+            BatteryDisplay::Round10 => format!("{}%", round(10)),
+            BatteryDisplay::Verbal => {
+                let loc = Localization::current();
+                match battery {
+                    60.. => loc.battery_high,
+                    30..60 => loc.battery_medium,
+                    10..30 => loc.battery_low,
+                    _ => loc.battery_critical,
+                }
+                .to_owned()
+            }
+        }
+    }
+}
+
+/// 名称过长时省略的位置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TruncateStrategy {
+    /// "LongDevic..."
+    #[default]
+    End,
+    /// "LongD...ame"
+    Middle,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,10 +297,18 @@ pub enum TrayIconSource {
     App,
     BatteryCustom {
         address: u64,
+        /// `address` 未连接时依次尝试的设备
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        fallback: Vec<u64>,
     },
     BatteryFont {
         address: u64,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        fallback: Vec<u64>,
         font_name: String,
+        /// 字体文件（.ttf、.otf），无需安装，设置后忽略 `font_name`；相对路径基于配置文件所在目录
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        font_file: Option<PathBuf>,
         /// "FollowSystemTheme"(Default),
         /// "ConnectColor"(连接状态颜色)
         /// Font Color in hex format (e.g. "#FFFFFF")
@@ -54,10 +316,56 @@ pub enum TrayIconSource {
         font_color: Option</* Hex color */ String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         font_size: Option<u8>, // Default: 64
+        /// 在电量后显示 "%"
+        #[serde(default)]
+        percent_sign: bool,
+        #[serde(default)]
+        layout: FontIconLayout,
+        #[serde(default)]
+        bold: bool,
+        #[serde(default)]
+        italic: bool,
+    },
+    /// 已连接设备中的最低电量，以字体绘制
+    LowestBattery {
+        /// 只统计该类别的设备
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        category: Option<DeviceCategory>,
+        #[serde(default = "default_font_name")]
+        font_name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        font_file: Option<PathBuf>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        font_color: Option</* Hex color */ String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        font_size: Option<u8>,
+        #[serde(default)]
+        percent_sign: bool,
+        #[serde(default)]
+        layout: FontIconLayout,
+        #[serde(default)]
+        bold: bool,
+        #[serde(default)]
+        italic: bool,
     },
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+fn default_font_name() -> String {
+    "Arial".to_owned()
+}
+
+/// 字体图标的排版
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FontIconLayout {
+    /// 只显示电量
+    #[default]
+    Single,
+    /// 电量在上，设备名称首字母在下
+    TwoLine,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct NotifyOptionsToml {
     mute: bool,
     low_battery: u8,
@@ -65,43 +373,133 @@ struct NotifyOptionsToml {
     reconnection: bool,
     added: bool,
     removed: bool,
+    /// 同一设备名称变化（如驱动更新后）时通知，该设备的别名会自动沿用到新名称
+    #[serde(default)]
+    renamed: bool,
+    /// 只为固定的设备发送通知（隐藏的设备始终不通知），Webhook 与命令不受影响
+    #[serde(default)]
+    pinned_only: bool,
+    /// 断开、重新连接的去抖窗口（秒），窗口内来回变化只按最终状态通知一次，为 0 时立即通知
+    #[serde(default)]
+    flap_window: u64,
+    /// 窗口内多次变化时改为通知一次“连接不稳定”
+    #[serde(default)]
+    flap_summary: bool,
+    /// 设备监听失效并被看门狗重建时通知
+    #[serde(default)]
+    watchdog: bool,
+    /// 电量每降过一个阶梯时通知
+    #[serde(default)]
+    milestones: bool,
+    #[serde(default = "default_milestone_steps")]
+    milestone_steps: Vec<u8>,
+}
+
+fn default_milestone_steps() -> Vec<u8> {
+    vec![50, 30, 20, 10]
+}
+
+/// 配置方案：托盘及通知设置的快照，切换时整体替换当前设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    #[serde(rename = "tray")]
+    tray_options: TrayOptionsToml,
+    #[serde(rename = "notify")]
+    notify_options: NotifyOptionsToml,
 }
 
 impl TrayIconSource {
+    /// 显示指定设备电量的图标，存在自定义图标时使用自定义图标，否则使用默认样式的字体图标
+    pub fn battery(address: u64) -> Self {
+        prefer_custom_icon(Self::BatteryFont {
+            address,
+            fallback: Vec::new(),
+            font_name: "Arial".to_owned(),
+            font_file: None,
+            font_color: Some("FollowSystemTheme".to_owned()),
+            font_size: Some(64),
+            percent_sign: false,
+            layout: FontIconLayout::Single,
+            bold: false,
+            italic: false,
+        })
+    }
+
     pub fn update_address(&mut self, new_address: u64) {
         match self {
-            Self::App => (),
-            Self::BatteryCustom { address } => {
-                *address = new_address;
-            }
-            Self::BatteryFont { address, .. } => {
+            Self::App | Self::LowestBattery { .. } => (),
+            Self::BatteryCustom { address, fallback }
+            | Self::BatteryFont {
+                address, fallback, ..
+            } => {
                 *address = new_address;
+                fallback.retain(|address| *address != new_address);
             }
         }
     }
 
-    pub fn get_address(&self) -> Option<u64> {
+    /// 图标设备及其后备设备，按优先顺序
+    pub fn get_addresses(&self) -> Vec<u64> {
         match self {
-            Self::App => None,
-            Self::BatteryCustom { address } => Some(*address),
-            Self::BatteryFont { address, .. } => Some(*address),
+            Self::App | Self::LowestBattery { .. } => Vec::new(),
+            Self::BatteryCustom { address, fallback }
+            | Self::BatteryFont {
+                address, fallback, ..
+            } => std::iter::once(*address)
+                .chain(fallback.iter().copied())
+                .collect(),
+        }
+    }
+
+    /// 当前显示的设备：列表中第一个已连接的设备，都未连接时为 `address`；
+    /// 最低电量模式下为电量最低的已连接设备
+    pub fn resolve_address(&self, bluetooth_devices_info: &HashSet<BluetoothInfo>) -> Option<u64> {
+        if let Self::LowestBattery { category, .. } = self {
+            return bluetooth_devices_info
+                .iter()
+                .filter(|info| info.status && category.is_none_or(|c| info.category == c))
+                .min_by_key(|info| info.battery)
+                .map(|info| info.address);
         }
+
+        let addresses = self.get_addresses();
+        addresses
+            .iter()
+            .copied()
+            .find(|address| {
+                bluetooth_devices_info
+                    .iter()
+                    .any(|info| info.address == *address && info.status)
+            })
+            .or_else(|| addresses.first().copied())
+    }
+
+    /// 设备变化时是否可能需要重新绘制图标
+    pub fn depends_on(&self, address: u64) -> bool {
+        matches!(self, Self::LowestBattery { .. }) || self.get_addresses().contains(&address)
     }
 
     pub fn update_connect_color(&mut self, should_update: bool) {
         match self {
             Self::App => (),
-            Self::BatteryCustom { address } => {
+            Self::BatteryCustom { address, fallback } => {
                 if should_update {
                     *self = TrayIconSource::BatteryFont {
                         address: address.to_owned(),
+                        fallback: std::mem::take(fallback),
                         font_name: "Arial".to_owned(),
+                        font_file: None,
                         font_color: Some("FollowSystemTheme".to_owned()),
                         font_size: Some(64),
+                        percent_sign: false,
+                        layout: FontIconLayout::Single,
+                        bold: false,
+                        italic: false,
                     }
                 }
             }
-            Self::BatteryFont { font_color, .. } => {
+            Self::BatteryFont { font_color, .. } | Self::LowestBattery { font_color, .. } => {
                 if should_update {
                     *font_color = Some("ConnectColor".to_owned());
                 } else if *font_color == Some("ConnectColor".to_owned()) {
@@ -112,6 +510,310 @@ impl TrayIconSource {
     }
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportOptions {
+    /// 每次更新后写入设备信息的 JSON 文件路径，相对路径基于配置文件所在目录
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_json: Option<PathBuf>,
+    /// 每次更新后写入的 Adaptive Card JSON，供 Windows 11 小组件等面板显示
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub widget_card: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpApiOptions {
+    pub enabled: bool,
+    pub address: String,
+    pub port: u16,
+    /// `Authorization: Bearer <token>` 或 `?token=<token>`，启用时为空则自动生成
+    pub token: String,
+    /// 局域网客户端访问时使用的本机名称或 IP（不含端口），本机地址总是允许
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allowed_hosts: Vec<String>,
+}
+
+impl Default for HttpApiOptions {
+    fn default() -> Self {
+        HttpApiOptions {
+            enabled: false,
+            address: "127.0.0.1".to_owned(),
+            port: 18650,
+            token: String::new(),
+            allowed_hosts: Vec::new(),
+        }
+    }
+}
+
+/// 在配置文件目录下记录电量变化（BlueGauge.history.jsonl），可从托盘菜单导出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryOptions {
+    pub enabled: bool,
+    /// 每周通知一次最近 7 天各设备的耗电速度、连接时长及最低电量
+    #[serde(default)]
+    pub weekly_summary: bool,
+    /// 同时将每周汇总写入该文本文件，相对路径基于配置文件目录
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weekly_report: Option<PathBuf>,
+}
+
+impl Default for HistoryOptions {
+    fn default() -> Self {
+        HistoryOptions {
+            enabled: true,
+            weekly_summary: false,
+            weekly_report: None,
+        }
+    }
+}
+
+/// `\\.\pipe\BlueGauge` 命名管道
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NamedPipeOptions {
+    pub enabled: bool,
+}
+
+/// 开机自启动（`--minimized`）时蓝牙可能尚未就绪
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StartupOptions {
+    /// 自启动后延迟首次枚举的秒数，期间托盘显示“正在初始化”
+    pub delay: u64,
+}
+
+/// 定期检查 GitHub 上的新版本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateOptions {
+    pub check: bool,
+    /// 检查间隔（小时）
+    pub interval: u64,
+}
+
+impl Default for UpdateOptions {
+    fn default() -> Self {
+        UpdateOptions {
+            check: true,
+            interval: 24,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageOptions {
+    /// "auto" 跟随系统，或 "es-ES"、"de" 等区域名称；可在程序目录的 `lang/` 下放置翻译文件
+    pub locale: String,
+}
+
+impl Default for LanguageOptions {
+    fn default() -> Self {
+        LanguageOptions {
+            locale: "auto".to_owned(),
+        }
+    }
+}
+
+/// 通过 SAPI 朗读设备事件，如 "Headphones at 15 percent"
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeechOptions {
+    /// 同 Webhook 的事件名称，为空时不朗读
+    pub events: Vec<NotifyEvent>,
+    /// 朗读的事件不再弹出通知
+    pub replace_notifications: bool,
+}
+
+impl SpeechOptions {
+    pub fn speaks(&self, event: NotifyEvent) -> bool {
+        self.events.contains(&event)
+    }
+
+    /// 该事件开启了通知且未被朗读替代
+    pub fn shows_notification(&self, event: NotifyEvent, enabled: bool) -> bool {
+        enabled && !(self.replace_notifications && self.speaks(event))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HotkeyOptions {
+    /// 弹出所有设备电量的全局快捷键，如 "Ctrl+Alt+B"，为空时不注册
+    pub summary: String,
+}
+
+/// 电量读数的迟滞，避免在 52%、55% 之间来回跳动时频繁刷新托盘和记录历史
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SmoothingOptions {
+    /// 连接状态不变时，电量变化达到该值才更新显示，为 0 时不平滑
+    pub hysteresis: u8,
+}
+
+/// 有已连接设备的电量低于临界值时闪烁托盘图标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlinkOptions {
+    pub enabled: bool,
+    /// 临界电量（%），低于该值时闪烁
+    pub critical_battery: u8,
+    /// 显示与隐藏图标的切换间隔（毫秒）
+    pub interval: u64,
+}
+
+impl Default for BlinkOptions {
+    fn default() -> Self {
+        BlinkOptions {
+            enabled: false,
+            critical_battery: 10,
+            interval: 500,
+        }
+    }
+}
+
+impl BlinkOptions {
+    /// 过短的间隔会让事件循环空转，至少 100 毫秒
+    pub fn get_interval(&self) -> Duration {
+        Duration::from_millis(self.interval.max(100))
+    }
+}
+
+/// 图标显示的设备低于低电量阈值时，替换托盘图标以便关闭通知时同样能看到
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LowBatteryIconStyle {
+    #[default]
+    Off,
+    /// 红色背景上显示白色电量
+    RedBackground,
+    /// 正常颜色的电量，右下角叠加红色感叹号
+    Exclamation,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LowBatteryIconOptions {
+    pub style: LowBatteryIconStyle,
+}
+
+/// 点击托盘图标时执行的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClickAction {
+    None,
+    /// 打开托盘菜单，只对左键单击有效
+    Menu,
+    /// 显示电量概览，与快捷键相同
+    Summary,
+    /// 立即更新所有设备
+    Refresh,
+    /// 连接或断开托盘图标显示的设备
+    ToggleConnection,
+    /// 打开配置文件
+    OpenSettings,
+}
+
+/// 左键单击、左键双击及中键单击托盘图标时的操作，右键始终打开菜单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClickOptions {
+    pub single: ClickAction,
+    pub double: ClickAction,
+    pub middle: ClickAction,
+}
+
+impl Default for ClickOptions {
+    fn default() -> Self {
+        ClickOptions {
+            single: ClickAction::Menu,
+            double: ClickAction::None,
+            middle: ClickAction::None,
+        }
+    }
+}
+
+/// BLE 设备的读取策略：优先使用电量通知及广播中的数据，避免频繁连接唤醒设备
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BleOptions {
+    /// 已知电量超过该分钟数未更新时才主动读取 GATT，为 0 时每次刷新都读取
+    pub max_data_age: u64,
+}
+
+impl Default for BleOptions {
+    fn default() -> Self {
+        BleOptions { max_data_age: 30 }
+    }
+}
+
+/// 需要建立私有连接的设备协议，默认关闭以免与厂商应用争用连接
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProtocolOptions {
+    /// 通过 Galaxy Buds 的 RFCOMM 通道读取左右耳及充电盒电量
+    pub galaxy_buds: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkOptions {
+    /// 禁止所有联网请求（检查更新、Webhook）
+    pub offline: bool,
+}
+
+/// 匿名的设备兼容性统计，需手动开启
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TelemetryOptions {
+    /// 上报设备型号及读取到电量的方式，不包括设备地址及名称中的所有者
+    #[serde(default)]
+    pub enabled: bool,
+    /// 接收统计的地址，为空时不上报
+    #[serde(default)]
+    pub endpoint: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookOptions {
+    pub url: String,
+    /// "low_battery"、"disconnection"、"reconnection"、"added"、"removed"，为空时发送所有事件
+    #[serde(default)]
+    pub events: Vec<NotifyEvent>,
+    /// 请求体模板，支持 {event}、{name}、{battery}、{address}、{status}
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// 默认 "application/json"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandOptions {
+    /// 通过 `cmd /C` 执行，支持 {event}、{name}、{battery}、{address}、{status}
+    pub command: String,
+    /// 同 Webhook 的事件名称，默认只在低电量时执行，为空时所有事件都执行
+    #[serde(default = "default_command_events")]
+    pub events: Vec<NotifyEvent>,
+    /// 设备名称或地址，为空时对所有设备生效
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<String>,
+}
+
+fn default_command_events() -> Vec<NotifyEvent> {
+    vec![NotifyEvent::LowBattery]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleOptions {
+    /// 设备名称或地址
+    pub device: String,
+    /// 已连接的设备电量低于该值时执行
+    pub below: u8,
+    pub action: RuleAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    /// 断开连接（保留配对）
+    Disconnect,
+}
+
 #[derive(Debug)]
 pub struct NotifyOptions {
     pub mute: AtomicBool,
@@ -120,6 +822,13 @@ pub struct NotifyOptions {
     pub reconnection: AtomicBool,
     pub added: AtomicBool,
     pub removed: AtomicBool,
+    pub renamed: AtomicBool,
+    pub pinned_only: AtomicBool,
+    pub flap_window: AtomicU64,
+    pub flap_summary: AtomicBool,
+    pub watchdog: AtomicBool,
+    pub milestones: AtomicBool,
+    pub milestone_steps: Mutex<Vec<u8>>,
 }
 
 impl Default for NotifyOptions {
@@ -131,6 +840,13 @@ impl Default for NotifyOptions {
             reconnection: AtomicBool::new(false),
             added: AtomicBool::new(false),
             removed: AtomicBool::new(false),
+            renamed: AtomicBool::new(false),
+            pinned_only: AtomicBool::new(false),
+            flap_window: AtomicU64::new(0),
+            flap_summary: AtomicBool::new(false),
+            watchdog: AtomicBool::new(false),
+            milestones: AtomicBool::new(false),
+            milestone_steps: Mutex::new(default_milestone_steps()),
         }
     }
 }
@@ -143,21 +859,63 @@ impl NotifyOptions {
             "reconnection" => self.reconnection.store(check, Ordering::Relaxed),
             "added" => self.added.store(check, Ordering::Relaxed),
             "removed" => self.removed.store(check, Ordering::Relaxed),
+            "renamed" => self.renamed.store(check, Ordering::Relaxed),
+            "pinned_only" => self.pinned_only.store(check, Ordering::Relaxed),
+            "milestones" => self.milestones.store(check, Ordering::Relaxed),
             _ => (),
         }
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct TooltipOptions {
     pub prefix_battery: AtomicBool,
     pub show_disconnected: AtomicBool,
     pub truncate_name: AtomicBool,
+    pub truncate_length: AtomicU8,
+    pub truncate_strategy: Mutex<TruncateStrategy>,
+    pub strip_affixes: AtomicBool,
+    pub show_last_updated: AtomicBool,
+    pub stale_minutes: AtomicU64,
+    pub stale_policy: Mutex<StalePolicy>,
+    pub device_filter: Mutex<DeviceFilter>,
+    pub battery_display: Mutex<BatteryDisplay>,
+    pub hide_device_list: AtomicBool,
+    pub hide_tooltip: AtomicBool,
+    pub rich_flyout: AtomicBool,
+    pub battery_bar: AtomicBool,
+    pub summary_header: AtomicBool,
+    pub show_default_audio: AtomicBool,
+}
+
+impl Default for TooltipOptions {
+    fn default() -> Self {
+        TooltipOptions {
+            prefix_battery: AtomicBool::new(false),
+            show_disconnected: AtomicBool::new(false),
+            truncate_name: AtomicBool::new(false),
+            truncate_length: AtomicU8::new(default_truncate_length()),
+            truncate_strategy: Mutex::new(TruncateStrategy::End),
+            strip_affixes: AtomicBool::new(false),
+            show_last_updated: AtomicBool::new(false),
+            stale_minutes: AtomicU64::new(0),
+            stale_policy: Mutex::new(StalePolicy::Dim),
+            device_filter: Mutex::new(DeviceFilter::All),
+            battery_display: Mutex::new(BatteryDisplay::Exact),
+            hide_device_list: AtomicBool::new(false),
+            hide_tooltip: AtomicBool::new(false),
+            rich_flyout: AtomicBool::new(false),
+            battery_bar: AtomicBool::new(false),
+            summary_header: AtomicBool::new(false),
+            show_default_audio: AtomicBool::new(false),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct TrayOptions {
     pub update_interval: AtomicU64,
+    pub adaptive_update_interval: AtomicBool,
     pub tooltip_options: TooltipOptions,
     pub tray_icon_source: Mutex<TrayIconSource>,
 }
@@ -166,6 +924,7 @@ impl Default for TrayOptions {
     fn default() -> Self {
         TrayOptions {
             update_interval: AtomicU64::new(60),
+            adaptive_update_interval: AtomicBool::new(false),
             tooltip_options: TooltipOptions::default(),
             tray_icon_source: Mutex::new(TrayIconSource::App),
         }
@@ -175,6 +934,9 @@ impl Default for TrayOptions {
 impl TrayOptions {
     pub fn update(&self, name: &str, check: bool) {
         match name {
+            "adaptive_update_interval" => self
+                .adaptive_update_interval
+                .store(check, Ordering::Relaxed),
             "show_disconnected" => self
                 .tooltip_options
                 .show_disconnected
@@ -187,6 +949,38 @@ impl TrayOptions {
                 .tooltip_options
                 .prefix_battery
                 .store(check, Ordering::Relaxed),
+            "strip_affixes" => self
+                .tooltip_options
+                .strip_affixes
+                .store(check, Ordering::Relaxed),
+            "show_last_updated" => self
+                .tooltip_options
+                .show_last_updated
+                .store(check, Ordering::Relaxed),
+            "hide_device_list" => self
+                .tooltip_options
+                .hide_device_list
+                .store(check, Ordering::Relaxed),
+            "hide_tooltip" => self
+                .tooltip_options
+                .hide_tooltip
+                .store(check, Ordering::Relaxed),
+            "rich_flyout" => self
+                .tooltip_options
+                .rich_flyout
+                .store(check, Ordering::Relaxed),
+            "battery_bar" => self
+                .tooltip_options
+                .battery_bar
+                .store(check, Ordering::Relaxed),
+            "summary_header" => self
+                .tooltip_options
+                .summary_header
+                .store(check, Ordering::Relaxed),
+            "show_default_audio" => self
+                .tooltip_options
+                .show_default_audio
+                .store(check, Ordering::Relaxed),
             _ => (),
         }
     }
@@ -195,18 +989,186 @@ impl TrayOptions {
 #[derive(Debug)]
 pub struct Config {
     pub config_path: PathBuf,
+    /// 本次启动时新建了配置文件（且没有导入旧版本配置），需要显示首次启动向导
+    pub first_run: bool,
     pub force_update: AtomicBool,
+    /// 只有显示相关的设置变化，以当前设备信息重建托盘，无需重新枚举设备
+    pub refresh_tray: AtomicBool,
+    /// 整体替换托盘及通知设置（切换配置方案、重新加载）时持有写锁，
+    /// 后台任务同时读取多项设置时持有读锁，不会读到新旧混合的设置
+    options_lock: RwLock<()>,
     pub tray_options: TrayOptions,
     pub notify_options: NotifyOptions,
-    pub device_aliases: HashMap<String, String>,
+    pub device_aliases: Mutex<HashMap<String, String>>,
+    pub hidden_devices: Mutex<Vec<String>>,
+    pub pinned_devices: Mutex<Vec<String>>,
+    pub keep_connected_devices: Mutex<Vec<String>>,
+    pub export_options: ExportOptions,
+    /// 令牌为空时由 HTTP API 启动时生成并写入
+    pub http_api_options: Mutex<HttpApiOptions>,
+    pub history_options: HistoryOptions,
+    pub named_pipe_options: NamedPipeOptions,
+    pub webhooks: Vec<WebhookOptions>,
+    pub commands: Vec<CommandOptions>,
+    pub rules: Vec<RuleOptions>,
+    pub startup_options: StartupOptions,
+    pub update_options: UpdateOptions,
+    pub network_options: NetworkOptions,
+    pub language_options: LanguageOptions,
+    pub hotkey_options: HotkeyOptions,
+    pub speech_options: SpeechOptions,
+    pub calibrations: Vec<CalibrationOptions>,
+    pub smoothing_options: SmoothingOptions,
+    pub blink_options: BlinkOptions,
+    pub click_options: ClickOptions,
+    pub active_profile: Mutex<Option<String>>,
+    pub profiles: Mutex<Vec<Profile>>,
+    pub ble_options: BleOptions,
+    pub protocol_options: ProtocolOptions,
+    pub companions: Vec<CompanionOptions>,
+    pub low_battery_icon_options: LowBatteryIconOptions,
+    pub telemetry_options: TelemetryOptions,
+}
+
+const CONFIG_FILE_NAME: &str = "BlueGauge.toml";
+
+/// 配置文件所在位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigLocation {
+    /// 程序所在目录（便携模式）
+    Portable,
+    /// %APPDATA%\BlueGauge
+    AppData,
+}
+
+impl ConfigLocation {
+    /// `--portable` 强制使用程序目录；否则优先使用已存在的 %APPDATA% 配置，
+    /// 程序目录不可写入时（如安装在 Program Files 下）自动回退到 %APPDATA%
+    pub fn resolve() -> Result<Self> {
+        if env::args().any(|arg| arg == "--portable") {
+            return Ok(Self::Portable);
+        }
+
+        if Self::AppData.config_path()?.is_file() {
+            return Ok(Self::AppData);
+        }
+
+        let exe_dir = Self::Portable.config_dir()?;
+        if is_dir_writable(&exe_dir) {
+            Ok(Self::Portable)
+        } else {
+            Ok(Self::AppData)
+        }
+    }
+
+    pub fn config_dir(&self) -> Result<PathBuf> {
+        match self {
+            Self::Portable => env::current_exe()
+                .ok()
+                .and_then(|exe_path| exe_path.parent().map(PathBuf::from))
+                .ok_or_else(|| anyhow!("Failed to get the BlueGauge directory")),
+            Self::AppData => env::var_os("APPDATA")
+                .map(|app_data| PathBuf::from(app_data).join("BlueGauge"))
+                .ok_or_else(|| anyhow!("Failed to get the %APPDATA% directory")),
+        }
+    }
+
+    pub fn config_path(&self) -> Result<PathBuf> {
+        self.config_dir().map(|dir| dir.join(CONFIG_FILE_NAME))
+    }
+
+    /// 当前位置没有配置文件时，从另一位置迁移配置文件
+    fn migrate(&self) -> Result<()> {
+        let config_path = self.config_path()?;
+        if config_path.is_file() {
+            return Ok(());
+        }
+
+        let other_config_path = match self {
+            Self::Portable => Self::AppData.config_path()?,
+            Self::AppData => Self::Portable.config_path()?,
+        };
+        if !other_config_path.is_file() {
+            return Ok(());
+        }
+
+        if let Some(config_dir) = config_path.parent() {
+            std::fs::create_dir_all(config_dir)?;
+        }
+        std::fs::copy(&other_config_path, &config_path)
+            .map_err(|e| anyhow!("Failed to migrate config from {other_config_path:?} - {e}"))?;
+        info!("Migrated config from {other_config_path:?} to {config_path:?}");
+
+        Ok(())
+    }
+}
+
+fn is_dir_writable(dir: &Path) -> bool {
+    let test_path = dir.join(".BlueGauge.write_test");
+    let writable = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(&test_path)
+        .is_ok();
+    let _ = std::fs::remove_file(&test_path);
+    writable
+}
+
+/// 可重新加载的配置项，其余项目在启动时读取
+const RELOADABLE_SECTIONS: [&str; 8] = [
+    "tray",
+    "notify",
+    "device_aliases",
+    "hidden_devices",
+    "pinned_devices",
+    "keep_connected_devices",
+    "active_profile",
+    "profiles",
+];
+
+/// 重新加载配置时变化的项目（如 `tray.tooltip.battery_bar`）
+#[derive(Debug, Default)]
+pub struct ConfigReload {
+    /// 已应用
+    pub applied: Vec<String>,
+    /// 需重启后生效
+    pub restart_required: Vec<String>,
+}
+
+/// 逐层比较两个表，记录值不同的键；数组整体比较
+fn changed_keys(prefix: &str, old: &toml::Value, new: &toml::Value, changed: &mut Vec<String>) {
+    match (old, new) {
+        (toml::Value::Table(old), toml::Value::Table(new)) => {
+            let keys = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+            for key in keys {
+                let path = if prefix.is_empty() {
+                    key.to_owned()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                match (old.get(key), new.get(key)) {
+                    (Some(old), Some(new)) => changed_keys(&path, old, new, changed),
+                    _ => changed.push(path),
+                }
+            }
+        }
+        (old, new) if old != new => changed.push(prefix.to_owned()),
+        _ => (),
+    }
 }
 
 impl Config {
     pub fn open() -> Result<Self> {
-        let config_path = env::current_exe()
-            .ok()
-            .map(|exe_path| exe_path.with_file_name("BlueGauge.toml"))
-            .ok_or_else(|| anyhow!("Failed to get config path"))?;
+        let config_location = ConfigLocation::resolve()?;
+        if let Err(e) = config_location.migrate() {
+            warn!("{e}");
+        }
+
+        let config_path = config_location.config_path()?;
+        if let Some(config_dir) = config_path.parent() {
+            std::fs::create_dir_all(config_dir)?;
+        }
 
         if config_path.is_file() {
             Config::read_toml(config_path.clone()).or_else(|e| {
@@ -214,52 +1176,234 @@ impl Config {
                 Config::create_toml(config_path)
             })
         } else {
-            Config::create_toml(config_path)
+            let mut config = Config::create_toml(config_path)?;
+            // 由旧版本升级时导入其 INI 配置，否则为全新安装
+            match find_legacy_config(&config.config_path) {
+                Some(ini_path) => match import_legacy_config(&config, &ini_path) {
+                    Ok(imported) => info!("Imported {imported} settings from {ini_path:?}"),
+                    Err(e) => warn!("Failed to import the legacy config - {e}"),
+                },
+                None => config.first_run = true,
+            }
+            Ok(config)
         }
     }
 
     pub fn save(&self) {
+        let toml_str = toml::to_string_pretty(&self.config_toml())
+            .expect("Failed to serialize ConfigToml structure as a String of TOML.");
+        std::fs::write(&self.config_path, toml_str)
+            .expect("Failed to TOML String to BlueGauge.toml");
+    }
+
+    /// 当前的全部设置，用于保存配置及比较重新读取的配置
+    fn config_toml(&self) -> ConfigToml {
+        ConfigToml {
+            tray_options: self.tray_options_toml(),
+            notify_options: self.notify_options_toml(),
+            device_aliases: self.device_aliases.lock().unwrap().clone(),
+            hidden_devices: self.hidden_devices.lock().unwrap().clone(),
+            pinned_devices: self.pinned_devices.lock().unwrap().clone(),
+            keep_connected_devices: self.keep_connected_devices.lock().unwrap().clone(),
+            export_options: self.export_options.clone(),
+            http_api_options: self.http_api_options.lock().unwrap().clone(),
+            history_options: self.history_options.clone(),
+            named_pipe_options: self.named_pipe_options.clone(),
+            webhooks: self.webhooks.clone(),
+            commands: self.commands.clone(),
+            rules: self.rules.clone(),
+            startup_options: self.startup_options.clone(),
+            update_options: self.update_options.clone(),
+            network_options: self.network_options.clone(),
+            language_options: self.language_options.clone(),
+            telemetry_options: self.telemetry_options.clone(),
+            low_battery_icon_options: self.low_battery_icon_options.clone(),
+            companions: self.companions.clone(),
+            protocol_options: self.protocol_options.clone(),
+            ble_options: self.ble_options.clone(),
+            click_options: self.click_options.clone(),
+            active_profile: self.active_profile.lock().unwrap().clone(),
+            profiles: self.profiles.lock().unwrap().clone(),
+            blink_options: self.blink_options.clone(),
+            smoothing_options: self.smoothing_options.clone(),
+            calibrations: self.calibrations.clone(),
+            speech_options: self.speech_options.clone(),
+            hotkey_options: self.hotkey_options.clone(),
+        }
+    }
+
+    /// 重新读取配置文件，校验后应用托盘、通知、设备列表及配置方案等可随时修改的设置。
+    /// 返回变化的项目，其余项目在启动时读取，需重启后生效
+    pub fn reload(&self) -> Result<ConfigReload> {
+        let content = std::fs::read_to_string(&self.config_path)
+            .with_context(|| format!("Failed to read {:?}", self.config_path))?;
+        let mut toml_config: ConfigToml = toml::from_str(&content)?;
+        // 与启动时相同，使比较时不因图标来源的转换而误报变化
+        toml_config.tray_options.tray_icon_source =
+            prefer_custom_icon(toml_config.tray_options.tray_icon_source);
+
+        if toml_config.tray_options.update_interval == 0 {
+            return Err(anyhow!("update_interval must be greater than 0"));
+        }
+        if let Some(active_profile) = &toml_config.active_profile
+            && !toml_config
+                .profiles
+                .iter()
+                .any(|p| &p.name == active_profile)
+        {
+            return Err(anyhow!("Profile '{active_profile}' does not exist"));
+        }
+
+        let mut changed = Vec::new();
+        changed_keys(
+            "",
+            &toml::Value::try_from(self.config_toml())?,
+            &toml::Value::try_from(&toml_config)?,
+            &mut changed,
+        );
+        let (applied, restart_required): (Vec<_>, Vec<_>) = changed.into_iter().partition(|key| {
+            let section = key.split('.').next().unwrap_or_default();
+            RELOADABLE_SECTIONS.contains(&section)
+        });
+
+        self.apply_options(toml_config.tray_options, toml_config.notify_options);
+        *self.device_aliases.lock().unwrap() = toml_config.device_aliases;
+        *self.hidden_devices.lock().unwrap() = toml_config.hidden_devices;
+        *self.pinned_devices.lock().unwrap() = toml_config.pinned_devices;
+        *self.keep_connected_devices.lock().unwrap() = toml_config.keep_connected_devices;
+        *self.active_profile.lock().unwrap() = toml_config.active_profile;
+        *self.profiles.lock().unwrap() = toml_config.profiles;
+
+        Ok(ConfigReload {
+            applied,
+            restart_required,
+        })
+    }
+
+    /// 当前的托盘设置，用于保存配置及配置方案
+    fn tray_options_toml(&self) -> TrayOptionsToml {
         let tray_icon_source = {
             let lock = self.tray_options.tray_icon_source.lock().unwrap();
             lock.clone()
         };
-        let toml_config = ConfigToml {
-            tray_options: TrayOptionsToml {
-                update_interval: self.tray_options.update_interval.load(Ordering::Relaxed),
-                tray_tooltip: TrayTooltipToml {
-                    show_disconnected: self
-                        .tray_options
-                        .tooltip_options
-                        .show_disconnected
-                        .load(Ordering::Relaxed),
-                    truncate_name: self
-                        .tray_options
-                        .tooltip_options
-                        .truncate_name
-                        .load(Ordering::Relaxed),
-                    prefix_battery: self
-                        .tray_options
-                        .tooltip_options
-                        .prefix_battery
-                        .load(Ordering::Relaxed),
-                },
-                tray_icon_source,
-            },
-            notify_options: NotifyOptionsToml {
-                mute: self.notify_options.mute.load(Ordering::Relaxed),
-                low_battery: self.notify_options.low_battery.load(Ordering::Relaxed),
-                disconnection: self.notify_options.disconnection.load(Ordering::Relaxed),
-                reconnection: self.notify_options.reconnection.load(Ordering::Relaxed),
-                added: self.notify_options.added.load(Ordering::Relaxed),
-                removed: self.notify_options.removed.load(Ordering::Relaxed),
+        TrayOptionsToml {
+            update_interval: self.tray_options.update_interval.load(Ordering::Relaxed),
+            adaptive_update_interval: self
+                .tray_options
+                .adaptive_update_interval
+                .load(Ordering::Relaxed),
+            tray_tooltip: TrayTooltipToml {
+                show_disconnected: self
+                    .tray_options
+                    .tooltip_options
+                    .show_disconnected
+                    .load(Ordering::Relaxed),
+                truncate_name: self
+                    .tray_options
+                    .tooltip_options
+                    .truncate_name
+                    .load(Ordering::Relaxed),
+                prefix_battery: self
+                    .tray_options
+                    .tooltip_options
+                    .prefix_battery
+                    .load(Ordering::Relaxed),
+                truncate_length: self
+                    .tray_options
+                    .tooltip_options
+                    .truncate_length
+                    .load(Ordering::Relaxed),
+                truncate_strategy: *self
+                    .tray_options
+                    .tooltip_options
+                    .truncate_strategy
+                    .lock()
+                    .unwrap(),
+                strip_affixes: self
+                    .tray_options
+                    .tooltip_options
+                    .strip_affixes
+                    .load(Ordering::Relaxed),
+                show_last_updated: self
+                    .tray_options
+                    .tooltip_options
+                    .show_last_updated
+                    .load(Ordering::Relaxed),
+                stale_minutes: self
+                    .tray_options
+                    .tooltip_options
+                    .stale_minutes
+                    .load(Ordering::Relaxed),
+                stale_policy: *self
+                    .tray_options
+                    .tooltip_options
+                    .stale_policy
+                    .lock()
+                    .unwrap(),
+                device_filter: *self
+                    .tray_options
+                    .tooltip_options
+                    .device_filter
+                    .lock()
+                    .unwrap(),
+                battery_display: *self
+                    .tray_options
+                    .tooltip_options
+                    .battery_display
+                    .lock()
+                    .unwrap(),
+                hide_device_list: self
+                    .tray_options
+                    .tooltip_options
+                    .hide_device_list
+                    .load(Ordering::Relaxed),
+                hide_tooltip: self
+                    .tray_options
+                    .tooltip_options
+                    .hide_tooltip
+                    .load(Ordering::Relaxed),
+                rich_flyout: self
+                    .tray_options
+                    .tooltip_options
+                    .rich_flyout
+                    .load(Ordering::Relaxed),
+                battery_bar: self
+                    .tray_options
+                    .tooltip_options
+                    .battery_bar
+                    .load(Ordering::Relaxed),
+                summary_header: self
+                    .tray_options
+                    .tooltip_options
+                    .summary_header
+                    .load(Ordering::Relaxed),
+                show_default_audio: self
+                    .tray_options
+                    .tooltip_options
+                    .show_default_audio
+                    .load(Ordering::Relaxed),
             },
-            device_aliases: self.device_aliases.clone(),
-        };
+            tray_icon_source,
+        }
+    }
 
-        let toml_str = toml::to_string_pretty(&toml_config)
-            .expect("Failed to serialize ConfigToml structure as a String of TOML.");
-        std::fs::write(&self.config_path, toml_str)
-            .expect("Failed to TOML String to BlueGauge.toml");
+    /// 当前的通知设置，用于保存配置及配置方案
+    fn notify_options_toml(&self) -> NotifyOptionsToml {
+        NotifyOptionsToml {
+            mute: self.notify_options.mute.load(Ordering::Relaxed),
+            low_battery: self.notify_options.low_battery.load(Ordering::Relaxed),
+            disconnection: self.notify_options.disconnection.load(Ordering::Relaxed),
+            reconnection: self.notify_options.reconnection.load(Ordering::Relaxed),
+            added: self.notify_options.added.load(Ordering::Relaxed),
+            removed: self.notify_options.removed.load(Ordering::Relaxed),
+            renamed: self.notify_options.renamed.load(Ordering::Relaxed),
+            pinned_only: self.notify_options.pinned_only.load(Ordering::Relaxed),
+            flap_window: self.notify_options.flap_window.load(Ordering::Relaxed),
+            flap_summary: self.notify_options.flap_summary.load(Ordering::Relaxed),
+            watchdog: self.notify_options.watchdog.load(Ordering::Relaxed),
+            milestones: self.notify_options.milestones.load(Ordering::Relaxed),
+            milestone_steps: self.notify_options.milestone_steps.lock().unwrap().clone(),
+        }
     }
 
     fn create_toml(config_path: PathBuf) -> Result<Self> {
@@ -269,10 +1413,25 @@ impl Config {
         let default_config = ConfigToml {
             tray_options: TrayOptionsToml {
                 update_interval: 60,
+                adaptive_update_interval: false,
                 tray_tooltip: TrayTooltipToml {
                     show_disconnected: false,
                     truncate_name: false,
                     prefix_battery: false,
+                    truncate_length: default_truncate_length(),
+                    truncate_strategy: TruncateStrategy::End,
+                    strip_affixes: false,
+                    show_last_updated: false,
+                    stale_minutes: 0,
+                    stale_policy: StalePolicy::Dim,
+                    device_filter: DeviceFilter::All,
+                    battery_display: BatteryDisplay::Exact,
+                    hide_device_list: false,
+                    hide_tooltip: false,
+                    rich_flyout: false,
+                    battery_bar: false,
+                    summary_header: false,
+                    show_default_audio: false,
                 },
                 tray_icon_source: TrayIconSource::App,
             },
@@ -283,8 +1442,42 @@ impl Config {
                 reconnection: false,
                 added: false,
                 removed: false,
+                renamed: false,
+                pinned_only: false,
+                flap_window: 0,
+                flap_summary: false,
+                watchdog: false,
+                milestones: false,
+                milestone_steps: default_milestone_steps(),
             },
             device_aliases: device_aliases.clone(),
+            hidden_devices: Vec::new(),
+            pinned_devices: Vec::new(),
+            keep_connected_devices: Vec::new(),
+            export_options: ExportOptions::default(),
+            http_api_options: HttpApiOptions::default(),
+            history_options: HistoryOptions::default(),
+            named_pipe_options: NamedPipeOptions::default(),
+            webhooks: Vec::new(),
+            commands: Vec::new(),
+            rules: Vec::new(),
+            startup_options: StartupOptions::default(),
+            update_options: UpdateOptions::default(),
+            network_options: NetworkOptions::default(),
+            language_options: LanguageOptions::default(),
+            telemetry_options: TelemetryOptions::default(),
+            low_battery_icon_options: LowBatteryIconOptions::default(),
+            companions: Vec::new(),
+            protocol_options: ProtocolOptions::default(),
+            ble_options: BleOptions::default(),
+            click_options: ClickOptions::default(),
+            active_profile: None,
+            profiles: Vec::new(),
+            blink_options: BlinkOptions::default(),
+            smoothing_options: SmoothingOptions::default(),
+            calibrations: Vec::new(),
+            speech_options: SpeechOptions::default(),
+            hotkey_options: HotkeyOptions::default(),
         };
 
         let toml_str = toml::to_string_pretty(&default_config)?;
@@ -292,9 +1485,15 @@ impl Config {
 
         Ok(Config {
             config_path,
+            first_run: false,
             force_update: AtomicBool::new(false),
+            refresh_tray: AtomicBool::new(false),
+            options_lock: RwLock::new(()),
             tray_options: TrayOptions {
                 update_interval: AtomicU64::new(default_config.tray_options.update_interval),
+                adaptive_update_interval: AtomicBool::new(
+                    default_config.tray_options.adaptive_update_interval,
+                ),
                 tray_icon_source: Mutex::new(default_config.tray_options.tray_icon_source),
                 tooltip_options: TooltipOptions {
                     show_disconnected: AtomicBool::new(
@@ -306,6 +1505,46 @@ impl Config {
                     prefix_battery: AtomicBool::new(
                         default_config.tray_options.tray_tooltip.prefix_battery,
                     ),
+                    truncate_length: AtomicU8::new(
+                        default_config.tray_options.tray_tooltip.truncate_length,
+                    ),
+                    truncate_strategy: Mutex::new(
+                        default_config.tray_options.tray_tooltip.truncate_strategy,
+                    ),
+                    strip_affixes: AtomicBool::new(
+                        default_config.tray_options.tray_tooltip.strip_affixes,
+                    ),
+                    show_last_updated: AtomicBool::new(
+                        default_config.tray_options.tray_tooltip.show_last_updated,
+                    ),
+                    stale_minutes: AtomicU64::new(
+                        default_config.tray_options.tray_tooltip.stale_minutes,
+                    ),
+                    stale_policy: Mutex::new(default_config.tray_options.tray_tooltip.stale_policy),
+                    device_filter: Mutex::new(
+                        default_config.tray_options.tray_tooltip.device_filter,
+                    ),
+                    battery_display: Mutex::new(
+                        default_config.tray_options.tray_tooltip.battery_display,
+                    ),
+                    hide_device_list: AtomicBool::new(
+                        default_config.tray_options.tray_tooltip.hide_device_list,
+                    ),
+                    hide_tooltip: AtomicBool::new(
+                        default_config.tray_options.tray_tooltip.hide_tooltip,
+                    ),
+                    rich_flyout: AtomicBool::new(
+                        default_config.tray_options.tray_tooltip.rich_flyout,
+                    ),
+                    battery_bar: AtomicBool::new(
+                        default_config.tray_options.tray_tooltip.battery_bar,
+                    ),
+                    summary_header: AtomicBool::new(
+                        default_config.tray_options.tray_tooltip.summary_header,
+                    ),
+                    show_default_audio: AtomicBool::new(
+                        default_config.tray_options.tray_tooltip.show_default_audio,
+                    ),
                 },
             },
             notify_options: NotifyOptions {
@@ -315,33 +1554,61 @@ impl Config {
                 reconnection: AtomicBool::new(default_config.notify_options.reconnection),
                 added: AtomicBool::new(default_config.notify_options.added),
                 removed: AtomicBool::new(default_config.notify_options.removed),
+                renamed: AtomicBool::new(default_config.notify_options.renamed),
+                pinned_only: AtomicBool::new(default_config.notify_options.pinned_only),
+                flap_window: AtomicU64::new(default_config.notify_options.flap_window),
+                flap_summary: AtomicBool::new(default_config.notify_options.flap_summary),
+                watchdog: AtomicBool::new(default_config.notify_options.watchdog),
+                milestones: AtomicBool::new(default_config.notify_options.milestones),
+                milestone_steps: Mutex::new(default_config.notify_options.milestone_steps),
             },
-            device_aliases,
+            device_aliases: Mutex::new(device_aliases),
+            hidden_devices: Mutex::new(default_config.hidden_devices),
+            pinned_devices: Mutex::new(default_config.pinned_devices),
+            keep_connected_devices: Mutex::new(default_config.keep_connected_devices),
+            export_options: default_config.export_options,
+            http_api_options: Mutex::new(default_config.http_api_options),
+            history_options: default_config.history_options,
+            named_pipe_options: default_config.named_pipe_options,
+            webhooks: default_config.webhooks,
+            commands: default_config.commands,
+            rules: default_config.rules,
+            startup_options: default_config.startup_options,
+            update_options: default_config.update_options,
+            network_options: default_config.network_options,
+            language_options: default_config.language_options,
+            telemetry_options: default_config.telemetry_options,
+            low_battery_icon_options: default_config.low_battery_icon_options,
+            companions: default_config.companions,
+            protocol_options: default_config.protocol_options,
+            ble_options: default_config.ble_options,
+            click_options: default_config.click_options,
+            active_profile: Mutex::new(default_config.active_profile),
+            profiles: Mutex::new(default_config.profiles),
+            blink_options: default_config.blink_options,
+            smoothing_options: default_config.smoothing_options,
+            calibrations: default_config.calibrations,
+            speech_options: default_config.speech_options,
+            hotkey_options: default_config.hotkey_options,
         })
     }
 
     fn read_toml(config_path: PathBuf) -> Result<Self> {
         let content = std::fs::read_to_string(&config_path)?;
         let toml_config: ConfigToml = toml::from_str(&content)?;
-        let tray_icon_source = if find_custom_icon().is_err() {
-            toml_config.tray_options.tray_icon_source
-        } else {
-            match toml_config.tray_options.tray_icon_source {
-                TrayIconSource::App => TrayIconSource::App,
-                TrayIconSource::BatteryCustom { address } => {
-                    TrayIconSource::BatteryCustom { address }
-                }
-                TrayIconSource::BatteryFont { address, .. } => {
-                    TrayIconSource::BatteryCustom { address }
-                }
-            }
-        };
+        let tray_icon_source = prefer_custom_icon(toml_config.tray_options.tray_icon_source);
 
         Ok(Config {
             config_path,
+            first_run: false,
             force_update: AtomicBool::new(false),
+            refresh_tray: AtomicBool::new(false),
+            options_lock: RwLock::new(()),
             tray_options: TrayOptions {
                 update_interval: AtomicU64::new(toml_config.tray_options.update_interval),
+                adaptive_update_interval: AtomicBool::new(
+                    toml_config.tray_options.adaptive_update_interval,
+                ),
                 tray_icon_source: Mutex::new(tray_icon_source),
                 tooltip_options: TooltipOptions {
                     show_disconnected: AtomicBool::new(
@@ -353,6 +1620,40 @@ impl Config {
                     prefix_battery: AtomicBool::new(
                         toml_config.tray_options.tray_tooltip.prefix_battery,
                     ),
+                    truncate_length: AtomicU8::new(
+                        toml_config.tray_options.tray_tooltip.truncate_length,
+                    ),
+                    truncate_strategy: Mutex::new(
+                        toml_config.tray_options.tray_tooltip.truncate_strategy,
+                    ),
+                    strip_affixes: AtomicBool::new(
+                        toml_config.tray_options.tray_tooltip.strip_affixes,
+                    ),
+                    show_last_updated: AtomicBool::new(
+                        toml_config.tray_options.tray_tooltip.show_last_updated,
+                    ),
+                    stale_minutes: AtomicU64::new(
+                        toml_config.tray_options.tray_tooltip.stale_minutes,
+                    ),
+                    stale_policy: Mutex::new(toml_config.tray_options.tray_tooltip.stale_policy),
+                    device_filter: Mutex::new(toml_config.tray_options.tray_tooltip.device_filter),
+                    battery_display: Mutex::new(
+                        toml_config.tray_options.tray_tooltip.battery_display,
+                    ),
+                    hide_device_list: AtomicBool::new(
+                        toml_config.tray_options.tray_tooltip.hide_device_list,
+                    ),
+                    hide_tooltip: AtomicBool::new(
+                        toml_config.tray_options.tray_tooltip.hide_tooltip,
+                    ),
+                    rich_flyout: AtomicBool::new(toml_config.tray_options.tray_tooltip.rich_flyout),
+                    battery_bar: AtomicBool::new(toml_config.tray_options.tray_tooltip.battery_bar),
+                    summary_header: AtomicBool::new(
+                        toml_config.tray_options.tray_tooltip.summary_header,
+                    ),
+                    show_default_audio: AtomicBool::new(
+                        toml_config.tray_options.tray_tooltip.show_default_audio,
+                    ),
                 },
             },
             notify_options: NotifyOptions {
@@ -362,8 +1663,42 @@ impl Config {
                 reconnection: AtomicBool::new(toml_config.notify_options.reconnection),
                 added: AtomicBool::new(toml_config.notify_options.added),
                 removed: AtomicBool::new(toml_config.notify_options.removed),
+                renamed: AtomicBool::new(toml_config.notify_options.renamed),
+                pinned_only: AtomicBool::new(toml_config.notify_options.pinned_only),
+                flap_window: AtomicU64::new(toml_config.notify_options.flap_window),
+                flap_summary: AtomicBool::new(toml_config.notify_options.flap_summary),
+                watchdog: AtomicBool::new(toml_config.notify_options.watchdog),
+                milestones: AtomicBool::new(toml_config.notify_options.milestones),
+                milestone_steps: Mutex::new(toml_config.notify_options.milestone_steps),
             },
-            device_aliases: toml_config.device_aliases,
+            device_aliases: Mutex::new(toml_config.device_aliases),
+            hidden_devices: Mutex::new(toml_config.hidden_devices),
+            pinned_devices: Mutex::new(toml_config.pinned_devices),
+            keep_connected_devices: Mutex::new(toml_config.keep_connected_devices),
+            export_options: toml_config.export_options,
+            http_api_options: Mutex::new(toml_config.http_api_options),
+            history_options: toml_config.history_options,
+            named_pipe_options: toml_config.named_pipe_options,
+            webhooks: toml_config.webhooks,
+            commands: toml_config.commands,
+            rules: toml_config.rules,
+            startup_options: toml_config.startup_options,
+            update_options: toml_config.update_options,
+            network_options: toml_config.network_options,
+            language_options: toml_config.language_options,
+            telemetry_options: toml_config.telemetry_options,
+            low_battery_icon_options: toml_config.low_battery_icon_options,
+            companions: toml_config.companions,
+            protocol_options: toml_config.protocol_options,
+            ble_options: toml_config.ble_options,
+            click_options: toml_config.click_options,
+            active_profile: Mutex::new(toml_config.active_profile),
+            profiles: Mutex::new(toml_config.profiles),
+            blink_options: toml_config.blink_options,
+            smoothing_options: toml_config.smoothing_options,
+            calibrations: toml_config.calibrations,
+            speech_options: toml_config.speech_options,
+            hotkey_options: toml_config.hotkey_options,
         })
     }
 }
@@ -371,15 +1706,234 @@ impl Config {
 impl Config {
     pub fn get_device_aliases_name(&self, device_name: &String) -> String {
         self.device_aliases
+            .lock()
+            .unwrap()
             .get(device_name)
             .unwrap_or(device_name)
             .to_owned()
     }
 
+    /// 设备改名后沿用旧名称的别名，新名称已有别名时保留新名称的别名，返回是否有变化
+    pub fn carry_over_device_alias(&self, old_name: &str, new_name: &str) -> bool {
+        let mut device_aliases = self.device_aliases.lock().unwrap();
+        let Some(alias) = device_aliases.get(old_name).cloned() else {
+            return false;
+        };
+        if device_aliases.contains_key(new_name) || alias == new_name {
+            return false;
+        }
+        device_aliases.insert(new_name.to_owned(), alias);
+        true
+    }
+
+    /// 设置设备别名，别名为空或与设备名称相同时移除
+    pub fn set_device_alias(&self, device_name: &str, alias: &str) {
+        let alias = alias.trim();
+        let mut device_aliases = self.device_aliases.lock().unwrap();
+        if alias.is_empty() || alias == device_name {
+            device_aliases.remove(device_name);
+        } else {
+            device_aliases.insert(device_name.to_owned(), alias.to_owned());
+        }
+    }
+
+    pub fn is_hidden_device(&self, info: &BluetoothInfo) -> bool {
+        self.hidden_devices
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|device| info.matches(device))
+    }
+
+    pub fn is_pinned_device(&self, info: &BluetoothInfo) -> bool {
+        self.pinned_devices
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|device| info.matches(device))
+    }
+
+    pub fn is_keep_connected_device(&self, info: &BluetoothInfo) -> bool {
+        self.keep_connected_devices
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|device| info.matches(device))
+    }
+
+    /// 隐藏的设备不发送通知；开启 `pinned_only` 时只为固定的设备发送通知
+    pub fn notifies_device(&self, info: &BluetoothInfo) -> bool {
+        !self.is_hidden_device(info) && (!self.get_pinned_only() || self.is_pinned_device(info))
+    }
+
+    /// 隐藏的设备读取失败时同样不显示
+    pub fn get_visible_device_errors(&self) -> Vec<DeviceError> {
+        let hidden_devices = self.hidden_devices.lock().unwrap();
+        get_device_errors()
+            .into_iter()
+            .filter(|error| !hidden_devices.iter().any(|device| error.matches(device)))
+            .collect()
+    }
+
+    pub fn get_active_profile(&self) -> Option<String> {
+        self.active_profile.lock().unwrap().clone()
+    }
+
+    pub fn get_profile_names(&self) -> Vec<String> {
+        self.profiles
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|profile| profile.name.clone())
+            .collect()
+    }
+
+    /// 以当前的托盘及通知设置保存配置方案（同名时覆盖），并设为当前方案
+    pub fn save_profile(&self, name: &str) {
+        let profile = Profile {
+            name: name.to_owned(),
+            tray_options: self.tray_options_toml(),
+            notify_options: self.notify_options_toml(),
+        };
+
+        let mut profiles = self.profiles.lock().unwrap();
+        match profiles.iter_mut().find(|p| p.name == name) {
+            Some(existing) => *existing = profile,
+            None => profiles.push(profile),
+        }
+        *self.active_profile.lock().unwrap() = Some(name.to_owned());
+    }
+
+    /// 切换配置方案：当前设置先保存回当前方案，再整体应用目标方案
+    pub fn switch_profile(&self, name: &str) -> Result<()> {
+        let profile = self
+            .profiles
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|p| p.name == name)
+            .cloned()
+            .ok_or_else(|| anyhow!("Profile '{name}' does not exist"))?;
+
+        if let Some(active_profile) = self.get_active_profile()
+            && active_profile != name
+        {
+            self.save_profile(&active_profile);
+        }
+
+        self.apply_options(profile.tray_options, profile.notify_options);
+        *self.active_profile.lock().unwrap() = Some(name.to_owned());
+        Ok(())
+    }
+
+    /// 读取期间不会应用其他配置方案，需同时读取多项托盘或通知设置时使用
+    pub fn read_options(&self) -> RwLockReadGuard<'_, ()> {
+        self.options_lock.read().unwrap()
+    }
+
+    fn apply_options(&self, tray: TrayOptionsToml, notify: NotifyOptionsToml) {
+        let _guard = self.options_lock.write().unwrap();
+        let tray_options = &self.tray_options;
+        tray_options
+            .update_interval
+            .store(tray.update_interval, Ordering::Relaxed);
+        tray_options
+            .adaptive_update_interval
+            .store(tray.adaptive_update_interval, Ordering::Relaxed);
+        *tray_options.tray_icon_source.lock().unwrap() = prefer_custom_icon(tray.tray_icon_source);
+
+        let tooltip = tray.tray_tooltip;
+        let tooltip_options = &tray_options.tooltip_options;
+        tooltip_options
+            .show_disconnected
+            .store(tooltip.show_disconnected, Ordering::Relaxed);
+        tooltip_options
+            .truncate_name
+            .store(tooltip.truncate_name, Ordering::Relaxed);
+        tooltip_options
+            .prefix_battery
+            .store(tooltip.prefix_battery, Ordering::Relaxed);
+        tooltip_options
+            .truncate_length
+            .store(tooltip.truncate_length, Ordering::Relaxed);
+        *tooltip_options.truncate_strategy.lock().unwrap() = tooltip.truncate_strategy;
+        tooltip_options
+            .strip_affixes
+            .store(tooltip.strip_affixes, Ordering::Relaxed);
+        tooltip_options
+            .show_last_updated
+            .store(tooltip.show_last_updated, Ordering::Relaxed);
+        tooltip_options
+            .stale_minutes
+            .store(tooltip.stale_minutes, Ordering::Relaxed);
+        *tooltip_options.stale_policy.lock().unwrap() = tooltip.stale_policy;
+        *tooltip_options.device_filter.lock().unwrap() = tooltip.device_filter;
+        *tooltip_options.battery_display.lock().unwrap() = tooltip.battery_display;
+        tooltip_options
+            .hide_device_list
+            .store(tooltip.hide_device_list, Ordering::Relaxed);
+        tooltip_options
+            .hide_tooltip
+            .store(tooltip.hide_tooltip, Ordering::Relaxed);
+        tooltip_options
+            .rich_flyout
+            .store(tooltip.rich_flyout, Ordering::Relaxed);
+        tooltip_options
+            .battery_bar
+            .store(tooltip.battery_bar, Ordering::Relaxed);
+        tooltip_options
+            .summary_header
+            .store(tooltip.summary_header, Ordering::Relaxed);
+        tooltip_options
+            .show_default_audio
+            .store(tooltip.show_default_audio, Ordering::Relaxed);
+
+        let notify_options = &self.notify_options;
+        notify_options.mute.store(notify.mute, Ordering::Relaxed);
+        notify_options
+            .low_battery
+            .store(notify.low_battery, Ordering::Relaxed);
+        notify_options
+            .disconnection
+            .store(notify.disconnection, Ordering::Relaxed);
+        notify_options
+            .reconnection
+            .store(notify.reconnection, Ordering::Relaxed);
+        notify_options.added.store(notify.added, Ordering::Relaxed);
+        notify_options
+            .removed
+            .store(notify.removed, Ordering::Relaxed);
+        notify_options
+            .renamed
+            .store(notify.renamed, Ordering::Relaxed);
+        notify_options
+            .pinned_only
+            .store(notify.pinned_only, Ordering::Relaxed);
+        notify_options
+            .flap_window
+            .store(notify.flap_window, Ordering::Relaxed);
+        notify_options
+            .flap_summary
+            .store(notify.flap_summary, Ordering::Relaxed);
+        notify_options
+            .watchdog
+            .store(notify.watchdog, Ordering::Relaxed);
+        notify_options
+            .milestones
+            .store(notify.milestones, Ordering::Relaxed);
+        *notify_options.milestone_steps.lock().unwrap() = notify.milestone_steps;
+    }
+
     pub fn get_update_interval(&self) -> u64 {
         self.tray_options.update_interval.load(Ordering::Acquire)
     }
 
+    pub fn get_adaptive_update_interval(&self) -> bool {
+        self.tray_options
+            .adaptive_update_interval
+            .load(Ordering::Acquire)
+    }
+
     pub fn get_prefix_battery(&self) -> bool {
         self.tray_options
             .tooltip_options
@@ -401,6 +1955,112 @@ impl Config {
             .load(Ordering::Acquire)
     }
 
+    pub fn get_truncate_length(&self) -> usize {
+        self.tray_options
+            .tooltip_options
+            .truncate_length
+            .load(Ordering::Acquire) as usize
+    }
+
+    pub fn get_truncate_strategy(&self) -> TruncateStrategy {
+        *self
+            .tray_options
+            .tooltip_options
+            .truncate_strategy
+            .lock()
+            .unwrap()
+    }
+
+    pub fn get_hide_device_list(&self) -> bool {
+        self.tray_options
+            .tooltip_options
+            .hide_device_list
+            .load(Ordering::Acquire)
+    }
+
+    pub fn get_hide_tooltip(&self) -> bool {
+        self.tray_options
+            .tooltip_options
+            .hide_tooltip
+            .load(Ordering::Acquire)
+    }
+
+    pub fn get_rich_flyout(&self) -> bool {
+        self.tray_options
+            .tooltip_options
+            .rich_flyout
+            .load(Ordering::Acquire)
+    }
+
+    pub fn get_battery_bar(&self) -> bool {
+        self.tray_options
+            .tooltip_options
+            .battery_bar
+            .load(Ordering::Acquire)
+    }
+
+    pub fn get_summary_header(&self) -> bool {
+        self.tray_options
+            .tooltip_options
+            .summary_header
+            .load(Ordering::Acquire)
+    }
+
+    pub fn get_show_default_audio(&self) -> bool {
+        self.tray_options
+            .tooltip_options
+            .show_default_audio
+            .load(Ordering::Acquire)
+    }
+
+    pub fn get_strip_affixes(&self) -> bool {
+        self.tray_options
+            .tooltip_options
+            .strip_affixes
+            .load(Ordering::Acquire)
+    }
+
+    pub fn get_show_last_updated(&self) -> bool {
+        self.tray_options
+            .tooltip_options
+            .show_last_updated
+            .load(Ordering::Acquire)
+    }
+
+    /// 过期时间（秒）及处理方式，未设置时为 `None`
+    pub fn get_stale_policy(&self) -> Option<(u64, StalePolicy)> {
+        let stale_minutes = self
+            .tray_options
+            .tooltip_options
+            .stale_minutes
+            .load(Ordering::Acquire);
+        let stale_policy = *self
+            .tray_options
+            .tooltip_options
+            .stale_policy
+            .lock()
+            .unwrap();
+        (stale_minutes > 0).then_some((stale_minutes * 60, stale_policy))
+    }
+
+    pub fn get_device_filter(&self) -> DeviceFilter {
+        *self
+            .tray_options
+            .tooltip_options
+            .device_filter
+            .lock()
+            .unwrap()
+    }
+
+    pub fn get_battery_display(&self) -> BatteryDisplay {
+        *self
+            .tray_options
+            .tooltip_options
+            .battery_display
+            .lock()
+            .unwrap()
+    }
+
     pub fn get_mute(&self) -> bool {
         self.notify_options.mute.load(Ordering::Acquire)
     }
@@ -425,6 +2085,79 @@ impl Config {
         self.notify_options.removed.load(Ordering::Acquire)
     }
 
+    pub fn get_renamed(&self) -> bool {
+        self.notify_options.renamed.load(Ordering::Acquire)
+    }
+
+    pub fn get_pinned_only(&self) -> bool {
+        self.notify_options.pinned_only.load(Ordering::Acquire)
+    }
+
+    pub fn get_flap_window(&self) -> Duration {
+        Duration::from_secs(self.notify_options.flap_window.load(Ordering::Acquire))
+    }
+
+    pub fn get_flap_summary(&self) -> bool {
+        self.notify_options.flap_summary.load(Ordering::Acquire)
+    }
+
+    pub fn get_watchdog(&self) -> bool {
+        self.notify_options.watchdog.load(Ordering::Acquire)
+    }
+
+    /// 未开启时为空
+    pub fn get_milestone_steps(&self) -> Vec<u8> {
+        if self.notify_options.milestones.load(Ordering::Acquire) {
+            self.notify_options.milestone_steps.lock().unwrap().clone()
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn get_milestones(&self) -> bool {
+        self.notify_options.milestones.load(Ordering::Acquire)
+    }
+
+    pub fn get_status_json_path(&self) -> Option<PathBuf> {
+        let status_json = self.export_options.status_json.as_ref()?;
+        let config_dir = self.config_path.parent()?;
+        Some(config_dir.join(status_json))
+    }
+
+    pub fn get_widget_card_path(&self) -> Option<PathBuf> {
+        let widget_card = self.export_options.widget_card.as_ref()?;
+        let config_dir = self.config_path.parent()?;
+        Some(config_dir.join(widget_card))
+    }
+
+    /// 相对路径基于配置文件所在目录
+    pub fn resolve_path(&self, path: &Path) -> PathBuf {
+        self.config_path
+            .parent()
+            .map_or_else(|| path.to_path_buf(), |config_dir| config_dir.join(path))
+    }
+
+    pub fn get_weekly_report_path(&self) -> Option<PathBuf> {
+        let weekly_report = self.history_options.weekly_report.as_ref()?;
+        let config_dir = self.config_path.parent()?;
+        Some(config_dir.join(weekly_report))
+    }
+
+    pub fn get_offline(&self) -> bool {
+        self.network_options.offline
+    }
+
+    /// 已开启且设置了接收地址，离线模式下不上报
+    pub fn get_telemetry(&self) -> bool {
+        self.telemetry_options.enabled
+            && !self.telemetry_options.endpoint.is_empty()
+            && !self.get_offline()
+    }
+
+    pub fn get_check_updates(&self) -> bool {
+        self.update_options.check && !self.get_offline()
+    }
+
     pub fn get_tray_battery_icon_bt_address(&self) -> Option<u64> {
         let tray_icon_source = {
             let lock = self.tray_options.tray_icon_source.lock().unwrap();
@@ -432,13 +2165,27 @@ impl Config {
         };
 
         match tray_icon_source {
-            TrayIconSource::App => None,
-            TrayIconSource::BatteryCustom { address } => Some(address),
+            TrayIconSource::App | TrayIconSource::LowestBattery { .. } => None,
+            TrayIconSource::BatteryCustom { address, .. } => Some(address),
             TrayIconSource::BatteryFont { address, .. } => Some(address),
         }
     }
 }
 
+/// 存在自定义图标时，以自定义图标代替字体电量图标
+fn prefer_custom_icon(tray_icon_source: TrayIconSource) -> TrayIconSource {
+    if find_custom_icon().is_err() {
+        return tray_icon_source;
+    }
+
+    match tray_icon_source {
+        TrayIconSource::BatteryFont {
+            address, fallback, ..
+        } => TrayIconSource::BatteryCustom { address, fallback },
+        tray_icon_source => tray_icon_source,
+    }
+}
+
 fn find_custom_icon() -> Result<()> {
     let assets_path = std::env::current_exe().map(|exe_path| exe_path.with_file_name("assets"))?;
 
@@ -472,3 +2219,41 @@ fn find_custom_icon() -> Result<()> {
         "Assets directory does not contain custom battery icons."
     ))
 }
+
+/// 测试用的默认配置，写入临时目录
+#[cfg(test)]
+pub fn test_config(name: &str) -> Config {
+    let config_dir = std::env::temp_dir().join("BlueGauge-test");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    Config::create_toml(config_dir.join(format!("{name}.toml"))).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changed(old: &str, new: &str) -> Vec<String> {
+        let old = toml::from_str::<toml::Value>(old).unwrap();
+        let new = toml::from_str::<toml::Value>(new).unwrap();
+        let mut changed = Vec::new();
+        changed_keys("", &old, &new, &mut changed);
+        changed
+    }
+
+    #[test]
+    fn changed_keys_are_nested_paths() {
+        let old = "a = 1\n[tray.tooltip]\nbattery_bar = true\nsteps = [1, 2]\n";
+        let new = "a = 1\n[tray.tooltip]\nbattery_bar = false\nsteps = [1, 3]\n";
+        assert_eq!(
+            changed(old, new),
+            ["tray.tooltip.battery_bar", "tray.tooltip.steps"]
+        );
+        assert!(changed(old, old).is_empty());
+    }
+
+    #[test]
+    fn added_and_removed_keys_are_changed() {
+        assert_eq!(changed("a = 1\nb = 2", "a = 1\nc = 3"), ["b", "c"]);
+        assert_eq!(changed("[http_api]\nport = 1", ""), ["http_api"]);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::bluetooth::info::BluetoothInfo;
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+/// 去抖窗口结束时的结果
+pub enum ConnectionOutcome {
+    /// 只变化了一次，按最终状态通知
+    Changed(BluetoothInfo),
+    /// 窗口内多次变化
+    Unstable {
+        info: BluetoothInfo,
+        /// 最终状态是否与窗口开始前不同
+        changed: bool,
+    },
+}
+
+struct PendingConnection {
+    original_status: bool,
+    latest: BluetoothInfo,
+    changes: u32,
+}
+
+/// 正处于去抖窗口内的设备
+static PENDING_CONNECTIONS: LazyLock<Mutex<HashMap</* address */ u64, PendingConnection>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 设备第一次变化时开始计时，窗口内的后续变化只更新最终状态，窗口结束后调用一次 `on_settled`
+pub fn debounce_connection(
+    info: &BluetoothInfo,
+    window: Duration,
+    on_settled: impl FnOnce(ConnectionOutcome) + Send + 'static,
+) {
+    let address = info.address;
+    {
+        let mut pending = PENDING_CONNECTIONS.lock().unwrap();
+        if let Some(pending) = pending.get_mut(&address) {
+            pending.latest = info.clone();
+            pending.changes += 1;
+            return;
+        }
+        pending.insert(
+            address,
+            PendingConnection {
+                original_status: !info.status,
+                latest: info.clone(),
+                changes: 1,
+            },
+        );
+    }
+
+    std::thread::spawn(move || {
+        std::thread::sleep(window);
+
+        let Some(pending) = PENDING_CONNECTIONS.lock().unwrap().remove(&address) else {
+            return;
+        };
+        let changed = pending.latest.status != pending.original_status;
+        let outcome = if pending.changes == 1 {
+            ConnectionOutcome::Changed(pending.latest)
+        } else {
+            ConnectionOutcome::Unstable {
+                info: pending.latest,
+                changed,
+            }
+        };
+        on_settled(outcome);
+    });
+}
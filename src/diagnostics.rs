@@ -0,0 +1,373 @@
+use crate::{
+    bluetooth::{
+        info::{BluetoothInfo, get_device_errors},
+        radio::{get_adapter_info, get_bluetooth_radio_state},
+    },
+    cli::format_address,
+    config::Config,
+    history::History,
+    logging::log_tail,
+};
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{Datelike, Local, Timelike};
+use windows::core::GUID;
+use windows_pnp::{PnpDeviceNodeInfo, PnpDevicePropertyValue, PnpEnumerator};
+use winreg::{RegKey, enums::HKEY_LOCAL_MACHINE};
+
+/// 报告中包含的最近电量记录行数
+const HISTORY_TAIL_LINES: usize = 200;
+/// 报告中包含的最近日志行数
+const LOG_TAIL_LINES: usize = 500;
+/// 蓝牙相关设备节点的枚举器：经典蓝牙、BLE 及免提音频
+const BT_ENUMERATORS: [&str; 4] = ["BTHENUM", "BTHLE", "BTHLEDEVICE", "BTHHFENUM"];
+/// 可能包含密钥或内网地址的配置项，值替换为 `REDACTED`
+const SECRET_KEYS: [&str; 5] = ["token", "url", "body", "command", "endpoint"];
+const REDACTED: &str = "<redacted>";
+
+/// 收集系统及适配器信息、蓝牙设备节点、当前设备、读取错误、最近的日志、电量记录及配置（隐藏密钥），
+/// 打包为 zip，用于提交问题报告
+pub fn create_diagnostics_report(
+    config: &Config,
+    bluetooth_devices_info: &HashSet<BluetoothInfo>,
+    path: &Path,
+) -> Result<()> {
+    let devices = serde_json::to_string_pretty(&bluetooth_devices_info)?;
+    let history = History::new(config)
+        .tail(HISTORY_TAIL_LINES)
+        .unwrap_or_else(|e| vec![format!("Failed to read history - {e}")])
+        .join("\n");
+    let log = config
+        .config_path
+        .parent()
+        .map(|config_dir| log_tail(config_dir, LOG_TAIL_LINES))
+        .unwrap_or_else(|| Ok(Vec::new()))
+        .unwrap_or_else(|e| vec![format!("Failed to read the log - {e}")])
+        .join("\n");
+    let config_text = std::fs::read_to_string(&config.config_path)
+        .map_err(anyhow::Error::from)
+        .and_then(|contents| redact_config(&contents))
+        .unwrap_or_else(|e| format!("Failed to read the config - {e}"));
+
+    let files = [
+        ("system.txt", system_report()),
+        ("devices.json", devices),
+        ("device-errors.txt", device_errors_report()),
+        ("pnp-devices.txt", pnp_report()),
+        ("log-tail.txt", log),
+        ("history-tail.jsonl", history),
+        ("config.toml", config_text),
+    ];
+    write_zip(path, &files).with_context(|| format!("Failed to write {path:?}"))
+}
+
+fn system_report() -> String {
+    let mut report = format!("BlueGauge {}\n", env!("CARGO_PKG_VERSION"));
+
+    let os = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey(r"SOFTWARE\Microsoft\Windows NT\CurrentVersion")
+        .map(|key| {
+            let value = |name: &str| key.get_value::<String, _>(name).unwrap_or_default();
+            let ubr = key.get_value::<u32, _>("UBR").unwrap_or_default();
+            format!(
+                "{} {} (build {}.{ubr})",
+                value("ProductName"),
+                value("DisplayVersion"),
+                value("CurrentBuild"),
+            )
+        });
+    match os {
+        Ok(os) => report.push_str(&format!("OS: {os}\n")),
+        Err(e) => report.push_str(&format!("OS: unknown - {e}\n")),
+    }
+
+    match get_bluetooth_radio_state() {
+        Ok(state) => report.push_str(&format!("Radio: {state:?}\n")),
+        Err(e) => report.push_str(&format!("Radio: unknown - {e}\n")),
+    }
+
+    match get_adapter_info() {
+        Ok(adapter) => report.push_str(&format!(
+            "Adapter: {} ({})\nClassic: {}\nLow Energy: {}\nPeripheral role: {}\nLE Secure Connections: {}\n",
+            adapter.name,
+            format_address(adapter.address),
+            adapter.classic,
+            adapter.low_energy,
+            adapter.peripheral_role,
+            adapter.secure_connections,
+        )),
+        Err(e) => report.push_str(&format!("Adapter: unknown - {e}\n")),
+    }
+
+    report
+}
+
+fn device_errors_report() -> String {
+    get_device_errors()
+        .iter()
+        .map(|error| {
+            format!(
+                "{} ({})\n{}",
+                error.name,
+                format_address(error.address),
+                error.error
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// 各蓝牙设备节点的实例 ID、Container ID 及所有实例属性
+fn pnp_report() -> String {
+    let mut report = String::new();
+    for enumerator in BT_ENUMERATORS {
+        match PnpEnumerator::enumerate_present_devices_by_pnp_enumerator_id(enumerator) {
+            Ok(devices) => {
+                for device in devices {
+                    report.push_str(&devnode_text(&device));
+                    report.push('\n');
+                }
+            }
+            Err(e) => report.push_str(&format!("Failed to enumerate {enumerator} - {e:?}\n\n")),
+        }
+    }
+    report
+}
+
+fn devnode_text(device: &PnpDeviceNodeInfo) -> String {
+    let mut text = format!("[{}]\n", device.device_instance_id);
+    if let Some(container_id) = &device.base_container_id {
+        text.push_str(&format!(
+            "ContainerId = {:?}\n",
+            GUID::from_u128(container_id.as_u128())
+        ));
+    }
+
+    let mut properties = device
+        .device_instance_properties
+        .iter()
+        .flatten()
+        .map(|(key, value)| {
+            format!(
+                "{:?},{} = {}",
+                GUID::from_u128(key.fmtid.as_u128()),
+                key.pid,
+                property_text(value)
+            )
+        })
+        .collect::<Vec<_>>();
+    // 属性存放在 HashMap 中，排序后便于对比不同的报告
+    properties.sort();
+    for property in properties {
+        text.push_str(&property);
+        text.push('\n');
+    }
+    text
+}
+
+fn property_text(value: &PnpDevicePropertyValue) -> String {
+    match value {
+        PnpDevicePropertyValue::ArrayOfValues(values)
+        | PnpDevicePropertyValue::ListOfValues(values) => format!(
+            "[{}]",
+            values
+                .iter()
+                .map(property_text)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        PnpDevicePropertyValue::Boolean(v) => v.to_string(),
+        PnpDevicePropertyValue::Byte(v) => v.to_string(),
+        PnpDevicePropertyValue::Guid(v) => format!("{:?}", GUID::from_u128(v.as_u128())),
+        PnpDevicePropertyValue::String(v) => format!("{v:?}"),
+        PnpDevicePropertyValue::UInt16(v) => v.to_string(),
+        PnpDevicePropertyValue::UInt32(v) => v.to_string(),
+        PnpDevicePropertyValue::UnsupportedPropertyDataType(t) => {
+            format!("<unsupported property type {t}>")
+        }
+        PnpDevicePropertyValue::UnsupportedRegistryDataType(t) => {
+            format!("<unsupported registry type {t}>")
+        }
+    }
+}
+
+/// 将 `SECRET_KEYS` 中非空的值替换为 `REDACTED`，包括 `[[webhooks]]` 等数组中的表
+fn redact_config(contents: &str) -> Result<String> {
+    let mut value = toml::Value::Table(toml::from_str::<toml::Table>(contents)?);
+    redact_value(&mut value);
+    Ok(toml::to_string_pretty(&value)?)
+}
+
+fn redact_value(value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table.iter_mut() {
+                match value {
+                    toml::Value::String(s) if SECRET_KEYS.contains(&key.as_str()) => {
+                        if !s.is_empty() {
+                            *s = REDACTED.to_owned();
+                        }
+                    }
+                    value => redact_value(value),
+                }
+            }
+        }
+        toml::Value::Array(values) => values.iter_mut().for_each(redact_value),
+        _ => (),
+    }
+}
+
+/// 以不压缩（stored）方式写入 zip，文件较小，无需引入压缩依赖
+fn write_zip(path: &Path, files: &[(&str, String)]) -> Result<()> {
+    let (time, date) = dos_date_time();
+    let mut zip = Vec::new();
+    let mut central_directory = Vec::new();
+
+    for (name, contents) in files {
+        let offset = zip.len() as u32;
+        let data = contents.as_bytes();
+        let crc = crc32(data);
+        let size = data.len() as u32;
+        let name = name.as_bytes();
+
+        // 本地文件头，通用标志位 11 表示文件名为 UTF-8
+        zip.extend_from_slice(&0x0403_4B50u32.to_le_bytes());
+        zip.extend_from_slice(&20u16.to_le_bytes());
+        zip.extend_from_slice(&0x0800u16.to_le_bytes());
+        zip.extend_from_slice(&0u16.to_le_bytes());
+        zip.extend_from_slice(&time.to_le_bytes());
+        zip.extend_from_slice(&date.to_le_bytes());
+        zip.extend_from_slice(&crc.to_le_bytes());
+        zip.extend_from_slice(&size.to_le_bytes());
+        zip.extend_from_slice(&size.to_le_bytes());
+        zip.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&0u16.to_le_bytes());
+        zip.extend_from_slice(name);
+        zip.extend_from_slice(data);
+
+        // 中央目录记录
+        central_directory.extend_from_slice(&0x0201_4B50u32.to_le_bytes());
+        central_directory.extend_from_slice(&20u16.to_le_bytes());
+        central_directory.extend_from_slice(&20u16.to_le_bytes());
+        central_directory.extend_from_slice(&0x0800u16.to_le_bytes());
+        central_directory.extend_from_slice(&0u16.to_le_bytes());
+        central_directory.extend_from_slice(&time.to_le_bytes());
+        central_directory.extend_from_slice(&date.to_le_bytes());
+        central_directory.extend_from_slice(&crc.to_le_bytes());
+        central_directory.extend_from_slice(&size.to_le_bytes());
+        central_directory.extend_from_slice(&size.to_le_bytes());
+        central_directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        // 扩展字段、注释长度、磁盘号、内部及外部属性
+        central_directory.extend_from_slice(&[0; 12]);
+        central_directory.extend_from_slice(&offset.to_le_bytes());
+        central_directory.extend_from_slice(name);
+    }
+
+    let central_directory_offset = zip.len() as u32;
+    zip.extend_from_slice(&central_directory);
+
+    // 中央目录结束记录
+    let count = files.len() as u16;
+    zip.extend_from_slice(&0x0605_4B50u32.to_le_bytes());
+    zip.extend_from_slice(&[0; 4]);
+    zip.extend_from_slice(&count.to_le_bytes());
+    zip.extend_from_slice(&count.to_le_bytes());
+    zip.extend_from_slice(&(central_directory.len() as u32).to_le_bytes());
+    zip.extend_from_slice(&central_directory_offset.to_le_bytes());
+    zip.extend_from_slice(&0u16.to_le_bytes());
+
+    std::fs::File::create(path)?.write_all(&zip)?;
+    Ok(())
+}
+
+/// zip 使用的 MS-DOS 时间及日期（本地时间，精确到 2 秒）
+fn dos_date_time() -> (u16, u16) {
+    let now = Local::now();
+    let time = (now.hour() << 11) | (now.minute() << 5) | (now.second() / 2);
+    let date = (((now.year().max(1980) - 1980) as u32) << 9) | (now.month() << 5) | now.day();
+    (time as u16, date as u16)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn redacts_secrets_in_nested_tables() {
+        let redacted = redact_config(
+            "[telemetry]\nenabled = true\nendpoint = \"https://example.com/t\"\n\n\
+             [[webhooks]]\nurl = \"https://example.com/hook\"\nbody = \"\"\n",
+        )
+        .unwrap();
+        let value = redacted.parse::<toml::Table>().unwrap();
+
+        assert_eq!(value["telemetry"]["endpoint"].as_str(), Some(REDACTED));
+        assert_eq!(value["telemetry"]["enabled"].as_bool(), Some(true));
+        assert_eq!(value["webhooks"][0]["url"].as_str(), Some(REDACTED));
+        // 空值保持不变，便于看出未配置
+        assert_eq!(value["webhooks"][0]["body"].as_str(), Some(""));
+    }
+
+    #[test]
+    fn zip_records_point_at_each_other() {
+        let path = std::env::temp_dir().join("BlueGauge-test-diagnostics.zip");
+        let files = [
+            ("devices.json", "[]".to_owned()),
+            ("设备.txt", "Buds".to_owned()),
+        ];
+        write_zip(&path, &files).unwrap();
+        let zip = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let u16_at = |offset: usize| u16::from_le_bytes([zip[offset], zip[offset + 1]]);
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(zip[offset..offset + 4].try_into().unwrap());
+
+        assert_eq!(u32_at(0), 0x0403_4B50);
+        assert_eq!(u32_at(14), crc32(b"[]"));
+        assert_eq!(&zip[30..42], b"devices.json");
+        assert_eq!(&zip[42..44], b"[]");
+
+        // 中央目录结束记录位于末尾 22 字节
+        let end = zip.len() - 22;
+        assert_eq!(u32_at(end), 0x0605_4B50);
+        assert_eq!(u16_at(end + 10), 2);
+        let central_directory_size = u32_at(end + 12) as usize;
+        let central_directory_offset = u32_at(end + 16) as usize;
+        assert_eq!(central_directory_offset + central_directory_size, end);
+        assert_eq!(u32_at(central_directory_offset), 0x0201_4B50);
+
+        // 第二个文件的本地文件头
+        let second = u32_at(central_directory_offset + 46 + "devices.json".len() + 42) as usize;
+        assert_eq!(u32_at(second), 0x0403_4B50);
+        assert_eq!(
+            &zip[second + 30..second + 30 + "设备.txt".len()],
+            "设备.txt".as_bytes()
+        );
+    }
+}
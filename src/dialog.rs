@@ -0,0 +1,343 @@
+use crate::language::Localization;
+
+use std::cell::RefCell;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use log::warn;
+use windows::{
+    Win32::{
+        Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM},
+        Graphics::Gdi::{COLOR_BTNFACE, DEFAULT_GUI_FONT, GetStockObject, GetSysColorBrush},
+        System::LibraryLoader::GetModuleHandleW,
+        UI::{
+            Controls::{
+                Dialogs::{
+                    GetSaveFileNameW, OFN_NOCHANGEDIR, OFN_OVERWRITEPROMPT, OFN_PATHMUSTEXIST,
+                    OPENFILENAMEW,
+                },
+                EM_SETSEL,
+            },
+            Input::KeyboardAndMouse::SetFocus,
+            WindowsAndMessaging::{
+                BS_DEFPUSHBUTTON, BS_PUSHBUTTON, CreateWindowExW, DefWindowProcW, DestroyWindow,
+                DispatchMessageW, ES_AUTOHSCROLL, GetDlgItem, GetMessageW, GetSystemMetrics,
+                GetWindowTextLengthW, GetWindowTextW, HMENU, IDC_ARROW, IDCANCEL, IDOK,
+                IsDialogMessageW, LoadCursorW, MB_ICONINFORMATION, MB_OK, MB_SETFOREGROUND, MSG,
+                MessageBoxW, PostQuitMessage, RegisterClassW, SM_CXSCREEN, SM_CYSCREEN, SW_SHOW,
+                SendMessageW, SetForegroundWindow, ShowWindow, TranslateMessage, WINDOW_EX_STYLE,
+                WINDOW_STYLE, WM_COMMAND, WM_DESTROY, WM_SETFONT, WNDCLASSW, WNDPROC, WS_CAPTION,
+                WS_CHILD, WS_EX_CLIENTEDGE, WS_EX_DLGMODALFRAME, WS_EX_TOPMOST, WS_SYSMENU,
+                WS_TABSTOP, WS_VISIBLE,
+            },
+        },
+    },
+    core::{HSTRING, PCWSTR, PWSTR, w},
+};
+
+const INPUT_DIALOG_WIDTH: i32 = 380;
+const INPUT_DIALOG_HEIGHT: i32 = 160;
+const INPUT_EDIT_ID: i32 = 100;
+
+/// (扩展样式, 类名, 文本, 样式, 控件 ID, x, y, 宽, 高)
+pub type DialogControl<'a> = (
+    WINDOW_EX_STYLE,
+    PCWSTR,
+    &'a str,
+    WINDOW_STYLE,
+    i32,
+    i32,
+    i32,
+    i32,
+    i32,
+);
+
+thread_local! {
+    /// 输入对话框确定时的内容，对话框在各自的线程中运行
+    static INPUT_RESULT: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// 显示“另存为”对话框，`filters` 为（名称，通配符）列表，如 `("CSV (*.csv)", "*.csv")`。
+/// 用户取消时返回 `None`
+pub fn save_file_dialog(
+    default_file_name: &str,
+    default_extension: &str,
+    filters: &[(&str, &str)],
+) -> Option<PathBuf> {
+    let mut file_buffer = [0u16; 1024];
+    default_file_name
+        .encode_utf16()
+        .take(file_buffer.len() - 1)
+        .enumerate()
+        .for_each(|(i, c)| file_buffer[i] = c);
+
+    // "名称\0通配符\0...\0\0"
+    let filter = filters
+        .iter()
+        .flat_map(|(name, pattern)| {
+            name.encode_utf16()
+                .chain([0])
+                .chain(pattern.encode_utf16())
+                .chain([0])
+        })
+        .chain([0])
+        .collect::<Vec<u16>>();
+
+    let default_extension = default_extension
+        .encode_utf16()
+        .chain([0])
+        .collect::<Vec<u16>>();
+
+    let mut open_file_name = OPENFILENAMEW {
+        lStructSize: std::mem::size_of::<OPENFILENAMEW>() as u32,
+        lpstrFilter: PCWSTR(filter.as_ptr()),
+        nFilterIndex: 1,
+        lpstrFile: PWSTR(file_buffer.as_mut_ptr()),
+        nMaxFile: file_buffer.len() as u32,
+        lpstrDefExt: PCWSTR(default_extension.as_ptr()),
+        Flags: OFN_OVERWRITEPROMPT | OFN_PATHMUSTEXIST | OFN_NOCHANGEDIR,
+        ..Default::default()
+    };
+
+    if !unsafe { GetSaveFileNameW(&mut open_file_name) }.as_bool() {
+        return None;
+    }
+
+    let len = file_buffer
+        .iter()
+        .position(|&c| c == 0)
+        .unwrap_or(file_buffer.len());
+    Some(PathBuf::from(String::from_utf16_lossy(&file_buffer[..len])))
+}
+
+/// 在后台线程显示信息对话框，不阻塞事件循环
+pub fn show_message(title: String, text: String) {
+    std::thread::spawn(move || unsafe {
+        MessageBoxW(
+            None,
+            &HSTRING::from(text),
+            &HSTRING::from(title),
+            MB_OK | MB_ICONINFORMATION | MB_SETFOREGROUND,
+        );
+    });
+}
+
+/// 在后台线程显示单行输入对话框，确定后以输入内容调用 `on_confirmed`，取消时不调用
+pub fn input_dialog(
+    title: String,
+    prompt: String,
+    default_text: String,
+    on_confirmed: impl FnOnce(String) + Send + 'static,
+) {
+    std::thread::spawn(
+        move || match run_input_dialog(&title, &prompt, &default_text) {
+            Ok(Some(text)) => on_confirmed(text),
+            Ok(None) => (),
+            Err(e) => warn!("Failed to show the input dialog - {e}"),
+        },
+    );
+}
+
+/// 创建窗口及提示、输入框、按钮，运行消息循环直到窗口关闭
+fn run_input_dialog(title: &str, prompt: &str, default_text: &str) -> Result<Option<String>> {
+    let loc = Localization::current();
+    let (hwnd, instance) = create_dialog_window(
+        w!("BlueGaugeInputDialog"),
+        Some(input_dialog_proc),
+        title,
+        INPUT_DIALOG_WIDTH,
+        INPUT_DIALOG_HEIGHT,
+    )?;
+
+    let controls: [DialogControl; 4] = [
+        (
+            WINDOW_EX_STYLE::default(),
+            w!("STATIC"),
+            prompt,
+            WINDOW_STYLE::default(),
+            0,
+            12,
+            12,
+            340,
+            20,
+        ),
+        (
+            WS_EX_CLIENTEDGE,
+            w!("EDIT"),
+            default_text,
+            WS_TABSTOP | WINDOW_STYLE(ES_AUTOHSCROLL as u32),
+            INPUT_EDIT_ID,
+            12,
+            38,
+            340,
+            24,
+        ),
+        (
+            WINDOW_EX_STYLE::default(),
+            w!("BUTTON"),
+            loc.ok,
+            WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32),
+            IDOK.0,
+            180,
+            76,
+            80,
+            26,
+        ),
+        (
+            WINDOW_EX_STYLE::default(),
+            w!("BUTTON"),
+            loc.cancel,
+            WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as u32),
+            IDCANCEL.0,
+            272,
+            76,
+            80,
+            26,
+        ),
+    ];
+
+    create_controls(hwnd, instance, &controls)?;
+
+    // 选中默认内容，直接输入即可替换
+    unsafe {
+        let edit = GetDlgItem(Some(hwnd), INPUT_EDIT_ID)?;
+        SendMessageW(edit, EM_SETSEL, Some(WPARAM(0)), Some(LPARAM(-1)));
+        show_dialog_window(hwnd, edit);
+    }
+
+    INPUT_RESULT.with_borrow_mut(|result| *result = None);
+    run_dialog_loop(hwnd);
+    Ok(INPUT_RESULT.with_borrow_mut(Option::take))
+}
+
+/// 注册窗口类并在屏幕中央创建对话框窗口，创建后需调用 `show_dialog_window` 显示
+pub fn create_dialog_window(
+    class_name: PCWSTR,
+    window_proc: WNDPROC,
+    title: &str,
+    width: i32,
+    height: i32,
+) -> Result<(HWND, HINSTANCE)> {
+    unsafe {
+        let instance: HINSTANCE = GetModuleHandleW(None)?.into();
+
+        // 窗口类已注册时返回 0，可直接使用已注册的窗口类
+        RegisterClassW(&WNDCLASSW {
+            lpfnWndProc: window_proc,
+            hInstance: instance,
+            hCursor: LoadCursorW(None, IDC_ARROW)?,
+            hbrBackground: GetSysColorBrush(COLOR_BTNFACE),
+            lpszClassName: class_name,
+            ..Default::default()
+        });
+
+        let hwnd = CreateWindowExW(
+            WS_EX_DLGMODALFRAME | WS_EX_TOPMOST,
+            class_name,
+            &HSTRING::from(title),
+            WS_CAPTION | WS_SYSMENU,
+            (GetSystemMetrics(SM_CXSCREEN) - width) / 2,
+            (GetSystemMetrics(SM_CYSCREEN) - height) / 2,
+            width,
+            height,
+            None,
+            None,
+            Some(instance),
+            None,
+        )?;
+
+        Ok((hwnd, instance))
+    }
+}
+
+/// 显示窗口并将焦点置于 `focus` 控件
+pub fn show_dialog_window(hwnd: HWND, focus: HWND) {
+    unsafe {
+        let _ = ShowWindow(hwnd, SW_SHOW);
+        let _ = SetForegroundWindow(hwnd);
+        let _ = SetFocus(Some(focus));
+    }
+}
+
+/// 以对话框默认字体创建子控件
+pub fn create_controls(hwnd: HWND, instance: HINSTANCE, controls: &[DialogControl]) -> Result<()> {
+    unsafe {
+        let font = GetStockObject(DEFAULT_GUI_FONT);
+        for &(ex_style, class, text, style, id, x, y, width, height) in controls {
+            let control = CreateWindowExW(
+                ex_style,
+                class,
+                &HSTRING::from(text),
+                WS_CHILD | WS_VISIBLE | style,
+                x,
+                y,
+                width,
+                height,
+                Some(hwnd),
+                Some(HMENU(id as isize as _)),
+                Some(instance),
+                None,
+            )?;
+            SendMessageW(
+                control,
+                WM_SETFONT,
+                Some(WPARAM(font.0 as usize)),
+                Some(LPARAM(1)),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// 运行消息循环直到窗口关闭。
+/// IsDialogMessageW 处理 Tab 切换焦点，以及 Enter / Esc 对应的确定 / 取消
+pub fn run_dialog_loop(hwnd: HWND) {
+    unsafe {
+        let mut message = MSG::default();
+        while GetMessageW(&mut message, None, 0, 0).as_bool() {
+            if !IsDialogMessageW(hwnd, &message).as_bool() {
+                let _ = TranslateMessage(&message);
+                DispatchMessageW(&message);
+            }
+        }
+    }
+}
+
+extern "system" fn input_dialog_proc(
+    hwnd: HWND,
+    message: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    unsafe {
+        match message {
+            WM_COMMAND => {
+                let id = (wparam.0 & 0xFFFF) as i32;
+                if id == IDOK.0 {
+                    let text = read_input_text(hwnd);
+                    INPUT_RESULT.with_borrow_mut(|result| *result = Some(text));
+                }
+                if id == IDOK.0 || id == IDCANCEL.0 {
+                    let _ = DestroyWindow(hwnd);
+                }
+                LRESULT(0)
+            }
+            WM_DESTROY => {
+                PostQuitMessage(0);
+                LRESULT(0)
+            }
+            _ => DefWindowProcW(hwnd, message, wparam, lparam),
+        }
+    }
+}
+
+fn read_input_text(hwnd: HWND) -> String {
+    unsafe {
+        let Ok(edit) = GetDlgItem(Some(hwnd), INPUT_EDIT_ID) else {
+            return String::new();
+        };
+        let mut buffer = vec![0u16; GetWindowTextLengthW(edit) as usize + 1];
+        let len = GetWindowTextW(edit, &mut buffer);
+        String::from_utf16_lossy(&buffer[..len as usize])
+    }
+}
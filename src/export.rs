@@ -0,0 +1,105 @@
+use crate::{
+    bluetooth::info::BluetoothInfo, cli::format_address, config::Config, language::Localization,
+};
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde_json::json;
+
+/// 将当前设备信息写入 JSON 文件，供 Rainmeter、AutoHotkey、PowerShell 等工具读取
+pub fn write_status_json(
+    path: &Path,
+    bluetooth_devices_info: &HashSet<BluetoothInfo>,
+) -> Result<()> {
+    write_atomically(path, format_status_json(bluetooth_devices_info)?)
+}
+
+/// 与状态 JSON 文件内容相同
+pub fn format_status_json(bluetooth_devices_info: &HashSet<BluetoothInfo>) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&sorted_infos(
+        bluetooth_devices_info,
+    ))?)
+}
+
+/// 每行一个设备：名称 (地址): 电量% · 连接状态
+pub fn format_status_text(
+    bluetooth_devices_info: &HashSet<BluetoothInfo>,
+    config: &Config,
+) -> String {
+    let loc = Localization::current();
+    sorted_infos(bluetooth_devices_info)
+        .into_iter()
+        .map(|info| {
+            let status = if info.status {
+                loc.connected
+            } else {
+                loc.disconnected
+            };
+            format!(
+                "{} ({}): {}% · {status}",
+                config.get_device_aliases_name(&info.name),
+                format_address(info.address),
+                info.battery
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 将设备电量写成 Adaptive Card（模板与数据合一），
+/// 供 Windows 11 小组件提供程序（需 MSIX 打包的 COM 服务器）或其他支持 Adaptive Card 的面板直接加载。
+/// 与托盘使用同一更新流程，每次更新后重写。
+pub fn write_widget_card(
+    path: &Path,
+    bluetooth_devices_info: &HashSet<BluetoothInfo>,
+    config: &Config,
+) -> Result<()> {
+    let facts = sorted_infos(bluetooth_devices_info)
+        .into_iter()
+        .filter(|info| info.status || config.get_show_disconnected())
+        .map(|info| {
+            let status_icon = if info.status { "🟢" } else { "🔴" };
+            json!({
+                "title": config.get_device_aliases_name(&info.name),
+                "value": format!("{status_icon} {}%", info.battery),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let card = json!({
+        "type": "AdaptiveCard",
+        "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+        "version": "1.5",
+        "body": [
+            {
+                "type": "TextBlock",
+                "text": "BlueGauge",
+                "weight": "Bolder",
+                "size": "Medium",
+            },
+            {
+                "type": "FactSet",
+                "facts": facts,
+            },
+        ],
+    });
+
+    write_atomically(path, serde_json::to_string_pretty(&card)?)
+}
+
+fn sorted_infos(bluetooth_devices_info: &HashSet<BluetoothInfo>) -> Vec<&BluetoothInfo> {
+    let mut infos = bluetooth_devices_info.iter().collect::<Vec<_>>();
+    infos.sort_by(|a, b| a.name.cmp(&b.name).then(a.address.cmp(&b.address)));
+    infos
+}
+
+/// 先写入临时文件再替换，避免其他程序读取到写了一半的文件
+fn write_atomically(path: &Path, contents: String) -> Result<()> {
+    let temp_path = path.with_extension("tmp");
+    std::fs::write(&temp_path, contents)
+        .with_context(|| format!("Failed to write {temp_path:?}"))?;
+    std::fs::rename(&temp_path, path).with_context(|| format!("Failed to replace {path:?}"))?;
+    Ok(())
+}
@@ -0,0 +1,566 @@
+use crate::{
+    config::BatteryDisplay,
+    icon::SystemTheme,
+    language::Localization,
+    tray::{TooltipDevice, format_data_age},
+};
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicIsize, AtomicU64, Ordering};
+
+use anyhow::Result;
+use log::warn;
+use windows::{
+    Win32::{
+        Foundation::{COLORREF, HINSTANCE, HWND, LPARAM, LRESULT, POINT, RECT, SIZE, WPARAM},
+        Graphics::Gdi::{
+            BeginPaint, CreateFontIndirectW, CreateSolidBrush, DRAW_TEXT_FORMAT, DT_END_ELLIPSIS,
+            DT_LEFT, DT_NOPREFIX, DT_RIGHT, DT_SINGLELINE, DT_VCENTER, DeleteObject, DrawTextW,
+            Ellipse, EndPaint, FillRect, GetDC, GetMonitorInfoW, GetStockObject,
+            GetTextExtentPoint32W, HDC, HFONT, MONITOR_DEFAULTTONEAREST, MONITORINFO,
+            MonitorFromPoint, NULL_PEN, PAINTSTRUCT, ReleaseDC, SelectObject, SetBkMode,
+            SetTextColor, TRANSPARENT,
+        },
+        System::LibraryLoader::GetModuleHandleW,
+        UI::WindowsAndMessaging::{
+            CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, GetClientRect,
+            GetMessageW, IDC_ARROW, LoadCursorW, MSG, NONCLIENTMETRICSW, PostMessageW,
+            PostQuitMessage, RegisterClassW, SPI_GETNONCLIENTMETRICS, SW_SHOWNOACTIVATE,
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS, SetTimer, ShowWindow, SystemParametersInfoW,
+            TranslateMessage, WM_CLOSE, WM_DESTROY, WM_PAINT, WM_TIMER, WNDCLASSW, WS_BORDER,
+            WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_EX_TOPMOST, WS_POPUP,
+        },
+    },
+    core::w,
+};
+
+/// 显示一段时间后自动关闭
+const FLYOUT_TIMEOUT_MS: u32 = 5000;
+const DISMISS_TIMER_ID: usize = 1;
+/// 与托盘图标之间的距离
+const ANCHOR_GAP: i32 = 8;
+const LOW_BATTERY_COLOR: COLORREF = rgb(0xE5, 0x39, 0x35);
+const BATTERY_COLOR: COLORREF = rgb(0x43, 0xA0, 0x47);
+const DISCONNECTED_COLOR: COLORREF = rgb(0x9E, 0x9E, 0x9E);
+
+/// 当前显示的浮窗，没有时为 0
+static FLYOUT_WINDOW: AtomicIsize = AtomicIsize::new(0);
+/// 每次显示或关闭时递增，创建完成前已被关闭或替换的浮窗不再显示
+static FLYOUT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// 浮窗的内容及布局，浮窗在各自的线程中运行
+    static FLYOUT: RefCell<Option<Flyout>> = const { RefCell::new(None) };
+}
+
+/// 浮窗中显示的内容：状态提示及设备列表
+pub struct FlyoutContent {
+    pub headers: Vec<String>,
+    pub devices: Vec<TooltipDevice>,
+    pub low_battery: u8,
+    pub battery_display: BatteryDisplay,
+}
+
+/// 在托盘图标附近显示浮窗，替换已显示的浮窗。`anchor` 为托盘图标的位置及大小
+pub fn show_flyout(content: FlyoutContent, anchor: tray_icon::Rect) {
+    hide_flyout();
+    let generation = FLYOUT_GENERATION.load(Ordering::Acquire);
+    std::thread::spawn(move || {
+        if let Err(e) = run_flyout(content, anchor, generation) {
+            warn!("Failed to show the flyout - {e}");
+        }
+    });
+}
+
+/// 关闭已显示的浮窗
+pub fn hide_flyout() {
+    FLYOUT_GENERATION.fetch_add(1, Ordering::AcqRel);
+    let hwnd = FLYOUT_WINDOW.swap(0, Ordering::AcqRel);
+    if hwnd != 0 {
+        // 浮窗在自己的线程中销毁
+        let _ = unsafe { PostMessageW(Some(HWND(hwnd as _)), WM_CLOSE, WPARAM(0), LPARAM(0)) };
+    }
+}
+
+/// 浮窗的内容及按字体测量出的布局
+struct Flyout {
+    content: FlyoutContent,
+    font: HFONT,
+    /// 字体高度，其余尺寸均以此为单位，随系统缩放
+    line_height: i32,
+    padding: i32,
+    row_height: i32,
+    name_width: i32,
+    bar_width: i32,
+    percent_width: i32,
+    status_width: i32,
+    width: i32,
+    height: i32,
+}
+
+impl Flyout {
+    fn new(content: FlyoutContent) -> Result<Self> {
+        let font = unsafe {
+            let mut metrics = NONCLIENTMETRICSW {
+                cbSize: std::mem::size_of::<NONCLIENTMETRICSW>() as u32,
+                ..Default::default()
+            };
+            SystemParametersInfoW(
+                SPI_GETNONCLIENTMETRICS,
+                metrics.cbSize,
+                Some(&mut metrics as *mut _ as _),
+                SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+            )?;
+            CreateFontIndirectW(&metrics.lfMessageFont)
+        };
+
+        let loc = Localization::current();
+        let (line_height, name_width, percent_width, status_width, header_width) = unsafe {
+            let hdc = GetDC(None);
+            let old_font = SelectObject(hdc, font.into());
+            let max_width = |texts: &mut dyn Iterator<Item = String>| {
+                texts
+                    .map(|text| text_size(hdc, &text).cx)
+                    .max()
+                    .unwrap_or(0)
+            };
+            let line_height = text_size(hdc, "0%").cy;
+            let name_width = max_width(&mut content.devices.iter().map(device_name));
+            let percent_width = max_width(
+                &mut content
+                    .devices
+                    .iter()
+                    .map(|device| device_percent(content.battery_display, device)),
+            );
+            let status_width = max_width(
+                &mut content
+                    .devices
+                    .iter()
+                    .map(|device| device_status(loc, device)),
+            );
+            let header_width = max_width(
+                &mut content.headers.iter().cloned().chain(
+                    content
+                        .devices
+                        .is_empty()
+                        .then(|| loc.no_bluetooth_devices.to_owned()),
+                ),
+            );
+            SelectObject(hdc, old_font);
+            ReleaseDC(None, hdc);
+            (
+                line_height,
+                name_width,
+                percent_width,
+                status_width,
+                header_width,
+            )
+        };
+
+        let padding = line_height * 3 / 4;
+        let row_height = line_height * 8 / 5;
+        let gap = line_height;
+        let bar_width = line_height * 5;
+        // 状态前的圆点占一个字高
+        let status_width = status_width + line_height;
+        let columns_width = if content.devices.is_empty() {
+            0
+        } else {
+            name_width + gap + bar_width + gap + percent_width + gap + status_width
+        };
+        let lines = content.headers.len() + usize::from(content.devices.is_empty());
+        let width = columns_width.max(header_width) + padding * 2;
+        let height = (lines + content.devices.len()) as i32 * row_height + padding * 2;
+
+        Ok(Flyout {
+            content,
+            font,
+            line_height,
+            padding,
+            row_height,
+            name_width,
+            bar_width,
+            percent_width,
+            status_width,
+            width,
+            height,
+        })
+    }
+
+    /// 状态提示在上，其下每行依次为名称、电量条、百分比及连接状态
+    fn paint(&self, hdc: HDC, client: RECT) {
+        let loc = Localization::current();
+        let palette = Palette::new(SystemTheme::current());
+        let gap = self.line_height;
+
+        unsafe {
+            fill_rect(hdc, client, palette.background);
+            let old_font = SelectObject(hdc, self.font.into());
+            SetBkMode(hdc, TRANSPARENT);
+
+            let mut top = self.padding;
+            let headers = self.content.headers.iter().map(String::as_str).chain(
+                self.content
+                    .devices
+                    .is_empty()
+                    .then_some(loc.no_bluetooth_devices),
+            );
+            for header in headers {
+                let row = RECT {
+                    left: self.padding,
+                    top,
+                    right: client.right - self.padding,
+                    bottom: top + self.row_height,
+                };
+                draw_text(
+                    hdc,
+                    header,
+                    row,
+                    palette.secondary,
+                    DT_LEFT | DT_END_ELLIPSIS,
+                );
+                top += self.row_height;
+            }
+
+            for device in &self.content.devices {
+                let text_color = if device.dimmed {
+                    palette.secondary
+                } else {
+                    palette.text
+                };
+                let mut left = self.padding;
+                let mut cell = |width: i32| {
+                    let cell = RECT {
+                        left,
+                        top,
+                        right: left + width,
+                        bottom: top + self.row_height,
+                    };
+                    left += width + gap;
+                    cell
+                };
+
+                let name_cell = cell(self.name_width);
+                draw_text(
+                    hdc,
+                    &device_name(device),
+                    name_cell,
+                    text_color,
+                    DT_LEFT | DT_END_ELLIPSIS,
+                );
+
+                let bar_cell = cell(self.bar_width);
+                let track = RECT {
+                    top: bar_cell.top + (self.row_height - self.line_height / 2) / 2,
+                    bottom: bar_cell.top + (self.row_height + self.line_height / 2) / 2,
+                    ..bar_cell
+                };
+                fill_rect(hdc, track, palette.track);
+                if let Some(battery) = device.battery {
+                    let bar_color = if device.dimmed {
+                        DISCONNECTED_COLOR
+                    } else if battery < self.content.low_battery {
+                        LOW_BATTERY_COLOR
+                    } else {
+                        BATTERY_COLOR
+                    };
+                    let fill = RECT {
+                        right: track.left + self.bar_width * i32::from(battery.min(100)) / 100,
+                        ..track
+                    };
+                    fill_rect(hdc, fill, bar_color);
+                }
+
+                let percent = device_percent(self.content.battery_display, device);
+                draw_text(
+                    hdc,
+                    &percent,
+                    cell(self.percent_width),
+                    text_color,
+                    DT_RIGHT,
+                );
+
+                let status_cell = cell(self.status_width);
+                let dot_color = if device.status && !device.dimmed {
+                    BATTERY_COLOR
+                } else {
+                    DISCONNECTED_COLOR
+                };
+                let dot_size = self.line_height / 2;
+                let dot_top = status_cell.top + (self.row_height - dot_size) / 2;
+                let brush = CreateSolidBrush(dot_color);
+                let old_brush = SelectObject(hdc, brush.into());
+                let old_pen = SelectObject(hdc, GetStockObject(NULL_PEN));
+                let _ = Ellipse(
+                    hdc,
+                    status_cell.left,
+                    dot_top,
+                    status_cell.left + dot_size,
+                    dot_top + dot_size,
+                );
+                SelectObject(hdc, old_pen);
+                SelectObject(hdc, old_brush);
+                let _ = DeleteObject(brush.into());
+                let status_text = RECT {
+                    left: status_cell.left + self.line_height,
+                    ..status_cell
+                };
+                draw_text(
+                    hdc,
+                    &device_status(loc, device),
+                    status_text,
+                    palette.secondary,
+                    DT_LEFT,
+                );
+
+                top += self.row_height;
+            }
+
+            SelectObject(hdc, old_font);
+        }
+    }
+}
+
+impl Drop for Flyout {
+    fn drop(&mut self) {
+        let _ = unsafe { DeleteObject(self.font.into()) };
+    }
+}
+
+/// 跟随系统主题的颜色
+struct Palette {
+    background: COLORREF,
+    text: COLORREF,
+    secondary: COLORREF,
+    track: COLORREF,
+}
+
+impl Palette {
+    fn new(theme: SystemTheme) -> Self {
+        match theme {
+            SystemTheme::Dark => Palette {
+                background: rgb(0x2B, 0x2B, 0x2B),
+                text: rgb(0xFF, 0xFF, 0xFF),
+                secondary: rgb(0xA0, 0xA0, 0xA0),
+                track: rgb(0x4A, 0x4A, 0x4A),
+            },
+            SystemTheme::Light => Palette {
+                background: rgb(0xF9, 0xF9, 0xF9),
+                text: rgb(0x1F, 0x1F, 0x1F),
+                secondary: rgb(0x60, 0x60, 0x60),
+                track: rgb(0xDA, 0xDA, 0xDA),
+            },
+        }
+    }
+}
+
+const fn rgb(r: u8, g: u8, b: u8) -> COLORREF {
+    COLORREF(r as u32 | (g as u32) << 8 | (b as u32) << 16)
+}
+
+/// 类别图标及名称，如 "🎧 WH-1000XM4"
+fn device_name(device: &TooltipDevice) -> String {
+    let name = match device.category_icon {
+        "" => device.name.clone(),
+        icon => format!("{icon} {}", device.name),
+    };
+    if device.default_audio {
+        format!("{name} 🎧")
+    } else {
+        name
+    }
+}
+
+/// 按显示方式格式化的电量，过期数据设置为隐藏电量时为 "--%"
+fn device_percent(battery_display: BatteryDisplay, device: &TooltipDevice) -> String {
+    device.battery.map_or_else(
+        || "--%".to_owned(),
+        |battery| battery_display.format(battery),
+    )
+}
+
+/// 连接状态，设置显示数据时间时附上距今多久，未连接时为断开距今多久，如 "已连接 · 5 分钟前"
+fn device_status(loc: &Localization, device: &TooltipDevice) -> String {
+    let status = if device.status {
+        loc.connected
+    } else {
+        loc.disconnected
+    };
+    match device.last_seen.or(device.age.filter(|age| *age >= 60)) {
+        Some(age) => format!("{status} · {}", format_data_age(age)),
+        None => status.to_owned(),
+    }
+}
+
+fn text_size(hdc: HDC, text: &str) -> SIZE {
+    let text = text.encode_utf16().collect::<Vec<u16>>();
+    let mut size = SIZE::default();
+    let _ = unsafe { GetTextExtentPoint32W(hdc, &text, &mut size) };
+    size
+}
+
+fn draw_text(hdc: HDC, text: &str, mut rect: RECT, color: COLORREF, format: DRAW_TEXT_FORMAT) {
+    let mut text = text.encode_utf16().collect::<Vec<u16>>();
+    unsafe {
+        SetTextColor(hdc, color);
+        DrawTextW(
+            hdc,
+            &mut text,
+            &mut rect,
+            DT_SINGLELINE | DT_VCENTER | DT_NOPREFIX | format,
+        );
+    }
+}
+
+fn fill_rect(hdc: HDC, rect: RECT, color: COLORREF) {
+    unsafe {
+        let brush = CreateSolidBrush(color);
+        FillRect(hdc, &rect, brush);
+        let _ = DeleteObject(brush.into());
+    }
+}
+
+/// 任务栏在下方时显示在图标上方，否则显示在图标下方，并限制在图标所在显示器的工作区内
+fn flyout_position(anchor: tray_icon::Rect, width: i32, height: i32) -> (i32, i32) {
+    let anchor = RECT {
+        left: anchor.position.x as i32,
+        top: anchor.position.y as i32,
+        right: anchor.position.x as i32 + anchor.size.width as i32,
+        bottom: anchor.position.y as i32 + anchor.size.height as i32,
+    };
+    let center = POINT {
+        x: (anchor.left + anchor.right) / 2,
+        y: (anchor.top + anchor.bottom) / 2,
+    };
+
+    let mut monitor_info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    let work_area = unsafe {
+        let monitor = MonitorFromPoint(center, MONITOR_DEFAULTTONEAREST);
+        GetMonitorInfoW(monitor, &mut monitor_info)
+            .as_bool()
+            .then_some(monitor_info.rcWork)
+    };
+
+    let x = center.x - width / 2;
+    let y = match work_area {
+        Some(work_area) if center.y < (work_area.top + work_area.bottom) / 2 => {
+            anchor.bottom + ANCHOR_GAP
+        }
+        _ => anchor.top - height - ANCHOR_GAP,
+    };
+
+    match work_area {
+        Some(work_area) => (
+            x.clamp(
+                work_area.left,
+                (work_area.right - width).max(work_area.left),
+            ),
+            y.clamp(
+                work_area.top,
+                (work_area.bottom - height).max(work_area.top),
+            ),
+        ),
+        None => (x, y),
+    }
+}
+
+/// 创建不抢占焦点的置顶窗口，运行消息循环直到关闭或超时
+fn run_flyout(content: FlyoutContent, anchor: tray_icon::Rect, generation: u64) -> Result<()> {
+    let flyout = Flyout::new(content)?;
+    let (x, y) = flyout_position(anchor, flyout.width, flyout.height);
+
+    unsafe {
+        let instance: HINSTANCE = GetModuleHandleW(None)?.into();
+        let class_name = w!("BlueGaugeFlyout");
+
+        // 窗口类已注册时返回 0，可直接使用已注册的窗口类
+        RegisterClassW(&WNDCLASSW {
+            lpfnWndProc: Some(flyout_proc),
+            hInstance: instance,
+            hCursor: LoadCursorW(None, IDC_ARROW)?,
+            lpszClassName: class_name,
+            ..Default::default()
+        });
+
+        let hwnd = CreateWindowExW(
+            WS_EX_TOPMOST | WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE,
+            class_name,
+            w!(""),
+            WS_POPUP | WS_BORDER,
+            x,
+            y,
+            flyout.width,
+            flyout.height,
+            None,
+            None,
+            Some(instance),
+            None,
+        )?;
+
+        FLYOUT.with_borrow_mut(|current| *current = Some(flyout));
+
+        // 先登记窗口再检查，创建期间已请求关闭或显示了新的浮窗时不再显示
+        FLYOUT_WINDOW.store(hwnd.0 as isize, Ordering::Release);
+        if FLYOUT_GENERATION.load(Ordering::Acquire) != generation {
+            let _ = DestroyWindow(hwnd);
+        } else {
+            SetTimer(Some(hwnd), DISMISS_TIMER_ID, FLYOUT_TIMEOUT_MS, None);
+            let _ = ShowWindow(hwnd, SW_SHOWNOACTIVATE);
+        }
+
+        let mut message = MSG::default();
+        while GetMessageW(&mut message, None, 0, 0).as_bool() {
+            let _ = TranslateMessage(&message);
+            DispatchMessageW(&message);
+        }
+
+        FLYOUT.with_borrow_mut(Option::take);
+    }
+
+    Ok(())
+}
+
+extern "system" fn flyout_proc(
+    hwnd: HWND,
+    message: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    unsafe {
+        match message {
+            WM_PAINT => {
+                let mut paint = PAINTSTRUCT::default();
+                let hdc = BeginPaint(hwnd, &mut paint);
+                let mut client = RECT::default();
+                if GetClientRect(hwnd, &mut client).is_ok() {
+                    FLYOUT.with_borrow(|flyout| {
+                        if let Some(flyout) = flyout {
+                            flyout.paint(hdc, client);
+                        }
+                    });
+                }
+                let _ = EndPaint(hwnd, &paint);
+                LRESULT(0)
+            }
+            WM_TIMER if wparam.0 == DISMISS_TIMER_ID => {
+                let _ = DestroyWindow(hwnd);
+                LRESULT(0)
+            }
+            WM_DESTROY => {
+                // 只清除自己，新的浮窗可能已经显示
+                let _ = FLYOUT_WINDOW.compare_exchange(
+                    hwnd.0 as isize,
+                    0,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                );
+                PostQuitMessage(0);
+                LRESULT(0)
+            }
+            _ => DefWindowProcW(hwnd, message, wparam, lparam),
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use crate::{
+    bluetooth::{events::DeviceEvent, info::BluetoothInfo},
+    cli::format_address,
+    config::Config,
+};
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+const HISTORY_FILE_NAME: &str = "BlueGauge.history.jsonl";
+/// 超过该大小时将记录移到 `BlueGauge.history.1.jsonl`（覆盖更早的记录）后重新开始，
+/// 读取时两个文件都包含在内
+const MAX_HISTORY_SIZE: u64 = 4 * 1024 * 1024;
+
+/// 一条电量记录，以 JSON Lines 格式追加保存在配置文件目录下
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatterySample {
+    /// Unix 时间戳（秒）
+    pub timestamp: u64,
+    pub address: u64,
+    pub name: String,
+    pub battery: u8,
+    pub status: bool,
+}
+
+#[derive(Clone)]
+pub struct History {
+    path: Option<PathBuf>,
+}
+
+impl History {
+    pub fn new(config: &Config) -> Self {
+        let path = config
+            .history_options
+            .enabled
+            .then(|| config.config_path.with_file_name(HISTORY_FILE_NAME));
+
+        History { path }
+    }
+
+    /// 记录所有设备的当前电量，用于首次枚举完成时
+    pub fn record(&self, bluetooth_devices_info: &HashSet<BluetoothInfo>) -> Result<()> {
+        self.append(bluetooth_devices_info.iter())
+    }
+
+    /// 只记录电量或连接状态变化的设备，同一设备的多个事件只追加一条记录
+    pub fn record_events(&self, events: &[DeviceEvent]) -> Result<()> {
+        let changed = events
+            .iter()
+            .filter_map(|event| match event {
+                DeviceEvent::Added { info }
+                | DeviceEvent::BatteryChanged { info, .. }
+                | DeviceEvent::ConnectionChanged { info } => Some((info.address, info)),
+                DeviceEvent::Removed { .. } | DeviceEvent::Renamed { .. } => None,
+            })
+            .collect::<HashMap<_, _>>();
+        self.append(changed.into_values())
+    }
+
+    fn append<'a>(&self, infos: impl Iterator<Item = &'a BluetoothInfo>) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+        let samples = infos
+            .map(|info| BatterySample {
+                timestamp,
+                address: info.address,
+                name: info.name.clone(),
+                battery: info.battery,
+                status: info.status,
+            })
+            .collect::<Vec<_>>();
+
+        if samples.is_empty() {
+            return Ok(());
+        }
+
+        if std::fs::metadata(path).is_ok_and(|metadata| metadata.len() >= MAX_HISTORY_SIZE) {
+            let rotated_path = rotated_path(path);
+            std::fs::rename(path, &rotated_path)
+                .with_context(|| format!("Failed to rotate history file to {rotated_path:?}"))?;
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open history file {path:?}"))?;
+
+        for sample in samples {
+            writeln!(file, "{}", serde_json::to_string(&sample)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// 每个设备最后一次处于连接状态的记录时间
+    pub fn last_connected(&self) -> Result<HashMap</* address */ u64, u64>> {
+        let last_connected = self
+            .load(None)?
+            .into_iter()
+            .filter(|sample| sample.status)
+            .map(|sample| (sample.address, sample.timestamp))
+            .collect();
+        Ok(last_connected)
+    }
+
+    /// 读取所有记录，`address` 为 `None` 时返回所有设备
+    pub fn load(&self, address: Option<u64>) -> Result<Vec<BatterySample>> {
+        let samples = self
+            .lines()?
+            .filter_map(|line| serde_json::from_str::<BatterySample>(&line).ok())
+            .filter(|sample| address.is_none_or(|address| sample.address == address))
+            .collect();
+
+        Ok(samples)
+    }
+
+    /// 最后 `count` 行原始记录，用于诊断报告
+    pub fn tail(&self, count: usize) -> Result<Vec<String>> {
+        let mut lines = VecDeque::with_capacity(count);
+        for line in self.lines()? {
+            if lines.len() == count {
+                lines.pop_front();
+            }
+            lines.push_back(line);
+        }
+
+        Ok(lines.into())
+    }
+
+    /// 按时间顺序读取轮换后的旧文件及当前文件中的每一行
+    fn lines(&self) -> Result<impl Iterator<Item = String>> {
+        let paths = match &self.path {
+            Some(path) => vec![rotated_path(path), path.clone()],
+            None => Vec::new(),
+        };
+
+        let mut files = Vec::new();
+        for path in paths.into_iter().filter(|path| path.is_file()) {
+            files.push(std::fs::File::open(path)?);
+        }
+
+        Ok(files
+            .into_iter()
+            .flat_map(|file| BufReader::new(file).lines().map_while(Result::ok)))
+    }
+}
+
+/// `BlueGauge.history.jsonl` → `BlueGauge.history.1.jsonl`
+fn rotated_path(path: &Path) -> PathBuf {
+    path.with_extension("1.jsonl")
+}
+
+/// 电量达到该值视为充满，部分设备以 5% 或 10% 为步长上报
+const FULL_BATTERY: u8 = 95;
+/// 相邻记录间隔超过该值（程序未运行或设备长时间未使用）时不计入耗电速度
+const MAX_DRAIN_GAP: u64 = 6 * 60 * 60;
+
+/// 由电量历史推算的单个设备电池统计
+#[derive(Debug, Default)]
+pub struct BatteryStatistics {
+    /// 充满 → 低电量 → 充满 的完整循环次数
+    pub cycles: u32,
+    /// 连接期间的平均耗电速度（%/小时）
+    pub drain_per_hour: Option<f64>,
+    /// 历史最低电量
+    pub lowest: Option<u8>,
+    /// 第一条记录的时间戳
+    pub since: Option<u64>,
+}
+
+/// `samples` 为单个设备按时间排列的记录，`low_battery` 为低电量阈值
+pub fn battery_statistics(samples: &[BatterySample], low_battery: u8) -> BatteryStatistics {
+    let mut cycles = 0;
+    let mut was_full = false;
+    let mut was_low = false;
+    for sample in samples {
+        if sample.battery >= FULL_BATTERY {
+            if was_full && was_low {
+                cycles += 1;
+            }
+            was_full = true;
+            was_low = false;
+        } else if was_full && sample.battery <= low_battery {
+            was_low = true;
+        }
+    }
+
+    // 只统计两次记录都处于连接状态且电量未上升（未在充电）的区间
+    let (drained, seconds) = samples
+        .windows(2)
+        .filter(|pair| {
+            let (prev, next) = (&pair[0], &pair[1]);
+            prev.status
+                && next.status
+                && next.battery <= prev.battery
+                && next.timestamp.saturating_sub(prev.timestamp) <= MAX_DRAIN_GAP
+        })
+        .fold((0u64, 0u64), |(drained, seconds), pair| {
+            (
+                drained + u64::from(pair[0].battery - pair[1].battery),
+                seconds + pair[1].timestamp.saturating_sub(pair[0].timestamp),
+            )
+        });
+    let drain_per_hour = (seconds > 0).then(|| drained as f64 * 3600.0 / seconds as f64);
+
+    BatteryStatistics {
+        cycles,
+        drain_per_hour,
+        lowest: samples.iter().map(|sample| sample.battery).min(),
+        since: samples.first().map(|sample| sample.timestamp),
+    }
+}
+
+/// 单个设备一段时间内的用量
+#[derive(Debug)]
+pub struct DeviceUsage {
+    pub address: u64,
+    pub name: String,
+    /// 连接期间的平均耗电速度（%/小时）
+    pub drain_per_hour: Option<f64>,
+    /// 连接时长（秒）
+    pub connected_seconds: u64,
+    pub lowest: Option<u8>,
+}
+
+/// 按设备汇总 `since` 之后的记录，按名称排序
+pub fn usage_since(samples: &[BatterySample], since: u64, low_battery: u8) -> Vec<DeviceUsage> {
+    let mut samples_by_device: HashMap<u64, Vec<&BatterySample>> = HashMap::new();
+    for sample in samples.iter().filter(|sample| sample.timestamp >= since) {
+        samples_by_device
+            .entry(sample.address)
+            .or_default()
+            .push(sample);
+    }
+
+    let mut usage = samples_by_device
+        .into_iter()
+        .filter_map(|(address, samples)| {
+            let samples = samples.into_iter().cloned().collect::<Vec<_>>();
+            let statistics = battery_statistics(&samples, low_battery);
+            // 与耗电速度相同，间隔过长的区间不计入
+            let connected_seconds = samples
+                .windows(2)
+                .filter(|pair| pair[0].status)
+                .map(|pair| {
+                    pair[1]
+                        .timestamp
+                        .saturating_sub(pair[0].timestamp)
+                        .min(MAX_DRAIN_GAP)
+                })
+                .sum();
+
+            Some(DeviceUsage {
+                address,
+                name: samples.last()?.name.clone(),
+                drain_per_hour: statistics.drain_per_hour,
+                connected_seconds,
+                lowest: statistics.lowest,
+            })
+        })
+        .collect::<Vec<_>>();
+    usage.sort_by(|a, b| a.name.cmp(&b.name).then(a.address.cmp(&b.address)));
+    usage
+}
+
+/// 根据扩展名导出为 JSON 或 CSV
+pub fn export_history(samples: &[BatterySample], path: &Path) -> Result<()> {
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+    let contents = if is_json {
+        serde_json::to_string_pretty(samples)?
+    } else {
+        let mut csv = String::from("time,address,name,battery,status\n");
+        for sample in samples {
+            let time = DateTime::from_timestamp(sample.timestamp as i64, 0)
+                .map(|t| {
+                    t.with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            let name = csv_text(&sample.name);
+            let status = if sample.status {
+                "connected"
+            } else {
+                "disconnected"
+            };
+            csv.push_str(&format!(
+                "{time},{},{name},{},{status}\n",
+                format_address(sample.address),
+                sample.battery
+            ));
+        }
+        csv
+    };
+
+    std::fs::write(path, contents).with_context(|| format!("Failed to export history to {path:?}"))
+}
+
+/// 加引号的 CSV 文本字段。以 `=`、`+`、`-`、`@` 等开头时前置 `'`，
+/// 避免名称在 Excel 中被当作公式执行
+fn csv_text(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{value}")
+    } else {
+        value.to_owned()
+    };
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_text_neutralizes_formulas() {
+        assert_eq!(csv_text("Buds"), r#""Buds""#);
+        assert_eq!(csv_text(r#"My "Pro" Buds"#), r#""My ""Pro"" Buds""#);
+        assert_eq!(
+            csv_text("=HYPERLINK(\"http://x\")"),
+            r#""'=HYPERLINK(""http://x"")""#
+        );
+        for name in ["+1 Buds", "-Buds", "@SUM(A1)", "\tBuds"] {
+            assert!(csv_text(name).starts_with("\"'"), "{name}");
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::{Result, anyhow};
+use windows::Win32::UI::{
+    Input::KeyboardAndMouse::{
+        HOT_KEY_MODIFIERS, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT, MOD_WIN, RegisterHotKey,
+    },
+    WindowsAndMessaging::{GetMessageW, MSG, WM_HOTKEY},
+};
+
+const SUMMARY_HOTKEY_ID: i32 = 1;
+
+/// 在独立线程中注册全局快捷键，`WM_HOTKEY` 投递到该线程的消息队列
+pub fn register_summary_hotkey(hotkey: &str, on_pressed: impl Fn() + Send + 'static) -> Result<()> {
+    let (modifiers, key) = parse_hotkey(hotkey)?;
+    let hotkey = hotkey.to_owned();
+    let (result_tx, result_rx) = std::sync::mpsc::channel();
+
+    std::thread::spawn(move || {
+        let result = unsafe { RegisterHotKey(None, SUMMARY_HOTKEY_ID, modifiers, key) }
+            .map_err(|e| anyhow!("Failed to register the hotkey {hotkey} - {e}"));
+        let registered = result.is_ok();
+        let _ = result_tx.send(result);
+        if !registered {
+            return;
+        }
+
+        let mut msg = MSG::default();
+        unsafe {
+            while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                if msg.message == WM_HOTKEY && msg.wParam.0 as i32 == SUMMARY_HOTKEY_ID {
+                    on_pressed();
+                }
+            }
+        }
+    });
+
+    result_rx.recv()?
+}
+
+/// 解析 "Ctrl+Alt+B"、"Win+Shift+F9" 形式的快捷键，按键支持 A-Z、0-9 及 F1-F24
+fn parse_hotkey(hotkey: &str) -> Result<(HOT_KEY_MODIFIERS, u32)> {
+    let mut modifiers = MOD_NOREPEAT;
+    let mut key = None;
+
+    for part in hotkey.split('+').map(str::trim) {
+        match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => modifiers |= MOD_CONTROL,
+            "alt" => modifiers |= MOD_ALT,
+            "shift" => modifiers |= MOD_SHIFT,
+            "win" => modifiers |= MOD_WIN,
+            name if key.is_none() => key = Some(parse_key(name)?),
+            _ => return Err(anyhow!("Hotkey {hotkey} has more than one key")),
+        }
+    }
+
+    if modifiers == MOD_NOREPEAT {
+        return Err(anyhow!("Hotkey {hotkey} needs at least one modifier"));
+    }
+
+    key.map(|key| (modifiers, key))
+        .ok_or_else(|| anyhow!("Hotkey {hotkey} has no key"))
+}
+
+fn parse_key(name: &str) -> Result<u32> {
+    let key = match name.as_bytes() {
+        // 虚拟键码与大写字母及数字的 ASCII 码相同
+        [c @ (b'a'..=b'z' | b'0'..=b'9')] => Some(c.to_ascii_uppercase() as u32),
+        [b'f', number @ ..] => std::str::from_utf8(number)
+            .ok()
+            .and_then(|number| number.parse::<u32>().ok())
+            .filter(|number| (1..=24).contains(number))
+            // VK_F1 = 0x70
+            .map(|number| 0x70 + number - 1),
+        _ => None,
+    };
+
+    key.ok_or_else(|| anyhow!("Unsupported hotkey key: {name}"))
+}
@@ -0,0 +1,325 @@
+use crate::{
+    bluetooth::{
+        events::{DeviceEvent, DeviceEvents},
+        info::BluetoothInfo,
+    },
+    cli::parse_address,
+    config::Config,
+};
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::sync::{Arc, Mutex, atomic::Ordering};
+
+use anyhow::{Result, anyhow};
+use log::{info, warn};
+use tiny_http::{Header, Method, Request, Response, Server};
+use tokio::sync::broadcast::{self, error::RecvError};
+use windows::Win32::Security::Cryptography::{BCRYPT_USE_SYSTEM_PREFERRED_RNG, BCryptGenRandom};
+
+/// 启动本地 HTTP API：
+/// - `GET /devices`：所有设备
+/// - `GET /devices/{address}`：单个设备（`AA:BB:CC:DD:EE:FF` 或十进制地址）
+/// - `POST /refresh`：立即更新设备信息
+/// - `GET /events`：以 Server-Sent Events 持续推送设备事件
+///
+/// 没有令牌时生成并写入配置；`Host` 不是本机地址或 `allowed_hosts` 的请求一律拒绝，防止 DNS 重绑定
+pub fn start_http_api(
+    config: Arc<Config>,
+    bluetooth_info: Arc<Mutex<HashSet<BluetoothInfo>>>,
+    device_events: Arc<DeviceEvents>,
+) -> Result<()> {
+    let mut options = config.http_api_options.lock().unwrap().clone();
+    if is_wildcard_address(&options.address) && options.allowed_hosts.is_empty() {
+        return Err(anyhow!(
+            "HTTP API listening on {} requires allowed_hosts under [http_api] \
+             (the host names or IPs that clients use to reach this computer)",
+            options.address
+        ));
+    }
+
+    if options.token.is_empty() {
+        options.token = generate_token()?;
+        config.http_api_options.lock().unwrap().token = options.token.clone();
+        config.save();
+        info!("Generated an HTTP API token in {:?}", config.config_path);
+    }
+
+    let server = Server::http((options.address.as_str(), options.port)).map_err(|e| {
+        anyhow!(
+            "Failed to start HTTP API on {}:{} - {e}",
+            options.address,
+            options.port
+        )
+    })?;
+
+    let allowed_hosts = allowed_hosts(&options.address, &options.allowed_hosts, options.port);
+
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            if !is_allowed_host(&request, &allowed_hosts) {
+                let response = json_response(403, r#"{"error":"forbidden host"}"#.to_owned());
+                if let Err(e) = request.respond(response) {
+                    warn!("Failed to respond to HTTP API request: {e}");
+                }
+                continue;
+            }
+
+            if is_event_stream(&request, &options.token) {
+                stream_events(request, device_events.subscribe());
+                continue;
+            }
+
+            let response = handle_request(&request, &config, &options.token, &bluetooth_info);
+            if let Err(e) = request.respond(response) {
+                warn!("Failed to respond to HTTP API request: {e}");
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn handle_request(
+    request: &Request,
+    config: &Config,
+    token: &str,
+    bluetooth_info: &Mutex<HashSet<BluetoothInfo>>,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+
+    if !is_authorized(request, query, token) {
+        return json_response(401, r#"{"error":"unauthorized"}"#.to_owned());
+    }
+
+    let segments = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+
+    match (request.method(), segments.as_slice()) {
+        (Method::Get, ["devices"]) => {
+            let mut infos = bluetooth_info
+                .lock()
+                .unwrap()
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>();
+            infos.sort_by(|a, b| a.name.cmp(&b.name));
+            to_json_response(&infos)
+        }
+        (Method::Get, ["devices", address]) => {
+            let Ok(address) = parse_address(address) else {
+                return json_response(400, r#"{"error":"invalid address"}"#.to_owned());
+            };
+            let info = bluetooth_info
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|i| i.address == address)
+                .cloned();
+            match info {
+                Some(info) => to_json_response(&info),
+                None => json_response(404, r#"{"error":"device not found"}"#.to_owned()),
+            }
+        }
+        (Method::Post, ["refresh"]) => {
+            config.force_update.store(true, Ordering::SeqCst);
+            json_response(202, r#"{"refresh":"scheduled"}"#.to_owned())
+        }
+        (_, ["devices"] | ["devices", _] | ["refresh"] | ["events"]) => {
+            json_response(405, r#"{"error":"method not allowed"}"#.to_owned())
+        }
+        _ => json_response(404, r#"{"error":"not found"}"#.to_owned()),
+    }
+}
+
+/// 不设置长度，以关闭连接结束响应
+const EVENT_STREAM_HEADER: &str = concat!(
+    "HTTP/1.1 200 OK\r\n",
+    "Content-Type: text/event-stream\r\n",
+    "Cache-Control: no-cache\r\n",
+    "Connection: close\r\n",
+    "\r\n",
+);
+
+fn is_event_stream(request: &Request, token: &str) -> bool {
+    let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+    *request.method() == Method::Get
+        && path.trim_end_matches('/') == "/events"
+        && is_authorized(request, query, token)
+}
+
+/// 每个连接在单独的线程中写入，直到客户端断开或程序退出
+fn stream_events(request: Request, mut device_events: broadcast::Receiver<DeviceEvent>) {
+    std::thread::spawn(move || {
+        let mut writer = request.into_writer();
+        if writer
+            .write_all(EVENT_STREAM_HEADER.as_bytes())
+            .and_then(|_| writer.flush())
+            .is_err()
+        {
+            return;
+        }
+
+        loop {
+            let event = match device_events.blocking_recv() {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("HTTP API event stream skipped {skipped} device events");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let data = match serde_json::to_string(&event) {
+                Ok(data) => data,
+                Err(e) => {
+                    warn!("Failed to serialize device event: {e}");
+                    continue;
+                }
+            };
+
+            // 客户端断开后写入失败
+            if writeln!(writer, "data: {data}\n")
+                .and_then(|_| writer.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+}
+
+/// 32 位十六进制的随机令牌
+fn generate_token() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    unsafe { BCryptGenRandom(None, &mut bytes, BCRYPT_USE_SYSTEM_PREFERRED_RNG) }
+        .ok()
+        .map_err(|e| anyhow!("Failed to generate an HTTP API token - {e}"))?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// 监听所有网卡时客户端使用的地址无法从监听地址得知
+fn is_wildcard_address(address: &str) -> bool {
+    matches!(address.trim(), "0.0.0.0" | "::" | "[::]")
+}
+
+/// 本机地址、监听地址（非通配地址时）及配置的 `allowed_hosts`，均需带端口
+fn allowed_hosts(address: &str, extra_hosts: &[String], port: u16) -> Vec<String> {
+    let address = (!is_wildcard_address(address)).then_some(address);
+    ["127.0.0.1", "localhost", "[::1]"]
+        .into_iter()
+        .chain(address)
+        .chain(extra_hosts.iter().map(String::as_str))
+        .map(|host| format!("{}:{port}", host.trim()).to_ascii_lowercase())
+        .collect()
+}
+
+fn is_allowed_host(request: &Request, allowed_hosts: &[String]) -> bool {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Host"))
+        .is_some_and(|h| allowed_hosts.contains(&h.value.as_str().trim().to_ascii_lowercase()))
+}
+
+/// 支持 `Authorization: Bearer <token>` 或 `?token=<token>`（可百分号编码）
+fn is_authorized(request: &Request, query: &str, token: &str) -> bool {
+    let header_token = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .and_then(|h| h.value.as_str().strip_prefix("Bearer "))
+        .map(str::trim);
+
+    let query_token = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="))
+        .and_then(percent_decode);
+
+    header_token
+        .map(str::to_owned)
+        .or(query_token)
+        .is_some_and(|candidate| constant_time_eq(candidate.as_bytes(), token.as_bytes()))
+}
+
+/// 比较时间与内容无关，避免逐字节猜测令牌
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// 解码查询参数中的 `%XX` 及 `+`，编码无效时返回 `None`
+fn percent_decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut input = value.bytes();
+    while let Some(byte) = input.next() {
+        match byte {
+            b'%' => {
+                let hex = [input.next()?, input.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            b'+' => bytes.push(b' '),
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn to_json_response<T: serde::Serialize>(value: &T) -> Response<std::io::Cursor<Vec<u8>>> {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => json_response(200, json),
+        Err(e) => json_response(500, format!(r#"{{"error":"{e}"}}"#)),
+    }
+}
+
+fn json_response(status_code: u16, body: String) -> Response<std::io::Cursor<Vec<u8>>> {
+    let content_type: Header = "Content-Type: application/json; charset=utf-8"
+        .parse()
+        .expect("Invalid Content-Type header");
+    Response::from_string(body)
+        .with_status_code(status_code)
+        .with_header(content_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_decode_query_values() {
+        assert_eq!(percent_decode("abc123").as_deref(), Some("abc123"));
+        assert_eq!(percent_decode("a%2Bb%3D%3d").as_deref(), Some("a+b=="));
+        assert_eq!(percent_decode("a+b").as_deref(), Some("a b"));
+        assert_eq!(percent_decode("%E4%B8%AD").as_deref(), Some("中"));
+        assert_eq!(percent_decode("%4"), None);
+        assert_eq!(percent_decode("%zz"), None);
+    }
+
+    #[test]
+    fn allowed_hosts_include_port() {
+        let hosts = allowed_hosts("127.0.0.1", &[], 18650);
+        assert!(hosts.contains(&"localhost:18650".to_owned()));
+        assert!(hosts.contains(&"127.0.0.1:18650".to_owned()));
+        assert!(!hosts.contains(&"localhost".to_owned()));
+        assert!(!hosts.contains(&"evil.example:18650".to_owned()));
+    }
+
+    #[test]
+    fn allowed_hosts_for_lan_clients() {
+        let hosts = allowed_hosts("192.168.1.10", &[], 18650);
+        assert!(hosts.contains(&"192.168.1.10:18650".to_owned()));
+
+        let hosts = allowed_hosts("0.0.0.0", &["MyPC".to_owned()], 18650);
+        assert!(hosts.contains(&"mypc:18650".to_owned()));
+        assert!(!hosts.contains(&"0.0.0.0:18650".to_owned()));
+    }
+
+    #[test]
+    fn tokens_are_compared_in_full() {
+        assert!(constant_time_eq(b"0123abcd", b"0123abcd"));
+        assert!(!constant_time_eq(b"0123abcd", b"0123abce"));
+        assert!(!constant_time_eq(b"0123abcd", b"0123abc"));
+        assert!(!constant_time_eq(b"", b"0123abcd"));
+    }
+}
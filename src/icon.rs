@@ -1,13 +1,18 @@
 use crate::{
     bluetooth::info::BluetoothInfo,
-    config::{Config, TrayIconSource},
+    config::{Config, FontIconLayout, LowBatteryIconStyle, TrayIconSource},
 };
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
 
 use anyhow::{Context, Result, anyhow};
+use log::{info, warn};
 use piet_common::{
-    Color, Device, FontFamily, ImageFormat, RenderContext, Text, TextLayout, TextLayoutBuilder,
+    Color, Device, FontFamily, FontStyle, FontWeight, ImageFormat, RenderContext, Text, TextLayout,
+    TextLayoutBuilder,
+    kurbo::{Circle, Point, Rect, RoundedRect},
 };
 use tray_icon::Icon;
 use winreg::{
@@ -21,6 +26,13 @@ const PERSONALIZE_REGISTRY_KEY: &str =
     r"Software\Microsoft\Windows\CurrentVersion\Themes\Personalize";
 const SYSTEM_USES_LIGHT_THEME_REGISTRY_KEY: &str = "SystemUsesLightTheme";
 
+/// 缓存的系统主题，渲染时不再读取注册表，主题变化时由 `ThemeChanged` 事件更新
+static CURRENT_THEME: LazyLock<RwLock<SystemTheme>> =
+    LazyLock::new(|| RwLock::new(SystemTheme::get()));
+
+/// 自定义图标包，首次使用时一次性读取默认及浅色、深色两套图标，切换主题时无需再读取磁盘
+static CUSTOM_ICON_PACK: LazyLock<CustomIconPack> = LazyLock::new(CustomIconPack::load);
+
 pub fn load_icon(icon_date: &[u8]) -> Result<Icon> {
     let (icon_rgba, icon_width, icon_height) = {
         let image = image::load_from_memory(icon_date)
@@ -45,79 +57,226 @@ pub fn load_battery_icon(
         lock.clone()
     };
 
+    let address = tray_icon_source.resolve_address(bluetooth_devices_info);
+    let info = bluetooth_devices_info
+        .iter()
+        .find(|i| Some(i.address) == address);
+
+    // 显示电量的设备低电量时，不论图标的显示方式都替换为警示图标
+    let low_battery_style = config.low_battery_icon_options.style;
+    if low_battery_style != LowBatteryIconStyle::Off
+        && !matches!(tray_icon_source, TrayIconSource::App)
+        && let Some(info) =
+            info.filter(|info| info.status && info.battery < config.get_low_battery())
+    {
+        return get_low_battery_icon(info.battery, low_battery_style);
+    }
+
     match tray_icon_source {
         TrayIconSource::App => default_icon(),
-        TrayIconSource::BatteryCustom { ref address }
-        | TrayIconSource::BatteryFont { ref address, .. } => bluetooth_devices_info
-            .iter()
-            .find(|i| i.address == *address)
-            .map_or_else(
-                || load_icon(UNPAIRED_ICON_DATA),
-                |i| match tray_icon_source {
-                    TrayIconSource::BatteryCustom { .. } => get_icon_from_custom(i.battery),
-                    TrayIconSource::BatteryFont {
-                        address: _,
-                        font_name,
-                        font_color,
-                        font_size,
-                    } => {
-                        let should_icon_connect_color = font_color
-                            .as_ref()
-                            .is_some_and(|c| c.eq("ConnectColor"))
-                            .then_some(i.status);
-
-                        get_icon_from_font(
-                            i.battery,
-                            &font_name,
-                            font_color,
-                            font_size,
-                            should_icon_connect_color,
-                        )
-                    }
-                    _ => load_icon(UNPAIRED_ICON_DATA),
-                },
-            ),
+        // 没有已连接的设备时显示应用图标
+        TrayIconSource::LowestBattery { .. } => match info {
+            Some(info) => {
+                get_icon_from_font(config, info, &FontIconStyle::new(config, tray_icon_source))
+            }
+            None => default_icon(),
+        },
+        TrayIconSource::BatteryCustom { .. } => info.map_or_else(
+            || load_icon(UNPAIRED_ICON_DATA),
+            |i| get_icon_from_custom(i.battery),
+        ),
+        TrayIconSource::BatteryFont { .. } => info.map_or_else(
+            || load_icon(UNPAIRED_ICON_DATA),
+            |i| get_icon_from_font(config, i, &FontIconStyle::new(config, tray_icon_source)),
+        ),
     }
 }
 
 fn get_icon_from_custom(battery_level: u8) -> Result<Icon> {
-    let custom_battery_icon_path = std::env::current_exe()
-        .map(|exe_path| exe_path.with_file_name("assets"))
-        .and_then(|icon_dir| {
-            let default_icon_path = icon_dir.join(format!("{battery_level}.png"));
-            if default_icon_path.is_file() {
-                return Ok(default_icon_path);
-            }
-            let theme_icon_path = match SystemTheme::get() {
-                SystemTheme::Light => icon_dir.join(format!("light\\{battery_level}.png")),
-                SystemTheme::Dark => icon_dir.join(format!("dark\\{battery_level}.png")),
-            };
-            if theme_icon_path.is_file() {
-                return Ok(theme_icon_path);
-            }
-            Err(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                format!("Failed to find {battery_level} default/theme PNG in Bluegauge directory"),
-            ))
+    let image = CUSTOM_ICON_PACK
+        .get(battery_level, SystemTheme::current())
+        .ok_or_else(|| {
+            anyhow!("Failed to find {battery_level} default/theme PNG in Bluegauge directory")
         })?;
 
-    let icon_data = std::fs::read(custom_battery_icon_path)?;
+    Icon::from_rgba(image.rgba.clone(), image.width, image.height)
+        .map_err(|e| anyhow!("Failed to get Icon - {e}"))
+}
 
-    load_icon(&icon_data)
+/// 解码后的图标像素
+struct IconImage {
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
 }
 
-fn get_icon_from_font(
-    battery_level: u8,
-    font_name: &str,
+impl IconImage {
+    fn read(path: &Path) -> Result<Self> {
+        let data = std::fs::read(path).with_context(|| format!("Failed to read {path:?}"))?;
+        let image = image::load_from_memory(&data)
+            .with_context(|| format!("Failed to decode {path:?}"))?
+            .into_rgba8();
+        let (width, height) = image.dimensions();
+        Ok(IconImage {
+            rgba: image.into_raw(),
+            width,
+            height,
+        })
+    }
+}
+
+/// 程序目录下 assets 中按电量命名的图标：`{电量}.png` 不区分主题，
+/// `light\{电量}.png` 与 `dark\{电量}.png` 分别用于浅色、深色主题
+#[derive(Default)]
+struct CustomIconPack {
+    default: HashMap<u8, IconImage>,
+    light: HashMap<u8, IconImage>,
+    dark: HashMap<u8, IconImage>,
+}
+
+impl CustomIconPack {
+    fn load() -> Self {
+        let icon_dir = match std::env::current_exe() {
+            Ok(exe_path) => exe_path.with_file_name("assets"),
+            Err(e) => {
+                warn!("Failed to locate the custom icon pack - {e}");
+                return Self::default();
+            }
+        };
+
+        let pack = CustomIconPack {
+            default: load_icon_dir(&icon_dir),
+            light: load_icon_dir(&icon_dir.join("light")),
+            dark: load_icon_dir(&icon_dir.join("dark")),
+        };
+        info!(
+            "Loaded custom icon pack from {icon_dir:?}: {} default, {} light, {} dark",
+            pack.default.len(),
+            pack.light.len(),
+            pack.dark.len()
+        );
+        pack
+    }
+
+    /// 优先使用不区分主题的图标
+    fn get(&self, battery_level: u8, theme: SystemTheme) -> Option<&IconImage> {
+        self.default.get(&battery_level).or_else(|| match theme {
+            SystemTheme::Light => self.light.get(&battery_level),
+            SystemTheme::Dark => self.dark.get(&battery_level),
+        })
+    }
+}
+
+fn load_icon_dir(dir: &Path) -> HashMap<u8, IconImage> {
+    (0..=100)
+        .filter_map(|battery_level: u8| {
+            let path = dir.join(format!("{battery_level}.png"));
+            if !path.is_file() {
+                return None;
+            }
+            IconImage::read(&path)
+                .inspect_err(|e| warn!("Skipped custom icon - {e}"))
+                .ok()
+                .map(|image| (battery_level, image))
+        })
+        .collect()
+}
+
+/// 字体图标的绘制参数
+struct FontIconStyle {
+    font_name: String,
+    /// 已按配置文件目录解析
+    font_file: Option<PathBuf>,
     font_color: Option<String>,
     font_size: Option<u8>,
-    should_icon_connect_color: Option<bool>,
+    percent_sign: bool,
+    layout: FontIconLayout,
+    bold: bool,
+    italic: bool,
+}
+
+impl FontIconStyle {
+    fn new(config: &Config, tray_icon_source: TrayIconSource) -> Self {
+        match tray_icon_source {
+            TrayIconSource::BatteryFont {
+                font_name,
+                font_file,
+                font_color,
+                font_size,
+                percent_sign,
+                layout,
+                bold,
+                italic,
+                ..
+            }
+            | TrayIconSource::LowestBattery {
+                font_name,
+                font_file,
+                font_color,
+                font_size,
+                percent_sign,
+                layout,
+                bold,
+                italic,
+                ..
+            } => FontIconStyle {
+                font_name,
+                font_file: font_file.map(|font_file| config.resolve_path(&font_file)),
+                font_color,
+                font_size,
+                percent_sign,
+                layout,
+                bold,
+                italic,
+            },
+            TrayIconSource::App | TrayIconSource::BatteryCustom { .. } => FontIconStyle {
+                font_name: "Arial".to_owned(),
+                font_file: None,
+                font_color: None,
+                font_size: None,
+                percent_sign: false,
+                layout: FontIconLayout::Single,
+                bold: false,
+                italic: false,
+            },
+        }
+    }
+}
+
+/// 绘制文本时的字体、颜色及字重
+struct TextStyle {
+    font_family: FontFamily,
+    font_color: String,
+    bold: bool,
+    italic: bool,
+}
+
+fn get_icon_from_font(
+    config: &Config,
+    info: &BluetoothInfo,
+    style: &FontIconStyle,
 ) -> Result<Icon> {
+    let should_icon_connect_color = style
+        .font_color
+        .as_ref()
+        .is_some_and(|c| c.eq("ConnectColor"))
+        .then_some(info.status);
+
+    // 两行排版时在电量下方显示设备名称（别名）的首字母
+    let label = (style.layout == FontIconLayout::TwoLine)
+        .then(|| {
+            config
+                .get_device_aliases_name(&info.name)
+                .chars()
+                .find(|c| c.is_alphanumeric())
+                .map(|c| c.to_uppercase().to_string())
+        })
+        .flatten();
+
     let (icon_rgba, icon_width, icon_height) = render_battery_font_icon(
-        battery_level,
-        font_name,
-        font_color,
-        font_size,
+        info.battery,
+        label.as_deref(),
+        style,
         should_icon_connect_color,
     )?;
     Icon::from_rgba(icon_rgba, icon_width, icon_height)
@@ -126,16 +285,19 @@ fn get_icon_from_font(
 
 fn render_battery_font_icon(
     battery_level: u8,
-    font_name: &str,
-    font_color: Option<String>, // 格式：#123456、#123456FF
-    font_size: Option<u8>,
+    label: Option<&str>,
+    style: &FontIconStyle,
     should_icon_connect_color: Option<bool>,
 ) -> Result<(Vec<u8>, u32, u32)> {
-    let indicator = battery_level.to_string();
+    let indicator = if style.percent_sign {
+        format!("{battery_level}%")
+    } else {
+        battery_level.to_string()
+    };
 
     let width = 64;
     let height = 64;
-    let font_size = font_size.and_then(|s| s.ne(&64).then_some(s as f64));
+    let font_size = style.font_size.and_then(|s| s.ne(&64).then_some(s as f64));
     let font_color = if let Some(should) = should_icon_connect_color {
         if should {
             "#4fc478".to_owned()
@@ -143,9 +305,11 @@ fn render_battery_font_icon(
             "#fe6666ff".to_owned()
         }
     } else {
-        font_color
+        style
+            .font_color
+            .clone()
             .and_then(|c| c.ne("FollowSystemTheme").then_some(c))
-            .unwrap_or_else(|| SystemTheme::get().get_font_color())
+            .unwrap_or_else(|| SystemTheme::current().get_font_color())
     };
 
     let mut device = Device::new().map_err(|e| anyhow!("Failed to get Device - {e}"))?;
@@ -156,34 +320,74 @@ fn render_battery_font_icon(
 
     let mut piet = bitmap_target.render_context();
 
-    // Dynamically calculated font size
-    let mut layout;
     let text = piet.text();
 
-    let mut fs = match (font_size, battery_level) {
-        (_, 100) => 42.0,
-        (Some(size), _) => size,
-        (None, b) if b < 10 => 70.0,
-        (None, _) => 64.0,
+    // 字体文件只加载到本次绘制中，无需安装到系统
+    let font_family = match &style.font_file {
+        Some(font_file) => {
+            let font_data = std::fs::read(font_file)
+                .with_context(|| format!("Failed to read the font file {font_file:?}"))?;
+            text.load_font(&font_data)
+                .map_err(|e| anyhow!("Failed to load the font file {font_file:?} - {e}"))?
+        }
+        None => FontFamily::new_unchecked(&style.font_name),
+    };
+    let text_style = TextStyle {
+        font_family,
+        font_color,
+        bold: style.bold,
+        italic: style.italic,
     };
 
-    if battery_level == 100 || font_size.is_none() {
-        while {
-            layout = build_text_layout(text, &indicator, font_name, fs, &font_color)?;
-            !(layout.size().width > width as f64 || layout.size().height > height as f64)
-        } {
-            fs += 2.0;
+    let (width, height) = (width as f64, height as f64);
+    let full = Rect::new(0.0, 0.0, width, height);
+
+    match label {
+        // 上方约三分之二显示电量，下方显示标签
+        Some(label) => {
+            let (top, bottom) = (
+                Rect::new(0.0, 0.0, width, height * 0.66),
+                Rect::new(0.0, height * 0.66, width, height),
+            );
+            let battery_layout = fit_text_layout(text, &indicator, &text_style, font_size, top)?;
+            let label_layout = fit_text_layout(text, label, &text_style, None, bottom)?;
+            piet.draw_text(&battery_layout, centered_origin(&battery_layout, top));
+            piet.draw_text(&label_layout, centered_origin(&label_layout, bottom));
         }
-    } else {
-        layout = build_text_layout(text, &indicator, font_name, fs, &font_color)?;
-    }
+        None if style.percent_sign => {
+            let layout = fit_text_layout(text, &indicator, &text_style, font_size, full)?;
+            piet.draw_text(&layout, centered_origin(&layout, full));
+        }
+        None => {
+            // Dynamically calculated font size
+            let mut layout;
+            let mut fs = match (font_size, battery_level) {
+                (_, 100) => 42.0,
+                (Some(size), _) => size,
+                (None, b) if b < 10 => 70.0,
+                (None, _) => 64.0,
+            };
 
-    let (x, y) = (
-        (width as f64 - layout.size().width) / 2.0,
-        (height as f64 - layout.size().height) / 2.0,
-    );
+            if battery_level == 100 || font_size.is_none() {
+                while {
+                    layout = build_text_layout(text, &indicator, &text_style, fs)?;
+                    !(layout.size().width > width || layout.size().height > height)
+                } {
+                    fs += 2.0;
+                }
+            } else {
+                layout = build_text_layout(text, &indicator, &text_style, fs)?;
+            }
+
+            let (x, y) = (
+                (width - layout.size().width) / 2.0,
+                (height - layout.size().height) / 2.0,
+            );
+
+            piet.draw_text(&layout, (x, y));
+        }
+    }
 
-    piet.draw_text(&layout, (x, y));
     piet.finish().map_err(|e| anyhow!("{e}"))?;
     drop(piet);
 
@@ -196,16 +400,123 @@ fn render_battery_font_icon(
     ))
 }
 
+const LOW_BATTERY_COLOR: &str = "#E53935";
+
+/// 低电量时替换托盘图标的警示图标，`style` 不为 `Off`
+fn get_low_battery_icon(battery_level: u8, style: LowBatteryIconStyle) -> Result<Icon> {
+    let (width, height) = (64, 64);
+
+    let mut device = Device::new().map_err(|e| anyhow!("Failed to get Device - {e}"))?;
+    let mut bitmap_target = device
+        .bitmap_target(width, height, 1.0)
+        .map_err(|e| anyhow!("Failed to create a new bitmap target. - {e}"))?;
+    let mut piet = bitmap_target.render_context();
+
+    let (width, height) = (width as f64, height as f64);
+    let full = Rect::new(0.0, 0.0, width, height);
+    let alert_color = Color::from_hex_str(LOW_BATTERY_COLOR)?;
+    let text_style = |font_color: String| TextStyle {
+        font_family: FontFamily::new_unchecked("Arial"),
+        font_color,
+        bold: true,
+        italic: false,
+    };
+    let indicator = battery_level.to_string();
+
+    if style == LowBatteryIconStyle::Exclamation {
+        let layout = fit_text_layout(
+            piet.text(),
+            &indicator,
+            &text_style(SystemTheme::current().get_font_color()),
+            None,
+            full,
+        )?;
+        piet.draw_text(&layout, centered_origin(&layout, full));
+
+        let badge = Rect::new(width * 0.5, height * 0.5, width, height);
+        piet.fill(
+            Circle::new(badge.center(), badge.width() / 2.0),
+            &alert_color,
+        );
+        let mark_layout = fit_text_layout(
+            piet.text(),
+            "!",
+            &text_style("#FFFFFF".to_owned()),
+            None,
+            badge.inset(-4.0),
+        )?;
+        piet.draw_text(&mark_layout, centered_origin(&mark_layout, badge));
+    } else {
+        piet.fill(RoundedRect::from_rect(full, 12.0), &alert_color);
+        let layout = fit_text_layout(
+            piet.text(),
+            &indicator,
+            &text_style("#FFFFFF".to_owned()),
+            None,
+            full.inset(-6.0),
+        )?;
+        piet.draw_text(&layout, centered_origin(&layout, full));
+    }
+
+    piet.finish().map_err(|e| anyhow!("{e}"))?;
+    drop(piet);
+
+    let image_buf = bitmap_target.to_image_buf(ImageFormat::RgbaPremul).unwrap();
+    Icon::from_rgba(
+        image_buf.raw_pixels().to_vec(),
+        image_buf.width() as u32,
+        image_buf.height() as u32,
+    )
+    .map_err(|e| anyhow!("Failed to get Icon - {e}"))
+}
+
+/// 从指定字号（默认按区域高度估算）开始逐步缩小，直到文字的实际笔画能放进区域
+fn fit_text_layout(
+    text: &mut piet_common::D2DText,
+    indicator: &str,
+    style: &TextStyle,
+    font_size: Option<f64>,
+    area: Rect,
+) -> Result<piet_common::D2DTextLayout> {
+    let mut fs = font_size.unwrap_or(area.height() * 1.4);
+    loop {
+        let layout = build_text_layout(text, indicator, style, fs)?;
+        let bounds = layout.image_bounds();
+        if fs <= 8.0 || (bounds.width() <= area.width() && bounds.height() <= area.height()) {
+            return Ok(layout);
+        }
+        fs -= 2.0;
+    }
+}
+
+/// 使文字笔画在区域内居中的绘制原点
+fn centered_origin(layout: &piet_common::D2DTextLayout, area: Rect) -> Point {
+    let bounds = layout.image_bounds();
+    Point::new(
+        area.x0 + (area.width() - bounds.width()) / 2.0 - bounds.x0,
+        area.y0 + (area.height() - bounds.height()) / 2.0 - bounds.y0,
+    )
+}
+
 fn build_text_layout(
     text: &mut piet_common::D2DText,
     indicator: &str,
-    font_name: &str,
+    style: &TextStyle,
     font_size: f64,
-    font_color: &str,
 ) -> Result<piet_common::D2DTextLayout> {
     text.new_text_layout(indicator.to_string())
-        .font(FontFamily::new_unchecked(font_name), font_size)
-        .text_color(Color::from_hex_str(font_color)?)
+        .font(style.font_family.clone(), font_size)
+        .default_attribute(if style.bold {
+            FontWeight::BOLD
+        } else {
+            FontWeight::REGULAR
+        })
+        .default_attribute(if style.italic {
+            FontStyle::Italic
+        } else {
+            FontStyle::Regular
+        })
+        .text_color(Color::from_hex_str(&style.font_color)?)
         .build()
         .map_err(|e| anyhow!("Failed to build text layout - {e}"))
 }
@@ -217,6 +528,7 @@ pub enum SystemTheme {
 }
 
 impl SystemTheme {
+    /// 从注册表读取当前的系统主题
     pub fn get() -> Self {
         let personalize_reg_key = RegKey::predef(HKEY_CURRENT_USER)
             .open_subkey_with_flags(PERSONALIZE_REGISTRY_KEY, KEY_READ | KEY_WRITE)
@@ -232,6 +544,15 @@ impl SystemTheme {
         }
     }
 
+    /// 缓存的系统主题
+    pub fn current() -> Self {
+        *CURRENT_THEME.read().unwrap()
+    }
+
+    pub fn set_current(theme: Self) {
+        *CURRENT_THEME.write().unwrap() = theme;
+    }
+
     fn get_font_color(&self) -> String {
         match self {
             Self::Dark => "#FFFFFF".to_owned(),
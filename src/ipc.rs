@@ -0,0 +1,202 @@
+use crate::{
+    bluetooth::{events::DeviceEvent, info::BluetoothInfo},
+    cli::parse_address,
+    config::Config,
+};
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, atomic::Ordering};
+
+use anyhow::{Result, anyhow};
+use log::warn;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::windows::named_pipe::{NamedPipeServer, ServerOptions},
+    runtime::Handle,
+    sync::broadcast,
+};
+
+const PIPE_NAME: &str = r"\\.\pipe\BlueGauge";
+
+/// 每行一个 JSON 请求，例如 `{"command":"list"}`
+#[derive(Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum IpcRequest {
+    List,
+    Get { address: String },
+    Refresh,
+    Subscribe,
+}
+
+pub struct IpcServer {
+    events: broadcast::Sender<String>,
+}
+
+impl IpcServer {
+    /// 在 `\\.\pipe\BlueGauge` 上提供以换行分隔的 JSON 查询，订阅的客户端同时收到设备事件
+    pub fn start(
+        runtime: &Handle,
+        config: Arc<Config>,
+        bluetooth_info: Arc<Mutex<HashSet<BluetoothInfo>>>,
+        mut device_events: broadcast::Receiver<DeviceEvent>,
+    ) -> Result<Self> {
+        let (events, _) = broadcast::channel(16);
+
+        // 命名管道需在 Tokio 运行时上下文中创建
+        let first_pipe = {
+            let _guard = runtime.enter();
+            ServerOptions::new()
+                .first_pipe_instance(true)
+                .create(PIPE_NAME)
+                .map_err(|e| anyhow!("Failed to create named pipe {PIPE_NAME} - {e}"))?
+        };
+
+        let server_events = events.clone();
+        runtime.spawn(async move {
+            if let Err(e) = serve(first_pipe, config, bluetooth_info, server_events).await {
+                warn!("Named pipe server stopped: {e}");
+            }
+        });
+
+        let relay_events = events.clone();
+        runtime.spawn(async move {
+            loop {
+                match device_events.recv().await {
+                    Ok(event) => {
+                        if relay_events.receiver_count() == 0 {
+                            continue;
+                        }
+                        match serde_json::to_string(&event) {
+                            Ok(event) => {
+                                let _ = relay_events.send(event);
+                            }
+                            Err(e) => warn!("Failed to serialize device event: {e}"),
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        Ok(IpcServer { events })
+    }
+
+    /// 向所有订阅的客户端推送最新的设备信息
+    pub fn publish(&self, bluetooth_devices_info: &HashSet<BluetoothInfo>) {
+        if self.events.receiver_count() == 0 {
+            return;
+        }
+
+        let event = json!({ "event": "devices", "devices": bluetooth_devices_info });
+        let _ = self.events.send(event.to_string());
+    }
+}
+
+async fn serve(
+    first_pipe: NamedPipeServer,
+    config: Arc<Config>,
+    bluetooth_info: Arc<Mutex<HashSet<BluetoothInfo>>>,
+    events: broadcast::Sender<String>,
+) -> Result<()> {
+    let mut server = first_pipe;
+
+    loop {
+        server.connect().await?;
+        let client = server;
+        // 在处理当前客户端前创建下一个实例，保证始终有实例可供连接
+        server = ServerOptions::new().create(PIPE_NAME)?;
+
+        let config = Arc::clone(&config);
+        let bluetooth_info = Arc::clone(&bluetooth_info);
+        let events = events.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_client(client, config, bluetooth_info, events).await {
+                warn!("Named pipe client error: {e}");
+            }
+        });
+    }
+}
+
+async fn handle_client(
+    pipe: NamedPipeServer,
+    config: Arc<Config>,
+    bluetooth_info: Arc<Mutex<HashSet<BluetoothInfo>>>,
+    events: broadcast::Sender<String>,
+) -> Result<()> {
+    let (reader, mut writer) = tokio::io::split(pipe);
+    let mut lines = BufReader::new(reader).lines();
+    let mut subscription: Option<broadcast::Receiver<String>> = None;
+
+    loop {
+        let message = tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line? else {
+                    break;
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                match serde_json::from_str::<IpcRequest>(&line) {
+                    Ok(IpcRequest::Subscribe) => {
+                        subscription = Some(events.subscribe());
+                        json!({ "subscribed": true }).to_string()
+                    }
+                    Ok(request) => handle_request(request, &config, &bluetooth_info),
+                    Err(e) => json!({ "error": e.to_string() }).to_string(),
+                }
+            }
+            event = recv_event(&mut subscription) => match event {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        };
+
+        writer.write_all(message.as_bytes()).await?;
+        writer.write_all(b"\n").await?;
+        writer.flush().await?;
+    }
+
+    Ok(())
+}
+
+async fn recv_event(
+    subscription: &mut Option<broadcast::Receiver<String>>,
+) -> Result<String, broadcast::error::RecvError> {
+    match subscription {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+fn handle_request(
+    request: IpcRequest,
+    config: &Config,
+    bluetooth_info: &Mutex<HashSet<BluetoothInfo>>,
+) -> String {
+    match request {
+        IpcRequest::List => {
+            let bluetooth_info = bluetooth_info.lock().unwrap();
+            json!({ "devices": &*bluetooth_info }).to_string()
+        }
+        IpcRequest::Get { address } => {
+            let Ok(address) = parse_address(&address) else {
+                return json!({ "error": "invalid address" }).to_string();
+            };
+            let bluetooth_info = bluetooth_info.lock().unwrap();
+            match bluetooth_info.iter().find(|i| i.address == address) {
+                Some(info) => json!({ "device": info }).to_string(),
+                None => json!({ "error": "device not found" }).to_string(),
+            }
+        }
+        IpcRequest::Refresh => {
+            config.force_update.store(true, Ordering::SeqCst);
+            json!({ "refresh": "scheduled" }).to_string()
+        }
+        IpcRequest::Subscribe => json!({ "subscribed": true }).to_string(),
+    }
+}
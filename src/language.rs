@@ -1,3 +1,45 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use log::{info, warn};
+use windows::Win32::Globalization::{
+    GetSystemDefaultLCID, LCIDToLocaleName, LOCALE_ALLOW_NEUTRAL_NAMES, LocaleNameToLCID,
+};
+use windows::core::HSTRING;
+
+/// 随程序内置的翻译文件，`lang/` 文件夹中的同名文件优先
+const BUILTIN_TRANSLATIONS: &[(&str, &str)] = &[
+    ("de", include_str!("../lang/de.toml")),
+    ("es", include_str!("../lang/es.toml")),
+    ("fr", include_str!("../lang/fr.toml")),
+    ("ja", include_str!("../lang/ja.toml")),
+    ("ko", include_str!("../lang/ko.toml")),
+    ("ru", include_str!("../lang/ru.toml")),
+];
+
+static CURRENT: OnceLock<&'static Localization> = OnceLock::new();
+
+/// 定义 `Localization` 的字段，并生成按键名覆盖翻译的方法
+macro_rules! localization {
+    ($($key:ident),* $(,)?) => {
+        #[derive(Clone, Copy)]
+        pub struct Localization {
+            $(pub $key: &'static str,)*
+        }
+
+        impl Localization {
+            /// 未知的键返回 `false`
+            fn set(&mut self, key: &str, value: &'static str) -> bool {
+                match key {
+                    $(stringify!($key) => self.$key = value,)*
+                    _ => return false,
+                }
+                true
+            }
+        }
+    };
+}
+
 impl Localization {
     pub fn get(language: Language) -> &'static Self {
         match language {
@@ -7,42 +49,248 @@ impl Localization {
             Language::Chinese_PeoplesRepublicOfChina => &ZH_CN,
             Language::Chinese_Singapore => &ZH_CN,
             Language::Chinese_Taiwan => &ZH_HANT,
-            Language::French_France => &FR_FR,
-            Language::German_Germany => &DE_DE,
-            Language::Japanese_Japan => &JA_JP,
-            Language::Korean_Korea => &KO_KR,
-            Language::Russian_Russia => &RU_RU,
             _ => &EN_US,
         }
     }
+
+    /// 启动时根据配置选择语言，`locale` 为 "auto" 或空时跟随系统，例如 "es-ES"、"de"
+    pub fn init(locale: &str) {
+        let _ = CURRENT.set(Self::load(locale));
+    }
+
+    pub fn current() -> &'static Self {
+        CURRENT.get_or_init(|| Self::load("auto"))
+    }
+
+    /// 以编译在程序中的中文、英文或阿拉伯文为基础，再用 `lang/{locale}.toml` 或 `lang/{语言}.toml`
+    /// （程序目录下的文件优先，其次为内置翻译文件）覆盖，缺少的键保留基础语言
+    fn load(locale: &str) -> &'static Self {
+        let locale_name = match locale.trim() {
+            "" | "auto" => get_system_locale_name(),
+            locale => locale.to_owned(),
+        };
+
+        let (language, locale_name) = match Language::from_locale_name(&locale_name) {
+            Some(language) => (language, locale_name),
+            None => {
+                warn!("Unknown locale '{locale_name}', following the system language");
+                (Language::get_system_language(), get_system_locale_name())
+            }
+        };
+        let base = Self::get(language);
+
+        let Some((source, content)) = find_translation(&locale_name) else {
+            return base;
+        };
+
+        let table = match content.parse::<toml::Table>() {
+            Ok(table) => table,
+            Err(e) => {
+                warn!("Failed to parse translation {source} - {e}");
+                return base;
+            }
+        };
+
+        let mut localization = *base;
+        for (key, value) in table {
+            let Some(value) = value.as_str() else {
+                warn!("Translation {source}: '{key}' is not a string");
+                continue;
+            };
+            // 翻译在整个运行期间使用，只加载一次
+            let value: &'static str = Box::leak(value.to_owned().into_boxed_str());
+            if !localization.set(&key, value) {
+                warn!("Translation {source}: unknown key '{key}'");
+            }
+        }
+
+        info!("Loaded translation {source}");
+        Box::leak(Box::new(localization))
+    }
+}
+
+/// 依次查找 `{locale}` 与主语言（如 "es-MX" → "es"）：先找程序目录下的 `lang/`，再找内置翻译
+fn find_translation(locale_name: &str) -> Option<(String, String)> {
+    let primary_language = locale_name.split(['-', '_']).next().unwrap_or(locale_name);
+    let names = [locale_name, primary_language];
+
+    let lang_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe_path| exe_path.parent().map(|dir| dir.join("lang")));
+    if let Some(lang_dir) = lang_dir {
+        for name in names {
+            let path: PathBuf = lang_dir.join(format!("{name}.toml"));
+            if let Ok(content) = std::fs::read_to_string(&path) {
+                return Some((path.display().to_string(), content));
+            }
+        }
+    }
+
+    names.iter().find_map(|name| {
+        BUILTIN_TRANSLATIONS
+            .iter()
+            .find(|(builtin, _)| builtin.eq_ignore_ascii_case(name))
+            .map(|(builtin, content)| (format!("<builtin {builtin}>"), content.to_string()))
+    })
 }
 
-pub struct Localization {
-    pub quit: &'static str,
-    pub about: &'static str,
-    pub force_update: &'static str,
-    pub startup: &'static str,
-    pub open_config: &'static str,
-    pub show_disconnected: &'static str,
-    pub truncate_name: &'static str,
-    pub prefix_battery: &'static str,
-    pub update_interval: &'static str,
-    pub low_battery: &'static str,
-    pub mute: &'static str,
-    pub reconnection: &'static str,
-    pub disconnection: &'static str,
-    pub added: &'static str,
-    pub removed: &'static str,
-    pub settings: &'static str,
-    pub notify_options: &'static str,
-    pub tray_config: &'static str,
-    pub bluetooth_battery_below: &'static str,
-    pub device_name: &'static str,
-    pub bluetooth_device_reconnected: &'static str,
-    pub new_bluetooth_device_add: &'static str,
-    pub old_bluetooth_device_removed: &'static str,
-    pub bluetooth_device_disconnected: &'static str,
-    pub set_icon_connect_color: &'static str,
+fn get_system_locale_name() -> String {
+    // LOCALE_NAME_MAX_LENGTH
+    let mut buffer = [0u16; 85];
+    let len = unsafe {
+        LCIDToLocaleName(
+            GetSystemDefaultLCID(),
+            Some(&mut buffer),
+            LOCALE_ALLOW_NEUTRAL_NAMES,
+        )
+    };
+    // 返回的长度包含结尾的 0
+    String::from_utf16_lossy(&buffer[..(len.max(1) - 1) as usize])
+}
+
+localization! {
+    quit,
+    about,
+    force_update,
+    startup,
+    open_config,
+    show_disconnected,
+    truncate_name,
+    prefix_battery,
+    update_interval,
+    low_battery,
+    mute,
+    reconnection,
+    disconnection,
+    added,
+    removed,
+    settings,
+    notify_options,
+    tray_config,
+    bluetooth_battery_below,
+    device_name,
+    bluetooth_device_reconnected,
+    new_bluetooth_device_add,
+    old_bluetooth_device_removed,
+    bluetooth_device_disconnected,
+    set_icon_connect_color,
+    export_history,
+    all_devices,
+    history_exported,
+    bluetooth_radio,
+    bluetooth_off,
+    bluetooth_unavailable,
+    adaptive_update_interval,
+    pause_monitoring,
+    until_resumed,
+    resume_monitoring,
+    monitoring_paused,
+    refresh_device,
+    initializing,
+    new_version_available,
+    install_update,
+    open_release_page,
+    truncate_options,
+    truncate_length,
+    truncate_end,
+    truncate_middle,
+    strip_affixes,
+    battery_statistics,
+    charge_cycles,
+    average_drain,
+    lowest_battery,
+    tracked_since,
+    no_history_data,
+    connected,
+    disconnected,
+    show_last_updated,
+    just_now,
+    minutes_ago,
+    hours_ago,
+    days_ago,
+    battery_summary,
+    no_bluetooth_devices,
+    speech_low_battery,
+    speech_disconnected,
+    speech_reconnected,
+    speech_added,
+    speech_removed,
+    copy_status,
+    as_text,
+    connection_unstable,
+    milestones,
+    weekly_summary,
+    connected_time,
+    device_filter,
+    audio_devices,
+    input_devices,
+    battery,
+    use_as_tray_icon,
+    connect,
+    disconnect,
+    rename,
+    rename_prompt,
+    ok,
+    cancel,
+    hide,
+    hidden_devices,
+    details,
+    hide_device_list,
+    hide_tooltip,
+    profiles,
+    save_profile,
+    profile_name_prompt,
+    onboarding_title,
+    onboarding_intro,
+    onboarding_tray_icon,
+    onboarding_app_icon,
+    onboarding_low_battery,
+    skip,
+    charging,
+    left_bud,
+    right_bud,
+    charging_case,
+    devices_failed,
+    device_errors,
+    showing_last_known,
+    renamed,
+    bluetooth_device_renamed,
+    speech_renamed,
+    pin_device,
+    pinned_only,
+    rich_flyout,
+    battery_bar,
+    summary_header,
+    connected_count,
+    lowest_device,
+    battery_display,
+    battery_exact,
+    battery_round5,
+    battery_round10,
+    battery_verbal,
+    battery_high,
+    battery_medium,
+    battery_low,
+    battery_critical,
+    last_seen,
+    keep_connected,
+    reconnect_failed,
+    show_default_audio,
+    set_default_audio,
+    audio_profile,
+    audio_stereo,
+    audio_hands_free,
+    connection_interval,
+    connection_latency,
+    supervision_timeout,
+    diagnostics_report,
+    diagnostics_report_created,
+    telemetry_enabled,
+    watcher_restarted,
+    reload_config,
+    config_reloaded,
+    config_unchanged,
+    restart_required,
 }
 
 const ZH_CN: Localization = Localization {
@@ -75,6 +323,123 @@ const ZH_CN: Localization = Localization {
     bluetooth_device_reconnected: "蓝牙设备重新连接",
     old_bluetooth_device_removed: "蓝牙设备被移除",
     bluetooth_device_disconnected: "蓝牙设备断开连接",
+    export_history: "导出电量历史…",
+    all_devices: "所有设备",
+    history_exported: "电量历史已导出",
+    bluetooth_radio: "蓝牙",
+    bluetooth_off: "蓝牙已关闭",
+    bluetooth_unavailable: "未找到可用的蓝牙适配器",
+    adaptive_update_interval: "智能调整",
+    pause_monitoring: "暂停监控",
+    until_resumed: "直到手动恢复",
+    resume_monitoring: "恢复监控",
+    monitoring_paused: "监控已暂停",
+    refresh_device: "更新单个设备",
+    initializing: "正在初始化…",
+    new_version_available: "新版本可用",
+    install_update: "下载并安装",
+    open_release_page: "打开发布页面",
+    truncate_options: "名称裁剪方式",
+    truncate_length: "最大长度",
+    truncate_end: "末尾省略",
+    truncate_middle: "中间省略",
+    strip_affixes: "去除 LE-、(2) 等前后缀",
+    battery_statistics: "电池统计",
+    charge_cycles: "充电循环",
+    average_drain: "平均耗电",
+    lowest_battery: "最低电量",
+    tracked_since: "记录起始",
+    no_history_data: "暂无该设备的电量历史",
+    connected: "已连接",
+    disconnected: "未连接",
+    show_last_updated: "显示更新时间",
+    just_now: "刚刚",
+    minutes_ago: "{n} 分钟前",
+    hours_ago: "{n} 小时前",
+    days_ago: "{n} 天前",
+    battery_summary: "电量概览",
+    no_bluetooth_devices: "没有可显示的蓝牙设备",
+    speech_low_battery: "{name} 电量剩余百分之 {battery}",
+    speech_disconnected: "{name} 已断开连接",
+    speech_reconnected: "{name} 已重新连接",
+    speech_added: "已添加 {name}",
+    speech_removed: "已移除 {name}",
+    copy_status: "复制状态",
+    as_text: "文本",
+    connection_unstable: "蓝牙设备连接不稳定",
+    milestones: "电量阶梯提醒",
+    weekly_summary: "每周电量汇总",
+    connected_time: "连接时长",
+    device_filter: "显示设备",
+    audio_devices: "仅音频设备",
+    input_devices: "仅输入设备",
+    battery: "电量",
+    use_as_tray_icon: "用作托盘图标",
+    connect: "连接",
+    disconnect: "断开连接",
+    rename: "重命名...",
+    rename_prompt: "显示名称（留空则恢复设备名称）：",
+    ok: "确定",
+    cancel: "取消",
+    hide: "隐藏",
+    hidden_devices: "已隐藏的设备",
+    details: "详细信息...",
+    hide_device_list: "隐藏设备列表",
+    hide_tooltip: "隐藏托盘提示",
+    profiles: "配置方案",
+    save_profile: "将当前设置保存为方案...",
+    profile_name_prompt: "方案名称（同名方案将被覆盖）：",
+    onboarding_title: "欢迎使用 BlueGauge",
+    onboarding_intro: "选择托盘图标显示的内容及低电量提醒，之后可随时在托盘菜单中修改。",
+    onboarding_tray_icon: "托盘图标：",
+    onboarding_app_icon: "BlueGauge 图标",
+    onboarding_low_battery: "低电量提醒：",
+    skip: "跳过",
+    charging: "充电中",
+    left_bud: "左",
+    right_bud: "右",
+    charging_case: "充电盒",
+    devices_failed: "{n} 个设备读取失败（点击查看详情）",
+    device_errors: "读取失败的设备",
+    showing_last_known: "上次记录的信息，正在更新…",
+    renamed: "设备改名时通知",
+    bluetooth_device_renamed: "蓝牙设备已改名",
+    speech_renamed: "设备已改名为 {name}",
+    pin_device: "固定",
+    pinned_only: "仅通知固定的设备",
+    rich_flyout: "悬停时显示浮窗",
+    battery_bar: "显示电量条",
+    summary_header: "首行显示概览",
+    connected_count: "{n} 个已连接",
+    lowest_device: "最低：{name} {battery}",
+    battery_display: "电量显示方式",
+    battery_exact: "精确",
+    battery_round5: "取整到 5%",
+    battery_round10: "取整到 10%",
+    battery_verbal: "高 / 中 / 低 / 严重",
+    battery_high: "高",
+    battery_medium: "中",
+    battery_low: "低",
+    battery_critical: "严重",
+    last_seen: "上次 {battery} · {age}",
+    keep_connected: "保持连接",
+    reconnect_failed: "自动重新连接失败",
+    show_default_audio: "标记默认播放设备",
+    set_default_audio: "设为默认播放设备",
+    audio_profile: "音频模式",
+    audio_stereo: "立体声（A2DP）",
+    audio_hands_free: "免提（HFP）",
+    connection_interval: "连接间隔",
+    connection_latency: "延迟",
+    supervision_timeout: "超时",
+    diagnostics_report: "生成诊断报告",
+    diagnostics_report_created: "诊断报告已生成，可附加到问题报告中",
+    telemetry_enabled: "📊 正在分享匿名的兼容性数据",
+    watcher_restarted: "设备监听已失效并重新启动：{names}",
+    reload_config: "重新加载配置",
+    config_reloaded: "配置已重新加载",
+    config_unchanged: "配置没有变化",
+    restart_required: "需重启后生效",
 };
 
 const ZH_HANT: Localization = Localization {
@@ -103,6 +468,123 @@ const ZH_HANT: Localization = Localization {
     new_bluetooth_device_add: "新藍牙設備連接",
     old_bluetooth_device_removed: "藍牙設備被移除",
     bluetooth_device_disconnected: "藍牙設備斷開連接",
+    export_history: "匯出電量歷史…",
+    all_devices: "所有設備",
+    history_exported: "電量歷史已匯出",
+    bluetooth_radio: "藍牙",
+    bluetooth_off: "藍牙已關閉",
+    bluetooth_unavailable: "找不到可用的藍牙介面卡",
+    adaptive_update_interval: "智慧調整",
+    pause_monitoring: "暫停監控",
+    until_resumed: "直到手動恢復",
+    resume_monitoring: "恢復監控",
+    monitoring_paused: "監控已暫停",
+    refresh_device: "更新單一裝置",
+    initializing: "正在初始化…",
+    new_version_available: "有新版本可用",
+    install_update: "下載並安裝",
+    open_release_page: "開啟發行頁面",
+    truncate_options: "名稱裁剪方式",
+    truncate_length: "最大長度",
+    truncate_end: "末尾省略",
+    truncate_middle: "中間省略",
+    strip_affixes: "去除 LE-、(2) 等前後綴",
+    battery_statistics: "電池統計",
+    charge_cycles: "充電循環",
+    average_drain: "平均耗電",
+    lowest_battery: "最低電量",
+    tracked_since: "記錄起始",
+    no_history_data: "暫無該裝置的電量歷史",
+    connected: "已連線",
+    disconnected: "未連線",
+    show_last_updated: "顯示更新時間",
+    just_now: "剛剛",
+    minutes_ago: "{n} 分鐘前",
+    hours_ago: "{n} 小時前",
+    days_ago: "{n} 天前",
+    battery_summary: "電量概覽",
+    no_bluetooth_devices: "沒有可顯示的藍牙裝置",
+    speech_low_battery: "{name} 電量剩餘百分之 {battery}",
+    speech_disconnected: "{name} 已斷開連接",
+    speech_reconnected: "{name} 已重新連接",
+    speech_added: "已新增 {name}",
+    speech_removed: "已移除 {name}",
+    copy_status: "複製狀態",
+    as_text: "文字",
+    connection_unstable: "藍牙裝置連接不穩定",
+    milestones: "電量階梯提醒",
+    weekly_summary: "每週電量彙總",
+    connected_time: "連接時長",
+    device_filter: "顯示設備",
+    audio_devices: "僅音訊設備",
+    input_devices: "僅輸入設備",
+    battery: "電量",
+    use_as_tray_icon: "用作系統匣圖示",
+    connect: "連線",
+    disconnect: "中斷連線",
+    rename: "重新命名...",
+    rename_prompt: "顯示名稱（留空則恢復裝置名稱）：",
+    ok: "確定",
+    cancel: "取消",
+    hide: "隱藏",
+    hidden_devices: "已隱藏的裝置",
+    details: "詳細資訊...",
+    hide_device_list: "隱藏裝置清單",
+    hide_tooltip: "隱藏系統匣提示",
+    profiles: "設定檔",
+    save_profile: "將目前設定儲存為設定檔...",
+    profile_name_prompt: "設定檔名稱（同名設定檔將被覆寫）：",
+    onboarding_title: "歡迎使用 BlueGauge",
+    onboarding_intro: "選擇系統匣圖示顯示的內容及低電量提醒，之後可隨時在系統匣選單中修改。",
+    onboarding_tray_icon: "系統匣圖示：",
+    onboarding_app_icon: "BlueGauge 圖示",
+    onboarding_low_battery: "低電量提醒：",
+    skip: "略過",
+    charging: "充電中",
+    left_bud: "左",
+    right_bud: "右",
+    charging_case: "充電盒",
+    devices_failed: "{n} 個裝置讀取失敗（點擊查看詳情）",
+    device_errors: "讀取失敗的裝置",
+    showing_last_known: "上次記錄的資訊，正在更新…",
+    renamed: "設備改名時通知",
+    bluetooth_device_renamed: "藍牙設備已改名",
+    speech_renamed: "設備已改名為 {name}",
+    pin_device: "釘選",
+    pinned_only: "僅通知釘選的設備",
+    rich_flyout: "懸停時顯示浮動視窗",
+    battery_bar: "顯示電量條",
+    summary_header: "首行顯示概覽",
+    connected_count: "{n} 個已連線",
+    lowest_device: "最低：{name} {battery}",
+    battery_display: "電量顯示方式",
+    battery_exact: "精確",
+    battery_round5: "取整到 5%",
+    battery_round10: "取整到 10%",
+    battery_verbal: "高 / 中 / 低 / 嚴重",
+    battery_high: "高",
+    battery_medium: "中",
+    battery_low: "低",
+    battery_critical: "嚴重",
+    last_seen: "上次 {battery} · {age}",
+    keep_connected: "保持連線",
+    reconnect_failed: "自動重新連線失敗",
+    show_default_audio: "標記預設播放裝置",
+    set_default_audio: "設為預設播放裝置",
+    audio_profile: "音訊模式",
+    audio_stereo: "立體聲（A2DP）",
+    audio_hands_free: "免持（HFP）",
+    connection_interval: "連線間隔",
+    connection_latency: "延遲",
+    supervision_timeout: "逾時",
+    diagnostics_report: "產生診斷報告",
+    diagnostics_report_created: "診斷報告已產生，可附加到問題回報中",
+    telemetry_enabled: "📊 正在分享匿名的相容性資料",
+    watcher_restarted: "裝置監聽已失效並重新啟動：{names}",
+    reload_config: "重新載入設定",
+    config_reloaded: "設定已重新載入",
+    config_unchanged: "設定沒有變更",
+    restart_required: "需重新啟動後生效",
 };
 
 const EN_US: Localization = Localization {
@@ -131,118 +613,123 @@ const EN_US: Localization = Localization {
     new_bluetooth_device_add: "New Bluetooth Device Connected",
     old_bluetooth_device_removed: "Bluetooth Device Removed",
     bluetooth_device_disconnected: "Bluetooth Device show_disconnected",
-};
-
-const JA_JP: Localization = Localization {
-    quit: "終了",
-    about: "約",
-    force_update: "情報を更新",
-    startup: "スタートアップで起動",
-    open_config: "設定ファイルを開く",
-    show_disconnected: "切断されたデバイスを表示",
-    truncate_name: "デバイス名を切り捨てる",
-    prefix_battery: "電池前に名前",
-    update_interval: "更新間隔",
-    set_icon_connect_color: "アイコンを接続状態の配色に設定する",
-    low_battery: "低バッテリー時に通知",
-    mute: "通知をミュート",
-    reconnection: "再接続時に通知",
-    disconnection: "切断時に通知",
-    added: "デバイス追加時に通知",
-    removed: "デバイス削除時に通知",
-    settings: "設定",
-    tray_config: "トレイオプション",
-    notify_options: "通知オプション",
-    bluetooth_battery_below: "Bluetoothバッテリーが以下",
-    device_name: "デバイス名",
-    bluetooth_device_reconnected: "Bluetoothデバイスが再接続されました",
-    new_bluetooth_device_add: "新しいBluetoothデバイスが接続されました",
-    old_bluetooth_device_removed: "Bluetoothデバイスが削除されました",
-    bluetooth_device_disconnected: "Bluetoothデバイスが切断されました",
-};
-
-const KO_KR: Localization = Localization {
-    quit: "종료",
-    about: "정보",
-    force_update: "정보 업데이트",
-    startup: "시작 시 실행",
-    open_config: "구성 열기",
-    show_disconnected: "연결 끊긴 장치 표시",
-    truncate_name: "장치 이름 자르기",
-    prefix_battery: "이름 앞에 배터리",
-    update_interval: "업데이트 간격",
-    set_icon_connect_color: "아이콘을 연결 색상으로 설정",
-    low_battery: "배터리 부족 시 알림",
-    mute: "알림 음소거",
-    reconnection: "재연결 시 알림",
-    disconnection: "연결 끊김 시 알림",
-    added: "장치 추가 시 알림",
-    removed: "장치 제거 시 알림",
-    settings: "설정",
-    tray_config: "트레이 옵션",
-    notify_options: "알림 옵션",
-    bluetooth_battery_below: "Bluetooth 배터리 이하",
-    device_name: "장치 이름",
-    bluetooth_device_reconnected: "Bluetooth 장치가 다시 연결됨",
-    new_bluetooth_device_add: "새 Bluetooth 장치가 연결됨",
-    old_bluetooth_device_removed: "Bluetooth 장치가 제거됨",
-    bluetooth_device_disconnected: "Bluetooth 장치가 연결 끊김",
-};
-
-const DE_DE: Localization = Localization {
-    quit: "Beenden",
-    about: "Über",
-    force_update: "Informationen aktualisieren",
-    startup: "Beim Start ausführen",
-    open_config: "Konfiguration öffnen",
-    show_disconnected: "Getrennte Geräte anzeigen",
-    truncate_name: "Gerätenamen kürzen",
-    prefix_battery: "Batterie vor Name",
-    update_interval: "Aktualisierungsintervall",
-    set_icon_connect_color: "Symbolfarbe auf „Verbunden“ setzen",
-    low_battery: "Bei niedrigem Batteriestand benachrichtigen",
-    mute: "Benachrichtigungen stummschalten",
-    reconnection: "Bei Wiederverbindung benachrichtigen",
-    disconnection: "Bei Trennung benachrichtigen",
-    added: "Bei hinzugefügten Geräten benachrichtigen",
-    removed: "Bei entfernten Geräten benachrichtigen",
-    settings: "Einstellungen",
-    tray_config: "Tray-Optionen",
-    notify_options: "Benachrichtigungsoptionen",
-    bluetooth_battery_below: "Bluetooth-Batterie unter",
-    device_name: "Gerätename",
-    bluetooth_device_reconnected: "Bluetooth-Gerät wieder verbunden",
-    new_bluetooth_device_add: "Neues Bluetooth-Gerät verbunden",
-    old_bluetooth_device_removed: "Bluetooth-Gerät entfernt",
-    bluetooth_device_disconnected: "Bluetooth-Gerät getrennt",
-};
-
-const RU_RU: Localization = Localization {
-    quit: "Выход",
-    about: "О программе",
-    force_update: "Обновить информацию",
-    startup: "Запуск при старте",
-    open_config: "Открыть конфигурацию",
-    show_disconnected: "Показать отключенные устройства",
-    truncate_name: "Обрезать имя устройства",
-    prefix_battery: "Батарея перед именем",
-    update_interval: "Интервал обновления",
-    set_icon_connect_color: "Установить цвет значка как при подключении",
-    low_battery: "Уведомлять при низком заряде батареи",
-    mute: "Отключить уведомления",
-    reconnection: "Уведомлять при повторном подключении",
-    disconnection: "Уведомлять при отключении",
-    added: "Уведомлять о добавленных устройствах",
-    removed: "Уведомлять об удаленных устройствах",
-    settings: "Настройки",
-    tray_config: "Параметры трея",
-    notify_options: "Параметры уведомлений",
-    bluetooth_battery_below: "Bluetooth батарея ниже",
-    device_name: "Имя устройства",
-    bluetooth_device_reconnected: "Bluetooth устройство переподключено",
-    new_bluetooth_device_add: "Новое Bluetooth устройство подключено",
-    old_bluetooth_device_removed: "Bluetooth устройство удалено",
-    bluetooth_device_disconnected: "Bluetooth устройство отключено",
+    export_history: "Export History…",
+    all_devices: "All Devices",
+    history_exported: "Battery history exported",
+    bluetooth_radio: "Bluetooth",
+    bluetooth_off: "Bluetooth is off",
+    bluetooth_unavailable: "No Bluetooth adapter available",
+    adaptive_update_interval: "Adaptive",
+    pause_monitoring: "Pause monitoring",
+    until_resumed: "Until resumed",
+    resume_monitoring: "Resume monitoring",
+    monitoring_paused: "Monitoring paused",
+    refresh_device: "Refresh Device",
+    initializing: "Initializing…",
+    new_version_available: "New Version Available",
+    install_update: "Download and Install",
+    open_release_page: "Open Release Page",
+    truncate_options: "Name Truncation",
+    truncate_length: "Max Length",
+    truncate_end: "Ellipsis at End",
+    truncate_middle: "Ellipsis in Middle",
+    strip_affixes: "Strip Prefixes like LE- / (2)",
+    battery_statistics: "Battery Statistics",
+    charge_cycles: "Charge cycles",
+    average_drain: "Average drain",
+    lowest_battery: "Lowest level",
+    tracked_since: "Tracked since",
+    no_history_data: "No battery history for this device yet",
+    connected: "Connected",
+    disconnected: "Disconnected",
+    show_last_updated: "Show Last Updated",
+    just_now: "just now",
+    minutes_ago: "{n} min ago",
+    hours_ago: "{n} h ago",
+    days_ago: "{n} d ago",
+    battery_summary: "Battery Summary",
+    no_bluetooth_devices: "No Bluetooth devices to show",
+    speech_low_battery: "{name} at {battery} percent",
+    speech_disconnected: "{name} disconnected",
+    speech_reconnected: "{name} reconnected",
+    speech_added: "{name} added",
+    speech_removed: "{name} removed",
+    copy_status: "Copy Status",
+    as_text: "Text",
+    connection_unstable: "Bluetooth Connection Unstable",
+    milestones: "Battery Milestones",
+    weekly_summary: "Weekly Battery Summary",
+    connected_time: "Connected",
+    device_filter: "Show Devices",
+    audio_devices: "Audio Devices Only",
+    input_devices: "Input Devices Only",
+    battery: "Battery",
+    use_as_tray_icon: "Use as Tray Icon",
+    connect: "Connect",
+    disconnect: "Disconnect",
+    rename: "Rename...",
+    rename_prompt: "Display name (leave empty to use the device name):",
+    ok: "OK",
+    cancel: "Cancel",
+    hide: "Hide",
+    hidden_devices: "Hidden Devices",
+    details: "Details...",
+    hide_device_list: "Hide Device List",
+    hide_tooltip: "Hide Tooltip",
+    profiles: "Profiles",
+    save_profile: "Save Current Settings as Profile...",
+    profile_name_prompt: "Profile name (an existing profile with the same name is overwritten):",
+    onboarding_title: "Welcome to BlueGauge",
+    onboarding_intro: "Choose what the tray icon shows and when to warn about low battery. You can change these later from the tray menu.",
+    onboarding_tray_icon: "Tray icon:",
+    onboarding_app_icon: "BlueGauge icon",
+    onboarding_low_battery: "Low battery alert:",
+    skip: "Skip",
+    charging: "Charging",
+    left_bud: "L",
+    right_bud: "R",
+    charging_case: "Case",
+    devices_failed: "{n} device(s) failed to read (click for details)",
+    device_errors: "Devices That Failed to Read",
+    showing_last_known: "Last known info, updating…",
+    renamed: "Notify on Renamed Devices",
+    bluetooth_device_renamed: "Bluetooth Device Renamed",
+    speech_renamed: "Device renamed to {name}",
+    pin_device: "Pin",
+    pinned_only: "Notify Only for Pinned Devices",
+    rich_flyout: "Show Flyout on Hover",
+    battery_bar: "Show Battery Bar",
+    summary_header: "Show Summary Line",
+    connected_count: "{n} connected",
+    lowest_device: "lowest: {name} {battery}",
+    battery_display: "Battery Display",
+    battery_exact: "Exact",
+    battery_round5: "Round to 5%",
+    battery_round10: "Round to 10%",
+    battery_verbal: "High / Medium / Low / Critical",
+    battery_high: "High",
+    battery_medium: "Medium",
+    battery_low: "Low",
+    battery_critical: "Critical",
+    last_seen: "last seen {battery} · {age}",
+    keep_connected: "Keep connected",
+    reconnect_failed: "Failed to reconnect automatically",
+    show_default_audio: "Mark Default Audio Device",
+    set_default_audio: "Set as Default Audio",
+    audio_profile: "Audio mode",
+    audio_stereo: "Stereo (A2DP)",
+    audio_hands_free: "Hands-free (HFP)",
+    connection_interval: "Connection interval",
+    connection_latency: "Latency",
+    supervision_timeout: "Timeout",
+    diagnostics_report: "Create Diagnostics Report",
+    diagnostics_report_created: "Diagnostics report created, attach it to your issue report",
+    telemetry_enabled: "📊 Sharing anonymous compatibility data",
+    watcher_restarted: "Device watch stopped working and was restarted: {names}",
+    reload_config: "Reload Config",
+    config_reloaded: "Config reloaded",
+    config_unchanged: "Config has not changed",
+    restart_required: "Takes effect after restart",
 };
 
 const AR_SA: Localization = Localization {
@@ -271,44 +758,151 @@ const AR_SA: Localization = Localization {
     new_bluetooth_device_add: "تم توصيل جهاز Bluetooth جديد",
     old_bluetooth_device_removed: "تمت إزالة جهاز Bluetooth",
     bluetooth_device_disconnected: "تم قطع اتصال جهاز Bluetooth",
-};
-
-const FR_FR: Localization = Localization {
-    quit: "Quitter",
-    about: "À propos",
-    force_update: "Mettre à jour les infos",
-    startup: "Lancer au démarrage",
-    open_config: "Ouvrir la configurationة",
-    show_disconnected: "Afficher les appareils déconnectés",
-    truncate_name: "Tronquer le nom de l'appareil",
-    prefix_battery: "Batterie avant nom",
-    update_interval: "Intervalle de mise à jour",
-    set_icon_connect_color: "Définir l’icône avec la couleur de connexion",
-    low_battery: "Notifier en cas de batterie faible",
-    mute: "Muet les notify_options",
-    reconnection: "Notifier en cas de reconnexion",
-    disconnection: "Notifier en cas de déconnexion",
-    added: "Notifier en cas d'ajout d'appareils",
-    removed: "Notifier en cas de suppression d'appareils",
-    settings: "Paramètres",
-    tray_config: "Options de la barre d’état",
-    notify_options: "Options de notification",
-    bluetooth_battery_below: "Bluetooth batterie en dessous de",
-    device_name: "Nom de l'appareil",
-    bluetooth_device_reconnected: "Appareil Bluetooth reconnecté",
-    new_bluetooth_device_add: "Nouvel appareil Bluetooth connecté",
-    old_bluetooth_device_removed: "Appareil Bluetooth supprimé",
-    bluetooth_device_disconnected: "Appareil Bluetooth déconnecté",
+    export_history: "تصدير السجل…",
+    all_devices: "جميع الأجهزة",
+    history_exported: "تم تصدير سجل البطارية",
+    bluetooth_radio: "البلوتوث",
+    bluetooth_off: "البلوتوث متوقف",
+    bluetooth_unavailable: "لا يوجد محول بلوتوث متاح",
+    adaptive_update_interval: "تكيفي",
+    pause_monitoring: "إيقاف المراقبة مؤقتًا",
+    until_resumed: "حتى الاستئناف",
+    resume_monitoring: "استئناف المراقبة",
+    monitoring_paused: "المراقبة متوقفة مؤقتًا",
+    refresh_device: "تحديث جهاز",
+    initializing: "جارٍ التهيئة…",
+    new_version_available: "يتوفر إصدار جديد",
+    install_update: "تنزيل وتثبيت",
+    open_release_page: "فتح صفحة الإصدار",
+    truncate_options: "اقتطاع الاسم",
+    truncate_length: "الحد الأقصى للطول",
+    truncate_end: "حذف النهاية",
+    truncate_middle: "حذف المنتصف",
+    strip_affixes: "إزالة البادئات مثل LE- و(2)",
+    battery_statistics: "إحصائيات البطارية",
+    charge_cycles: "دورات الشحن",
+    average_drain: "متوسط الاستهلاك",
+    lowest_battery: "أدنى مستوى",
+    tracked_since: "مُتتبَّع منذ",
+    no_history_data: "لا يوجد سجل بطارية لهذا الجهاز بعد",
+    connected: "متصل",
+    disconnected: "غير متصل",
+    show_last_updated: "إظهار وقت آخر تحديث",
+    just_now: "الآن",
+    minutes_ago: "منذ {n} دقيقة",
+    hours_ago: "منذ {n} ساعة",
+    days_ago: "منذ {n} يوم",
+    battery_summary: "ملخص البطارية",
+    no_bluetooth_devices: "لا توجد أجهزة بلوتوث لعرضها",
+    speech_low_battery: "{name} عند {battery} بالمئة",
+    speech_disconnected: "تم قطع اتصال {name}",
+    speech_reconnected: "تمت إعادة اتصال {name}",
+    speech_added: "تمت إضافة {name}",
+    speech_removed: "تمت إزالة {name}",
+    copy_status: "نسخ الحالة",
+    as_text: "نص",
+    connection_unstable: "اتصال البلوتوث غير مستقر",
+    milestones: "مراحل البطارية",
+    weekly_summary: "ملخص البطارية الأسبوعي",
+    connected_time: "مدة الاتصال",
+    device_filter: "الأجهزة المعروضة",
+    audio_devices: "أجهزة الصوت فقط",
+    input_devices: "أجهزة الإدخال فقط",
+    battery: "البطارية",
+    use_as_tray_icon: "استخدام كأيقونة علبة النظام",
+    connect: "اتصال",
+    disconnect: "قطع الاتصال",
+    rename: "إعادة التسمية...",
+    rename_prompt: "اسم العرض (اتركه فارغًا لاستخدام اسم الجهاز):",
+    ok: "موافق",
+    cancel: "إلغاء",
+    hide: "إخفاء",
+    hidden_devices: "الأجهزة المخفية",
+    details: "التفاصيل...",
+    hide_device_list: "إخفاء قائمة الأجهزة",
+    hide_tooltip: "إخفاء التلميح",
+    profiles: "ملفات التعريف",
+    save_profile: "حفظ الإعدادات الحالية كملف تعريف...",
+    profile_name_prompt: "اسم ملف التعريف (سيتم استبدال ملف التعريف الذي يحمل الاسم نفسه):",
+    onboarding_title: "مرحبًا بك في BlueGauge",
+    onboarding_intro: "اختر ما تعرضه أيقونة شريط المهام ومتى يتم التنبيه بانخفاض البطارية. يمكنك تغيير ذلك لاحقًا من قائمة شريط المهام.",
+    onboarding_tray_icon: "أيقونة شريط المهام:",
+    onboarding_app_icon: "أيقونة BlueGauge",
+    onboarding_low_battery: "تنبيه انخفاض البطارية:",
+    skip: "تخطي",
+    charging: "جارٍ الشحن",
+    left_bud: "يسار",
+    right_bud: "يمين",
+    charging_case: "العلبة",
+    devices_failed: "تعذرت قراءة {n} جهاز (انقر للتفاصيل)",
+    device_errors: "الأجهزة التي تعذرت قراءتها",
+    showing_last_known: "آخر معلومات معروفة، جارٍ التحديث…",
+    renamed: "إعلام عند إعادة تسمية الأجهزة",
+    bluetooth_device_renamed: "تمت إعادة تسمية جهاز Bluetooth",
+    speech_renamed: "تمت إعادة تسمية الجهاز إلى {name}",
+    pin_device: "تثبيت",
+    pinned_only: "الإعلام للأجهزة المثبتة فقط",
+    rich_flyout: "إظهار نافذة منبثقة عند التمرير",
+    battery_bar: "إظهار شريط البطارية",
+    summary_header: "إظهار سطر الملخص",
+    connected_count: "{n} متصل",
+    lowest_device: "الأدنى: {name} {battery}",
+    battery_display: "عرض البطارية",
+    battery_exact: "دقيق",
+    battery_round5: "التقريب إلى 5%",
+    battery_round10: "التقريب إلى 10%",
+    battery_verbal: "مرتفع / متوسط / منخفض / حرج",
+    battery_high: "مرتفع",
+    battery_medium: "متوسط",
+    battery_low: "منخفض",
+    battery_critical: "حرج",
+    last_seen: "آخر ظهور {battery} · {age}",
+    keep_connected: "الإبقاء على الاتصال",
+    reconnect_failed: "فشلت إعادة الاتصال التلقائية",
+    show_default_audio: "تمييز جهاز الصوت الافتراضي",
+    set_default_audio: "تعيين كجهاز الصوت الافتراضي",
+    audio_profile: "وضع الصوت",
+    audio_stereo: "ستيريو (A2DP)",
+    audio_hands_free: "حر اليدين (HFP)",
+    connection_interval: "فاصل الاتصال",
+    connection_latency: "زمن الانتقال",
+    supervision_timeout: "المهلة",
+    diagnostics_report: "إنشاء تقرير تشخيصي",
+    diagnostics_report_created: "تم إنشاء التقرير التشخيصي، أرفقه بتقرير المشكلة",
+    telemetry_enabled: "📊 تتم مشاركة بيانات توافق مجهولة",
+    watcher_restarted: "توقفت مراقبة الجهاز وأُعيد تشغيلها: {names}",
+    reload_config: "إعادة تحميل الإعدادات",
+    config_reloaded: "تمت إعادة تحميل الإعدادات",
+    config_unchanged: "لم تتغير الإعدادات",
+    restart_required: "يسري بعد إعادة التشغيل",
 };
 
 impl Language {
     pub fn get_system_language() -> Language {
-        let sys_lcid = unsafe { windows::Win32::Globalization::GetSystemDefaultLCID() };
+        let sys_lcid = unsafe { GetSystemDefaultLCID() };
+
+        Self::from_lcid(sys_lcid).unwrap_or(Language::English_UnitedStates)
+    }
+
+    /// "zh-TW"、"de-DE" 等区域名称，只有语言（如 "de"）时对应该语言的默认区域
+    pub fn from_locale_name(locale_name: &str) -> Option<Language> {
+        let lcid =
+            unsafe { LocaleNameToLCID(&HSTRING::from(locale_name), LOCALE_ALLOW_NEUTRAL_NAMES) };
+        Self::from_lcid(lcid)
+    }
+
+    fn from_lcid(lcid: u32) -> Option<Language> {
+        // 中性区域（如 "de"）的 LCID 只有主语言 ID，对应该语言的默认区域（SUBLANG_DEFAULT）
+        let lcid = if lcid != 0 && lcid <= 0x3FF {
+            lcid | 0x0400
+        } else {
+            lcid
+        };
 
         TABLE
             .iter()
-            .find(|&&(lcid, ..)| lcid as u32 == sys_lcid)
-            .map_or(Language::English_UnitedStates, |&(_, lang, ..)| lang)
+            .find(|&&(table_lcid, ..)| table_lcid as u32 == lcid)
+            .map(|&(_, lang, ..)| lang)
     }
 }
 
@@ -733,3 +1327,23 @@ static TABLE: &[(u16, Language, &str, &str)] = &[
     (0x0478, Language::Yi_PRC, "Yi", "PRC"),
     (0x046A, Language::Yoruba_Nigeria, "Yoruba", "Nigeria"),
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_translations_use_known_keys() {
+        for (name, content) in BUILTIN_TRANSLATIONS {
+            let table = content.parse::<toml::Table>().unwrap();
+            let mut localization = EN_US;
+            for (key, value) in table {
+                let value = value
+                    .as_str()
+                    .unwrap_or_else(|| panic!("{name}: '{key}' is not a string"));
+                let value: &'static str = Box::leak(value.to_owned().into_boxed_str());
+                assert!(localization.set(&key, value), "{name}: unknown key '{key}'");
+            }
+        }
+    }
+}
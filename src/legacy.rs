@@ -0,0 +1,146 @@
+use crate::config::{Config, ConfigLocation};
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+
+use anyhow::{Context, Result};
+use log::warn;
+
+/// 旧版本（systray.rs）使用的 INI 配置文件
+const LEGACY_CONFIG_FILE_NAME: &str = "BlueGauge.ini";
+/// 导入后旧配置文件改为该名称，避免再次导入
+const IMPORTED_LEGACY_CONFIG_FILE_NAME: &str = "BlueGauge.ini.bak";
+
+/// 依次在配置文件目录、程序目录查找旧版本的 INI 配置
+pub fn find_legacy_config(config_path: &Path) -> Option<PathBuf> {
+    let config_dir = config_path.parent().map(Path::to_path_buf);
+    let exe_dir = ConfigLocation::Portable.config_dir().ok();
+
+    [config_dir, exe_dir]
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join(LEGACY_CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+/// 将 INI 中的设置写入新配置并保存，随后重命名 INI 文件，返回导入的设置数量。
+/// 节名不影响设置项（`[aliases]` 除外），`notify_` 前缀可省略
+pub fn import_legacy_config(config: &Config, ini_path: &Path) -> Result<usize> {
+    let content = std::fs::read_to_string(ini_path)
+        .with_context(|| format!("Failed to read {ini_path:?}"))?;
+
+    let mut imported = 0;
+    for (section, key, value) in parse_ini(&content) {
+        if matches!(section.as_str(), "aliases" | "device_aliases") {
+            config.set_device_alias(&key, &value);
+            imported += 1;
+            continue;
+        }
+
+        let key = key.to_ascii_lowercase();
+        let name = key.strip_prefix("notify_").unwrap_or(&key);
+        let applied = match name {
+            "update_interval" => value.parse::<u64>().ok().map(|interval| {
+                config
+                    .tray_options
+                    .update_interval
+                    .store(interval, Ordering::Relaxed)
+            }),
+            "low_battery" => parse_low_battery(&value).map(|low_battery| {
+                config
+                    .notify_options
+                    .low_battery
+                    .store(low_battery, Ordering::Relaxed)
+            }),
+            "mute" | "disconnection" | "reconnection" | "added" | "removed" => {
+                parse_bool(&value).map(|check| config.notify_options.update(name, check))
+            }
+            "show_disconnected" | "truncate_name" | "prefix_battery" => {
+                parse_bool(&value).map(|check| config.tray_options.update(name, check))
+            }
+            _ => {
+                warn!("Skipped unknown legacy setting '{key}'");
+                continue;
+            }
+        };
+
+        match applied {
+            Some(()) => imported += 1,
+            None => warn!("Skipped invalid legacy setting '{key} = {value}'"),
+        }
+    }
+
+    config.save();
+
+    let imported_path = ini_path.with_file_name(IMPORTED_LEGACY_CONFIG_FILE_NAME);
+    std::fs::rename(ini_path, &imported_path)
+        .with_context(|| format!("Failed to rename {ini_path:?} to {imported_path:?}"))?;
+
+    Ok(imported)
+}
+
+/// (小写节名, 键, 值)，忽略空行及 `;`、`#` 开头的注释
+fn parse_ini(content: &str) -> Vec<(String, String, String)> {
+    let mut section = String::new();
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with([';', '#']))
+        .filter_map(|line| {
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.trim().to_ascii_lowercase();
+                return None;
+            }
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim().trim_matches('"');
+            Some((section.clone(), key.trim().to_owned(), value.to_owned()))
+        })
+        .collect()
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// 旧版本以小数保存低电量阈值（如 `0.15`），也接受百分比（如 `15`、`1`）。
+/// 只有带小数点且小于 1 的值视为小数
+fn parse_low_battery(value: &str) -> Option<u8> {
+    let value = value.trim().trim_end_matches('%');
+    let is_fraction = value.contains('.');
+    let value = value.parse::<f64>().ok()?;
+    let percent = if is_fraction && value < 1.0 {
+        value * 100.0
+    } else {
+        value
+    };
+    (0.0..=100.0)
+        .contains(&percent)
+        .then(|| percent.round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn low_battery_fractions_and_percentages() {
+        assert_eq!(parse_low_battery("0.15"), Some(15));
+        assert_eq!(parse_low_battery("0.5"), Some(50));
+        assert_eq!(parse_low_battery("15"), Some(15));
+        assert_eq!(parse_low_battery("15%"), Some(15));
+        assert_eq!(parse_low_battery("1"), Some(1));
+        assert_eq!(parse_low_battery("1.0"), Some(1));
+        assert_eq!(parse_low_battery("100"), Some(100));
+    }
+
+    #[test]
+    fn low_battery_out_of_range() {
+        assert_eq!(parse_low_battery("150"), None);
+        assert_eq!(parse_low_battery("-0.1"), None);
+        assert_eq!(parse_low_battery("low"), None);
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use env_logger::{Env, Target, WriteStyle};
+
+const LOG_FILE_NAME: &str = "BlueGauge.log";
+/// 超过该大小时将日志移到 `BlueGauge.1.log`（覆盖更早的日志）后重新开始
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+
+/// 日志写入配置文件目录下的 `BlueGauge.log`，默认级别为 info，可通过 `RUST_LOG` 调整
+pub fn init_logging(config_dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(config_dir)?;
+    let file = RotatingFile::open(config_dir.join(LOG_FILE_NAME))?;
+
+    env_logger::Builder::from_env(Env::default().default_filter_or("info"))
+        .target(Target::Pipe(Box::new(file)))
+        .write_style(WriteStyle::Never)
+        .try_init()?;
+    Ok(())
+}
+
+/// 按时间顺序读取轮换后的旧日志及当前日志的最后 `count` 行，用于诊断报告
+pub fn log_tail(config_dir: &Path, count: usize) -> Result<Vec<String>> {
+    let path = config_dir.join(LOG_FILE_NAME);
+    let mut lines = VecDeque::with_capacity(count);
+    for path in [rotated_path(&path), path]
+        .iter()
+        .filter(|path| path.is_file())
+    {
+        let file = File::open(path).with_context(|| format!("Failed to open {path:?}"))?;
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            if lines.len() == count {
+                lines.pop_front();
+            }
+            lines.push_back(line);
+        }
+    }
+
+    Ok(lines.into())
+}
+
+/// `BlueGauge.log` → `BlueGauge.1.log`
+fn rotated_path(path: &Path) -> PathBuf {
+    path.with_extension("1.log")
+}
+
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf) -> Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open log file {path:?}"))?;
+        let size = file.metadata()?.len();
+        Ok(RotatingFile { path, file, size })
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        std::fs::rename(&self.path, rotated_path(&self.path))?;
+        self.file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // 轮换失败（如文件被占用）时继续写入当前文件，再写入 `MAX_LOG_SIZE` 后重试
+        if self.size >= MAX_LOG_SIZE && self.rotate().is_err() {
+            self.size = 0;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
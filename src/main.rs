@@ -2,96 +2,292 @@
 #![cfg(target_os = "windows")]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod audio;
+mod blink;
 mod bluetooth;
+mod cli;
+mod click;
+mod clipboard;
+mod command;
 mod config;
+mod debounce;
+mod diagnostics;
+mod dialog;
+mod export;
+mod flyout;
+mod history;
+mod hotkey;
+mod http_api;
 mod icon;
+mod ipc;
 mod language;
+mod legacy;
+mod logging;
 mod menu_handlers;
 mod notify;
+mod onboarding;
+mod reconnect;
+mod rules;
+mod speech;
 mod startup;
+mod summary;
+mod system_events;
+mod telemetry;
 mod tray;
+mod updater;
+mod webhook;
 
+use crate::audio::watch_audio_endpoints;
+use crate::blink::{IconBlinker, blank_icon, has_critical_device};
+use crate::bluetooth::ble::init_gatt_read_policy;
+use crate::bluetooth::companion::init_companions;
+use crate::bluetooth::engine::UpdateEngine;
+use crate::bluetooth::events::{DeviceEvent, DeviceEvents};
+use crate::bluetooth::galaxy_buds::init_galaxy_buds;
 use crate::bluetooth::info::{
-    BluetoothInfo, compare_bt_info_to_send_notifications, find_bluetooth_devices,
-    get_bluetooth_info,
+    BluetoothInfo, get_device_errors, init_calibrations, record_last_seen, record_last_updated,
+    seed_last_updated, send_initial_low_battery_notifications, send_notifications,
+    set_device_errors,
 };
-use crate::bluetooth::listen::{Watcher, listen_bluetooth_devices_info};
+use crate::bluetooth::provider::{BluetoothProvider, WindowsBluetoothProvider};
+use crate::bluetooth::radio::{BluetoothRadioState, get_bluetooth_radio_state};
+use crate::bluetooth::simulate::{is_simulating, start_simulation};
+use crate::bluetooth::snapshot::{LastKnown, load_last_known, save_last_known};
+use crate::cli::Cli;
+use crate::click::TrayClicks;
 use crate::config::*;
-use crate::icon::{SystemTheme, load_battery_icon};
+use crate::export::{write_status_json, write_widget_card};
+use crate::flyout::{FlyoutContent, hide_flyout, show_flyout};
+use crate::history::History;
+use crate::hotkey::register_summary_hotkey;
+use crate::http_api::start_http_api;
+use crate::icon::SystemTheme;
+use crate::ipc::IpcServer;
+use crate::language::Localization;
+use crate::logging::init_logging;
 use crate::menu_handlers::MenuHandlers;
-use crate::notify::app_notify;
-use crate::tray::{convert_tray_info, create_menu, create_tray};
+use crate::notify::{app_notify, set_activation_handler, summary_notify};
+use crate::onboarding::{OnboardingChoice, show_onboarding};
+use crate::reconnect::watch_reconnects;
+use crate::rules::RuleEngine;
+use crate::startup::STARTUP_ARG;
+use crate::summary::watch_weekly_summary;
+use crate::system_events::{SystemEvent, watch_system_events};
+use crate::telemetry::watch_telemetry;
+use crate::tray::{
+    DeviceMenus, TrayState, create_menu, create_tooltip, create_tray, create_tray_tooltip,
+    load_tray_icon, tooltip_devices, tooltip_headers,
+};
+use crate::updater::{
+    Release, get_available_update, install_update, remove_old_exe, watch_updates,
+};
+use crate::webhook::watch_webhooks;
 
 use std::collections::HashSet;
-use std::sync::{Arc, Mutex, RwLock};
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use bluegauge_core::retry::RetryPolicy;
+use tokio::runtime::Runtime;
 use tray_icon::{
-    TrayIcon,
-    menu::{CheckMenuItem, MenuEvent},
+    Rect, TrayIcon, TrayIconEvent,
+    menu::{CheckMenuItem, Menu, MenuEvent},
 };
 use winit::{
     application::ApplicationHandler,
-    event::WindowEvent,
-    event_loop::{ActiveEventLoop, EventLoop, EventLoopProxy},
+    event::{StartCause, WindowEvent},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy},
     window::WindowId,
 };
 
 fn main() -> anyhow::Result<()> {
+    if let Some(cli) = Cli::parse() {
+        return cli?.run();
+    }
+
+    if let Err(e) = ConfigLocation::resolve()
+        .and_then(|location| location.config_dir())
+        .and_then(|config_dir| init_logging(&config_dir))
+    {
+        eprintln!("Failed to initialize logging: {e}");
+    }
+
     std::panic::set_hook(Box::new(|info| {
+        log::error!("Panic: {info}");
         app_notify(format!("⚠️ Panic: {info}"));
     }));
 
+    if let Err(e) = startup::migrate_startup() {
+        log::warn!("Failed to migrate the autostart registry value: {e}");
+    }
+
+    remove_old_exe();
+
     let event_loop = EventLoop::<UserEvent>::with_user_event().build()?;
 
     let proxy = event_loop.create_proxy();
     MenuEvent::set_event_handler(Some(move |event| {
-        proxy
-            .send_event(UserEvent::MenuEvent(event))
-            .expect("Failed to send MenuEvent");
+        let _ = proxy.send_event(UserEvent::MenuEvent(event));
     }));
 
+    // 移动事件过于频繁，只转发点击及进入、离开（用于悬停浮窗）
+    let proxy = event_loop.create_proxy();
+    TrayIconEvent::set_event_handler(Some(move |event| {
+        if matches!(
+            event,
+            TrayIconEvent::Click { .. }
+                | TrayIconEvent::DoubleClick { .. }
+                | TrayIconEvent::Enter { .. }
+                | TrayIconEvent::Leave { .. }
+        ) {
+            let _ = proxy.send_event(UserEvent::TrayIconEvent(event));
+        }
+    }));
+
+    let proxy = event_loop.create_proxy();
+    set_activation_handler(move |address| {
+        let _ = proxy.send_event(UserEvent::ShowDeviceDetails(address));
+    });
+
     let mut app = App::default();
     let proxy = event_loop.create_proxy();
     app.add_proxy(Some(proxy));
 
     event_loop.run_app(&mut app)?;
 
+    app.shutdown();
+
+    // 更新完成后先移除托盘等资源，再启动新版本
+    if let Some(exe_path) = app.relaunch.take() {
+        drop(app);
+        let args = std::env::args()
+            .skip(1)
+            .filter(|arg| arg != STARTUP_ARG)
+            .collect::<Vec<_>>();
+        std::process::Command::new(exe_path).args(args).spawn()?;
+    }
+
     Ok(())
 }
 
+/// 资源管理器重启期间设置或创建托盘图标会短暂失败
+const TRAY_RETRY: RetryPolicy =
+    RetryPolicy::new(3, Duration::from_millis(200), Duration::from_secs(1));
+
 struct App {
+    /// 更新引擎、命名管道及后台定时任务共用的运行时，退出时取消所有任务
+    runtime: Runtime,
     bluetooth_info: Arc<Mutex<HashSet<BluetoothInfo>>>,
+    /// 设备信息变化时向 Webhook、HTTP API 及命名管道广播的事件
+    device_events: Arc<DeviceEvents>,
+    provider: Arc<dyn BluetoothProvider>,
     config: Arc<Config>,
-    watcher: Option<Watcher>,
+    engine: Option<UpdateEngine>,
+    ipc_server: Option<IpcServer>,
+    history: History,
+    rules: RuleEngine,
     event_loop_proxy: Option<EventLoopProxy<UserEvent>>,
     /// 存储已经通知过的低电量设备，避免再次通知
     notified_low_battery_devices: Arc<Mutex<HashSet<u64>>>,
-    system_theme: Arc<RwLock<SystemTheme>>,
+    tray_state: TrayState,
     tray: Mutex<Option<TrayIcon>>,
     tray_check_menus: Mutex<Option<Vec<CheckMenuItem>>>,
+    /// 各设备的子菜单，数据时间变化时更新标题
+    device_menus: Mutex<Option<DeviceMenus>>,
+    blinker: IconBlinker,
+    tray_clicks: TrayClicks,
+    /// 首次启动向导需要列出设备，等待首次枚举结束后显示
+    onboarding_pending: bool,
+    /// 已安装更新，退出后启动的新 exe
+    relaunch: Option<PathBuf>,
 }
 
 impl Default for App {
     fn default() -> Self {
         let config = Config::open().expect("Failed to open config");
+        Localization::init(&config.language_options.locale);
+        init_calibrations(config.calibrations.clone());
+        init_gatt_read_policy(config.ble_options.max_data_age);
+        init_galaxy_buds(config.protocol_options.galaxy_buds);
+        init_companions(config.companions.clone());
 
-        let bluetooth_devices = find_bluetooth_devices().expect("Failed to find bluetooth devices");
-        let bluetooth_devices_info =
-            get_bluetooth_info((&bluetooth_devices.0, &bluetooth_devices.1))
-                .expect("Failed to get bluetooth devices info");
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("bluegauge-runtime")
+            .enable_all()
+            .build()
+            .expect("Failed to create the async runtime");
 
-        let (tray, tray_check_menus) =
-            create_tray(&config, &bluetooth_devices_info).expect("Failed to create tray");
+        let provider: Arc<dyn BluetoothProvider> = if is_simulating() {
+            Arc::new(start_simulation())
+        } else {
+            Arc::new(WindowsBluetoothProvider)
+        };
+
+        let radio_state = if provider.is_simulated() {
+            BluetoothRadioState::On
+        } else {
+            get_bluetooth_radio_state()
+                .inspect_err(|e| log::warn!("Failed to get the Bluetooth radio state: {e}"))
+                .unwrap_or(BluetoothRadioState::On)
+        };
+
+        // 不等待枚举，先以上次退出时保存的设备信息（没有时为空列表）显示托盘并提示“正在初始化”，
+        // 由更新引擎在后台完成首次枚举，读取到的设备逐个显示；
+        // 蓝牙关闭时等待适配器状态变化，重新打开时会立即刷新
+        let last_known = if provider.is_simulated() {
+            LastKnown::default()
+        } else {
+            load_last_known(&config)
+        };
+        let bluetooth_devices_info = last_known.devices;
+
+        let tray_state = TrayState {
+            radio_state,
+            monitoring_paused: false,
+            initializing: radio_state == BluetoothRadioState::On,
+            restored: !bluetooth_devices_info.is_empty(),
+        };
+
+        let history = History::new(&config);
+
+        // 开机自启动时任务栏可能尚未就绪，创建失败时在下次刷新托盘时重新创建
+        let (tray, tray_check_menus, device_menus) = match TRAY_RETRY.retry("Tray creation", || {
+            create_tray(&config, tray_state, &bluetooth_devices_info)
+        }) {
+            Ok((tray, tray_check_menus, device_menus)) => {
+                (Some(tray), Some(tray_check_menus), Some(device_menus))
+            }
+            Err(e) => {
+                log::error!("Failed to create tray: {e}");
+                (None, None, None)
+            }
+        };
+
+        let rules = RuleEngine::new(&config);
 
         Self {
+            runtime,
             bluetooth_info: Arc::new(Mutex::new(bluetooth_devices_info)),
+            device_events: Arc::new(DeviceEvents::new()),
+            provider,
             config: Arc::new(config),
-            watcher: None,
+            engine: None,
+            ipc_server: None,
+            history,
+            rules,
             event_loop_proxy: None,
-            notified_low_battery_devices: Arc::new(Mutex::new(HashSet::new())),
-            system_theme: Arc::new(RwLock::new(SystemTheme::get())),
-            tray: Mutex::new(Some(tray)),
-            tray_check_menus: Mutex::new(Some(tray_check_menus)),
+            notified_low_battery_devices: Arc::new(Mutex::new(last_known.low_battery_notified)),
+            tray_state,
+            tray: Mutex::new(tray),
+            tray_check_menus: Mutex::new(tray_check_menus),
+            device_menus: Mutex::new(device_menus),
+            blinker: IconBlinker::default(),
+            tray_clicks: TrayClicks::default(),
+            onboarding_pending: false,
+            relaunch: None,
         }
     }
 }
@@ -99,8 +295,45 @@ impl Default for App {
 #[derive(Debug)]
 enum UserEvent {
     MenuEvent(MenuEvent),
+    TrayIconEvent(TrayIconEvent),
     UpdateTray(/* Force Update */ bool), // bool: Force Update
-    UpdateTrayForBluetooth(BluetoothInfo),
+    /// 只有显示相关的设置变化，以当前设备信息重建托盘
+    RedrawTray,
+    /// 系统的浅色/深色主题变化
+    ThemeChanged(SystemTheme),
+    /// 首次枚举中读取到的单个设备
+    InitialDevice(BluetoothInfo),
+    BluetoothInfoReady(
+        anyhow::Result<HashSet<BluetoothInfo>>,
+        /* Force Update */ bool,
+    ),
+    /// 短时间内合并的设备变化
+    UpdateTrayForBluetooth(Vec<BluetoothInfo>),
+    AddBluetoothInfo(BluetoothInfo),
+    RemoveBluetoothInfo(/* address */ u64),
+    RadioStateChanged(BluetoothRadioState),
+    SystemEvent(SystemEvent),
+    ResumeMonitoring,
+    Initialized(anyhow::Result<HashSet<BluetoothInfo>>),
+    UpdateAvailable(Release),
+    UpdateInstalled(anyhow::Result<PathBuf>),
+    /// 点击了设备通知
+    ShowDeviceDetails(/* address */ u64),
+    /// 按下了电量概览快捷键
+    ShowSummary,
+    /// 完成了首次启动向导
+    OnboardingFinished(OnboardingChoice),
+    /// 看门狗定时检查设备监听
+    CheckWatchers,
+}
+
+/// 仅开机自启动时延迟首次枚举
+fn startup_delay(config: &Config) -> Duration {
+    if std::env::args().any(|arg| arg == STARTUP_ARG) {
+        Duration::from_secs(config.startup_options.delay)
+    } else {
+        Duration::ZERO
+    }
 }
 
 impl App {
@@ -109,27 +342,434 @@ impl App {
         self
     }
 
-    fn start_watch_device(&mut self, device: BluetoothInfo) {
-        // 如果已有一个监控任务在运行，先停止它
-        if let Some(monitor) = self.watcher.take() {
-            if let Err(e) = monitor.stop() {
-                eprintln!("Stop the previous watch failed: {e}");
+    /// 退出前停止更新引擎及设备监听（注销事件并关闭 GATT 通知），
+    /// 再保存设备信息及已发送的低电量通知，下次启动时立即显示且不重复通知
+    fn shutdown(&mut self) {
+        if let Some(mut engine) = self.engine.take() {
+            engine.shutdown();
+        }
+
+        if !self.provider.is_simulated()
+            && let Err(e) = save_last_known(
+                &self.config,
+                &self.bluetooth_info.lock().unwrap(),
+                &self.notified_low_battery_devices.lock().unwrap(),
+            )
+        {
+            log::warn!("Failed to save the last known devices: {e}");
+        }
+    }
+
+    /// 监听所有设备的连接状态与 BLE 电量通知，托盘图标来源设备额外轮询电量
+    fn sync_watchers(&mut self) {
+        let Some(engine) = self.engine.as_mut() else {
+            return;
+        };
+
+        let bluetooth_info = self.bluetooth_info.lock().unwrap();
+
+        let watch_bt_address = self
+            .config
+            .tray_options
+            .tray_icon_source
+            .lock()
+            .unwrap()
+            .resolve_address(&bluetooth_info);
+
+        engine.watch_devices(&bluetooth_info, watch_bt_address);
+    }
+
+    /// 应用事件带来的设备变化（无需重新枚举）：发送通知、导出并刷新托盘
+    fn update_tray_for_bluetooth(
+        &mut self,
+        current_bt_infos: HashSet<BluetoothInfo>,
+        changed_addresses: &[u64],
+    ) {
+        record_last_updated(&current_bt_infos);
+
+        // 事件触发的断开/重新连接/低电量同样需要通知，同时更新共享的设备信息
+        self.apply_bt_info(&current_bt_infos, false);
+
+        self.export_status(&current_bt_infos);
+
+        if let Some(ipc_server) = &self.ipc_server {
+            ipc_server.publish(&current_bt_infos);
+        }
+
+        self.rules.evaluate(&current_bt_infos);
+
+        // 后备设备的连接变化同样可能改变图标显示的设备
+        let icon_depends_on_changed = {
+            let tray_icon_source = self.config.tray_options.tray_icon_source.lock().unwrap();
+            changed_addresses
+                .iter()
+                .any(|address| tray_icon_source.depends_on(*address))
+        };
+
+        self.refresh_tray(&current_bt_infos, icon_depends_on_changed);
+
+        // 图标显示的设备切换时，额外轮询电量的设备随之切换
+        if icon_depends_on_changed {
+            self.sync_watchers();
+        }
+    }
+
+    /// 以新的设备信息替换当前信息，按各设备的变化发送通知并记录电量历史，返回设备信息是否变化。
+    /// `baseline` 为真或此前没有比较基准时，只作为之后比较的基准并记录所有设备
+    fn apply_bt_info(&self, new_bt_info: &HashSet<BluetoothInfo>, baseline: bool) -> bool {
+        let old_bt_info = std::mem::replace(
+            &mut *self.bluetooth_info.lock().unwrap(),
+            new_bt_info.clone(),
+        );
+        if !baseline && old_bt_info == *new_bt_info {
+            return false;
+        }
+
+        let events = if baseline {
+            self.device_events.reset(new_bt_info);
+            None
+        } else {
+            let unreadable = get_device_errors()
+                .iter()
+                .map(|error| error.address)
+                .collect::<Vec<_>>();
+            self.device_events.publish(new_bt_info, &unreadable)
+        };
+
+        let recorded = match events {
+            Some(events) => {
+                // 别名以设备名称保存，设备改名后沿用原来的别名
+                let mut aliases_changed = false;
+                for event in &events {
+                    if let DeviceEvent::Renamed { info, old_name } = event {
+                        aliases_changed |=
+                            self.config.carry_over_device_alias(old_name, &info.name);
+                    }
+                }
+                if aliases_changed {
+                    self.config.save();
+                }
+
+                record_last_seen(&events);
+                let recorded = self.history.record_events(&events);
+                send_notifications(
+                    &self.config,
+                    Arc::clone(&self.notified_low_battery_devices),
+                    events,
+                );
+                recorded
             }
+            None => {
+                send_initial_low_battery_notifications(
+                    &self.config,
+                    Arc::clone(&self.notified_low_battery_devices),
+                    new_bt_info,
+                );
+                self.history.record(new_bt_info)
+            }
+        };
+        if let Err(e) = recorded {
+            log::warn!("Failed to record battery history: {e}");
         }
 
-        if let Some(proxy) = &self.event_loop_proxy {
-            match Watcher::start(device, proxy.clone()) {
-                Ok(monitor) => self.watcher = Some(monitor),
-                Err(e) => eprintln!("Failed to start the bluetooth watch: {e}"),
+        true
+    }
+
+    /// 按设备信息及蓝牙适配器状态重建托盘菜单、提示与图标
+    fn rebuild_tray(&self, bluetooth_devices_info: &HashSet<BluetoothInfo>) {
+        match create_menu(&self.config, self.tray_state, bluetooth_devices_info) {
+            Ok(menus) => self.apply_tray(bluetooth_devices_info, Some(menus), true),
+            Err(e) => app_notify(format!("Failed to create tray  menu - {e}")),
+        }
+    }
+
+    fn show_pending_onboarding(&mut self) {
+        if !std::mem::take(&mut self.onboarding_pending) {
+            return;
+        }
+        let Some(proxy) = self.event_loop_proxy.clone() else {
+            return;
+        };
+        show_onboarding(
+            &self.config,
+            &self.bluetooth_info.lock().unwrap(),
+            move |choice| {
+                let _ = proxy.send_event(UserEvent::OnboardingFinished(choice));
+            },
+        );
+    }
+
+    /// 悬停时以当前设备信息显示浮窗，内容与托盘提示相同
+    fn show_flyout(&self, anchor: Rect) {
+        let bluetooth_devices_info = self.bluetooth_info.lock().unwrap();
+        show_flyout(
+            FlyoutContent {
+                headers: tooltip_headers(self.tray_state),
+                devices: tooltip_devices(&bluetooth_devices_info, &self.config),
+                low_battery: self.config.get_low_battery(),
+                battery_display: self.config.get_battery_display(),
+            },
+            anchor,
+        );
+    }
+
+    /// 以当前设备信息重建托盘，用于只影响显示的设置变化
+    fn redraw_tray(&self) {
+        let bluetooth_devices_info = self.bluetooth_info.lock().unwrap().clone();
+        self.rebuild_tray(&bluetooth_devices_info);
+    }
+
+    /// 设备列表不变时原地更新设备菜单，只替换提示与图标；否则重建菜单
+    fn refresh_tray(&self, bluetooth_devices_info: &HashSet<BluetoothInfo>, update_icon: bool) {
+        let updated_in_place = self
+            .device_menus
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|device_menus| device_menus.update(&self.config, bluetooth_devices_info));
+        if updated_in_place {
+            self.apply_tray(bluetooth_devices_info, None, update_icon);
+            return;
+        }
+
+        match create_menu(&self.config, self.tray_state, bluetooth_devices_info) {
+            Ok(menus) => self.apply_tray(bluetooth_devices_info, Some(menus), update_icon),
+            Err(e) => app_notify(format!("Failed to create tray menu - {e}")),
+        }
+    }
+
+    /// 设置托盘的菜单（`None` 时保留原菜单）、提示与图标。失败时重试，
+    /// 仍失败说明托盘图标已失效（如资源管理器重启），此时重新创建托盘图标
+    fn apply_tray(
+        &self,
+        bluetooth_devices_info: &HashSet<BluetoothInfo>,
+        menus: Option<(Menu, Vec<CheckMenuItem>, DeviceMenus)>,
+        update_icon: bool,
+    ) {
+        let (tray_menu, menu_handles) = match menus {
+            Some((tray_menu, tray_check_menus, device_menus)) => {
+                (Some(tray_menu), Some((tray_check_menus, device_menus)))
+            }
+            None => (None, None),
+        };
+
+        let tooltip = create_tray_tooltip(&self.config, self.tray_state, bluetooth_devices_info);
+        // 图标加载失败时保留原图标
+        let icon = update_icon
+            .then(|| load_tray_icon(&self.config, self.tray_state, bluetooth_devices_info))
+            .transpose()
+            .inspect_err(|e| log::warn!("Failed to load battery icon: {e}"))
+            .ok()
+            .flatten();
+
+        let tray = self.tray.lock().unwrap();
+        let updated = tray.as_ref().is_some_and(|tray| {
+            if let Some(tray_menu) = tray_menu {
+                tray.set_menu(Some(Box::new(tray_menu)));
+            }
+            TRAY_RETRY
+                .retry("Tray update", || {
+                    tray.set_tooltip(tooltip.as_deref())?;
+                    if let Some(icon) = &icon {
+                        tray.set_icon(Some(icon.clone()))?;
+                    }
+                    Ok(())
+                })
+                .inspect_err(|e| log::warn!("Failed to update the tray, recreating it - {e}"))
+                .is_ok()
+        });
+        drop(tray);
+
+        if updated {
+            if let Some((tray_check_menus, device_menus)) = menu_handles {
+                *self.tray_check_menus.lock().unwrap() = Some(tray_check_menus);
+                *self.device_menus.lock().unwrap() = Some(device_menus);
+            }
+        } else {
+            self.recreate_tray(bluetooth_devices_info);
+        }
+    }
+
+    /// 移除原托盘图标后按当前状态重新创建，用于托盘图标失效或启动时创建失败
+    fn recreate_tray(&self, bluetooth_devices_info: &HashSet<BluetoothInfo>) {
+        let mut tray = self.tray.lock().unwrap();
+        tray.take();
+
+        match TRAY_RETRY.retry("Tray creation", || {
+            create_tray(&self.config, self.tray_state, bluetooth_devices_info)
+        }) {
+            Ok((new_tray, tray_check_menus, device_menus)) => {
+                *tray = Some(new_tray);
+                *self.tray_check_menus.lock().unwrap() = Some(tray_check_menus);
+                *self.device_menus.lock().unwrap() = Some(device_menus);
+            }
+            Err(e) => log::error!("Failed to recreate the tray - {e}"),
+        }
+    }
+
+    /// 设备信息无变化时只更新菜单及提示中的数据时间，无需重建菜单
+    fn refresh_data_age(&self, bluetooth_devices_info: &HashSet<BluetoothInfo>) {
+        if let Some(device_menus) = self.device_menus.lock().unwrap().as_ref() {
+            device_menus.update(&self.config, bluetooth_devices_info);
+        }
+
+        // 数据过期与否同样随时间变化
+        if (self.config.get_show_last_updated() || self.config.get_stale_policy().is_some())
+            && let Some(tray) = self.tray.lock().unwrap().as_ref()
+        {
+            let tooltip =
+                create_tray_tooltip(&self.config, self.tray_state, bluetooth_devices_info);
+            let _ = tray.set_tooltip(tooltip);
+        }
+    }
+
+    /// `refresh_device:{address}`
+    fn refresh_device(&self, menu_event_id: &str) {
+        let Some(engine) = &self.engine else {
+            return;
+        };
+
+        let device = menu_event_id
+            .strip_prefix("refresh_device:")
+            .and_then(|address| address.parse::<u64>().ok())
+            .and_then(|address| {
+                self.bluetooth_info
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .find(|info| info.address == address)
+                    .cloned()
+            });
+
+        if let Some(device) = device {
+            engine.refresh_device(device);
+        }
+    }
+
+    fn pause_monitoring(&mut self, duration: Option<Duration>) {
+        let Some(engine) = self.engine.as_mut() else {
+            return;
+        };
+        engine.pause(duration);
+        self.tray_state.monitoring_paused = true;
+
+        let current_bt_infos = self.bluetooth_info.lock().unwrap().clone();
+        self.rebuild_tray(&current_bt_infos);
+    }
+
+    fn resume_monitoring(&mut self) {
+        let Some(engine) = self.engine.as_mut() else {
+            return;
+        };
+        engine.resume();
+        engine.refresh(true);
+        self.tray_state.monitoring_paused = false;
+
+        self.sync_watchers();
+        let current_bt_infos = self.bluetooth_info.lock().unwrap().clone();
+        self.rebuild_tray(&current_bt_infos);
+    }
+
+    /// 重建失效的设备监听，开启 `watchdog` 通知时提示重建的设备
+    fn check_watchers(&mut self) {
+        let Some(engine) = self.engine.as_mut() else {
+            return;
+        };
+
+        let restarted = engine.restart_dead_watchers(&self.bluetooth_info.lock().unwrap());
+        if !restarted.is_empty() && self.config.get_watchdog() {
+            let loc = Localization::current();
+            app_notify(
+                loc.watcher_restarted
+                    .replace("{names}", &restarted.join(", ")),
+            );
+        }
+    }
+
+    /// 在后台下载并替换 exe，完成后发送 `UserEvent::UpdateInstalled`
+    fn install_update(&self) {
+        let (Some(release), Some(proxy)) = (get_available_update(), self.event_loop_proxy.clone())
+        else {
+            return;
+        };
+
+        self.runtime.spawn_blocking(move || {
+            let _ = proxy.send_event(UserEvent::UpdateInstalled(install_update(&release)));
+        });
+    }
+
+    /// 与托盘提示内容相同，包含蓝牙状态及暂停监控等提示
+    fn show_summary(&self) {
+        let loc = Localization::current();
+        let tooltip = create_tooltip(
+            &self.config,
+            self.tray_state,
+            &self.bluetooth_info.lock().unwrap(),
+        );
+        let text = if tooltip.is_empty() {
+            loc.no_bluetooth_devices.to_owned()
+        } else {
+            tooltip
+        };
+        summary_notify(loc.battery_summary, text, self.config.get_mute());
+    }
+
+    fn run_click_action(&self, action: ClickAction) {
+        match action {
+            ClickAction::None | ClickAction::Menu => (),
+            ClickAction::Summary => self.show_summary(),
+            ClickAction::Refresh => MenuHandlers::force_update(&self.config),
+            ClickAction::ToggleConnection => {
+                let device = self
+                    .config
+                    .get_tray_battery_icon_bt_address()
+                    .and_then(|address| {
+                        self.bluetooth_info
+                            .lock()
+                            .unwrap()
+                            .iter()
+                            .find(|info| info.address == address)
+                            .cloned()
+                    });
+                if let Some(device) = device {
+                    MenuHandlers::switch_connection(Arc::clone(&self.config), device);
+                }
             }
+            ClickAction::OpenSettings => MenuHandlers::open_config(&self.config),
         }
     }
 
-    fn stop_watch(&mut self) {
-        if let Some(monitor) = self.watcher.take() {
-            if let Err(e) = monitor.stop() {
-                eprintln!("Stop the previous watch failed: {e}");
+    /// 闪烁时切换透明图标与正常图标
+    fn show_blink_frame(&self, hidden: bool) {
+        let icon = if hidden {
+            blank_icon()
+        } else {
+            let bluetooth_devices_info = self.bluetooth_info.lock().unwrap();
+            load_tray_icon(&self.config, self.tray_state, &bluetooth_devices_info)
+        };
+
+        match icon {
+            Ok(icon) => {
+                if let Some(tray) = self.tray.lock().unwrap().as_ref() {
+                    let _ = tray.set_icon(Some(icon));
+                }
             }
+            Err(e) => log::warn!("Failed to load the blinking tray icon: {e}"),
+        }
+    }
+
+    fn export_status(&self, bluetooth_devices_info: &HashSet<BluetoothInfo>) {
+        if let Some(status_json_path) = self.config.get_status_json_path()
+            && let Err(e) = write_status_json(&status_json_path, bluetooth_devices_info)
+        {
+            log::warn!("Failed to export status JSON: {e}");
+        }
+
+        if let Some(widget_card_path) = self.config.get_widget_card_path()
+            && let Err(e) =
+                write_widget_card(&widget_card_path, bluetooth_devices_info, &self.config)
+        {
+            log::warn!("Failed to export widget card: {e}");
         }
     }
 }
@@ -139,49 +779,167 @@ impl ApplicationHandler<UserEvent> for App {
         let config = Arc::clone(&self.config);
         let proxy = self.event_loop_proxy.clone().expect("Failed to get proxy");
 
-        let watch_bt_address = {
-            config
-                .tray_options
-                .tray_icon_source
-                .lock()
-                .unwrap()
-                .get_address()
-        };
+        let engine = UpdateEngine::start(
+            self.runtime.handle().clone(),
+            config.clone(),
+            Arc::clone(&self.provider),
+            Arc::clone(&self.bluetooth_info),
+            proxy.clone(),
+        );
+        if self.tray_state.initializing {
+            engine.initialize(startup_delay(&config));
+        }
+        self.engine = Some(engine);
 
-        if let Some(address) = watch_bt_address {
-            let bt_devices = self.bluetooth_info.lock().unwrap().clone();
+        self.sync_watchers();
 
-            if let Some(i) = bt_devices.iter().find(|i| i.address == address) {
-                self.start_watch_device(i.clone());
+        // 电量历史可能较大，托盘显示后在后台读取各设备最后一次连接的时间，
+        // 补全没有保存数据时间的设备后刷新菜单及提示
+        let history = self.history.clone();
+        let history_proxy = proxy.clone();
+        self.runtime
+            .spawn_blocking(move || match history.last_connected() {
+                Ok(last_connected) => {
+                    seed_last_updated(last_connected);
+                    let _ = history_proxy.send_event(UserEvent::RedrawTray);
+                }
+                Err(e) => log::warn!("Failed to read the battery history: {e}"),
+            });
+
+        let system_event_proxy = proxy.clone();
+        if let Err(e) = watch_system_events(move |event| {
+            let _ = system_event_proxy.send_event(UserEvent::SystemEvent(event));
+        }) {
+            log::warn!("Failed to watch sleep, session and taskbar events: {e}");
+        }
+
+        let hotkey = &config.hotkey_options.summary;
+        if !hotkey.is_empty() {
+            let hotkey_proxy = proxy.clone();
+            if let Err(e) = register_summary_hotkey(hotkey, move || {
+                let _ = hotkey_proxy.send_event(UserEvent::ShowSummary);
+            }) {
+                app_notify(e.to_string());
             }
         }
 
-        listen_bluetooth_devices_info(config.clone(), proxy.clone());
+        let update_proxy = proxy.clone();
+        watch_updates(self.runtime.handle(), config.clone(), move |release| {
+            let _ = update_proxy.send_event(UserEvent::UpdateAvailable(release));
+        });
+
+        watch_weekly_summary(self.runtime.handle(), config.clone());
 
-        let system_theme = Arc::clone(&self.system_theme);
-        std::thread::spawn(move || {
+        watch_telemetry(
+            self.runtime.handle(),
+            config.clone(),
+            Arc::clone(&self.bluetooth_info),
+        );
+
+        let audio_proxy = proxy.clone();
+        watch_audio_endpoints(self.runtime.handle(), move || {
+            let _ = audio_proxy.send_event(UserEvent::RedrawTray);
+        });
+
+        self.onboarding_pending = config.first_run;
+        if !self.tray_state.initializing {
+            self.show_pending_onboarding();
+        }
+
+        watch_reconnects(
+            self.runtime.handle(),
+            config.clone(),
+            self.device_events.subscribe(),
+        );
+
+        if !config.webhooks.is_empty() {
+            watch_webhooks(config.clone(), self.device_events.subscribe());
+        }
+
+        // 先取出开关再启动，启动时可能写入生成的令牌
+        let http_api_enabled = config.http_api_options.lock().unwrap().enabled;
+        if http_api_enabled
+            && let Err(e) = start_http_api(
+                config.clone(),
+                Arc::clone(&self.bluetooth_info),
+                Arc::clone(&self.device_events),
+            )
+        {
+            app_notify(e.to_string());
+        }
+
+        if config.named_pipe_options.enabled {
+            match IpcServer::start(
+                self.runtime.handle(),
+                config.clone(),
+                Arc::clone(&self.bluetooth_info),
+                self.device_events.subscribe(),
+            ) {
+                Ok(ipc_server) => self.ipc_server = Some(ipc_server),
+                Err(e) => app_notify(e.to_string()),
+            }
+        }
+
+        self.runtime.spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(5));
+            let mut last_system_theme = SystemTheme::current();
             loop {
-                let original_system_theme = {
-                    let system_theme = system_theme.read().unwrap();
-                    *system_theme
-                };
+                interval.tick().await;
 
                 let current_system_theme = SystemTheme::get();
 
-                if original_system_theme != current_system_theme {
-                    let mut system_theme = system_theme.write().unwrap();
-                    *system_theme = current_system_theme;
+                if last_system_theme != current_system_theme {
+                    last_system_theme = current_system_theme;
 
-                    proxy
-                        .send_event(UserEvent::UpdateTray(true))
-                        .expect("Failed to send UpdateTray Event");
+                    // 事件循环已退出
+                    if proxy
+                        .send_event(UserEvent::ThemeChanged(current_system_theme))
+                        .is_err()
+                    {
+                        break;
+                    }
                 }
-
-                std::thread::sleep(std::time::Duration::from_secs(5));
             }
         });
     }
 
+    fn new_events(&mut self, _event_loop: &ActiveEventLoop, cause: StartCause) {
+        let StartCause::ResumeTimeReached { .. } = cause else {
+            return;
+        };
+
+        let interval = self.config.blink_options.get_interval();
+        if let Some(hidden) = self.blinker.toggle(interval) {
+            self.show_blink_frame(hidden);
+        }
+
+        if let Some(action) = self.tray_clicks.take_due(&self.config.click_options) {
+            self.run_click_action(action);
+        }
+    }
+
+    /// 每批事件处理完后按设备电量开始或停止闪烁，闪烁或等待执行单击时定时唤醒事件循环
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let blinking = self.tray_state.radio_state == BluetoothRadioState::On
+            && !self.tray_state.initializing
+            && !self.tray_state.restored
+            && has_critical_device(
+                &self.config.blink_options,
+                &self.bluetooth_info.lock().unwrap(),
+            );
+        let interval = self.config.blink_options.get_interval();
+
+        if self.blinker.set_blinking(blinking, interval) {
+            self.show_blink_frame(false);
+        }
+
+        let wake_up = [self.blinker.next_toggle(), self.tray_clicks.next_due()]
+            .into_iter()
+            .flatten()
+            .min();
+        event_loop.set_control_flow(wake_up.map_or(ControlFlow::Wait, ControlFlow::WaitUntil));
+    }
+
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
         if event == WindowEvent::CloseRequested {
             event_loop.exit()
@@ -192,19 +950,82 @@ impl ApplicationHandler<UserEvent> for App {
         match event {
             UserEvent::MenuEvent(event) => {
                 let config = Arc::clone(&self.config);
-                let tray_check_menus = self
-                    .tray_check_menus
-                    .lock()
-                    .unwrap()
-                    .clone()
-                    .expect("Tray check menus not initialized");
+                let Some(tray_check_menus) = self.tray_check_menus.lock().unwrap().clone() else {
+                    return;
+                };
 
                 let menu_event_id = event.id().as_ref();
                 match menu_event_id {
                     "quit" => MenuHandlers::qpp_quit(event_loop),
                     "force_update" => MenuHandlers::force_update(&config),
+                    "device_errors" => MenuHandlers::show_device_errors(&config),
                     "startup" => MenuHandlers::startup(tray_check_menus),
-                    "open_config" => MenuHandlers::open_config(),
+                    "open_config" => MenuHandlers::open_config(&config),
+                    "reload_config" => {
+                        MenuHandlers::reload_config(&config);
+                        self.sync_watchers();
+                    }
+                    "diagnostics_report" => MenuHandlers::create_diagnostics_report(
+                        config.clone(),
+                        self.bluetooth_info.lock().unwrap().clone(),
+                    ),
+                    "install_update" => self.install_update(),
+                    "open_release_page" => MenuHandlers::open_release_page(),
+                    "bluetooth_radio" => {
+                        MenuHandlers::toggle_bluetooth_radio(self.tray_state.radio_state)
+                    }
+                    "resume_monitoring" => self.resume_monitoring(),
+                    id if id.starts_with("pause_monitoring") => {
+                        self.pause_monitoring(MenuHandlers::pause_duration(id))
+                    }
+                    id if id.starts_with("refresh_device:") => self.refresh_device(id),
+                    id if id.starts_with("toggle_connection:") => {
+                        MenuHandlers::toggle_device_connection(
+                            config,
+                            &self.bluetooth_info.lock().unwrap(),
+                            id,
+                        )
+                    }
+                    id if id.starts_with("set_default_audio:") => {
+                        MenuHandlers::set_default_audio(&self.bluetooth_info.lock().unwrap(), id)
+                    }
+                    id if id.starts_with("rename_device:") => MenuHandlers::rename_device(
+                        config,
+                        &self.bluetooth_info.lock().unwrap(),
+                        id,
+                    ),
+                    id if id.starts_with("hide_device:") => {
+                        MenuHandlers::hide_device(&config, &self.bluetooth_info.lock().unwrap(), id)
+                    }
+                    id if id.starts_with("pin_device:") => MenuHandlers::toggle_pin_device(
+                        &config,
+                        &self.bluetooth_info.lock().unwrap(),
+                        id,
+                    ),
+                    id if id.starts_with("keep_connected:") => MenuHandlers::toggle_keep_connected(
+                        &config,
+                        &self.bluetooth_info.lock().unwrap(),
+                        id,
+                    ),
+                    id if id.starts_with("unhide_device:") => {
+                        MenuHandlers::unhide_device(&config, id)
+                    }
+                    id if id.starts_with("copy_status:") => {
+                        MenuHandlers::copy_status(&config, &self.bluetooth_info.lock().unwrap(), id)
+                    }
+                    id if id.starts_with("export_history") => {
+                        MenuHandlers::export_history(&self.history, id)
+                    }
+                    id if id.starts_with("battery_statistics:")
+                        || id.starts_with("device_details:") =>
+                    {
+                        MenuHandlers::show_battery_statistics(
+                            &config,
+                            &self.history,
+                            &self.bluetooth_info.lock().unwrap(),
+                            id,
+                        )
+                    }
                     "set_icon_connect_color" => MenuHandlers::set_icon_connect_color(
                         &config,
                         menu_event_id,
@@ -214,6 +1035,11 @@ impl ApplicationHandler<UserEvent> for App {
                     "15" | "30" | "60" | "300" | "600" | "1800" => {
                         MenuHandlers::set_update_interval(&config, menu_event_id, tray_check_menus);
                     }
+                    "adaptive_update_interval" => MenuHandlers::set_adaptive_update_interval(
+                        &config,
+                        menu_event_id,
+                        tray_check_menus,
+                    ),
                     // 通知设置：低电量
                     "0.01" | "0.05" | "0.1" | "0.15" | "0.2" | "0.25" => {
                         MenuHandlers::set_notify_low_battery(
@@ -222,8 +1048,9 @@ impl ApplicationHandler<UserEvent> for App {
                             tray_check_menus,
                         );
                     }
-                    // 通知设置：静音/断开连接/重新连接/添加/删除
-                    "mute" | "disconnection" | "reconnection" | "added" | "removed" => {
+                    // 通知设置：静音/断开连接/重新连接/添加/删除/改名/仅固定的设备
+                    "mute" | "disconnection" | "reconnection" | "added" | "removed" | "renamed"
+                    | "pinned_only" | "milestones" => {
                         MenuHandlers::set_notify_device_change(
                             &config,
                             menu_event_id,
@@ -231,131 +1058,298 @@ impl ApplicationHandler<UserEvent> for App {
                         );
                     }
                     // 托盘设置：提示内容设置
-                    "show_disconnected" | "truncate_name" | "prefix_battery" => {
+                    "show_disconnected" | "truncate_name" | "prefix_battery" | "strip_affixes"
+                    | "show_last_updated" | "battery_bar" | "summary_header"
+                    | "show_default_audio" | "hide_device_list" | "hide_tooltip"
+                    | "rich_flyout" => {
                         MenuHandlers::set_tray_tooltip(&config, menu_event_id, tray_check_menus);
                     }
+                    id if id.starts_with("truncate_length:") => {
+                        MenuHandlers::set_truncate_length(&config, id, tray_check_menus);
+                    }
+                    "truncate_end" | "truncate_middle" => {
+                        MenuHandlers::set_truncate_strategy(
+                            &config,
+                            menu_event_id,
+                            tray_check_menus,
+                        );
+                    }
+                    id if id.starts_with("device_filter:") => {
+                        MenuHandlers::set_device_filter(&config, id, tray_check_menus);
+                    }
+                    id if id.starts_with("battery_display:") => {
+                        MenuHandlers::set_battery_display(&config, id, tray_check_menus);
+                    }
+                    // 配置方案
+                    id if id.starts_with("profile:") => {
+                        MenuHandlers::switch_profile(&config, id, tray_check_menus);
+                        self.sync_watchers();
+                    }
+                    "save_profile" => MenuHandlers::save_profile(config),
                     _ => {
-                        let need_watch = MenuHandlers::set_tray_icon_source(
-                            self.bluetooth_info.lock().unwrap().clone(),
+                        MenuHandlers::set_tray_icon_source(
                             &config,
                             menu_event_id,
                             tray_check_menus,
                         );
-                        if let Some(info) = need_watch {
-                            self.start_watch_device(info);
-                        } else {
-                            self.stop_watch();
-                        }
+                        self.sync_watchers();
                     }
                 }
+
+                // 菜单中修改的显示设置立即生效，无需等待下一次枚举
+                if self.config.refresh_tray.swap(false, Ordering::SeqCst) {
+                    self.redraw_tray();
+                }
             }
+            UserEvent::TrayIconEvent(event) => match event {
+                TrayIconEvent::Enter { rect, .. } => {
+                    if self.config.get_rich_flyout() && !self.config.get_hide_tooltip() {
+                        self.show_flyout(rect);
+                    }
+                }
+                TrayIconEvent::Leave { .. } => hide_flyout(),
+                event => {
+                    // 点击后显示菜单或执行操作，浮窗会遮挡
+                    hide_flyout();
+                    if let Some(action) =
+                        self.tray_clicks.handle(&self.config.click_options, &event)
+                    {
+                        self.run_click_action(action);
+                    }
+                }
+            },
             UserEvent::UpdateTray(need_force_update) => {
-                let bluetooth_devices = match find_bluetooth_devices() {
-                    Ok(devices) => devices,
+                // 枚举在更新引擎中进行，完成后发送 `BluetoothInfoReady`
+                if let Some(engine) = &self.engine {
+                    engine.refresh(need_force_update);
+                }
+            }
+            UserEvent::RedrawTray => self.redraw_tray(),
+            UserEvent::CheckWatchers => self.check_watchers(),
+            UserEvent::ThemeChanged(theme) => {
+                // 图标按缓存的主题渲染，无需重新枚举设备
+                SystemTheme::set_current(theme);
+                self.redraw_tray();
+            }
+            UserEvent::BluetoothInfoReady(new_bt_info, need_force_update) => {
+                let new_bt_info = match new_bt_info {
+                    Ok(infos) => infos,
                     Err(e) => {
-                        app_notify(format!("Failed to find bluetooth devices - {e}"));
+                        // 蓝牙关闭时枚举失败是预期的，托盘已显示适配器状态；初始化期间由重试处理
+                        if self.tray_state.radio_state == BluetoothRadioState::On
+                            && !self.tray_state.initializing
+                        {
+                            app_notify(e.to_string());
+                        }
                         return;
                     }
                 };
 
-                let new_bt_info =
-                    match get_bluetooth_info((&bluetooth_devices.0, &bluetooth_devices.1)) {
-                        Ok(infos) => infos,
-                        Err(e) => {
-                            app_notify(format!("Failed to get bluetooth devices info - {e}"));
-                            return;
-                        }
-                    };
+                // 定时刷新可能先于初始化重试完成，此时同样需要结束初始化状态
+                let initialized = std::mem::take(&mut self.tray_state.initializing);
+                // 此前显示的是上次保存或逐个读取的设备信息，不与其比较发送通知或产生设备事件，
+                // 并完整执行记录历史、监听设备等后续更新
+                let restored = std::mem::take(&mut self.tray_state.restored);
+                let need_force_update = need_force_update || initialized || restored;
 
-                let config = Arc::clone(&self.config);
+                record_last_updated(&new_bt_info);
 
-                if let Some(e) = compare_bt_info_to_send_notifications(
-                    &config,
-                    Arc::clone(&self.notified_low_battery_devices),
-                    Arc::clone(&self.bluetooth_info),
-                    &new_bt_info,
-                ) {
-                    e.expect("Failed to compare bluetooth info");
+                self.export_status(&new_bt_info);
+
+                if self.apply_bt_info(&new_bt_info, initialized || restored) {
+                    if let Some(ipc_server) = &self.ipc_server {
+                        ipc_server.publish(&new_bt_info);
+                    }
+
+                    self.rules.evaluate(&new_bt_info);
+
+                    self.sync_watchers();
                 } else {
                     // 避免菜单事件或配置更新后，因蓝牙信息无变化而不执行后续更新代码
                     if !need_force_update {
+                        self.refresh_data_age(&new_bt_info);
                         return;
                     }
                 }
 
-                let (tray_menu, new_tray_check_menus) = match create_menu(&config, &new_bt_info) {
-                    Ok(menu) => menu,
-                    Err(e) => {
-                        app_notify(format!("Failed to create tray  menu - {e}"));
-                        return;
-                    }
-                };
-
-                if let Some(tray) = &self.tray.lock().unwrap().as_mut() {
-                    let icon = load_battery_icon(&config, &new_bt_info)
-                        .expect("Failed to load battery icon");
-                    let bluetooth_tooltip_info = convert_tray_info(&new_bt_info, &config);
-                    tray.set_menu(Some(Box::new(tray_menu)));
-                    tray.set_tooltip(Some(bluetooth_tooltip_info.join("\n")))
-                        .expect("Failed to update tray tooltip");
-                    tray.set_icon(Some(icon)).expect("Failed to set tray icon");
+                // 菜单事件或配置更新后需重建菜单，否则设备列表不变时原地更新
+                if need_force_update {
+                    self.rebuild_tray(&new_bt_info);
+                } else {
+                    self.refresh_tray(&new_bt_info, true);
                 }
 
-                if let Some(tray_check_menus) = self.tray_check_menus.lock().unwrap().as_mut() {
-                    *tray_check_menus = new_tray_check_menus;
+                if initialized {
+                    self.show_pending_onboarding();
                 }
             }
-            UserEvent::UpdateTrayForBluetooth(bluetooth_info) => {
+            UserEvent::InitialDevice(info) => {
+                // 首次枚举结束后由 `BluetoothInfoReady` 统一更新
+                if !self.tray_state.initializing {
+                    return;
+                }
+
+                let current_bt_infos = {
+                    let mut bluetooth_info = self.bluetooth_info.lock().unwrap();
+                    bluetooth_info.retain(|current| current.address != info.address);
+                    bluetooth_info.insert(info.clone());
+                    bluetooth_info.clone()
+                };
+                record_last_updated(&HashSet::from([info]));
+                self.refresh_tray(&current_bt_infos, true);
+            }
+            UserEvent::UpdateTrayForBluetooth(changed_bt_infos) => {
+                let changed_addresses = changed_bt_infos
+                    .iter()
+                    .map(|info| info.address)
+                    .collect::<Vec<_>>();
                 println!(
                     "Need to update the info immediately: {}",
-                    bluetooth_info.name
+                    changed_bt_infos
+                        .iter()
+                        .map(|info| info.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
                 );
-                let update_bt_info_address = bluetooth_info.address;
 
                 let current_bt_infos = {
-                    let mut original_bt_info = self.bluetooth_info.lock().unwrap();
-                    original_bt_info.retain(|i| i.address != bluetooth_info.address);
-                    original_bt_info.insert(bluetooth_info);
-                    original_bt_info.clone()
+                    let mut current_bt_infos = self.bluetooth_info.lock().unwrap().clone();
+                    current_bt_infos.retain(|i| !changed_addresses.contains(&i.address));
+                    current_bt_infos.extend(changed_bt_infos);
+                    current_bt_infos
                 };
 
-                let config = Arc::clone(&self.config);
+                self.update_tray_for_bluetooth(current_bt_infos, &changed_addresses);
+            }
+            UserEvent::AddBluetoothInfo(bluetooth_info) => {
+                log::info!("Bluetooth device paired: {}", bluetooth_info.name);
+                let add_bt_info_address = bluetooth_info.address;
 
-                let (tray_menu, new_tray_check_menus) =
-                    match create_menu(&config, &current_bt_infos) {
-                        Ok(menu) => menu,
-                        Err(e) => {
-                            app_notify(format!("Failed to create tray menu - {e}"));
-                            return;
-                        }
-                    };
-
-                if let Some(tray) = &self.tray.lock().unwrap().as_mut() {
-                    let bluetooth_tooltip_info = convert_tray_info(&current_bt_infos, &config);
-                    tray.set_menu(Some(Box::new(tray_menu)));
-                    tray.set_tooltip(Some(bluetooth_tooltip_info.join("\n")))
-                        .expect("Failed to update tray tooltip");
-
-                    let tray_icon_bt_address = {
-                        self.config
-                            .tray_options
-                            .tray_icon_source
-                            .lock()
-                            .unwrap()
-                            .get_address()
-                    };
+                let current_bt_infos = {
+                    let mut current_bt_infos = self.bluetooth_info.lock().unwrap().clone();
+                    current_bt_infos.retain(|i| i.address != bluetooth_info.address);
+                    current_bt_infos.insert(bluetooth_info);
+                    current_bt_infos
+                };
 
-                    if let Some(tray_icon_bt_address) = tray_icon_bt_address
-                        && tray_icon_bt_address == update_bt_info_address
-                    {
-                        let icon = load_battery_icon(&config, &current_bt_infos)
-                            .expect("Failed to load battery icon");
-                        tray.set_icon(Some(icon)).expect("Failed to set tray icon");
+                self.update_tray_for_bluetooth(current_bt_infos, &[add_bt_info_address]);
+                self.sync_watchers();
+            }
+            UserEvent::RemoveBluetoothInfo(address) => {
+                // 已取消配对的设备不再显示为读取失败，也不再保留其上次的信息
+                set_device_errors(
+                    get_device_errors()
+                        .into_iter()
+                        .filter(|error| error.address != address)
+                        .collect(),
+                );
+
+                let current_bt_infos = {
+                    let mut current_bt_infos = self.bluetooth_info.lock().unwrap().clone();
+                    current_bt_infos.retain(|i| i.address != address);
+                    current_bt_infos
+                };
+
+                self.update_tray_for_bluetooth(current_bt_infos, &[address]);
+                self.sync_watchers();
+            }
+            // tray-icon 只会重新添加旧的图标与提示，且启动时创建失败的托盘需在此时创建
+            UserEvent::SystemEvent(SystemEvent::TaskbarCreated) => {
+                let bluetooth_devices_info = self.bluetooth_info.lock().unwrap().clone();
+                self.recreate_tray(&bluetooth_devices_info);
+            }
+            UserEvent::SystemEvent(system_event) => {
+                log::debug!("System event: {system_event:?}");
+                let Some(engine) = &self.engine else {
+                    return;
+                };
+
+                match system_event {
+                    SystemEvent::Suspend => engine.set_suspended(true),
+                    // 唤醒或解锁后立即刷新，避免显示睡眠前的旧数据
+                    SystemEvent::Resume | SystemEvent::SessionUnlock => {
+                        engine.set_suspended(false);
+                        engine.refresh(true);
+                    }
+                    SystemEvent::SessionLock | SystemEvent::TaskbarCreated => (),
+                }
+            }
+            UserEvent::ResumeMonitoring => self.resume_monitoring(),
+            UserEvent::Initialized(result) => {
+                // 定时刷新已先完成初始化
+                if !self.tray_state.initializing {
+                    return;
+                }
+
+                match result {
+                    Ok(infos) => {
+                        self.user_event(event_loop, UserEvent::BluetoothInfoReady(Ok(infos), true))
                     }
+                    Err(e) => {
+                        self.tray_state.initializing = false;
+                        app_notify(format!("Failed to initialize Bluetooth devices - {e}"));
+                        self.show_pending_onboarding();
+
+                        let current_bt_infos = self.bluetooth_info.lock().unwrap().clone();
+                        self.rebuild_tray(&current_bt_infos);
+                    }
+                }
+            }
+            UserEvent::UpdateAvailable(release) => {
+                let loc = Localization::current();
+                app_notify(format!(
+                    "{} v{}",
+                    loc.new_version_available, release.version
+                ));
+
+                let current_bt_infos = self.bluetooth_info.lock().unwrap().clone();
+                self.rebuild_tray(&current_bt_infos);
+            }
+            UserEvent::UpdateInstalled(result) => match result {
+                Ok(exe_path) => {
+                    self.relaunch = Some(exe_path);
+                    event_loop.exit();
+                }
+                Err(e) => {
+                    app_notify(format!("Failed to install the update - {e}"));
+                    MenuHandlers::open_release_page();
+                }
+            },
+            UserEvent::ShowDeviceDetails(address) => MenuHandlers::show_device_details(
+                &self.config,
+                &self.history,
+                &self.bluetooth_info.lock().unwrap(),
+                address,
+            ),
+            UserEvent::ShowSummary => self.show_summary(),
+            UserEvent::OnboardingFinished(choice) => {
+                if let Err(e) = choice.apply(&self.config) {
+                    app_notify(format!("Failed to apply the onboarding settings - {e}"));
+                }
+                self.sync_watchers();
+                if self.config.refresh_tray.swap(false, Ordering::SeqCst) {
+                    self.redraw_tray();
                 }
+            }
+            UserEvent::RadioStateChanged(radio_state) => {
+                if radio_state == self.tray_state.radio_state {
+                    return;
+                }
+                self.tray_state.radio_state = radio_state;
+                // 蓝牙关闭后不再等待初始化，重新打开时会立即刷新
+                if radio_state != BluetoothRadioState::On {
+                    self.tray_state.initializing = false;
+                    self.show_pending_onboarding();
+                }
+
+                let current_bt_infos = self.bluetooth_info.lock().unwrap().clone();
+                self.rebuild_tray(&current_bt_infos);
 
-                if let Some(tray_check_menus) = self.tray_check_menus.lock().unwrap().as_mut() {
-                    *tray_check_menus = new_tray_check_menus;
+                // 蓝牙重新打开后立即刷新设备信息
+                if radio_state == BluetoothRadioState::On
+                    && let Some(engine) = &self.engine
+                {
+                    engine.refresh(true);
                 }
             }
         }
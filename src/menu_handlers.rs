@@ -1,12 +1,35 @@
-use std::{collections::HashSet, ops::Deref, path::Path, sync::atomic::Ordering};
+use std::{
+    collections::HashSet,
+    ops::Deref,
+    sync::{Arc, atomic::Ordering},
+    time::Duration,
+};
 
 use crate::{
-    bluetooth::info::BluetoothInfo,
-    config::{Config, TrayIconSource},
+    audio::{audio_endpoint, audio_profile},
+    bluetooth::{
+        audio::{AudioProfile, set_default_audio_endpoint},
+        ble::{BleConnectionInfo, BlePhy, get_ble_connection_info},
+        control::{connect_device, disconnect_device},
+        info::{BluetoothInfo, BluetoothType},
+        radio::{BluetoothRadioState, set_bluetooth_radio_state},
+    },
+    cli::format_address,
+    clipboard::set_clipboard_text,
+    config::{BatteryDisplay, Config, DeviceFilter, TrayIconSource, TruncateStrategy},
+    diagnostics::create_diagnostics_report,
+    dialog::{input_dialog, save_file_dialog, show_message},
+    export::{format_status_json, format_status_text},
+    history::{History, battery_statistics, export_history},
+    language::Localization,
     notify::app_notify,
+    reconnect::skip_reconnect,
     startup::set_startup,
+    updater::get_available_update,
 };
 
+use chrono::{DateTime, Local};
+use log::warn;
 use tray_icon::menu::CheckMenuItem;
 use winit::event_loop::ActiveEventLoop;
 
@@ -21,6 +44,25 @@ impl MenuHandlers {
         config.force_update.store(true, Ordering::SeqCst)
     }
 
+    /// 列出最近一次枚举中读取失败的设备及原因
+    pub fn show_device_errors(config: &Config) {
+        let loc = Localization::current();
+        let text = config
+            .get_visible_device_errors()
+            .iter()
+            .map(|error| {
+                format!(
+                    "{} ({})\n{}",
+                    config.get_device_aliases_name(&error.name),
+                    format_address(error.address),
+                    error.error
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        show_message(loc.device_errors.to_owned(), text);
+    }
+
     pub fn startup(tray_check_menus: Vec<CheckMenuItem>) {
         if let Some(item) = tray_check_menus.iter().find(|item| item.id() == "startup") {
             set_startup(item.is_checked()).expect("Failed to set Launch at Startup")
@@ -53,24 +95,404 @@ impl MenuHandlers {
             }
 
             config.save();
-            config.force_update.store(true, Ordering::SeqCst);
+            config.refresh_tray.store(true, Ordering::SeqCst);
         }
     }
 
-    pub fn open_config() {
-        let config_path = std::env::current_exe()
-            .ok()
-            .and_then(|exe_path| exe_path.parent().map(Path::to_path_buf))
-            .map(|parent_path| parent_path.join("BlueGauge.toml"))
-            .expect("Failed to get config path");
+    pub fn open_config(config: &Config) {
         if let Err(e) = std::process::Command::new("notepad.exe")
-            .arg(config_path)
+            .arg(&config.config_path)
             .spawn()
         {
             app_notify(format!("Failed to open config file - {e}"));
         };
     }
 
+    /// 重新读取配置文件并应用，通知变化的项目；读取或校验失败时保留当前设置
+    pub fn reload_config(config: &Config) {
+        let reload = match config.reload() {
+            Ok(reload) => reload,
+            Err(e) => {
+                app_notify(format!("Failed to reload config - {e}"));
+                return;
+            }
+        };
+
+        let loc = Localization::current();
+        if reload.applied.is_empty() && reload.restart_required.is_empty() {
+            app_notify(loc.config_unchanged);
+            return;
+        }
+
+        let mut text = loc.config_reloaded.to_owned();
+        if !reload.applied.is_empty() {
+            text.push_str(&format!("\n{}", changed_items_text(&reload.applied)));
+        }
+        if !reload.restart_required.is_empty() {
+            text.push_str(&format!(
+                "\n{}: {}",
+                loc.restart_required,
+                changed_items_text(&reload.restart_required)
+            ));
+        }
+        app_notify(text);
+
+        // 以新设置重建托盘菜单及图标
+        config.force_update.store(true, Ordering::SeqCst);
+    }
+
+    pub fn open_release_page() {
+        let Some(release) = get_available_update() else {
+            return;
+        };
+
+        if let Err(e) = std::process::Command::new("explorer.exe")
+            .arg(&release.page_url)
+            .spawn()
+        {
+            app_notify(format!("Failed to open the release page - {e}"));
+        };
+    }
+
+    /// 切换蓝牙开关可能需要数秒，完成后由适配器状态变化事件更新托盘
+    pub fn toggle_bluetooth_radio(radio_state: BluetoothRadioState) {
+        let on = radio_state != BluetoothRadioState::On;
+        std::thread::spawn(move || {
+            if let Err(e) = set_bluetooth_radio_state(on) {
+                app_notify(format!("Failed to switch Bluetooth - {e}"));
+            }
+        });
+    }
+
+    /// `pause_monitoring` 直到手动恢复，`pause_monitoring:{seconds}` 暂停指定时长
+    pub fn pause_duration(menu_event_id: &str) -> Option<Duration> {
+        menu_event_id
+            .strip_prefix("pause_monitoring:")
+            .and_then(|seconds| seconds.parse().ok())
+            .map(Duration::from_secs)
+    }
+
+    /// `export_history` 导出所有设备，`export_history:{address}` 导出单个设备
+    pub fn export_history(history: &History, menu_event_id: &str) {
+        let address = menu_event_id
+            .strip_prefix("export_history:")
+            .and_then(|address| address.parse::<u64>().ok());
+
+        let Some(path) = save_file_dialog(
+            "BlueGauge-history.csv",
+            "csv",
+            &[("CSV (*.csv)", "*.csv"), ("JSON (*.json)", "*.json")],
+        ) else {
+            return;
+        };
+
+        let result = history
+            .load(address)
+            .and_then(|samples| export_history(&samples, &path));
+
+        match result {
+            Ok(()) => {
+                let loc = Localization::current();
+                app_notify(loc.history_exported);
+            }
+            Err(e) => app_notify(format!("Failed to export history - {e}")),
+        }
+    }
+
+    /// 选择保存位置后在后台生成诊断报告，完成后通知
+    pub fn create_diagnostics_report(
+        config: Arc<Config>,
+        bluetooth_devices_info: HashSet<BluetoothInfo>,
+    ) {
+        let Some(path) = save_file_dialog(
+            "BlueGauge-diagnostics.zip",
+            "zip",
+            &[("ZIP (*.zip)", "*.zip")],
+        ) else {
+            return;
+        };
+
+        std::thread::spawn(move || {
+            match create_diagnostics_report(&config, &bluetooth_devices_info, &path) {
+                Ok(()) => {
+                    let loc = Localization::current();
+                    app_notify(loc.diagnostics_report_created);
+                }
+                Err(e) => app_notify(format!("Failed to create the diagnostics report - {e}")),
+            }
+        });
+    }
+
+    /// `copy_status:text` 复制文本，`copy_status:json` 复制 JSON
+    pub fn copy_status(
+        config: &Config,
+        bluetooth_devices_info: &HashSet<BluetoothInfo>,
+        menu_event_id: &str,
+    ) {
+        let status = match menu_event_id {
+            "copy_status:json" => format_status_json(bluetooth_devices_info),
+            _ => Ok(format_status_text(bluetooth_devices_info, config)),
+        };
+
+        if let Err(e) = status.and_then(|status| set_clipboard_text(&status)) {
+            app_notify(format!("Failed to copy status - {e}"));
+        }
+    }
+
+    /// `battery_statistics:{address}` 或 `device_details:{address}` 显示该设备的详情
+    pub fn show_battery_statistics(
+        config: &Config,
+        history: &History,
+        bluetooth_devices_info: &HashSet<BluetoothInfo>,
+        menu_event_id: &str,
+    ) {
+        if let Some(address) = menu_event_id
+            .split_once(':')
+            .and_then(|(_, address)| address.parse::<u64>().ok())
+        {
+            Self::show_device_details(config, history, bluetooth_devices_info, address);
+        }
+    }
+
+    /// 显示设备的当前电量、连接状态，以及由电量历史推算的充电循环、耗电速度及最低电量
+    pub fn show_device_details(
+        config: &Config,
+        history: &History,
+        bluetooth_devices_info: &HashSet<BluetoothInfo>,
+        address: u64,
+    ) {
+        let samples = match history.load(Some(address)) {
+            Ok(samples) => samples,
+            Err(e) => {
+                app_notify(format!("Failed to load history - {e}"));
+                return;
+            }
+        };
+
+        let loc = Localization::current();
+        let current = bluetooth_devices_info
+            .iter()
+            .find(|info| info.address == address);
+        let name = match (current, samples.last()) {
+            (Some(info), _) => &info.name,
+            (None, Some(sample)) => &sample.name,
+            (None, None) => {
+                show_message(
+                    loc.battery_statistics.to_owned(),
+                    loc.no_history_data.to_owned(),
+                );
+                return;
+            }
+        };
+
+        let mut text = format!(
+            "{}\n{}",
+            config.get_device_aliases_name(name),
+            format_address(address)
+        );
+        if let Some(info) = current {
+            let status = if info.status {
+                loc.connected
+            } else {
+                loc.disconnected
+            };
+            text.push_str(&format!("\n{}% · {status}", info.battery));
+        }
+        // 免提模式音质较差且更耗电
+        if let Some(profile) = audio_profile(address) {
+            let profile = match profile {
+                AudioProfile::Stereo => loc.audio_stereo,
+                AudioProfile::HandsFree => loc.audio_hands_free,
+            };
+            text.push_str(&format!("\n{}: {profile}", loc.audio_profile));
+        }
+        let ble_address = current
+            .filter(|info| info.status && info.r#type == BluetoothType::LowEnergy)
+            .map(|info| info.address);
+
+        if samples.is_empty() {
+            text.push_str(&format!("\n\n{}", loc.no_history_data));
+            show_details(loc, text, ble_address);
+            return;
+        }
+
+        let statistics = battery_statistics(&samples, config.get_low_battery());
+        let format_or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_owned());
+        let since = statistics
+            .since
+            .and_then(|timestamp| DateTime::from_timestamp(timestamp as i64, 0))
+            .map(|time| time.with_timezone(&Local).format("%Y-%m-%d").to_string());
+
+        text.push_str(&format!(
+            "\n\n{}: {}\n{}: {}\n{}: {}\n{}: {}",
+            loc.charge_cycles,
+            statistics.cycles,
+            loc.average_drain,
+            format_or_dash(
+                statistics
+                    .drain_per_hour
+                    .map(|drain| format!("{drain:.1}%/h"))
+            ),
+            loc.lowest_battery,
+            format_or_dash(statistics.lowest.map(|lowest| format!("{lowest}%"))),
+            loc.tracked_since,
+            format_or_dash(since),
+        ));
+        show_details(loc, text, ble_address);
+    }
+
+    /// `toggle_connection:{address}`，按设备当前状态连接或断开
+    pub fn toggle_device_connection(
+        config: Arc<Config>,
+        bluetooth_devices_info: &HashSet<BluetoothInfo>,
+        menu_event_id: &str,
+    ) {
+        if let Some(info) = find_menu_device(bluetooth_devices_info, menu_event_id) {
+            Self::switch_connection(config, info);
+        }
+    }
+
+    /// 在后台连接未连接的设备或断开已连接的设备，完成后刷新
+    pub fn switch_connection(config: Arc<Config>, info: BluetoothInfo) {
+        std::thread::spawn(move || {
+            let result = if info.status {
+                skip_reconnect(info.address);
+                disconnect_device(&info)
+            } else {
+                connect_device(&info)
+            };
+            match result {
+                Ok(()) => config.force_update.store(true, Ordering::SeqCst),
+                Err(e) => app_notify(format!("Failed to switch the connection - {e}")),
+            }
+        });
+    }
+
+    /// `set_default_audio:{address}`，在后台设为默认播放设备，托盘在读取到变化后更新
+    pub fn set_default_audio(bluetooth_devices_info: &HashSet<BluetoothInfo>, menu_event_id: &str) {
+        let Some(info) = find_menu_device(bluetooth_devices_info, menu_event_id) else {
+            return;
+        };
+        let Some(endpoint_id) = audio_endpoint(info.address) else {
+            return;
+        };
+
+        std::thread::spawn(move || {
+            if let Err(e) = set_default_audio_endpoint(&endpoint_id) {
+                app_notify(format!(
+                    "Failed to set {} as the default audio device - {e}",
+                    info.name
+                ));
+            }
+        });
+    }
+
+    /// `rename_device:{address}`，输入的名称保存为设备别名
+    pub fn rename_device(
+        config: Arc<Config>,
+        bluetooth_devices_info: &HashSet<BluetoothInfo>,
+        menu_event_id: &str,
+    ) {
+        let Some(info) = find_menu_device(bluetooth_devices_info, menu_event_id) else {
+            return;
+        };
+
+        let loc = Localization::current();
+        let alias = config.get_device_aliases_name(&info.name);
+        input_dialog(
+            info.name.clone(),
+            loc.rename_prompt.to_owned(),
+            alias,
+            move |alias| {
+                config.set_device_alias(&info.name, &alias);
+                config.save();
+                config.refresh_tray.store(true, Ordering::SeqCst);
+            },
+        );
+    }
+
+    /// `hide_device:{address}`，以地址记录，设备改名后仍然隐藏
+    pub fn hide_device(
+        config: &Config,
+        bluetooth_devices_info: &HashSet<BluetoothInfo>,
+        menu_event_id: &str,
+    ) {
+        let Some(info) = find_menu_device(bluetooth_devices_info, menu_event_id) else {
+            return;
+        };
+
+        {
+            let mut hidden_devices = config.hidden_devices.lock().unwrap();
+            if !hidden_devices.iter().any(|device| info.matches(device)) {
+                hidden_devices.push(format_address(info.address));
+            }
+        }
+        config.save();
+        config.refresh_tray.store(true, Ordering::SeqCst);
+    }
+
+    /// `pin_device:{address}`，固定时以地址记录，取消固定时移除与该设备匹配的名称或地址
+    pub fn toggle_pin_device(
+        config: &Config,
+        bluetooth_devices_info: &HashSet<BluetoothInfo>,
+        menu_event_id: &str,
+    ) {
+        let Some(info) = find_menu_device(bluetooth_devices_info, menu_event_id) else {
+            return;
+        };
+
+        {
+            let mut pinned_devices = config.pinned_devices.lock().unwrap();
+            if pinned_devices.iter().any(|device| info.matches(device)) {
+                pinned_devices.retain(|device| !info.matches(device));
+            } else {
+                pinned_devices.push(format_address(info.address));
+            }
+        }
+        config.save();
+        config.refresh_tray.store(true, Ordering::SeqCst);
+    }
+
+    /// `keep_connected:{address}`，与固定设备相同，以地址记录
+    pub fn toggle_keep_connected(
+        config: &Config,
+        bluetooth_devices_info: &HashSet<BluetoothInfo>,
+        menu_event_id: &str,
+    ) {
+        let Some(info) = find_menu_device(bluetooth_devices_info, menu_event_id) else {
+            return;
+        };
+
+        {
+            let mut keep_connected_devices = config.keep_connected_devices.lock().unwrap();
+            if keep_connected_devices
+                .iter()
+                .any(|device| info.matches(device))
+            {
+                keep_connected_devices.retain(|device| !info.matches(device));
+            } else {
+                keep_connected_devices.push(format_address(info.address));
+            }
+        }
+        config.save();
+        config.refresh_tray.store(true, Ordering::SeqCst);
+    }
+
+    /// `unhide_device:{device}`
+    pub fn unhide_device(config: &Config, menu_event_id: &str) {
+        let Some(device) = menu_event_id.strip_prefix("unhide_device:") else {
+            return;
+        };
+
+        config
+            .hidden_devices
+            .lock()
+            .unwrap()
+            .retain(|hidden_device| hidden_device != device);
+        config.save();
+        config.refresh_tray.store(true, Ordering::SeqCst);
+    }
+
     pub fn set_update_interval(
         config: &Config,
         menu_event_id: &str,
@@ -125,6 +547,21 @@ impl MenuHandlers {
         config.force_update.store(true, Ordering::SeqCst);
     }
 
+    pub fn set_adaptive_update_interval(
+        config: &Config,
+        menu_event_id: &str,
+        tray_check_menus: Vec<CheckMenuItem>,
+    ) {
+        if let Some(item) = tray_check_menus
+            .iter()
+            .find(|item| item.id().as_ref() == menu_event_id)
+        {
+            config.tray_options.update(menu_event_id, item.is_checked());
+            config.save();
+            config.force_update.store(true, Ordering::SeqCst);
+        }
+    }
+
     pub fn set_notify_low_battery(
         config: &Config,
         menu_event_id: &str,
@@ -217,26 +654,176 @@ impl MenuHandlers {
             }
         }
 
+        config.refresh_tray.store(true, Ordering::SeqCst);
+    }
+
+    /// 最大长度单选，再次点击已选中的项时保持选中
+    pub fn set_truncate_length(
+        config: &Config,
+        menu_event_id: &str,
+        tray_check_menus: Vec<CheckMenuItem>,
+    ) {
+        let Some(truncate_length) = menu_event_id
+            .strip_prefix("truncate_length:")
+            .and_then(|length| length.parse::<u8>().ok())
+        else {
+            return;
+        };
+
+        tray_check_menus
+            .iter()
+            .filter(|item| item.id().as_ref().starts_with("truncate_length:"))
+            .for_each(|item| item.set_checked(item.id().as_ref() == menu_event_id));
+
+        config
+            .tray_options
+            .tooltip_options
+            .truncate_length
+            .store(truncate_length, Ordering::Relaxed);
+        config.save();
+        config.refresh_tray.store(true, Ordering::SeqCst);
+    }
+
+    pub fn set_truncate_strategy(
+        config: &Config,
+        menu_event_id: &str,
+        tray_check_menus: Vec<CheckMenuItem>,
+    ) {
+        let truncate_strategy = match menu_event_id {
+            "truncate_middle" => TruncateStrategy::Middle,
+            _ => TruncateStrategy::End,
+        };
+
+        tray_check_menus
+            .iter()
+            .filter(|item| ["truncate_end", "truncate_middle"].contains(&item.id().as_ref()))
+            .for_each(|item| item.set_checked(item.id().as_ref() == menu_event_id));
+
+        *config
+            .tray_options
+            .tooltip_options
+            .truncate_strategy
+            .lock()
+            .unwrap() = truncate_strategy;
+        config.save();
+        config.refresh_tray.store(true, Ordering::SeqCst);
+    }
+
+    pub fn set_device_filter(
+        config: &Config,
+        menu_event_id: &str,
+        tray_check_menus: Vec<CheckMenuItem>,
+    ) {
+        let device_filter = match menu_event_id {
+            "device_filter:audio" => DeviceFilter::Audio,
+            "device_filter:input" => DeviceFilter::Input,
+            _ => DeviceFilter::All,
+        };
+
+        tray_check_menus
+            .iter()
+            .filter(|item| item.id().as_ref().starts_with("device_filter:"))
+            .for_each(|item| item.set_checked(item.id().as_ref() == menu_event_id));
+
+        *config
+            .tray_options
+            .tooltip_options
+            .device_filter
+            .lock()
+            .unwrap() = device_filter;
+        config.save();
+        config.refresh_tray.store(true, Ordering::SeqCst);
+    }
+
+    pub fn set_battery_display(
+        config: &Config,
+        menu_event_id: &str,
+        tray_check_menus: Vec<CheckMenuItem>,
+    ) {
+        let battery_display = match menu_event_id {
+            "battery_display:round5" => BatteryDisplay::Round5,
+            "battery_display:round10" => BatteryDisplay::Round10,
+            "battery_display:verbal" => BatteryDisplay::Verbal,
+            _ => BatteryDisplay::Exact,
+        };
+
+        tray_check_menus
+            .iter()
+            .filter(|item| item.id().as_ref().starts_with("battery_display:"))
+            .for_each(|item| item.set_checked(item.id().as_ref() == menu_event_id));
+
+        *config
+            .tray_options
+            .tooltip_options
+            .battery_display
+            .lock()
+            .unwrap() = battery_display;
+        config.save();
+        config.refresh_tray.store(true, Ordering::SeqCst);
+    }
+
+    /// `profile:{name}`，整体替换托盘及通知设置后重建托盘
+    pub fn switch_profile(
+        config: &Config,
+        menu_event_id: &str,
+        tray_check_menus: Vec<CheckMenuItem>,
+    ) {
+        let Some(name) = menu_event_id.strip_prefix("profile:") else {
+            return;
+        };
+
+        tray_check_menus
+            .iter()
+            .filter(|item| item.id().as_ref().starts_with("profile:"))
+            .for_each(|item| item.set_checked(item.id().as_ref() == menu_event_id));
+
+        if let Err(e) = config.switch_profile(name) {
+            app_notify(format!("Failed to switch profile - {e}"));
+            return;
+        }
+        config.save();
         config.force_update.store(true, Ordering::SeqCst);
     }
 
+    /// 输入名称后以当前设置保存配置方案，同名时覆盖
+    pub fn save_profile(config: Arc<Config>) {
+        let loc = Localization::current();
+        let name = config.get_active_profile().unwrap_or_default();
+        input_dialog(
+            loc.profiles.to_owned(),
+            loc.profile_name_prompt.to_owned(),
+            name,
+            move |name| {
+                let name = name.trim();
+                if name.is_empty() {
+                    return;
+                }
+                config.save_profile(name);
+                config.save();
+                config.refresh_tray.store(true, Ordering::SeqCst);
+            },
+        );
+    }
+
     pub fn set_tray_icon_source(
-        bluetooth_devices_info: HashSet<BluetoothInfo>,
         config: &Config,
         menu_event_id: &str,
         tray_check_menus: Vec<CheckMenuItem>,
-    ) -> Option<BluetoothInfo> {
+    ) {
         let not_bluetooth_item_id = [
             "quit",
             "force_update",
             "startup",
             "open_config",
+            "reload_config",
+            "diagnostics_report",
             "15",
             "30",
             "60",
             "300",
             "600",
             "1800",
+            "adaptive_update_interval",
             "0.01",
             "0.05",
             "0.1",
@@ -248,9 +835,22 @@ impl MenuHandlers {
             "reconnection",
             "added",
             "removed",
+            "renamed",
+            "pinned_only",
+            "milestones",
             "show_disconnected",
             "truncate_name",
             "prefix_battery",
+            "show_last_updated",
+            "battery_bar",
+            "summary_header",
+            "show_default_audio",
+            "hide_device_list",
+            "hide_tooltip",
+            "rich_flyout",
+            "truncate_end",
+            "truncate_middle",
+            "strip_affixes",
         ];
 
         let show_battery_icon_bt_address = menu_event_id.parse::<u64>().expect("Menu Event Id");
@@ -258,7 +858,13 @@ impl MenuHandlers {
         // 只处理显示蓝牙电量图标相关的菜单项
         let bluetooth_menus: Vec<_> = tray_check_menus
             .iter()
-            .filter(|item| !not_bluetooth_item_id.contains(&item.id().as_ref()))
+            .filter(|item| {
+                !not_bluetooth_item_id.contains(&item.id().as_ref())
+                    && !item.id().as_ref().starts_with("truncate_length:")
+                    && !item.id().as_ref().starts_with("device_filter:")
+                    && !item.id().as_ref().starts_with("battery_display:")
+                    && !item.id().as_ref().starts_with("profile:")
+            })
             .collect();
 
         let new_bt_menu_is_checked = bluetooth_menus
@@ -272,52 +878,107 @@ impl MenuHandlers {
 
         let mut original_tray_icon_source = config.tray_options.tray_icon_source.lock().unwrap();
 
-        let need_watch = match original_tray_icon_source.deref() {
-            TrayIconSource::App if new_bt_menu_is_checked => {
-                let have_custom_icons = std::env::current_exe()
-                    .ok()
-                    .and_then(|exe_path| exe_path.parent().map(Path::to_path_buf))
-                    .map(|p| (0..=100).all(|i| p.join(format!("assets\\{i}.png")).is_file()))
-                    .unwrap_or(false);
-
-                if have_custom_icons {
-                    *original_tray_icon_source = TrayIconSource::BatteryCustom {
-                        address: show_battery_icon_bt_address.to_owned(),
-                    };
-                } else {
-                    *original_tray_icon_source = TrayIconSource::BatteryFont {
-                        address: show_battery_icon_bt_address.to_owned(),
-                        font_name: "Arial".to_owned(),
-                        font_color: Some("FollowSystemTheme".to_owned()),
-                        font_size: Some(64),
-                    };
-                };
-
-                bluetooth_devices_info
-                    .iter()
-                    .find(|i| i.address == show_battery_icon_bt_address)
-                    .cloned()
+        match original_tray_icon_source.deref() {
+            // 勾选设备后由最低电量模式切换为显示该设备
+            TrayIconSource::App | TrayIconSource::LowestBattery { .. }
+                if new_bt_menu_is_checked =>
+            {
+                *original_tray_icon_source = TrayIconSource::battery(show_battery_icon_bt_address);
             }
             TrayIconSource::BatteryCustom { .. } | TrayIconSource::BatteryFont { .. } => {
                 if new_bt_menu_is_checked {
                     original_tray_icon_source.update_address(show_battery_icon_bt_address);
-                    bluetooth_devices_info
-                        .iter()
-                        .find(|i| i.address == show_battery_icon_bt_address)
-                        .cloned()
                 } else {
                     *original_tray_icon_source = TrayIconSource::App;
-
-                    None
                 }
             }
-            _ => None,
-        };
+            _ => (),
+        }
 
         // 更新配置
         drop(original_tray_icon_source); // 释放锁，避免在Config的svae发生死锁.
         config.save();
-        config.force_update.store(true, Ordering::SeqCst);
-        need_watch
+        config.refresh_tray.store(true, Ordering::SeqCst);
+    }
+}
+
+/// 由 `{action}:{address}` 形式的菜单 ID 找到对应设备
+/// 显示设备详情。已连接的 BLE 设备在后台读取连接参数后追加到末尾，避免阻塞事件循环
+fn show_details(loc: &'static Localization, mut text: String, ble_address: Option<u64>) {
+    std::thread::spawn(move || {
+        if let Some(address) = ble_address {
+            match get_ble_connection_info(address) {
+                Ok(connection_info) => {
+                    text.push_str(&format!(
+                        "\n\n{}",
+                        ble_connection_text(loc, &connection_info)
+                    ));
+                }
+                Err(e) => warn!("Failed to read the BLE connection parameters - {e}"),
+            }
+        }
+        show_message(loc.battery_statistics.to_owned(), text);
+    });
+}
+
+/// 如 "连接间隔: 30 ms · 延迟: 0 · 超时: 5000 ms\nPHY: 2M / 2M\nMTU: 247"，
+/// 第一个数值为发送、第二个为接收的物理层
+fn ble_connection_text(loc: &Localization, connection_info: &BleConnectionInfo) -> String {
+    let format_or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_owned());
+    let phy_text = |phy: BlePhy| match phy {
+        BlePhy::Le1M => "1M",
+        BlePhy::Le2M => "2M",
+        BlePhy::Coded => "Coded",
+    };
+
+    format!(
+        "{}: {} · {}: {} · {}: {}\nPHY: {}\nMTU: {}",
+        loc.connection_interval,
+        format_or_dash(
+            connection_info
+                .interval_ms
+                .map(|interval| format!("{interval} ms"))
+        ),
+        loc.connection_latency,
+        format_or_dash(connection_info.latency.map(|latency| latency.to_string())),
+        loc.supervision_timeout,
+        format_or_dash(
+            connection_info
+                .timeout_ms
+                .map(|timeout| format!("{timeout} ms"))
+        ),
+        format_or_dash(connection_info.phy.map(|(tx, rx)| format!(
+            "{} / {}",
+            phy_text(tx),
+            phy_text(rx)
+        ))),
+        format_or_dash(connection_info.mtu.map(|mtu| mtu.to_string())),
+    )
+}
+
+fn find_menu_device(
+    bluetooth_devices_info: &HashSet<BluetoothInfo>,
+    menu_event_id: &str,
+) -> Option<BluetoothInfo> {
+    let address = menu_event_id.split_once(':')?.1.parse::<u64>().ok()?;
+    bluetooth_devices_info
+        .iter()
+        .find(|info| info.address == address)
+        .cloned()
+}
+
+/// 通知中最多列出的变化项目
+const MAX_CHANGED_ITEMS: usize = 6;
+
+fn changed_items_text(items: &[String]) -> String {
+    let mut text = items
+        .iter()
+        .take(MAX_CHANGED_ITEMS)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if items.len() > MAX_CHANGED_ITEMS {
+        text.push_str(&format!(" (+{})", items.len() - MAX_CHANGED_ITEMS));
     }
+    text
 }
@@ -1,16 +1,144 @@
-use tauri_winrt_notification::*;
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use tauri_winrt_notification::{Duration, Sound, Toast};
+use windows::{
+    Data::Xml::Dom::XmlDocument,
+    Foundation::TypedEventHandler,
+    UI::Notifications::{ToastNotification, ToastNotificationManager},
+    core::HSTRING,
+};
 
 // HKEY_CLASSES_ROOT\AppUserModelId\Windows.SystemToast.BthQuickPair
 const BLUETOOTH_APP_ID: &str = "Windows.SystemToast.BthQuickPair";
 
-pub fn notify(title: impl AsRef<str>, text: impl AsRef<str>, mute: bool) {
-    Toast::new(BLUETOOTH_APP_ID)
-        .title(title.as_ref())
-        .text1(text.as_ref())
-        .sound((!mute).then_some(Sound::Default))
-        .duration(Duration::Short)
-        .show()
-        .expect("Failied to send notification");
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyEvent {
+    LowBattery,
+    /// 电量降过 `milestone_steps` 中的一个阶梯
+    Milestone,
+    Disconnection,
+    Reconnection,
+    Added,
+    Removed,
+    /// 同一地址的设备名称变化
+    Renamed,
+}
+
+impl NotifyEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::LowBattery => "low_battery",
+            Self::Milestone => "milestone",
+            Self::Disconnection => "disconnection",
+            Self::Reconnection => "reconnection",
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Renamed => "renamed",
+        }
+    }
+
+    /// 操作中心内通知的分组，同一设备同一分组的通知互相替换：
+    /// 重新连接的通知会替换之前的断开连接通知
+    fn group(&self) -> &'static str {
+        match self {
+            Self::LowBattery | Self::Milestone => "low_battery",
+            Self::Disconnection | Self::Reconnection => "connection",
+            Self::Added | Self::Removed | Self::Renamed => "pairing",
+        }
+    }
+}
+
+/// 点击设备通知时的回调，参数为设备地址
+static ACTIVATION_HANDLER: OnceLock<Box<dyn Fn(u64) + Send + Sync>> = OnceLock::new();
+
+pub fn set_activation_handler(handler: impl Fn(u64) + Send + Sync + 'static) {
+    let _ = ACTIVATION_HANDLER.set(Box::new(handler));
+}
+
+/// 设备相关的通知，以设备地址为标签、事件类别为分组，在操作中心替换该设备之前的同类通知；
+/// 通知显示期间点击可打开该设备的详情
+pub fn notify(
+    event: NotifyEvent,
+    title: impl AsRef<str>,
+    text: impl AsRef<str>,
+    mute: bool,
+    address: u64,
+) {
+    let tag = format!("{address:012X}");
+    if let Err(e) = show_toast(
+        title.as_ref(),
+        text.as_ref(),
+        mute,
+        &tag,
+        event.group(),
+        Some(address),
+    ) {
+        warn!("Failed to send notification: {e}");
+    }
+}
+
+/// 所有设备电量的概览，再次弹出时替换上一条
+pub fn summary_notify(title: impl AsRef<str>, text: impl AsRef<str>, mute: bool) {
+    if let Err(e) = show_toast(
+        title.as_ref(),
+        text.as_ref(),
+        mute,
+        "summary",
+        "summary",
+        None,
+    ) {
+        warn!("Failed to send notification: {e}");
+    }
+}
+
+fn show_toast(
+    title: &str,
+    text: &str,
+    mute: bool,
+    tag: &str,
+    group: &str,
+    address: Option<u64>,
+) -> Result<()> {
+    let audio = if mute {
+        r#"<audio silent="true"/>"#
+    } else {
+        ""
+    };
+    let xml = XmlDocument::new()?;
+    xml.LoadXml(&HSTRING::from(format!(
+        r#"<toast duration="short"><visual><binding template="ToastGeneric"><text>{}</text><text>{}</text></binding></visual>{audio}</toast>"#,
+        escape_xml(title),
+        escape_xml(text),
+    )))?;
+
+    let toast = ToastNotification::CreateToastNotification(&xml)?;
+    toast.SetTag(&HSTRING::from(tag))?;
+    toast.SetGroup(&HSTRING::from(group))?;
+    if let Some(address) = address {
+        toast.Activated(&TypedEventHandler::new(move |_, _| {
+            if let Some(handler) = ACTIVATION_HANDLER.get() {
+                handler(address);
+            }
+            Ok(())
+        }))?;
+    }
+
+    ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(BLUETOOTH_APP_ID))?
+        .Show(&toast)?;
+
+    Ok(())
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
 }
 
 pub fn app_notify(text: impl AsRef<str>) {
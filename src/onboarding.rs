@@ -0,0 +1,319 @@
+use crate::{
+    bluetooth::info::BluetoothInfo,
+    config::{Config, TrayIconSource},
+    dialog::{
+        DialogControl, create_controls, create_dialog_window, run_dialog_loop, show_dialog_window,
+    },
+    language::Localization,
+    startup::{get_startup_status, set_startup},
+};
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::atomic::Ordering;
+
+use anyhow::Result;
+use log::warn;
+use windows::{
+    Win32::{
+        Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+        UI::{
+            Controls::BST_CHECKED,
+            WindowsAndMessaging::{
+                BM_GETCHECK, BM_SETCHECK, BS_AUTOCHECKBOX, BS_DEFPUSHBUTTON, BS_PUSHBUTTON,
+                CB_ADDSTRING, CB_GETCURSEL, CB_SETCURSEL, CBS_DROPDOWNLIST, DefWindowProcW,
+                DestroyWindow, GetDlgItem, IDCANCEL, IDOK, PostQuitMessage, SendMessageW,
+                WINDOW_EX_STYLE, WINDOW_STYLE, WM_COMMAND, WM_DESTROY, WS_TABSTOP, WS_VSCROLL,
+            },
+        },
+    },
+    core::{HSTRING, w},
+};
+
+const ONBOARDING_WIDTH: i32 = 420;
+const ONBOARDING_HEIGHT: i32 = 250;
+const TRAY_ICON_COMBO_ID: i32 = 101;
+const LOW_BATTERY_COMBO_ID: i32 = 102;
+const STARTUP_CHECK_ID: i32 = 103;
+/// 与托盘菜单中的低电量选项一致
+const LOW_BATTERY_OPTIONS: [u8; 5] = [5, 10, 15, 20, 25];
+
+thread_local! {
+    /// 确定时的选择：(托盘图标选项, 低电量选项, 开机自启动)
+    static ONBOARDING_RESULT: RefCell<Option<(usize, usize, bool)>> = const { RefCell::new(None) };
+}
+
+/// 首次启动向导的选择结果
+#[derive(Debug)]
+pub struct OnboardingChoice {
+    /// 托盘图标显示电量的设备，`None` 为程序图标
+    pub address: Option<u64>,
+    pub low_battery: u8,
+    pub startup: bool,
+}
+
+impl OnboardingChoice {
+    /// 写入配置并刷新托盘
+    pub fn apply(&self, config: &Config) -> Result<()> {
+        if let Some(address) = self.address {
+            *config.tray_options.tray_icon_source.lock().unwrap() =
+                TrayIconSource::battery(address);
+        }
+        config
+            .notify_options
+            .low_battery
+            .store(self.low_battery, Ordering::Relaxed);
+        config.save();
+        config.refresh_tray.store(true, Ordering::SeqCst);
+
+        // 未设置自启动时删除注册表值会失败，只在状态变化时修改
+        if get_startup_status().unwrap_or(false) != self.startup {
+            set_startup(self.startup)?;
+        }
+        Ok(())
+    }
+}
+
+/// 在后台线程显示首次启动向导：托盘图标显示的设备、低电量阈值及开机自启动。
+/// 默认选中第一个已连接的设备，以便新用户直接看到电量图标；跳过时不调用 `on_finished`
+pub fn show_onboarding(
+    config: &Config,
+    bluetooth_devices_info: &HashSet<BluetoothInfo>,
+    on_finished: impl FnOnce(OnboardingChoice) + Send + 'static,
+) {
+    let mut devices: Vec<&BluetoothInfo> = bluetooth_devices_info.iter().collect();
+    devices.sort_by_key(|info| (!info.status, info.name.to_lowercase()));
+    let devices: Vec<(u64, String)> = devices
+        .into_iter()
+        .map(|info| {
+            let name = config.get_device_aliases_name(&info.name);
+            let label = if info.status {
+                format!("{name} ({}%)", info.battery)
+            } else {
+                name
+            };
+            (info.address, label)
+        })
+        .collect();
+    let low_battery = config.get_low_battery();
+
+    std::thread::spawn(move || match run_onboarding(&devices, low_battery) {
+        Ok(Some((tray_icon_index, low_battery_index, startup))) => {
+            on_finished(OnboardingChoice {
+                // 第一项为程序图标
+                address: tray_icon_index
+                    .checked_sub(1)
+                    .and_then(|index| devices.get(index))
+                    .map(|(address, _)| *address),
+                low_battery: LOW_BATTERY_OPTIONS
+                    .get(low_battery_index)
+                    .copied()
+                    .unwrap_or(low_battery),
+                startup,
+            })
+        }
+        Ok(None) => (),
+        Err(e) => warn!("Failed to show the onboarding window - {e}"),
+    });
+}
+
+fn run_onboarding(
+    devices: &[(u64, String)],
+    low_battery: u8,
+) -> Result<Option<(usize, usize, bool)>> {
+    let loc = Localization::current();
+    let (hwnd, instance) = create_dialog_window(
+        w!("BlueGaugeOnboarding"),
+        Some(onboarding_proc),
+        loc.onboarding_title,
+        ONBOARDING_WIDTH,
+        ONBOARDING_HEIGHT,
+    )?;
+
+    let combo_style = WS_TABSTOP | WS_VSCROLL | WINDOW_STYLE(CBS_DROPDOWNLIST as u32);
+    let controls: [DialogControl; 8] = [
+        (
+            WINDOW_EX_STYLE::default(),
+            w!("STATIC"),
+            loc.onboarding_intro,
+            WINDOW_STYLE::default(),
+            0,
+            12,
+            12,
+            380,
+            36,
+        ),
+        (
+            WINDOW_EX_STYLE::default(),
+            w!("STATIC"),
+            loc.onboarding_tray_icon,
+            WINDOW_STYLE::default(),
+            0,
+            12,
+            62,
+            150,
+            20,
+        ),
+        (
+            WINDOW_EX_STYLE::default(),
+            w!("COMBOBOX"),
+            "",
+            combo_style,
+            TRAY_ICON_COMBO_ID,
+            170,
+            58,
+            222,
+            200,
+        ),
+        (
+            WINDOW_EX_STYLE::default(),
+            w!("STATIC"),
+            loc.onboarding_low_battery,
+            WINDOW_STYLE::default(),
+            0,
+            12,
+            98,
+            150,
+            20,
+        ),
+        (
+            WINDOW_EX_STYLE::default(),
+            w!("COMBOBOX"),
+            "",
+            combo_style,
+            LOW_BATTERY_COMBO_ID,
+            170,
+            94,
+            222,
+            200,
+        ),
+        (
+            WINDOW_EX_STYLE::default(),
+            w!("BUTTON"),
+            loc.startup,
+            WS_TABSTOP | WINDOW_STYLE(BS_AUTOCHECKBOX as u32),
+            STARTUP_CHECK_ID,
+            12,
+            132,
+            380,
+            22,
+        ),
+        (
+            WINDOW_EX_STYLE::default(),
+            w!("BUTTON"),
+            loc.ok,
+            WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32),
+            IDOK.0,
+            220,
+            172,
+            80,
+            26,
+        ),
+        (
+            WINDOW_EX_STYLE::default(),
+            w!("BUTTON"),
+            loc.skip,
+            WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as u32),
+            IDCANCEL.0,
+            312,
+            172,
+            80,
+            26,
+        ),
+    ];
+    create_controls(hwnd, instance, &controls)?;
+
+    let tray_icon_items = std::iter::once(loc.onboarding_app_icon.to_owned())
+        .chain(devices.iter().map(|(_, label)| label.to_owned()))
+        .collect::<Vec<_>>();
+    // 有设备时默认显示第一个设备（已连接的排在前面）的电量
+    let tray_icon_index = usize::from(!devices.is_empty());
+    let low_battery_items = LOW_BATTERY_OPTIONS
+        .iter()
+        .map(|percent| format!("{percent}%"))
+        .collect::<Vec<_>>();
+    let low_battery_index = LOW_BATTERY_OPTIONS
+        .iter()
+        .position(|percent| *percent == low_battery)
+        .unwrap_or(2);
+
+    unsafe {
+        let tray_icon_combo = GetDlgItem(Some(hwnd), TRAY_ICON_COMBO_ID)?;
+        fill_combo(tray_icon_combo, &tray_icon_items, tray_icon_index);
+        let low_battery_combo = GetDlgItem(Some(hwnd), LOW_BATTERY_COMBO_ID)?;
+        fill_combo(low_battery_combo, &low_battery_items, low_battery_index);
+
+        if get_startup_status().unwrap_or(false) {
+            let startup_check = GetDlgItem(Some(hwnd), STARTUP_CHECK_ID)?;
+            SendMessageW(
+                startup_check,
+                BM_SETCHECK,
+                Some(WPARAM(BST_CHECKED.0 as usize)),
+                None,
+            );
+        }
+
+        show_dialog_window(hwnd, tray_icon_combo);
+    }
+
+    ONBOARDING_RESULT.with_borrow_mut(|result| *result = None);
+    run_dialog_loop(hwnd);
+    Ok(ONBOARDING_RESULT.with_borrow_mut(Option::take))
+}
+
+fn fill_combo(combo: HWND, items: &[String], selected: usize) {
+    unsafe {
+        for item in items {
+            let item = HSTRING::from(item);
+            SendMessageW(
+                combo,
+                CB_ADDSTRING,
+                None,
+                Some(LPARAM(item.as_ptr() as isize)),
+            );
+        }
+        SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(selected)), None);
+    }
+}
+
+extern "system" fn onboarding_proc(
+    hwnd: HWND,
+    message: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    unsafe {
+        match message {
+            WM_COMMAND => {
+                let id = (wparam.0 & 0xFFFF) as i32;
+                if id == IDOK.0 {
+                    let choice = read_choice(hwnd);
+                    ONBOARDING_RESULT.with_borrow_mut(|result| *result = Some(choice));
+                }
+                if id == IDOK.0 || id == IDCANCEL.0 {
+                    let _ = DestroyWindow(hwnd);
+                }
+                LRESULT(0)
+            }
+            WM_DESTROY => {
+                PostQuitMessage(0);
+                LRESULT(0)
+            }
+            _ => DefWindowProcW(hwnd, message, wparam, lparam),
+        }
+    }
+}
+
+fn read_choice(hwnd: HWND) -> (usize, usize, bool) {
+    let send = |id: i32, message: u32| unsafe {
+        GetDlgItem(Some(hwnd), id)
+            .map(|control| SendMessageW(control, message, None, None).0)
+            .unwrap_or(-1)
+    };
+    // 未选择时为 CB_ERR (-1)
+    let tray_icon_index = usize::try_from(send(TRAY_ICON_COMBO_ID, CB_GETCURSEL)).unwrap_or(0);
+    let low_battery_index = usize::try_from(send(LOW_BATTERY_COMBO_ID, CB_GETCURSEL))
+        .unwrap_or(LOW_BATTERY_OPTIONS.len());
+    let startup = send(STARTUP_CHECK_ID, BM_GETCHECK) == BST_CHECKED.0 as isize;
+    (tray_icon_index, low_battery_index, startup)
+}
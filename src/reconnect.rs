@@ -0,0 +1,109 @@
+use crate::{
+    bluetooth::{
+        control::reconnect_device,
+        events::DeviceEvent,
+        info::BluetoothInfo,
+        radio::{BluetoothRadioState, get_bluetooth_radio_state},
+    },
+    config::Config,
+    language::Localization,
+    notify::{NotifyEvent, notify},
+};
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+use bluegauge_core::retry::RetryPolicy;
+use log::{info, warn};
+use tokio::runtime::Handle;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::AbortHandle;
+
+/// 每次尝试后等待设备连接的时间，从 10 秒开始翻倍，共尝试 5 次
+const RECONNECT_RETRY: RetryPolicy =
+    RetryPolicy::new(5, Duration::from_secs(10), Duration::from_secs(120));
+
+/// 通过菜单或规则主动断开的设备，下次断开时不重新连接
+static MANUAL_DISCONNECTS: LazyLock<Mutex<HashSet<u64>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// 主动断开设备前调用，避免随即又被重新连接
+pub fn skip_reconnect(address: u64) {
+    MANUAL_DISCONNECTS.lock().unwrap().insert(address);
+}
+
+/// 订阅设备事件，保持连接的设备意外断开时在后台尝试重新连接；
+/// 设备重新连接后取消尝试，所有尝试后仍未连接时发送通知
+pub fn watch_reconnects(
+    runtime: &Handle,
+    config: Arc<Config>,
+    mut device_events: broadcast::Receiver<DeviceEvent>,
+) {
+    let handle = runtime.clone();
+    runtime.spawn(async move {
+        let mut reconnecting: HashMap</* address */ u64, AbortHandle> = HashMap::new();
+        loop {
+            let info = match device_events.recv().await {
+                Ok(DeviceEvent::ConnectionChanged { info }) => info,
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Reconnects skipped {skipped} device events");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+
+            reconnecting.retain(|_, task| !task.is_finished());
+            if let Some(task) = reconnecting.remove(&info.address) {
+                task.abort();
+            }
+
+            let manual = MANUAL_DISCONNECTS.lock().unwrap().remove(&info.address);
+            if info.status || manual || !config.is_keep_connected_device(&info) {
+                continue;
+            }
+
+            let task = handle.spawn(reconnect(Arc::clone(&config), info.clone()));
+            reconnecting.insert(info.address, task.abort_handle());
+        }
+    });
+}
+
+async fn reconnect(config: Arc<Config>, info: BluetoothInfo) {
+    for attempt in 1..=RECONNECT_RETRY.max_attempts {
+        // 蓝牙关闭时所有设备都会断开，不需要重新连接
+        let radio_on = tokio::task::spawn_blocking(get_bluetooth_radio_state)
+            .await
+            .is_ok_and(|state| matches!(state, Ok(BluetoothRadioState::On)));
+        if !radio_on {
+            return;
+        }
+
+        let device = info.clone();
+        match tokio::task::spawn_blocking(move || reconnect_device(&device)).await {
+            Ok(Ok(())) => info!("[{}]: Reconnect attempt {attempt} sent", info.name),
+            Ok(Err(e)) => warn!("[{}]: Reconnect attempt {attempt} failed - {e}", info.name),
+            Err(e) => warn!(
+                "[{}]: Reconnect attempt {attempt} panicked - {e}",
+                info.name
+            ),
+        }
+
+        // 设备连接后本任务被取消
+        tokio::time::sleep(RECONNECT_RETRY.delay(attempt)).await;
+    }
+
+    warn!(
+        "[{}]: Still disconnected after {} reconnect attempts",
+        info.name, RECONNECT_RETRY.max_attempts
+    );
+    let loc = Localization::current();
+    notify(
+        NotifyEvent::Disconnection,
+        loc.reconnect_failed,
+        format!("{}: {}", loc.device_name, info.name),
+        config.get_mute(),
+        info.address,
+    );
+}
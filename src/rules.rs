@@ -0,0 +1,63 @@
+use crate::{
+    bluetooth::{control::disconnect_device, info::BluetoothInfo},
+    config::{Config, RuleAction, RuleOptions},
+    notify::app_notify,
+    reconnect::skip_reconnect,
+};
+
+use std::collections::HashSet;
+
+use log::info;
+
+/// 按 `[[rules]]` 在设备电量越过阈值时执行动作，每次低于阈值只执行一次，
+/// 电量回到阈值以上后才会再次执行
+pub struct RuleEngine {
+    rules: Vec<RuleOptions>,
+    triggered: HashSet<(/* rule index */ usize, /* address */ u64)>,
+}
+
+impl RuleEngine {
+    pub fn new(config: &Config) -> Self {
+        RuleEngine {
+            rules: config.rules.clone(),
+            triggered: HashSet::new(),
+        }
+    }
+
+    pub fn evaluate(&mut self, bluetooth_devices_info: &HashSet<BluetoothInfo>) {
+        for (index, rule) in self.rules.iter().enumerate() {
+            for info in bluetooth_devices_info
+                .iter()
+                .filter(|info| info.matches(&rule.device))
+            {
+                if info.battery >= rule.below {
+                    self.triggered.remove(&(index, info.address));
+                    continue;
+                }
+
+                if !info.status || !self.triggered.insert((index, info.address)) {
+                    continue;
+                }
+
+                info!(
+                    "[{}]: Battery {}% is below {}%, running {:?}",
+                    info.name, info.battery, rule.below, rule.action
+                );
+                run_action(rule.action, info.clone());
+            }
+        }
+    }
+}
+
+fn run_action(action: RuleAction, info: BluetoothInfo) {
+    match action {
+        RuleAction::Disconnect => {
+            std::thread::spawn(move || {
+                skip_reconnect(info.address);
+                if let Err(e) = disconnect_device(&info) {
+                    app_notify(format!("Failed to disconnect {} - {e}", info.name));
+                }
+            });
+        }
+    }
+}
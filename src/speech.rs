@@ -0,0 +1,67 @@
+use crate::{bluetooth::info::BluetoothInfo, language::Localization, notify::NotifyEvent};
+
+use std::sync::{
+    LazyLock,
+    mpsc::{Sender, channel},
+};
+
+use log::warn;
+use windows::{
+    Win32::{
+        Media::Speech::{ISpVoice, SPF_IS_NOT_XML, SpVoice},
+        System::Com::{CLSCTX_ALL, COINIT_MULTITHREADED, CoCreateInstance, CoInitializeEx},
+    },
+    core::HSTRING,
+};
+
+/// SAPI 语音对象只在创建它的线程中使用，朗读请求依次排队，避免互相打断
+static SPEAKER: LazyLock<Sender<String>> = LazyLock::new(|| {
+    let (tx, rx) = channel::<String>();
+
+    std::thread::spawn(move || {
+        let voice = unsafe {
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+            CoCreateInstance::<_, ISpVoice>(&SpVoice, None, CLSCTX_ALL)
+        };
+        let voice = match voice {
+            Ok(voice) => voice,
+            Err(e) => {
+                warn!("Failed to create the SAPI voice - {e}");
+                return;
+            }
+        };
+
+        for text in rx {
+            if let Err(e) =
+                unsafe { voice.Speak(&HSTRING::from(&text), SPF_IS_NOT_XML.0 as u32, None) }
+            {
+                warn!("Failed to speak \"{text}\" - {e}");
+            }
+        }
+    });
+
+    tx
+});
+
+/// 通过 Windows 默认语音朗读
+pub fn speak(text: impl Into<String>) {
+    let _ = SPEAKER.send(text.into());
+}
+
+/// 朗读设备事件，如 "Headphones at 15 percent"
+pub fn speak_event(event: NotifyEvent, info: &BluetoothInfo) {
+    let loc = Localization::current();
+    let template = match event {
+        NotifyEvent::LowBattery | NotifyEvent::Milestone => loc.speech_low_battery,
+        NotifyEvent::Disconnection => loc.speech_disconnected,
+        NotifyEvent::Reconnection => loc.speech_reconnected,
+        NotifyEvent::Added => loc.speech_added,
+        NotifyEvent::Removed => loc.speech_removed,
+        NotifyEvent::Renamed => loc.speech_renamed,
+    };
+    speak(
+        template
+            .replace("{name}", &info.name)
+            .replace("{battery}", &info.battery.to_string()),
+    );
+}
@@ -1,15 +1,50 @@
+use std::path::{Path, PathBuf};
+
 use anyhow::{Context, Result, anyhow};
+use log::{info, warn};
 use winreg::RegKey;
 use winreg::enums::*;
 
 const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+const STARTUP_VALUE_NAME: &str = "BlueGauge";
+/// 旧版本使用过的值名称，启动时迁移到 `STARTUP_VALUE_NAME`
+const LEGACY_VALUE_NAMES: &[&str] = &["CapsGlow"];
+/// 开机自启动时附加的参数，表示由系统登录启动
+pub const STARTUP_ARG: &str = "--minimized";
+
+fn get_exe_path() -> Result<PathBuf> {
+    std::env::current_exe().context("Failed to get the exe path")
+}
 
-fn get_exe_path() -> Result<String> {
-    let exe_path = std::env::current_exe()?
+/// `"C:\Path\BlueGauge.exe" --minimized [--portable]`，路径加引号以支持空格
+fn get_startup_command() -> Result<String> {
+    let exe_path = get_exe_path()?;
+    let exe_path = exe_path
         .to_str()
-        .ok_or_else(|| anyhow!("Failed to convert exe path to string"))?
-        .to_owned();
-    Ok(exe_path)
+        .ok_or_else(|| anyhow!("Failed to convert exe path to string"))?;
+
+    let mut command = format!("\"{exe_path}\" {STARTUP_ARG}");
+    if std::env::args().any(|arg| arg == "--portable") {
+        command.push_str(" --portable");
+    }
+    Ok(command)
+}
+
+/// 从启动命令中取出程序路径，兼容未加引号的旧值
+fn parse_command_path(command: &str) -> PathBuf {
+    let command = command.trim();
+    let path = match command.strip_prefix('"') {
+        Some(rest) => rest.split('"').next().unwrap_or(rest),
+        None => command
+            .find(".exe")
+            .map_or(command, |end| &command[..end + ".exe".len()]),
+    };
+    PathBuf::from(path)
+}
+
+fn is_same_path(a: &Path, b: &Path) -> bool {
+    a.to_string_lossy()
+        .eq_ignore_ascii_case(&b.to_string_lossy())
 }
 
 pub fn set_startup(enabled: bool) -> Result<()> {
@@ -17,13 +52,13 @@ pub fn set_startup(enabled: bool) -> Result<()> {
     let (run_key, _disp) = hkcu.create_subkey(RUN_KEY)?;
 
     if enabled {
-        let exe_path = get_exe_path()?;
+        let command = get_startup_command()?;
         run_key
-            .set_value("BlueGauge", &exe_path)
+            .set_value(STARTUP_VALUE_NAME, &command)
             .with_context(|| "Failed to set the autostart registry key")?;
     } else {
         run_key
-            .delete_value("BlueGauge")
+            .delete_value(STARTUP_VALUE_NAME)
             .with_context(|| "Failed to delete the autostart registry key")?;
     }
 
@@ -36,12 +71,51 @@ pub fn get_startup_status() -> Result<bool> {
         .open_subkey_with_flags(RUN_KEY, KEY_READ)
         .map_err(|e| anyhow!("Failed to open HKEY_CURRENT_USER\\...\\Run - {e}"))?;
 
-    match run_key.get_value::<String, _>("BlueGauge") {
-        Ok(value) => {
+    match run_key.get_value::<String, _>(STARTUP_VALUE_NAME) {
+        Ok(command) => {
             let exe_path = get_exe_path()?;
-            Ok(value == exe_path)
+            Ok(is_same_path(&parse_command_path(&command), &exe_path))
         }
         Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
         Err(e) => Err(anyhow!("Failed to get the autostart registry key - {e}")),
     }
 }
+
+/// 迁移旧的自启动值：
+/// - 旧名称下指向本程序的值改用 `STARTUP_VALUE_NAME`
+/// - 程序被移动后（原路径已不存在）更新为当前路径
+pub fn migrate_startup() -> Result<()> {
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let run_key = hkcu
+        .open_subkey_with_flags(RUN_KEY, KEY_READ | KEY_WRITE)
+        .map_err(|e| anyhow!("Failed to open HKEY_CURRENT_USER\\...\\Run - {e}"))?;
+
+    let exe_path = get_exe_path()?;
+    let mut should_enable = false;
+
+    for legacy_name in LEGACY_VALUE_NAMES {
+        if let Ok(command) = run_key.get_value::<String, _>(legacy_name)
+            && is_same_path(&parse_command_path(&command), &exe_path)
+        {
+            info!("Migrating the autostart registry value '{legacy_name}'");
+            run_key
+                .delete_value(legacy_name)
+                .with_context(|| format!("Failed to delete the autostart value '{legacy_name}'"))?;
+            should_enable = true;
+        }
+    }
+
+    if let Ok(command) = run_key.get_value::<String, _>(STARTUP_VALUE_NAME) {
+        let path = parse_command_path(&command);
+        if !path.is_file() {
+            warn!("Autostart points to a missing file {path:?}, updating it");
+            should_enable = true;
+        }
+    }
+
+    if should_enable {
+        set_startup(true)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use crate::{
+    config::Config,
+    history::{DeviceUsage, History, usage_since},
+    language::Localization,
+    notify::summary_notify,
+};
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use log::{info, warn};
+use tokio::runtime::Handle;
+
+const WEEKLY_SUMMARY_FILE_NAME: &str = "BlueGauge.weekly-summary";
+const WEEK: u64 = 7 * 24 * 60 * 60;
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// 每小时检查一次距上次汇总是否已满一周。
+/// 上次汇总的时间保存在配置文件目录下，重启后不会重复通知；第一次开启时从当时开始计时
+pub fn watch_weekly_summary(runtime: &Handle, config: Arc<Config>) {
+    let history_options = &config.history_options;
+    if !history_options.enabled
+        || (!history_options.weekly_summary && history_options.weekly_report.is_none())
+    {
+        return;
+    }
+
+    let state_path = config.config_path.with_file_name(WEEKLY_SUMMARY_FILE_NAME);
+
+    runtime.spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let config = Arc::clone(&config);
+            let state_path = state_path.clone();
+            let _ = tokio::task::spawn_blocking(move || check_weekly_summary(&config, &state_path))
+                .await;
+        }
+    });
+}
+
+fn check_weekly_summary(config: &Config, state_path: &Path) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+
+    let due = match read_last_summary(state_path) {
+        Some(last_summary) => now.saturating_sub(last_summary) >= WEEK,
+        None => {
+            write_last_summary(state_path, now);
+            false
+        }
+    };
+
+    if due {
+        if let Err(e) = send_weekly_summary(config, now) {
+            warn!("Failed to send the weekly summary - {e}");
+        }
+        write_last_summary(state_path, now);
+    }
+}
+
+fn read_last_summary(path: &Path) -> Option<u64> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+fn write_last_summary(path: &Path, timestamp: u64) {
+    if let Err(e) = std::fs::write(path, timestamp.to_string()) {
+        warn!("Failed to write {path:?} - {e}");
+    }
+}
+
+fn send_weekly_summary(config: &Config, now: u64) -> Result<()> {
+    let since = now.saturating_sub(WEEK);
+    let samples = History::new(config).load(None)?;
+    let usage = usage_since(&samples, since, config.get_low_battery());
+    if usage.is_empty() {
+        info!("No battery history in the last week, skipping the weekly summary");
+        return Ok(());
+    }
+
+    let loc = Localization::current();
+    let text = usage
+        .iter()
+        .map(|usage| format_usage(config, loc, usage))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if config.history_options.weekly_summary {
+        summary_notify(loc.weekly_summary, &text, config.get_mute());
+    }
+
+    if let Some(path) = config.get_weekly_report_path() {
+        let format_date = |timestamp: u64| {
+            DateTime::from_timestamp(timestamp as i64, 0)
+                .map(|time| time.with_timezone(&Local).format("%Y-%m-%d").to_string())
+                .unwrap_or_default()
+        };
+        let report = format!(
+            "{} ({} - {})\n\n{text}\n",
+            loc.weekly_summary,
+            format_date(since),
+            format_date(now)
+        );
+        std::fs::write(&path, report)
+            .with_context(|| format!("Failed to write the weekly report to {path:?}"))?;
+    }
+
+    Ok(())
+}
+
+/// 名称：平均耗电 · 连接时长 · 最低电量
+fn format_usage(config: &Config, loc: &Localization, usage: &DeviceUsage) -> String {
+    let drain = usage
+        .drain_per_hour
+        .map_or_else(|| "-".to_owned(), |drain| format!("{drain:.1}%/h"));
+    let lowest = usage
+        .lowest
+        .map_or_else(|| "-".to_owned(), |lowest| format!("{lowest}%"));
+
+    format!(
+        "{}: {} {drain} · {} {:.1}h · {} {lowest}",
+        config.get_device_aliases_name(&usage.name),
+        loc.average_drain,
+        loc.connected_time,
+        usage.connected_seconds as f64 / 3600.0,
+        loc.lowest_battery,
+    )
+}
@@ -0,0 +1,150 @@
+use std::cell::OnceCell;
+use std::sync::OnceLock;
+
+use anyhow::{Result, anyhow};
+use log::warn;
+use windows::{
+    Win32::{
+        Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+        System::{
+            LibraryLoader::GetModuleHandleW,
+            RemoteDesktop::{NOTIFY_FOR_THIS_SESSION, WTSRegisterSessionNotification},
+        },
+        UI::WindowsAndMessaging::{
+            ChangeWindowMessageFilterEx, CreateWindowExW, DefWindowProcW, DispatchMessageW,
+            GetMessageW, MSG, MSGFLT_ALLOW, PBT_APMRESUMEAUTOMATIC, PBT_APMSUSPEND, RegisterClassW,
+            RegisterWindowMessageW, TranslateMessage, WINDOW_EX_STYLE, WINDOW_STYLE,
+            WM_POWERBROADCAST, WM_WTSSESSION_CHANGE, WNDCLASSW, WTS_SESSION_LOCK,
+            WTS_SESSION_UNLOCK,
+        },
+    },
+    core::w,
+};
+
+/// 系统睡眠/唤醒、会话锁定/解锁与任务栏重新创建
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemEvent {
+    Suspend,
+    Resume,
+    SessionLock,
+    SessionUnlock,
+    /// 资源管理器（重新）启动后广播，此前添加的托盘图标已不存在
+    TaskbarCreated,
+}
+
+/// `RegisterWindowMessageW("TaskbarCreated")` 的消息 ID，创建窗口时获取
+static WM_TASKBAR_CREATED: OnceLock<u32> = OnceLock::new();
+
+thread_local! {
+    static EVENT_HANDLER: OnceCell<Box<dyn Fn(SystemEvent)>> = const { OnceCell::new() };
+}
+
+/// 在独立线程中创建隐藏窗口接收 `WM_POWERBROADCAST`、`WM_WTSSESSION_CHANGE` 与
+/// `TaskbarCreated`（仅消息窗口收不到广播消息）
+pub fn watch_system_events(on_event: impl Fn(SystemEvent) + Send + 'static) -> Result<()> {
+    let (result_tx, result_rx) = std::sync::mpsc::channel();
+
+    std::thread::spawn(move || {
+        EVENT_HANDLER.with(|handler| {
+            let _ = handler.set(Box::new(on_event));
+        });
+
+        let result = create_event_window();
+        let created = result.is_ok();
+        let _ = result_tx.send(result);
+        if !created {
+            return;
+        }
+
+        let mut msg = MSG::default();
+        unsafe {
+            while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                let _ = TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        }
+    });
+
+    result_rx.recv()?
+}
+
+fn create_event_window() -> Result<()> {
+    let class_name = w!("BlueGaugeSystemEvents");
+
+    let hwnd = unsafe {
+        let instance = GetModuleHandleW(None)?;
+
+        let window_class = WNDCLASSW {
+            lpfnWndProc: Some(window_proc),
+            hInstance: instance.into(),
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        if RegisterClassW(&window_class) == 0 {
+            return Err(anyhow!(
+                "Failed to register the system event window class - {}",
+                windows::core::Error::from_win32()
+            ));
+        }
+
+        CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            class_name,
+            w!("BlueGauge"),
+            WINDOW_STYLE::default(),
+            0,
+            0,
+            0,
+            0,
+            None,
+            None,
+            Some(instance.into()),
+            None,
+        )
+        .map_err(|e| anyhow!("Failed to create the system event window - {e}"))?
+    };
+
+    let taskbar_created = unsafe { RegisterWindowMessageW(w!("TaskbarCreated")) };
+    if taskbar_created != 0 {
+        let _ = WM_TASKBAR_CREATED.set(taskbar_created);
+        // 以管理员身份运行时，UIPI 会拦截来自资源管理器的广播消息
+        if let Err(e) =
+            unsafe { ChangeWindowMessageFilterEx(hwnd, taskbar_created, MSGFLT_ALLOW, None) }
+        {
+            warn!("Failed to allow the TaskbarCreated message - {e}");
+        }
+    }
+
+    // 无法接收会话事件时仍可接收睡眠/唤醒事件
+    if let Err(e) = unsafe { WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) } {
+        warn!("Failed to register session notifications - {e}");
+    }
+
+    Ok(())
+}
+
+unsafe extern "system" fn window_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    let event = match (msg, wparam.0 as u32) {
+        (WM_POWERBROADCAST, PBT_APMSUSPEND) => Some(SystemEvent::Suspend),
+        (WM_POWERBROADCAST, PBT_APMRESUMEAUTOMATIC) => Some(SystemEvent::Resume),
+        (WM_WTSSESSION_CHANGE, WTS_SESSION_LOCK) => Some(SystemEvent::SessionLock),
+        (WM_WTSSESSION_CHANGE, WTS_SESSION_UNLOCK) => Some(SystemEvent::SessionUnlock),
+        _ if WM_TASKBAR_CREATED.get() == Some(&msg) => Some(SystemEvent::TaskbarCreated),
+        _ => None,
+    };
+
+    if let Some(event) = event {
+        EVENT_HANDLER.with(|handler| {
+            if let Some(handler) = handler.get() {
+                handler(event);
+            }
+        });
+    }
+
+    unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+}
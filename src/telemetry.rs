@@ -0,0 +1,223 @@
+use crate::{
+    bluetooth::{
+        btc::{BT_INSTANCE_ID, HFP_ENUMERATOR_ID, get_device_model},
+        info::{BluetoothInfo, BluetoothType, DeviceCategory, get_device_errors},
+        pnp::get_cached_pnp_instances,
+    },
+    config::Config,
+};
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::runtime::Handle;
+
+const TELEMETRY_STATE_FILE_NAME: &str = "BlueGauge.telemetry";
+/// 启动后等待设备读取完成再上报
+const INITIAL_DELAY: Duration = Duration::from_secs(5 * 60);
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// 读取到电量的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum BatterySource {
+    /// BLE Battery Service
+    Gatt,
+    /// 经典蓝牙设备的 PnP 电量属性
+    Pnp,
+    /// 免提（HFP）音频设备的电量属性
+    Hfp,
+    /// 没有设备节点，通过配套协议（如真无线耳机的私有广播）读取
+    Companion,
+    /// 读取失败
+    None,
+}
+
+/// 一条兼容性记录，不包括设备地址及名称。
+/// 制造商及型号只取自有厂商及产品 ID 的设备节点属性
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct DeviceReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    manufacturer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vendor_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    product_id: Option<String>,
+    category: DeviceCategory,
+    source: BatterySource,
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    app_version: &'a str,
+    devices: &'a [DeviceReport],
+}
+
+/// 开启后定时上报设备型号及读取到电量的方式。
+/// 已上报的记录保存在配置文件目录下，只上报新出现的型号或方式
+pub fn watch_telemetry(
+    runtime: &Handle,
+    config: Arc<Config>,
+    bluetooth_info: Arc<Mutex<HashSet<BluetoothInfo>>>,
+) {
+    if !config.get_telemetry() {
+        return;
+    }
+
+    let state_path = config.config_path.with_file_name(TELEMETRY_STATE_FILE_NAME);
+
+    runtime.spawn(async move {
+        tokio::time::sleep(INITIAL_DELAY).await;
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let devices = bluetooth_info.lock().unwrap().clone();
+            let endpoint = config.telemetry_options.endpoint.clone();
+            let state_path = state_path.clone();
+            let _ = tokio::task::spawn_blocking(move || {
+                let reports = device_reports(&devices);
+                if let Err(e) = send_new_reports(&endpoint, &state_path, reports) {
+                    warn!("Failed to send telemetry: {e}");
+                }
+            })
+            .await;
+        }
+    });
+}
+
+/// 设备节点读取须在阻塞线程中调用
+fn device_reports(bluetooth_devices_info: &HashSet<BluetoothInfo>) -> HashSet<DeviceReport> {
+    let mut reports = bluetooth_devices_info
+        .iter()
+        .map(|info| {
+            let instance_id = match &info.r#type {
+                BluetoothType::Classic(instance_id) => Some(instance_id.as_str()),
+                BluetoothType::LowEnergy => None,
+            };
+            device_report(instance_id, info.category, battery_source(&info.r#type))
+        })
+        .collect::<HashSet<_>>();
+
+    // 读取失败的设备只能通过缓存的 BTHENUM 实例对应到厂商及产品 ID，
+    // 对应不到时上报的记录没有可区分的信息，直接跳过
+    let instances = get_cached_pnp_instances().unwrap_or_default();
+    reports.extend(get_device_errors().iter().filter_map(|error| {
+        let instance_id = instances
+            .iter()
+            .find(|(_, address)| **address == error.address)
+            .map(|(instance_id, _)| instance_id.as_str())?;
+        let report = device_report(
+            Some(instance_id),
+            DeviceCategory::Other,
+            BatterySource::None,
+        );
+        report.vendor_id.is_some().then_some(report)
+    }));
+
+    reports
+}
+
+fn device_report(
+    instance_id: Option<&str>,
+    category: DeviceCategory,
+    source: BatterySource,
+) -> DeviceReport {
+    let (vendor_id, product_id) = instance_id.map_or((None, None), vendor_product_id);
+
+    let (manufacturer, model) = match instance_id {
+        Some(instance_id) if vendor_id.is_some() && instance_id.starts_with(BT_INSTANCE_ID) => {
+            get_device_model(instance_id).unwrap_or_else(|e| {
+                warn!("Failed to get the model of {instance_id}: {e}");
+                (None, None)
+            })
+        }
+        _ => (None, None),
+    };
+
+    DeviceReport {
+        manufacturer,
+        model,
+        vendor_id,
+        product_id,
+        category,
+        source,
+    }
+}
+
+fn battery_source(r#type: &BluetoothType) -> BatterySource {
+    match r#type {
+        BluetoothType::LowEnergy => BatterySource::Gatt,
+        BluetoothType::Classic(instance_id) if instance_id.is_empty() => BatterySource::Companion,
+        BluetoothType::Classic(instance_id) if instance_id.starts_with(HFP_ENUMERATOR_ID) => {
+            BatterySource::Hfp
+        }
+        BluetoothType::Classic(_) => BatterySource::Pnp,
+    }
+}
+
+/// 从实例 ID（如 `BTHENUM\{...}_VID&0001004C_PID&2002\...`）中读取厂商及产品 ID，
+/// `VID&` 后的前 4 位为 ID 来源
+fn vendor_product_id(instance_id: &str) -> (Option<String>, Option<String>) {
+    let instance_id = instance_id.to_uppercase();
+    let field = |name: &str, len: usize| {
+        let start = instance_id.find(name)? + name.len();
+        instance_id.get(start..start + len).map(str::to_owned)
+    };
+    let vendor_id = field("_VID&", 8).and_then(|vid| vid.get(4..).map(str::to_owned));
+    (vendor_id, field("_PID&", 4))
+}
+
+fn send_new_reports(
+    endpoint: &str,
+    state_path: &Path,
+    reports: HashSet<DeviceReport>,
+) -> Result<()> {
+    let sent = read_sent_reports(state_path);
+    let new_reports = reports
+        .into_iter()
+        .filter(|report| !sent.contains(report))
+        .collect::<Vec<_>>();
+    if new_reports.is_empty() {
+        return Ok(());
+    }
+
+    let body = serde_json::to_string(&Payload {
+        app_version: env!("CARGO_PKG_VERSION"),
+        devices: &new_reports,
+    })?;
+    ureq::post(endpoint)
+        .header("Content-Type", "application/json")
+        .send(body)?;
+    info!("Sent telemetry for {} devices", new_reports.len());
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(state_path)
+        .with_context(|| format!("Failed to open {state_path:?}"))?;
+    for report in new_reports {
+        writeln!(file, "{}", serde_json::to_string(&report)?)?;
+    }
+    Ok(())
+}
+
+/// 每行一条已上报的记录，无法解析的行忽略
+fn read_sent_reports(state_path: &Path) -> HashSet<DeviceReport> {
+    std::fs::read_to_string(state_path)
+        .map(|contents| {
+            contents
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -1,19 +1,45 @@
 use std::collections::HashSet;
 use std::ops::Deref;
 
-use crate::bluetooth::info::BluetoothInfo;
-use crate::config::{Config, TrayIconSource};
+use crate::audio::{audio_endpoint, is_default_audio};
+use crate::bluetooth::{
+    info::{
+        BluetoothInfo, BluetoothType, ChargeState, ComponentBattery, PowerStatus, get_data_age,
+        get_last_seen, last_seen_age,
+    },
+    radio::BluetoothRadioState,
+};
+use crate::config::{
+    BatteryDisplay, ClickAction, Config, DeviceFilter, StalePolicy, TrayIconSource,
+    TruncateStrategy,
+};
 use crate::icon::{LOGO_DATA, load_battery_icon, load_icon};
-use crate::language::{Language, Localization};
+use crate::language::Localization;
 use crate::notify::app_notify;
 use crate::startup::get_startup_status;
+use crate::updater::get_available_update;
 
 use anyhow::{Context, Result, anyhow};
 use tray_icon::menu::{IsMenuItem, Submenu};
 use tray_icon::{
-    TrayIcon, TrayIconBuilder,
+    Icon, TrayIcon, TrayIconBuilder,
     menu::{AboutMetadata, CheckMenuItem, Menu, MenuItem, PredefinedMenuItem},
 };
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// 名称裁剪可选的最大长度
+const TRUNCATE_LENGTHS: [usize; 5] = [8, 10, 12, 16, 20];
+
+/// 影响托盘显示的运行状态
+#[derive(Debug, Clone, Copy)]
+pub struct TrayState {
+    pub radio_state: BluetoothRadioState,
+    pub monitoring_paused: bool,
+    /// 启动时蓝牙尚未就绪，正在等待或重试首次枚举
+    pub initializing: bool,
+    /// 显示的是上次退出时保存的设备信息，首次枚举完成前视为过期
+    pub restored: bool,
+}
 
 struct CreateMenuItem;
 impl CreateMenuItem {
@@ -42,10 +68,168 @@ impl CreateMenuItem {
         MenuItem::with_id("force_update", text, true, None)
     }
 
+    /// 蓝牙开关，没有可用适配器时禁用
+    fn bluetooth_radio(text: &str, radio_state: BluetoothRadioState) -> CheckMenuItem {
+        CheckMenuItem::with_id(
+            "bluetooth_radio",
+            text,
+            radio_state != BluetoothRadioState::Unavailable,
+            radio_state == BluetoothRadioState::On,
+            None,
+        )
+    }
+
+    /// 暂停时显示“恢复监控”，否则显示暂停时长子菜单
+    fn pause_monitoring(
+        loc: &Localization,
+        monitoring_paused: bool,
+    ) -> Result<Box<dyn IsMenuItem>> {
+        if monitoring_paused {
+            return Ok(Box::new(MenuItem::with_id(
+                "resume_monitoring",
+                loc.resume_monitoring,
+                true,
+                None,
+            )));
+        }
+
+        let pause_items = [
+            MenuItem::with_id("pause_monitoring", loc.until_resumed, true, None),
+            MenuItem::with_id("pause_monitoring:3600", "1h", true, None),
+            MenuItem::with_id("pause_monitoring:28800", "8h", true, None),
+        ];
+        let pause_items: Vec<&dyn IsMenuItem> = pause_items
+            .iter()
+            .map(|item| item as &dyn IsMenuItem)
+            .collect();
+
+        let menu_pause_monitoring = Submenu::with_items(loc.pause_monitoring, true, &pause_items)
+            .context("Failed to create 'Pause Monitoring' submenu")?;
+        Ok(Box::new(menu_pause_monitoring))
+    }
+
+    /// 检查到新版本时显示：下载并安装（发布中有 exe 时）、打开发布页面
+    fn available_update(loc: &Localization) -> Result<Option<Submenu>> {
+        let Some(release) = get_available_update() else {
+            return Ok(None);
+        };
+
+        let menu_install_update = MenuItem::with_id(
+            "install_update",
+            loc.install_update,
+            release.download.is_some(),
+            None,
+        );
+        let menu_open_release_page =
+            MenuItem::with_id("open_release_page", loc.open_release_page, true, None);
+
+        Submenu::with_items(
+            format!("{} v{}", loc.new_version_available, release.version),
+            true,
+            &[&menu_install_update, &menu_open_release_page],
+        )
+        .map(Some)
+        .context("Failed to create 'New Version Available' submenu")
+    }
+
+    /// 有设备读取失败时显示数量，点击查看原因
+    fn device_errors(loc: &Localization, count: usize) -> Option<MenuItem> {
+        (count > 0).then(|| {
+            MenuItem::with_id(
+                "device_errors",
+                loc.devices_failed.replace("{n}", &count.to_string()),
+                true,
+                None,
+            )
+        })
+    }
+
+    /// 开启兼容性统计时显示，仅作提示不可点击
+    fn telemetry(config: &Config, text: &str) -> Option<MenuItem> {
+        config
+            .get_telemetry()
+            .then(|| MenuItem::with_id("telemetry", text, false, None))
+    }
+
+    /// 以文本或 JSON 复制所有设备的状态
+    fn copy_status(loc: &Localization) -> Result<Submenu> {
+        let menu_copy_text = MenuItem::with_id("copy_status:text", loc.as_text, true, None);
+        let menu_copy_json = MenuItem::with_id("copy_status:json", "JSON", true, None);
+
+        Submenu::with_items(loc.copy_status, true, &[&menu_copy_text, &menu_copy_json])
+            .context("Failed to create 'Copy Status' submenu")
+    }
+
     fn open_config(text: &str) -> MenuItem {
         MenuItem::with_id("open_config", text, true, None)
     }
 
+    fn reload_config(text: &str) -> MenuItem {
+        MenuItem::with_id("reload_config", text, true, None)
+    }
+
+    fn diagnostics_report(text: &str) -> MenuItem {
+        MenuItem::with_id("diagnostics_report", text, true, None)
+    }
+
+    fn export_history(
+        config: &Config,
+        loc: &Localization,
+        bluetooth_devices_info: &HashSet<BluetoothInfo>,
+    ) -> Result<Submenu> {
+        let menu_all_devices = MenuItem::with_id("export_history", loc.all_devices, true, None);
+        let menu_devices: Vec<MenuItem> = bluetooth_devices_info
+            .iter()
+            .map(|info| {
+                MenuItem::with_id(
+                    format!("export_history:{}", info.address),
+                    config.get_device_aliases_name(&info.name),
+                    true,
+                    None,
+                )
+            })
+            .collect();
+
+        let mut export_history_items: Vec<&dyn IsMenuItem> = vec![&menu_all_devices];
+        export_history_items.extend(menu_devices.iter().map(|item| item as &dyn IsMenuItem));
+
+        Submenu::with_items(
+            loc.export_history,
+            config.history_options.enabled,
+            &export_history_items,
+        )
+        .context("Failed to create 'Export History' submenu")
+    }
+
+    fn battery_statistics(
+        config: &Config,
+        loc: &Localization,
+        bluetooth_devices_info: &HashSet<BluetoothInfo>,
+    ) -> Result<Submenu> {
+        let menu_devices: Vec<MenuItem> = bluetooth_devices_info
+            .iter()
+            .map(|info| {
+                MenuItem::with_id(
+                    format!("battery_statistics:{}", info.address),
+                    config.get_device_aliases_name(&info.name),
+                    true,
+                    None,
+                )
+            })
+            .collect();
+        let menu_devices: Vec<&dyn IsMenuItem> = menu_devices
+            .iter()
+            .map(|item| item as &dyn IsMenuItem)
+            .collect();
+
+        Submenu::with_items(
+            loc.battery_statistics,
+            config.history_options.enabled && !menu_devices.is_empty(),
+            &menu_devices,
+        )
+        .context("Failed to create 'Battery Statistics' submenu")
+    }
+
     fn startup(text: &str, tray_check_menus: &mut Vec<CheckMenuItem>) -> Result<CheckMenuItem> {
         let should_startup = get_startup_status()?;
         let menu_startup = CheckMenuItem::with_id("startup", text, true, should_startup, None);
@@ -53,28 +237,180 @@ impl CreateMenuItem {
         Ok(menu_startup)
     }
 
+    /// 每个设备一个子菜单 `device:{address}`，包含状态、电量及该设备的所有操作，
+    /// “用作托盘图标”的 ID 为设备地址
     fn bluetooth_devices(
         config: &Config,
+        loc: &Localization,
         tray_check_menus: &mut Vec<CheckMenuItem>,
-        bluetooth_devices_info: &HashSet<BluetoothInfo>,
-    ) -> Result<Vec<CheckMenuItem>> {
+        devices: &[&BluetoothInfo],
+    ) -> Result<Vec<DeviceMenu>> {
         let show_tray_battery_icon_bt_address = config.get_tray_battery_icon_bt_address();
-        let bluetooth_check_items: Vec<CheckMenuItem> = bluetooth_devices_info
+
+        devices
             .iter()
             .map(|info| {
-                CheckMenuItem::with_id(
-                    info.address,
-                    config.get_device_aliases_name(&info.name),
+                let address = info.address;
+                let menu_status = MenuItem::new(device_status_text(loc, info), false, None);
+                let menu_battery =
+                    MenuItem::new(device_battery_text(config, loc, info), false, None);
+
+                let menu_use_as_tray_icon = CheckMenuItem::with_id(
+                    address,
+                    loc.use_as_tray_icon,
+                    true,
+                    show_tray_battery_icon_bt_address.is_some_and(|id| id.eq(&address)),
+                    None,
+                );
+                tray_check_menus.push(menu_use_as_tray_icon.clone());
+
+                let menu_refresh = MenuItem::with_id(
+                    format!("refresh_device:{address}"),
+                    loc.refresh_device,
                     true,
-                    show_tray_battery_icon_bt_address.is_some_and(|id| id.eq(&info.address)),
                     None,
+                );
+
+                // 只有经典蓝牙设备可以主动连接或断开
+                let can_connect = matches!(info.r#type, BluetoothType::Classic(_));
+                let menu_connection = MenuItem::with_id(
+                    format!("toggle_connection:{address}"),
+                    device_connection_text(loc, info),
+                    can_connect,
+                    None,
+                );
+
+                // 已是默认播放设备或没有播放端点时不可点击
+                let menu_default_audio = MenuItem::with_id(
+                    format!("set_default_audio:{address}"),
+                    loc.set_default_audio,
+                    audio_endpoint(address).is_some() && !is_default_audio(address),
+                    None,
+                );
+
+                let menu_rename =
+                    MenuItem::with_id(format!("rename_device:{address}"), loc.rename, true, None);
+                let menu_hide =
+                    MenuItem::with_id(format!("hide_device:{address}"), loc.hide, true, None);
+                let menu_pin = CheckMenuItem::with_id(
+                    format!("pin_device:{address}"),
+                    loc.pin_device,
+                    true,
+                    config.is_pinned_device(info),
+                    None,
+                );
+                let menu_keep_connected = CheckMenuItem::with_id(
+                    format!("keep_connected:{address}"),
+                    loc.keep_connected,
+                    true,
+                    config.is_keep_connected_device(info),
+                    None,
+                );
+                let menu_details =
+                    MenuItem::with_id(format!("device_details:{address}"), loc.details, true, None);
+
+                let menu_separator = CreateMenuItem::separator();
+                let device_items: [&dyn IsMenuItem; 13] = [
+                    &menu_status,
+                    &menu_battery,
+                    &menu_separator,
+                    &menu_use_as_tray_icon,
+                    &menu_refresh,
+                    &menu_connection,
+                    &menu_keep_connected,
+                    &menu_default_audio,
+                    &menu_separator,
+                    &menu_rename,
+                    &menu_pin,
+                    &menu_hide,
+                    &menu_details,
+                ];
+
+                let submenu = Submenu::with_id_and_items(
+                    format!("device:{address}"),
+                    device_menu_text(config, info),
+                    true,
+                    &device_items,
                 )
+                .with_context(|| format!("Failed to create the submenu of {}", info.name))?;
+
+                Ok(DeviceMenu {
+                    address,
+                    name: info.name.clone(),
+                    submenu,
+                    status: menu_status,
+                    battery: menu_battery,
+                    connection: menu_connection,
+                })
             })
+            .collect()
+    }
+
+    /// 已隐藏的设备，点击 `unhide_device:{device}` 重新显示
+    fn hidden_devices(
+        config: &Config,
+        loc: &Localization,
+        bluetooth_devices_info: &HashSet<BluetoothInfo>,
+    ) -> Result<Submenu> {
+        let hidden_devices = config.hidden_devices.lock().unwrap().clone();
+        let menu_devices: Vec<MenuItem> = hidden_devices
+            .iter()
+            .map(|device| {
+                // 设备仍在列表中时显示其名称
+                let name = bluetooth_devices_info
+                    .iter()
+                    .find(|info| info.matches(device))
+                    .map_or_else(
+                        || device.to_owned(),
+                        |info| config.get_device_aliases_name(&info.name),
+                    );
+                MenuItem::with_id(format!("unhide_device:{device}"), name, true, None)
+            })
+            .collect();
+        let menu_devices: Vec<&dyn IsMenuItem> = menu_devices
+            .iter()
+            .map(|item| item as &dyn IsMenuItem)
             .collect();
 
-        tray_check_menus.extend(bluetooth_check_items.iter().cloned());
+        Submenu::with_items(
+            loc.hidden_devices,
+            !hidden_devices.is_empty(),
+            &menu_devices,
+        )
+        .context("Failed to create 'Hidden Devices' submenu")
+    }
 
-        Ok(bluetooth_check_items)
+    /// 配置方案 `profile:{name}` 单选，及“将当前设置保存为配置方案”
+    fn profiles(
+        config: &Config,
+        loc: &Localization,
+        tray_check_menus: &mut Vec<CheckMenuItem>,
+    ) -> Result<Submenu> {
+        let active_profile = config.get_active_profile();
+        let menu_profiles: Vec<CheckMenuItem> = config
+            .get_profile_names()
+            .into_iter()
+            .map(|name| {
+                let checked = active_profile.as_ref() == Some(&name);
+                CheckMenuItem::with_id(format!("profile:{name}"), &name, true, checked, None)
+            })
+            .collect();
+        tray_check_menus.extend(menu_profiles.iter().cloned());
+
+        let menu_separator = PredefinedMenuItem::separator();
+        let menu_save_profile = MenuItem::with_id("save_profile", loc.save_profile, true, None);
+
+        let mut menu_profile_items: Vec<&dyn IsMenuItem> = menu_profiles
+            .iter()
+            .map(|item| item as &dyn IsMenuItem)
+            .collect();
+        if !menu_profile_items.is_empty() {
+            menu_profile_items.push(&menu_separator as &dyn IsMenuItem);
+        }
+        menu_profile_items.push(&menu_save_profile as &dyn IsMenuItem);
+
+        Submenu::with_items(loc.profiles, true, &menu_profile_items)
+            .context("Failed to create 'Profiles' submenu")
     }
 
     fn update_interval(
@@ -93,21 +429,198 @@ impl CreateMenuItem {
         update_interval_items
     }
 
+    fn adaptive_update_interval(
+        config: &Config,
+        loc: &Localization,
+        tray_check_menus: &mut Vec<CheckMenuItem>,
+    ) -> CheckMenuItem {
+        let menu_adaptive_update_interval = CheckMenuItem::with_id(
+            "adaptive_update_interval",
+            loc.adaptive_update_interval,
+            true,
+            config.get_adaptive_update_interval(),
+            None,
+        );
+        tray_check_menus.push(menu_adaptive_update_interval.clone());
+        menu_adaptive_update_interval
+    }
+
     #[rustfmt::skip]
     fn set_tray_tooltip(
         config: &Config,
         loc: &Localization,
         tray_check_menus: &mut Vec<CheckMenuItem>,
-    ) -> [CheckMenuItem; 3] {
+    ) -> [CheckMenuItem; 10] {
         let menu_set_tray_tooltip = [
             CheckMenuItem::with_id("show_disconnected", loc.show_disconnected, true, config.get_show_disconnected(), None),
             CheckMenuItem::with_id("truncate_name", loc.truncate_name, true, config.get_truncate_name(), None),
             CheckMenuItem::with_id("prefix_battery", loc.prefix_battery, true, config.get_prefix_battery(), None),
+            CheckMenuItem::with_id("show_last_updated", loc.show_last_updated, true, config.get_show_last_updated(), None),
+            CheckMenuItem::with_id("battery_bar", loc.battery_bar, true, config.get_battery_bar(), None),
+            CheckMenuItem::with_id("summary_header", loc.summary_header, true, config.get_summary_header(), None),
+            CheckMenuItem::with_id("show_default_audio", loc.show_default_audio, true, config.get_show_default_audio(), None),
+            CheckMenuItem::with_id("hide_device_list", loc.hide_device_list, true, config.get_hide_device_list(), None),
+            CheckMenuItem::with_id("hide_tooltip", loc.hide_tooltip, true, config.get_hide_tooltip(), None),
+            CheckMenuItem::with_id("rich_flyout", loc.rich_flyout, true, config.get_rich_flyout(), None),
         ];
         tray_check_menus.extend(menu_set_tray_tooltip.iter().cloned());
         menu_set_tray_tooltip
     }
 
+    /// 名称裁剪的最大长度（`truncate_length:{n}`）、省略位置及去除常见前后缀
+    fn truncate_options(
+        config: &Config,
+        loc: &Localization,
+        tray_check_menus: &mut Vec<CheckMenuItem>,
+    ) -> Result<Submenu> {
+        let truncate_length = config.get_truncate_length();
+        let truncate_strategy = config.get_truncate_strategy();
+
+        let menu_truncate_length = TRUNCATE_LENGTHS
+            .iter()
+            .map(|&length| {
+                CheckMenuItem::with_id(
+                    format!("truncate_length:{length}"),
+                    length.to_string(),
+                    true,
+                    truncate_length == length,
+                    None,
+                )
+            })
+            .collect::<Vec<_>>();
+        tray_check_menus.extend(menu_truncate_length.iter().cloned());
+        let menu_truncate_length: Vec<&dyn IsMenuItem> = menu_truncate_length
+            .iter()
+            .map(|item| item as &dyn IsMenuItem)
+            .collect();
+        let menu_truncate_length =
+            Submenu::with_items(loc.truncate_length, true, &menu_truncate_length)?;
+
+        let menu_truncate_strategy = [
+            CheckMenuItem::with_id(
+                "truncate_end",
+                loc.truncate_end,
+                true,
+                truncate_strategy == TruncateStrategy::End,
+                None,
+            ),
+            CheckMenuItem::with_id(
+                "truncate_middle",
+                loc.truncate_middle,
+                true,
+                truncate_strategy == TruncateStrategy::Middle,
+                None,
+            ),
+        ];
+        let menu_strip_affixes = CheckMenuItem::with_id(
+            "strip_affixes",
+            loc.strip_affixes,
+            true,
+            config.get_strip_affixes(),
+            None,
+        );
+        tray_check_menus.extend(menu_truncate_strategy.iter().cloned());
+        tray_check_menus.push(menu_strip_affixes.clone());
+
+        Submenu::with_items(
+            loc.truncate_options,
+            true,
+            &[
+                &menu_truncate_length,
+                &PredefinedMenuItem::separator(),
+                &menu_truncate_strategy[0],
+                &menu_truncate_strategy[1],
+                &PredefinedMenuItem::separator(),
+                &menu_strip_affixes,
+            ],
+        )
+        .context("Failed to create 'Name Truncation' submenu")
+    }
+
+    /// 只显示某一类设备（`device_filter:{all|audio|input}`）
+    fn device_filter(
+        config: &Config,
+        loc: &Localization,
+        tray_check_menus: &mut Vec<CheckMenuItem>,
+    ) -> Result<Submenu> {
+        let device_filter = config.get_device_filter();
+        let menu_device_filter = [
+            (DeviceFilter::All, "device_filter:all", loc.all_devices),
+            (
+                DeviceFilter::Audio,
+                "device_filter:audio",
+                loc.audio_devices,
+            ),
+            (
+                DeviceFilter::Input,
+                "device_filter:input",
+                loc.input_devices,
+            ),
+        ]
+        .map(|(filter, id, text)| {
+            CheckMenuItem::with_id(id, text, true, device_filter == filter, None)
+        });
+        tray_check_menus.extend(menu_device_filter.iter().cloned());
+
+        Submenu::with_items(
+            loc.device_filter,
+            true,
+            &[
+                &menu_device_filter[0],
+                &menu_device_filter[1],
+                &menu_device_filter[2],
+            ],
+        )
+        .context("Failed to create 'Show Devices' submenu")
+    }
+
+    /// 电量的显示方式（`battery_display:{exact|round5|round10|verbal}`）
+    fn battery_display(
+        config: &Config,
+        loc: &Localization,
+        tray_check_menus: &mut Vec<CheckMenuItem>,
+    ) -> Result<Submenu> {
+        let battery_display = config.get_battery_display();
+        let menu_battery_display = [
+            (
+                BatteryDisplay::Exact,
+                "battery_display:exact",
+                loc.battery_exact,
+            ),
+            (
+                BatteryDisplay::Round5,
+                "battery_display:round5",
+                loc.battery_round5,
+            ),
+            (
+                BatteryDisplay::Round10,
+                "battery_display:round10",
+                loc.battery_round10,
+            ),
+            (
+                BatteryDisplay::Verbal,
+                "battery_display:verbal",
+                loc.battery_verbal,
+            ),
+        ]
+        .map(|(display, id, text)| {
+            CheckMenuItem::with_id(id, text, true, battery_display == display, None)
+        });
+        tray_check_menus.extend(menu_battery_display.iter().cloned());
+
+        Submenu::with_items(
+            loc.battery_display,
+            true,
+            &[
+                &menu_battery_display[0],
+                &menu_battery_display[1],
+                &menu_battery_display[2],
+                &menu_battery_display[3],
+            ],
+        )
+        .context("Failed to create 'Battery Display' submenu")
+    }
+
     fn notify_low_battery(
         low_battery: u8,
         tray_check_menus: &mut Vec<CheckMenuItem>,
@@ -129,13 +642,16 @@ impl CreateMenuItem {
         config: &Config,
         loc: &Localization,
         tray_check_menus: &mut Vec<CheckMenuItem>,
-    ) -> [CheckMenuItem; 5] {
+    ) -> [CheckMenuItem; 8] {
         let menu_device_change = [
             CheckMenuItem::with_id("mute", loc.mute, true, config.get_mute(), None),
             CheckMenuItem::with_id("disconnection", loc.disconnection, true, config.get_disconnection(), None),
             CheckMenuItem::with_id("reconnection", loc.reconnection, true, config.get_reconnection(), None),
             CheckMenuItem::with_id("added", loc.added, true, config.get_added(), None),
             CheckMenuItem::with_id("removed", loc.removed, true, config.get_removed(), None),
+            CheckMenuItem::with_id("renamed", loc.renamed, true, config.get_renamed(), None),
+            CheckMenuItem::with_id("pinned_only", loc.pinned_only, true, config.get_pinned_only(), None),
+            CheckMenuItem::with_id("milestones", loc.milestones, true, config.get_milestones(), None),
         ];
         tray_check_menus.extend(menu_device_change.iter().cloned());
         menu_device_change
@@ -146,7 +662,8 @@ impl CreateMenuItem {
         loc: &Localization,
         tray_check_menus: &mut Vec<CheckMenuItem>,
     ) -> CheckMenuItem {
-        let connection_toggle_menu = if let TrayIconSource::BatteryFont { font_color, .. } =
+        let connection_toggle_menu = if let TrayIconSource::BatteryFont { font_color, .. }
+        | TrayIconSource::LowestBattery { font_color, .. } =
             config.tray_options.tray_icon_source.lock().unwrap().deref()
         {
             CheckMenuItem::with_id(
@@ -172,12 +689,99 @@ impl CreateMenuItem {
     }
 }
 
+/// 设备子菜单中随设备信息变化的项目
+#[derive(Clone)]
+struct DeviceMenu {
+    address: u64,
+    /// 创建时的设备名称，改名后需重建菜单（导出历史等子菜单同样显示名称）
+    name: String,
+    submenu: Submenu,
+    status: MenuItem,
+    battery: MenuItem,
+    connection: MenuItem,
+}
+
+impl DeviceMenu {
+    fn update(&self, config: &Config, loc: &Localization, info: &BluetoothInfo) {
+        self.submenu.set_text(device_menu_text(config, info));
+        self.status.set_text(device_status_text(loc, info));
+        self.battery
+            .set_text(device_battery_text(config, loc, info));
+        self.connection.set_text(device_connection_text(loc, info));
+    }
+}
+
+/// 托盘菜单中的设备部分。设备列表不变时原地更新文本，
+/// 避免替换整个菜单而关闭用户正在浏览的子菜单
+#[derive(Clone, Default)]
+pub struct DeviceMenus {
+    menus: Vec<DeviceMenu>,
+    /// 创建时显示的读取失败设备数
+    error_count: usize,
+}
+
+impl DeviceMenus {
+    /// 显示的设备（地址及名称）与读取失败数同创建时一致时，更新各设备菜单并返回 `true`；
+    /// 否则返回 `false`，需重建菜单
+    pub fn update(&self, config: &Config, bluetooth_devices_info: &HashSet<BluetoothInfo>) -> bool {
+        let devices = menu_devices(config, bluetooth_devices_info);
+        if devices.len() != self.menus.len() || menu_device_error_count(config) != self.error_count
+        {
+            return false;
+        }
+
+        let Some(updates) = self
+            .menus
+            .iter()
+            .map(|menu| {
+                devices
+                    .iter()
+                    .find(|info| info.address == menu.address && info.name == menu.name)
+                    .map(|info| (menu, *info))
+            })
+            .collect::<Option<Vec<_>>>()
+        else {
+            return false;
+        };
+
+        let loc = Localization::current();
+        for (menu, info) in updates {
+            menu.update(config, loc, info);
+        }
+        true
+    }
+}
+
+/// 菜单中显示的设备：未隐藏设备列表时，符合设备筛选且未被隐藏的设备
+fn menu_devices<'a>(
+    config: &Config,
+    bluetooth_devices_info: &'a HashSet<BluetoothInfo>,
+) -> Vec<&'a BluetoothInfo> {
+    if config.get_hide_device_list() {
+        return Vec::new();
+    }
+
+    let device_filter = config.get_device_filter();
+    bluetooth_devices_info
+        .iter()
+        .filter(|info| device_filter.matches(info.category) && !config.is_hidden_device(info))
+        .collect()
+}
+
+fn menu_device_error_count(config: &Config) -> usize {
+    if config.get_hide_device_list() {
+        0
+    } else {
+        config.get_visible_device_errors().len()
+    }
+}
+
 pub fn create_menu(
     config: &Config,
+    tray_state: TrayState,
     bluetooth_devices_info: &HashSet<BluetoothInfo>,
-) -> Result<(Menu, Vec<CheckMenuItem>)> {
-    let language = Language::get_system_language();
-    let loc = Localization::get(language);
+) -> Result<(Menu, Vec<CheckMenuItem>, DeviceMenus)> {
+    let loc = Localization::current();
 
     let mut tray_check_menus: Vec<CheckMenuItem> = Vec::new();
 
@@ -189,26 +793,61 @@ pub fn create_menu(
 
     let menu_about = CreateMenuItem::about(loc.about);
 
+    let menu_available_update = CreateMenuItem::available_update(loc)?;
+
     let menu_force_update = CreateMenuItem::force_update(loc.force_update);
 
-    let menu_bluetooth_devicess =
-        CreateMenuItem::bluetooth_devices(config, &mut tray_check_menus, bluetooth_devices_info)?;
-    let menu_bluetooth_devicess: Vec<&dyn IsMenuItem> = menu_bluetooth_devicess
+    let menu_telemetry = CreateMenuItem::telemetry(config, loc.telemetry_enabled);
+
+    let menu_copy_status = CreateMenuItem::copy_status(loc)?;
+
+    let menu_bluetooth_radio =
+        CreateMenuItem::bluetooth_radio(loc.bluetooth_radio, tray_state.radio_state);
+
+    let menu_pause_monitoring =
+        CreateMenuItem::pause_monitoring(loc, tray_state.monitoring_paused)?;
+
+    // 隐藏设备列表时菜单只保留设置等项目
+    let device_menus = DeviceMenus {
+        menus: CreateMenuItem::bluetooth_devices(
+            config,
+            loc,
+            &mut tray_check_menus,
+            &menu_devices(config, bluetooth_devices_info),
+        )?,
+        error_count: menu_device_error_count(config),
+    };
+    let menu_device_errors = CreateMenuItem::device_errors(loc, device_menus.error_count);
+    let menu_bluetooth_devicess: Vec<&dyn IsMenuItem> = device_menus
+        .menus
         .iter()
-        .map(|item| item as &dyn IsMenuItem)
+        .map(|menu| &menu.submenu as &dyn IsMenuItem)
         .collect();
 
     let menu_startup = &CreateMenuItem::startup(loc.startup, &mut tray_check_menus)?;
 
     let menu_open_config = &CreateMenuItem::open_config(loc.open_config);
 
+    let menu_reload_config = &CreateMenuItem::reload_config(loc.reload_config);
+
+    let menu_export_history = &CreateMenuItem::export_history(config, loc, bluetooth_devices_info)?;
+
+    let menu_diagnostics_report = &CreateMenuItem::diagnostics_report(loc.diagnostics_report);
+
+    let menu_battery_statistics =
+        &CreateMenuItem::battery_statistics(config, loc, bluetooth_devices_info)?;
+
     let menu_tray_options = {
         let menu_update_interval =
             CreateMenuItem::update_interval(config.get_update_interval(), &mut tray_check_menus);
-        let menu_update_interval: Vec<&dyn IsMenuItem> = menu_update_interval
+        let menu_adaptive_update_interval =
+            CreateMenuItem::adaptive_update_interval(config, loc, &mut tray_check_menus);
+        let mut menu_update_interval: Vec<&dyn IsMenuItem> = menu_update_interval
             .iter()
             .map(|item| item as &dyn IsMenuItem)
             .collect();
+        menu_update_interval.push(&menu_separator as &dyn IsMenuItem);
+        menu_update_interval.push(&menu_adaptive_update_interval as &dyn IsMenuItem);
         let menu_update_interval = &Submenu::with_id_and_items(
             "update_interval",
             loc.update_interval,
@@ -219,6 +858,13 @@ pub fn create_menu(
             CreateMenuItem::set_icon_connect_color(config, loc, &mut tray_check_menus);
         let menu_set_tray_tooltip =
             CreateMenuItem::set_tray_tooltip(config, loc, &mut tray_check_menus);
+        let menu_truncate_options =
+            CreateMenuItem::truncate_options(config, loc, &mut tray_check_menus)?;
+        let menu_device_filter = CreateMenuItem::device_filter(config, loc, &mut tray_check_menus)?;
+        let menu_battery_display =
+            CreateMenuItem::battery_display(config, loc, &mut tray_check_menus)?;
+        let menu_hidden_devices =
+            CreateMenuItem::hidden_devices(config, loc, bluetooth_devices_info)?;
 
         let mut menu_tray_options: Vec<&dyn IsMenuItem> = Vec::new();
         menu_tray_options.push(menu_update_interval as &dyn IsMenuItem);
@@ -228,6 +874,10 @@ pub fn create_menu(
                 .iter()
                 .map(|item| item as &dyn IsMenuItem),
         );
+        menu_tray_options.push(&menu_truncate_options as &dyn IsMenuItem);
+        menu_tray_options.push(&menu_device_filter as &dyn IsMenuItem);
+        menu_tray_options.push(&menu_battery_display as &dyn IsMenuItem);
+        menu_tray_options.push(&menu_hidden_devices as &dyn IsMenuItem);
         &Submenu::with_items(loc.tray_config, true, &menu_tray_options)?
     };
 
@@ -259,27 +909,62 @@ pub fn create_menu(
         menu_notify_options as &dyn IsMenuItem,
         menu_startup as &dyn IsMenuItem,
         menu_open_config as &dyn IsMenuItem,
+        menu_reload_config as &dyn IsMenuItem,
+        menu_export_history as &dyn IsMenuItem,
+        menu_battery_statistics as &dyn IsMenuItem,
+        menu_diagnostics_report as &dyn IsMenuItem,
     ];
     let menu_setting = Submenu::with_items(loc.settings, true, settings_items)?;
 
-    tray_menu
-        .prepend_items(&menu_bluetooth_devicess)
-        .context("Failed to prepend 'Bluetooth Items' to Tray Menu")?;
-    tray_menu
-        .append(&menu_separator)
-        .context("Failed to apped 'Separator' to Tray Menu")?;
+    let menu_profiles = CreateMenuItem::profiles(config, loc, &mut tray_check_menus)?;
+
+    if !menu_bluetooth_devicess.is_empty() || menu_device_errors.is_some() {
+        tray_menu
+            .prepend_items(&menu_bluetooth_devicess)
+            .context("Failed to prepend 'Bluetooth Items' to Tray Menu")?;
+        if let Some(menu_device_errors) = &menu_device_errors {
+            tray_menu
+                .append(menu_device_errors)
+                .context("Failed to apped 'Device Errors' to Tray Menu")?;
+        }
+        tray_menu
+            .append(&menu_separator)
+            .context("Failed to apped 'Separator' to Tray Menu")?;
+    }
     tray_menu
         .append(&menu_setting)
         .context("Failed to apped 'Settings' to Tray Menu")?;
+    tray_menu
+        .append(&menu_profiles)
+        .context("Failed to apped 'Profiles' to Tray Menu")?;
     tray_menu
         .append(&menu_separator)
         .context("Failed to apped 'Separator' to Tray Menu")?;
+    tray_menu
+        .append(&menu_bluetooth_radio)
+        .context("Failed to apped 'Bluetooth' to Tray Menu")?;
     tray_menu
         .append(&menu_force_update)
         .context("Failed to apped 'Force Update' to Tray Menu")?;
+    tray_menu
+        .append(&menu_copy_status)
+        .context("Failed to apped 'Copy Status' to Tray Menu")?;
+    tray_menu
+        .append(menu_pause_monitoring.as_ref())
+        .context("Failed to apped 'Pause Monitoring' to Tray Menu")?;
     tray_menu
         .append(&menu_separator)
         .context("Failed to apped 'Separator' to Tray Menu")?;
+    if let Some(menu_available_update) = &menu_available_update {
+        tray_menu
+            .append(menu_available_update)
+            .context("Failed to apped 'New Version Available' to Tray Menu")?;
+    }
+    if let Some(menu_telemetry) = &menu_telemetry {
+        tray_menu
+            .append(menu_telemetry)
+            .context("Failed to apped 'Telemetry' to Tray Menu")?;
+    }
     tray_menu
         .append(&menu_about)
         .context("Failed to apped 'About' to Tray Menu")?;
@@ -290,75 +975,603 @@ pub fn create_menu(
         .append(&menu_quit)
         .context("Failed to apped 'Quit' to Tray Menu")?;
 
-    Ok((tray_menu, tray_check_menus))
+    Ok((tray_menu, tray_check_menus, device_menus))
 }
 
 #[rustfmt::skip]
 pub fn create_tray(
     config: &Config,
+    tray_state: TrayState,
     bluetooth_devices_info: &HashSet<BluetoothInfo>,
-) -> Result<(TrayIcon, Vec<CheckMenuItem>)> {
-    let (tray_menu, tray_check_menus) =
-        create_menu(config, bluetooth_devices_info).map_err(|e| anyhow!("Failed to create menu. - {e}"))?;
+) -> Result<(TrayIcon, Vec<CheckMenuItem>, DeviceMenus)> {
+    let (tray_menu, tray_check_menus, device_menus) =
+        create_menu(config, tray_state, bluetooth_devices_info).map_err(|e| anyhow!("Failed to create menu. - {e}"))?;
 
-    let icon = load_battery_icon(config, bluetooth_devices_info)
+    let icon = load_tray_icon(config, tray_state, bluetooth_devices_info)
         .inspect_err(|e| app_notify(format!("Failed to get battery icon: {e}")))
-        .unwrap_or_else(|_| load_icon(LOGO_DATA).expect("Failed to load logo icon"));
-
-    let bluetooth_tooltip_info = convert_tray_info(bluetooth_devices_info, config);
+        .or_else(|_| load_icon(LOGO_DATA))?;
 
-    let tray_icon = TrayIconBuilder::new()
-        .with_menu_on_left_click(true)
+    let tray_icon_builder = TrayIconBuilder::new()
+        .with_menu_on_left_click(config.click_options.single == ClickAction::Menu)
         .with_icon(icon)
-        .with_tooltip(bluetooth_tooltip_info.join("\n"))
-        .with_menu(Box::new(tray_menu))
+        .with_menu(Box::new(tray_menu));
+
+    let tray_icon_builder = match create_tray_tooltip(config, tray_state, bluetooth_devices_info) {
+        Some(tooltip) => tray_icon_builder.with_tooltip(tooltip),
+        None => tray_icon_builder,
+    };
+
+    let tray_icon = tray_icon_builder
         .build()
         .map_err(|e| anyhow!("Failed to build tray - {e}"))?;
 
-    Ok((tray_icon, tray_check_menus))
+    Ok((tray_icon, tray_check_menus, device_menus))
 }
 
-/// 返回托盘提示及菜单内容
-pub fn convert_tray_info(
+/// 蓝牙不可用或正在初始化（且没有上次保存的设备信息）时显示应用图标
+pub fn load_tray_icon(
+    config: &Config,
+    tray_state: TrayState,
+    bluetooth_devices_info: &HashSet<BluetoothInfo>,
+) -> Result<Icon> {
+    match tray_state.radio_state {
+        BluetoothRadioState::On if !tray_state.initializing || tray_state.restored => {
+            load_battery_icon(config, bluetooth_devices_info)
+        }
+        _ => load_icon(LOGO_DATA),
+    }
+}
+
+/// 托盘图标的提示，设置为不显示提示或以浮窗代替时为 `None`
+pub fn create_tray_tooltip(
+    config: &Config,
+    tray_state: TrayState,
     bluetooth_devices_info: &HashSet<BluetoothInfo>,
+) -> Option<String> {
+    (!config.get_hide_tooltip() && !config.get_rich_flyout())
+        .then(|| create_tooltip(config, tray_state, bluetooth_devices_info))
+}
+
+/// 蓝牙关闭或不可用、正在初始化或显示上次保存的信息、监控已暂停时，首行提示对应状态
+pub fn create_tooltip(
     config: &Config,
-) -> Vec<String> {
+    tray_state: TrayState,
+    bluetooth_devices_info: &HashSet<BluetoothInfo>,
+) -> String {
+    tooltip_headers(tray_state)
+        .into_iter()
+        .chain(convert_tray_info(bluetooth_devices_info, config))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 托盘提示及浮窗中设备列表前的状态提示
+pub fn tooltip_headers(tray_state: TrayState) -> Vec<String> {
+    let loc = Localization::current();
+
+    let radio_header = match tray_state.radio_state {
+        BluetoothRadioState::On => None,
+        BluetoothRadioState::Off => Some(loc.bluetooth_off.to_owned()),
+        BluetoothRadioState::Unavailable => Some(loc.bluetooth_unavailable.to_owned()),
+    };
+    let initializing_header = if tray_state.restored {
+        Some(loc.showing_last_known.to_owned())
+    } else {
+        tray_state.initializing.then(|| loc.initializing.to_owned())
+    };
+    let paused_header = tray_state
+        .monitoring_paused
+        .then(|| loc.monitoring_paused.to_owned());
+
+    radio_header
+        .into_iter()
+        .chain(initializing_header)
+        .chain(paused_header)
+        .collect()
+}
+
+/// 托盘提示及浮窗中的一个设备
+pub struct TooltipDevice {
+    pub name: String,
+    pub category_icon: &'static str,
+    /// 过期数据设置为隐藏电量时为 `None`
+    pub battery: Option<u8>,
+    pub status: bool,
+    /// 数据已过期且设置为淡化显示
+    pub dimmed: bool,
+    /// 设置显示数据时间时，数据距今的秒数
+    pub age: Option<u64>,
+    /// 未连接且记录了断开前电量的设备断开距今的秒数，此时 `battery` 为断开前的电量
+    pub last_seen: Option<u64>,
+    /// 设置显示默认播放设备时，是否为当前的默认播放设备
+    pub default_audio: bool,
+}
+
+/// 按提示内容设置筛选设备并处理名称与过期数据
+pub fn tooltip_devices(
+    bluetooth_devices_info: &HashSet<BluetoothInfo>,
+    config: &Config,
+) -> Vec<TooltipDevice> {
     let should_truncate_name = config.get_truncate_name();
-    let should_prefix_battery = config.get_prefix_battery();
+    let truncate_length = config.get_truncate_length();
+    let truncate_strategy = config.get_truncate_strategy();
+    let should_strip_affixes = config.get_strip_affixes();
     let should_show_disconnected = config.get_show_disconnected();
+    let should_show_last_updated = config.get_show_last_updated();
+    let stale_policy = config.get_stale_policy();
+    let device_filter = config.get_device_filter();
+    let should_show_default_audio = config.get_show_default_audio();
 
     bluetooth_devices_info
         .iter()
+        .filter(|blue_info| {
+            device_filter.matches(blue_info.category) && !config.is_hidden_device(blue_info)
+        })
         .filter_map(|blue_info| {
+            // 没有记录时数据的时间未知，同样视为过期
+            let stale_policy = stale_policy
+                .filter(|(stale_after, _)| {
+                    get_data_age(blue_info.address).is_none_or(|age| age > *stale_after)
+                })
+                .map(|(_, policy)| policy);
+
             // 根据配置和设备状态决定是否包含在提示中
-            let include_in_tooltip = blue_info.status || should_show_disconnected;
+            let include_in_tooltip = (blue_info.status || should_show_disconnected)
+                && stale_policy != Some(StalePolicy::Hide);
 
-            if include_in_tooltip {
+            include_in_tooltip.then(|| {
+                let last_seen = get_last_seen(blue_info.address).filter(|_| !blue_info.status);
                 let name = {
                     let name = config.get_device_aliases_name(&blue_info.name);
-                    truncate_with_ellipsis(should_truncate_name, name, 10)
-                };
-                let battery = blue_info.battery;
-                let status_icon = if blue_info.status { "🟢" } else { "🔴" };
-                let info = if should_prefix_battery {
-                    format!("{status_icon}{battery:3}% - {name}")
-                } else {
-                    format!("{status_icon}{name} - {battery}%")
+                    let name = if should_strip_affixes {
+                        strip_common_affixes(name)
+                    } else {
+                        name
+                    };
+                    isolate_rtl(truncate_with_ellipsis(
+                        should_truncate_name,
+                        name,
+                        truncate_length,
+                        truncate_strategy,
+                    ))
                 };
-                Some(info)
-            } else {
-                None
-            }
+                TooltipDevice {
+                    name,
+                    category_icon: blue_info.category.icon(),
+                    battery: (stale_policy != Some(StalePolicy::HideBattery)).then_some(
+                        last_seen.map_or(blue_info.battery, |last_seen| last_seen.battery),
+                    ),
+                    status: blue_info.status,
+                    dimmed: stale_policy == Some(StalePolicy::Dim),
+                    age: get_data_age(blue_info.address).filter(|_| should_show_last_updated),
+                    last_seen: last_seen.as_ref().map(last_seen_age),
+                    default_audio: should_show_default_audio && is_default_audio(blue_info.address),
+                }
+            })
+        })
+        .collect()
+}
+
+/// 返回托盘提示及菜单内容
+pub fn convert_tray_info(
+    bluetooth_devices_info: &HashSet<BluetoothInfo>,
+    config: &Config,
+) -> Vec<String> {
+    let loc = Localization::current();
+    let should_prefix_battery = config.get_prefix_battery();
+    let should_show_battery_bar = config.get_battery_bar();
+    let battery_display = config.get_battery_display();
+
+    let devices = tooltip_devices(bluetooth_devices_info, config);
+    let summary = config
+        .get_summary_header()
+        .then(|| tooltip_summary(&devices, battery_display));
+
+    let lines = devices.into_iter().map(|device| {
+        let name = device.name;
+        let battery = device.battery.map_or_else(
+            || "--%".to_owned(),
+            |battery| battery_display.format(battery),
+        );
+        // 未连接的设备显示断开前的电量及断开距今多久，如 "上次 74% · 2 小时前"
+        let battery = match device.last_seen {
+            Some(age) => loc
+                .last_seen
+                .replace("{battery}", &battery)
+                .replace("{age}", &format_data_age(age)),
+            None => battery,
+        };
+        let status_icon = if device.dimmed {
+            "⚪"
+        } else if device.status {
+            "🟢"
+        } else {
+            "🔴"
+        };
+        let status_icon = format!("{status_icon}{}", device.category_icon);
+        // 托盘提示为比例字体，用与数字等宽的 FIGURE SPACE 补齐电量
+        let mut info = if should_prefix_battery {
+            format!("{status_icon}{battery:\u{2007}>4} - {name}")
+        } else {
+            format!("{status_icon}{name} - {battery}")
+        };
+        if should_show_battery_bar && let Some(battery) = device.battery {
+            info.push_str(&format!(" {}", battery_bar(battery)));
+        }
+        if device.default_audio {
+            info.push_str(" 🎧");
+        }
+        // 托盘提示长度有限，刚更新的数据不显示时间
+        if device.last_seen.is_none()
+            && let Some(age) = device.age.filter(|age| *age >= 60)
+        {
+            info.push_str(&format!(" · {}", format_data_age(age)));
+        }
+        info
+    });
+
+    summary.into_iter().chain(lines).collect()
+}
+
+/// 托盘提示首行的概览：已连接的设备数及其中电量最低的设备，如 "3 个已连接 · 最低：Mouse 18%"
+fn tooltip_summary(devices: &[TooltipDevice], battery_display: BatteryDisplay) -> String {
+    let loc = Localization::current();
+    let connected = devices.iter().filter(|device| device.status);
+    let summary = loc
+        .connected_count
+        .replace("{n}", &connected.clone().count().to_string());
+
+    match connected
+        .filter_map(|device| Some((device, device.battery?)))
+        .min_by_key(|(_, battery)| *battery)
+    {
+        Some((device, battery)) => format!(
+            "{summary} · {}",
+            loc.lowest_device
+                .replace("{name}", &device.name)
+                .replace("{battery}", &battery_display.format(battery))
+        ),
+        None => summary,
+    }
+}
+
+/// 与电量成比例的方块电量条，如 64% 为 "▰▰▰▱▱"
+fn battery_bar(battery: u8) -> String {
+    const SEGMENTS: usize = 5;
+    // 四舍五入到最近的一格，有电量时至少显示一格
+    let filled =
+        ((usize::from(battery.min(100)) * SEGMENTS + 50) / 100).max(usize::from(battery > 0));
+    "▰".repeat(filled) + &"▱".repeat(SEGMENTS - filled)
+}
+
+/// 设备子菜单标题：类别图标、名称、电量及连接状态，如 "⌨️ Keychron K2 — 64% 🟢"，
+/// 制表符后右对齐显示数据距今多久；未连接时为断开前的电量及断开距今多久
+fn device_menu_text(config: &Config, info: &BluetoothInfo) -> String {
+    let name = match info.category.icon() {
+        "" => config.get_device_aliases_name(&info.name),
+        icon => format!("{icon} {}", config.get_device_aliases_name(&info.name)),
+    };
+    let status_icon = if info.status { "🟢" } else { "🔴" };
+    let last_seen = get_last_seen(info.address).filter(|_| !info.status);
+    let battery = config
+        .get_battery_display()
+        .format(last_seen.map_or(info.battery, |last_seen| last_seen.battery));
+    let text = format!("{name} — {battery} {status_icon}");
+    let text = if config.get_show_default_audio() && is_default_audio(info.address) {
+        format!("{text} 🎧")
+    } else {
+        text
+    };
+    let age = match last_seen {
+        Some(last_seen) => Some(last_seen_age(&last_seen)),
+        None => get_data_age(info.address),
+    };
+    match age {
+        Some(age) => format!("{text}\t{}", format_data_age(age)),
+        None => text,
+    }
+}
+
+/// 设备子菜单中的连接状态，如 "🟢 已连接"
+fn device_status_text(loc: &Localization, info: &BluetoothInfo) -> String {
+    if info.status {
+        format!("🟢 {}", loc.connected)
+    } else {
+        format!("🔴 {}", loc.disconnected)
+    }
+}
+
+/// 设备子菜单中的电量及电源、左右耳信息
+fn device_battery_text(config: &Config, loc: &Localization, info: &BluetoothInfo) -> String {
+    let battery_display = config.get_battery_display();
+    let battery = format!("{}: {}", loc.battery, battery_display.format(info.battery));
+    let battery = match info.power {
+        Some(power) => format!("{battery}{}", power_status_text(loc, &power)),
+        None => battery,
+    };
+    match info.components {
+        Some(components) => format!(
+            "{battery}{}",
+            components_text(loc, &components, battery_display)
+        ),
+        None => battery,
+    }
+}
+
+/// 已连接时为“断开连接”，否则为“连接”
+fn device_connection_text<'a>(loc: &'a Localization, info: &BluetoothInfo) -> &'a str {
+    if info.status {
+        loc.disconnect
+    } else {
+        loc.connect
+    }
+}
+
+/// 电量后附加的电源信息，如 " · ⚡ 充电中 · 1.2 Wh"
+fn power_status_text(loc: &Localization, power: &PowerStatus) -> String {
+    let mut text = String::new();
+    match power.charge_state {
+        ChargeState::Charging => text.push_str(&format!(" · ⚡ {}", loc.charging)),
+        _ if power.external_power => text.push_str(" · 🔌"),
+        _ => (),
+    }
+    if let Some(available_energy) = power.available_energy {
+        text.push_str(&format!(
+            " · {:.1} Wh",
+            f64::from(available_energy) / 1000.0
+        ));
+    }
+    text
+}
+
+/// 电量后附加的各部分电量，如 " · 左 80% · 右 75% · 充电盒 60%"
+fn components_text(
+    loc: &Localization,
+    components: &ComponentBattery,
+    battery_display: BatteryDisplay,
+) -> String {
+    [
+        (loc.left_bud, components.left),
+        (loc.right_bud, components.right),
+        (loc.charging_case, components.case),
+    ]
+    .into_iter()
+    .filter_map(|(label, battery)| {
+        battery.map(|battery| format!(" · {label} {}", battery_display.format(battery)))
+    })
+    .collect()
+}
+
+/// "刚刚"、"5 分钟前"、"2 小时前"、"3 天前"
+pub fn format_data_age(age: u64) -> String {
+    let loc = Localization::current();
+    let (template, value) = match age {
+        0..60 => return loc.just_now.to_owned(),
+        60..3600 => (loc.minutes_ago, age / 60),
+        3600..86400 => (loc.hours_ago, age / 3600),
+        _ => (loc.days_ago, age / 86400),
+    };
+    template.replace("{n}", &value.to_string())
+}
+
+/// 按显示宽度截断（CJK 全角字符占 2 列），`max_width` 为列数
+fn truncate_with_ellipsis(
+    truncate_device_name: bool,
+    name: String,
+    max_width: usize,
+    strategy: TruncateStrategy,
+) -> String {
+    if !truncate_device_name || name.width() <= max_width {
+        return name;
+    }
+
+    match strategy {
+        TruncateStrategy::End => format!("{}...", take_width(name.chars(), max_width)),
+        TruncateStrategy::Middle => {
+            let head = take_width(name.chars(), max_width.div_ceil(2));
+            let tail = take_width(name.chars().rev(), max_width / 2)
+                .chars()
+                .rev()
+                .collect::<String>();
+            format!("{head}...{tail}")
+        }
+    }
+}
+
+/// 取不超过 `max_width` 列的字符
+fn take_width(chars: impl Iterator<Item = char>, max_width: usize) -> String {
+    let mut width = 0;
+    chars
+        .take_while(|c| {
+            width += c.width().unwrap_or(0);
+            width <= max_width
         })
         .collect()
 }
 
-fn truncate_with_ellipsis(truncate_device_name: bool, name: String, max_chars: usize) -> String {
-    if truncate_device_name && name.chars().count() > max_chars {
-        let mut result = name.chars().take(max_chars).collect::<String>();
-        result.push_str("...");
-        result
+/// 去除 "LE-"、"LE_" 前缀及 " (2)" 这类重复配对产生的后缀，去除后为空时保留原名
+fn strip_common_affixes(name: String) -> String {
+    let mut stripped = name.trim();
+
+    for prefix in ["LE-", "LE_", "LE "] {
+        if let Some(rest) = stripped.strip_prefix(prefix) {
+            stripped = rest.trim_start();
+            break;
+        }
+    }
+
+    if let Some(rest) = stripped.strip_suffix(')')
+        && let Some((rest, number)) = rest.rsplit_once('(')
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+    {
+        stripped = rest.trim_end();
+    }
+
+    if stripped.is_empty() {
+        name
+    } else {
+        stripped.to_owned()
+    }
+}
+
+/// 含有从右到左文字（阿拉伯文、希伯来文等）的名称用 FSI/PDI 隔离，
+/// 避免与后面的 " - 80%" 一起被重新排序
+fn isolate_rtl(name: String) -> String {
+    if name.chars().any(is_rtl) {
+        format!("\u{2068}{name}\u{2069}")
     } else {
-        name.to_string()
+        name
+    }
+}
+
+fn is_rtl(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF | 0x10800..=0x10FFF | 0x1E800..=0x1EFFF
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bluetooth::{
+            info::DeviceCategory,
+            provider::{BluetoothProvider, MockBluetoothProvider, mock_device},
+        },
+        config::{DeviceFilter, test_config},
+    };
+
+    use std::sync::atomic::Ordering;
+
+    const TRAY_STATE: TrayState = TrayState {
+        radio_state: BluetoothRadioState::On,
+        monitoring_paused: false,
+        initializing: false,
+        restored: false,
+    };
+
+    fn mock_devices(provider: &MockBluetoothProvider) -> HashSet<BluetoothInfo> {
+        provider.get_devices().unwrap().devices
+    }
+
+    #[test]
+    fn tooltip_lists_mock_devices() {
+        let config = test_config("tooltip_lists_mock_devices");
+        let provider = MockBluetoothProvider::new([
+            mock_device("Test Buds", 1, 80),
+            mock_device("Test Mouse", 2, 45),
+        ]);
+        provider.set_connected(2, false);
+        let devices = mock_devices(&provider);
+
+        let tooltip = create_tooltip(&config, TRAY_STATE, &devices);
+        assert_eq!(tooltip.lines().count(), 1);
+        assert!(tooltip.contains("Test Buds - 80%"));
+        assert!(!tooltip.contains("Test Mouse"));
+
+        config
+            .tray_options
+            .tooltip_options
+            .show_disconnected
+            .store(true, Ordering::Relaxed);
+        let tooltip = create_tooltip(&config, TRAY_STATE, &devices);
+        assert_eq!(tooltip.lines().count(), 2);
+        assert!(tooltip.contains("Test Mouse - 45%"));
+    }
+
+    #[test]
+    fn tooltip_headers_come_first() {
+        let config = test_config("tooltip_headers_come_first");
+        let loc = Localization::current();
+        let devices = mock_devices(&MockBluetoothProvider::new([mock_device(
+            "Test Buds",
+            1,
+            80,
+        )]));
+
+        let tray_state = TrayState {
+            radio_state: BluetoothRadioState::Off,
+            monitoring_paused: true,
+            ..TRAY_STATE
+        };
+        let tooltip = create_tooltip(&config, tray_state, &devices);
+        let lines = tooltip.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], loc.bluetooth_off);
+        assert_eq!(lines[1], loc.monitoring_paused);
+        assert!(lines[2].contains("Test Buds"));
+
+        assert!(create_tooltip(&config, TRAY_STATE, &HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn menu_lists_filtered_mock_devices() {
+        let config = test_config("menu_lists_filtered_mock_devices");
+        let provider = MockBluetoothProvider::new([
+            BluetoothInfo {
+                category: DeviceCategory::Audio,
+                ..mock_device("Test Buds", 1, 80)
+            },
+            BluetoothInfo {
+                category: DeviceCategory::Mouse,
+                ..mock_device("Test Mouse", 2, 45)
+            },
+        ]);
+        provider.set_connected(2, false);
+        let devices = mock_devices(&provider);
+        let names = |config: &Config| {
+            let mut names = menu_devices(config, &devices)
+                .iter()
+                .map(|info| info.name.as_str())
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+
+        assert_eq!(names(&config), ["Test Buds", "Test Mouse"]);
+
+        let tooltip_options = &config.tray_options.tooltip_options;
+        *tooltip_options.device_filter.lock().unwrap() = DeviceFilter::Input;
+        assert_eq!(names(&config), ["Test Mouse"]);
+
+        *tooltip_options.device_filter.lock().unwrap() = DeviceFilter::All;
+        config
+            .hidden_devices
+            .lock()
+            .unwrap()
+            .push("00:00:00:00:00:02".to_owned());
+        assert_eq!(names(&config), ["Test Buds"]);
+
+        tooltip_options
+            .hide_device_list
+            .store(true, Ordering::Relaxed);
+        assert!(names(&config).is_empty());
+    }
+
+    #[test]
+    fn device_menu_text_follows_mock_state() {
+        let config = test_config("device_menu_text_follows_mock_state");
+        let provider = MockBluetoothProvider::new([BluetoothInfo {
+            category: DeviceCategory::Audio,
+            ..mock_device("Test Buds", 0x11, 80)
+        }]);
+        let menu_text = || {
+            let devices = mock_devices(&provider);
+            device_menu_text(&config, devices.iter().next().unwrap())
+        };
+
+        assert_eq!(menu_text(), "🎧 Test Buds — 80% 🟢");
+
+        provider.set_battery(0x11, 42);
+        provider.set_connected(0x11, false);
+        assert_eq!(menu_text(), "🎧 Test Buds — 42% 🔴");
+
+        config
+            .device_aliases
+            .lock()
+            .unwrap()
+            .insert("Test Buds".to_owned(), "Buds".to_owned());
+        assert_eq!(menu_text(), "🎧 Buds — 42% 🔴");
     }
 }
@@ -0,0 +1,350 @@
+use crate::config::Config;
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+use log::{info, warn};
+use serde::Deserialize;
+use tokio::runtime::Handle;
+use windows::Win32::Security::Cryptography::{
+    BCRYPT_HASH_HANDLE, BCRYPT_SHA256_ALG_HANDLE, BCryptCreateHash, BCryptDestroyHash,
+    BCryptFinishHash, BCryptHashData,
+};
+
+const LATEST_RELEASE_API: &str =
+    "https://api.github.com/repos/iKineticate/BlueGauge/releases/latest";
+const USER_AGENT: &str = concat!("BlueGauge/", env!("CARGO_PKG_VERSION"));
+/// 发布附件中 exe 的名称，只下载与之完全相同的附件
+const EXE_ASSET_NAME: &str = "BlueGauge.exe";
+/// API 未返回附件摘要时，从该附件读取 exe 的 SHA-256
+const CHECKSUM_ASSET_NAME: &str = "BlueGauge.exe.sha256";
+/// 启动后稍等再检查，避免与首次枚举争抢，也给开机时的网络留出连接时间
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub struct Release {
+    pub version: String,
+    pub page_url: String,
+    /// 发布附件中的 exe，没有或缺少校验值时只能打开发布页面
+    pub download: Option<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReleaseAsset {
+    pub url: String,
+    /// 小写十六进制的 SHA-256
+    pub sha256: String,
+}
+
+/// 已检查到的新版本，供托盘菜单显示
+static AVAILABLE_UPDATE: Mutex<Option<Release>> = Mutex::new(None);
+
+pub fn get_available_update() -> Option<Release> {
+    AVAILABLE_UPDATE.lock().unwrap().clone()
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+    /// 如 `sha256:...`，较早的发布没有该字段
+    #[serde(default)]
+    digest: Option<String>,
+}
+
+/// 查询 GitHub 最新发布，版本比当前新时返回
+pub fn check_for_update() -> Result<Option<Release>> {
+    let mut response = ureq::get(LATEST_RELEASE_API)
+        .header("User-Agent", USER_AGENT)
+        .header("Accept", "application/vnd.github+json")
+        .call()
+        .map_err(|e| anyhow!("Failed to query the latest release - {e}"))?;
+    let body = response.body_mut().read_to_string()?;
+    let release: GithubRelease = serde_json::from_str(&body)?;
+
+    let is_newer = match (
+        parse_version(&release.tag_name),
+        parse_version(env!("CARGO_PKG_VERSION")),
+    ) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    };
+    if !is_newer {
+        return Ok(None);
+    }
+
+    let download = release
+        .assets
+        .iter()
+        .find(|asset| asset.name == EXE_ASSET_NAME)
+        .and_then(|asset| {
+            let sha256 = match asset.digest.as_deref() {
+                Some(digest) => digest.strip_prefix("sha256:").map(str::to_owned),
+                None => release
+                    .assets
+                    .iter()
+                    .find(|asset| asset.name == CHECKSUM_ASSET_NAME)
+                    .and_then(|checksum| {
+                        fetch_checksum(&checksum.browser_download_url)
+                            .inspect_err(|e| warn!("{e}"))
+                            .ok()
+                    }),
+            };
+            sha256
+                .filter(|sha256| is_sha256_hex(sha256))
+                .map(|sha256| ReleaseAsset {
+                    url: asset.browser_download_url.clone(),
+                    sha256: sha256.to_ascii_lowercase(),
+                })
+        });
+
+    Ok(Some(Release {
+        version: release.tag_name.trim_start_matches('v').to_owned(),
+        page_url: release.html_url,
+        download,
+    }))
+}
+
+/// 校验文件格式同 `sha256sum`：`<hex>  BlueGauge.exe`
+fn fetch_checksum(url: &str) -> Result<String> {
+    let mut response = ureq::get(url)
+        .header("User-Agent", USER_AGENT)
+        .call()
+        .map_err(|e| anyhow!("Failed to download {url} - {e}"))?;
+    let body = response.body_mut().read_to_string()?;
+    body.split_whitespace()
+        .next()
+        .map(str::to_owned)
+        .ok_or_else(|| anyhow!("{CHECKSUM_ASSET_NAME} is empty"))
+}
+
+fn is_sha256_hex(text: &str) -> bool {
+    text.len() == 64 && text.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// `v1.2.3`、`1.2`、`1.2.3-beta` → (1, 2, 3)
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.trim().trim_start_matches(['v', 'V']);
+    let version = version.split(['-', '+']).next()?;
+    let mut parts = version.split('.').map(|part| part.parse::<u64>());
+
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+    Some((major, minor, patch))
+}
+
+/// 按 `[update]` 的间隔在后台检查更新，每个新版本只回调一次；
+/// 关闭检查或 `[network] offline = true` 时不联网
+pub fn watch_updates(
+    runtime: &Handle,
+    config: Arc<Config>,
+    on_update: impl Fn(Release) + Send + 'static,
+) {
+    if !config.get_check_updates() {
+        return;
+    }
+
+    runtime.spawn(async move {
+        tokio::time::sleep(FIRST_CHECK_DELAY).await;
+
+        let mut notified_version = None;
+        loop {
+            let result = tokio::task::spawn_blocking(check_for_update)
+                .await
+                .unwrap_or_else(|e| Err(e.into()));
+            match result {
+                Ok(Some(release)) if notified_version.as_ref() != Some(&release.version) => {
+                    info!("New version available: {}", release.version);
+                    notified_version = Some(release.version.clone());
+                    *AVAILABLE_UPDATE.lock().unwrap() = Some(release.clone());
+                    on_update(release);
+                }
+                Ok(_) => (),
+                Err(e) => warn!("{e}"),
+            }
+
+            let interval = config.update_options.interval.max(1);
+            tokio::time::sleep(Duration::from_secs(interval * 60 * 60)).await;
+        }
+    });
+}
+
+/// 下载新版本并校验 SHA-256 后替换当前 exe，返回需要重新启动的路径。
+/// 运行中的 exe 不能覆盖，但可以重命名，旧文件在下次启动时删除
+pub fn install_update(release: &Release) -> Result<PathBuf> {
+    let asset = release
+        .download
+        .as_ref()
+        .ok_or_else(|| anyhow!("Release {} has no exe to download", release.version))?;
+    let download_url = asset.url.as_str();
+
+    let exe_path = std::env::current_exe().context("Failed to get the exe path")?;
+    let new_path = exe_path.with_extension("exe.new");
+    let old_path = exe_path.with_extension("exe.old");
+
+    let mut response = ureq::get(download_url)
+        .header("User-Agent", USER_AGENT)
+        .call()
+        .map_err(|e| anyhow!("Failed to download {download_url} - {e}"))?;
+    let file = std::fs::File::create(&new_path)
+        .with_context(|| format!("Failed to create {new_path:?}"))?;
+    let mut writer = Sha256Writer::new(file)?;
+    std::io::copy(&mut response.body_mut().as_reader(), &mut writer)
+        .with_context(|| format!("Failed to download {download_url}"))?;
+
+    let actual = writer.finish()?;
+    if actual != asset.sha256 {
+        let _ = std::fs::remove_file(&new_path);
+        return Err(anyhow!(
+            "Checksum mismatch for {EXE_ASSET_NAME}: expected {}, got {actual}",
+            asset.sha256
+        ));
+    }
+
+    let _ = std::fs::remove_file(&old_path);
+    std::fs::rename(&exe_path, &old_path)
+        .with_context(|| format!("Failed to rename {exe_path:?}"))?;
+    if let Err(e) = std::fs::rename(&new_path, &exe_path) {
+        let _ = std::fs::rename(&old_path, &exe_path);
+        return Err(anyhow!("Failed to replace {exe_path:?} - {e}"));
+    }
+
+    info!("Updated to {}", release.version);
+    Ok(exe_path)
+}
+
+/// 删除更新后留下的旧 exe，旧进程可能仍在退出，稍后重试
+pub fn remove_old_exe() {
+    let Ok(exe_path) = std::env::current_exe() else {
+        return;
+    };
+    let old_path = exe_path.with_extension("exe.old");
+    if !old_path.is_file() {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        for _ in 0..5 {
+            if std::fs::remove_file(&old_path).is_ok() {
+                return;
+            }
+            std::thread::sleep(Duration::from_secs(2));
+        }
+        warn!("Failed to remove {old_path:?}");
+    });
+}
+
+/// 写入 `inner` 的同时用 CNG 计算 SHA-256，下载时无需再读回文件
+struct Sha256Writer<W: Write> {
+    inner: W,
+    hash: BCRYPT_HASH_HANDLE,
+}
+
+impl<W: Write> Sha256Writer<W> {
+    fn new(inner: W) -> Result<Self> {
+        let mut hash = BCRYPT_HASH_HANDLE::default();
+        unsafe { BCryptCreateHash(BCRYPT_SHA256_ALG_HANDLE, &mut hash, None, None, 0) }
+            .ok()
+            .map_err(|e| anyhow!("Failed to create a SHA-256 hash - {e}"))?;
+        Ok(Sha256Writer { inner, hash })
+    }
+
+    /// 返回小写十六进制的摘要，`inner` 随之关闭
+    fn finish(mut self) -> Result<String> {
+        self.inner.flush()?;
+        let mut digest = [0u8; 32];
+        unsafe { BCryptFinishHash(self.hash, &mut digest, 0) }
+            .ok()
+            .map_err(|e| anyhow!("Failed to finish the SHA-256 hash - {e}"))?;
+        Ok(digest.iter().map(|b| format!("{b:02x}")).collect())
+    }
+}
+
+impl<W: Write> Write for Sha256Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        unsafe { BCryptHashData(self.hash, &buf[..written], 0) }.ok()?;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for Sha256Writer<W> {
+    fn drop(&mut self) {
+        let _ = unsafe { BCryptDestroyHash(self.hash) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sha256_hex(data: &[u8]) -> String {
+        let mut writer = Sha256Writer::new(Vec::new()).unwrap();
+        writer.write_all(data).unwrap();
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn versions_are_parsed() {
+        assert_eq!(parse_version("v1.2.3"), Some((1, 2, 3)));
+        assert_eq!(parse_version("1.2"), Some((1, 2, 0)));
+        assert_eq!(parse_version("V2"), Some((2, 0, 0)));
+        assert_eq!(parse_version("1.2.3-beta.1"), Some((1, 2, 3)));
+        assert_eq!(parse_version("1.2.3+build"), Some((1, 2, 3)));
+        assert_eq!(parse_version("1.x"), None);
+        assert_eq!(parse_version("latest"), None);
+        assert!(parse_version("v0.10.0") > parse_version("v0.9.9"));
+    }
+
+    #[test]
+    fn sha256_matches_known_digests() {
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            sha256_hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn sha256_writer_passes_data_through() {
+        let mut inner = Vec::new();
+        let mut writer = Sha256Writer::new(&mut inner).unwrap();
+        writer.write_all(b"ab").unwrap();
+        writer.write_all(b"c").unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(inner, b"abc");
+    }
+
+    #[test]
+    fn sha256_hex_is_validated() {
+        assert!(is_sha256_hex(&sha256_hex(b"abc")));
+        assert!(!is_sha256_hex("abc"));
+        assert!(!is_sha256_hex(&"g".repeat(64)));
+    }
+}
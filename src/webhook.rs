@@ -0,0 +1,166 @@
+use crate::{
+    bluetooth::{
+        events::DeviceEvent,
+        info::{BluetoothInfo, crossed_milestone},
+    },
+    cli::format_address,
+    config::{Config, WebhookOptions},
+    notify::NotifyEvent,
+};
+
+use std::sync::Arc;
+
+use log::warn;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// 订阅设备事件，按事件调用 Webhook；离线模式下不发送
+pub fn watch_webhooks(config: Arc<Config>, mut device_events: broadcast::Receiver<DeviceEvent>) {
+    std::thread::spawn(move || {
+        loop {
+            let event = match device_events.blocking_recv() {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Webhooks skipped {skipped} device events");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+
+            if config.get_offline() {
+                continue;
+            }
+
+            for notify_event in notify_events(&config, &event) {
+                send_webhooks(&config.webhooks, notify_event, event.info());
+            }
+        }
+    });
+}
+
+/// 设备事件对应的通知事件，电量变化只在低于低电量阈值或越过电量阶梯时发送
+fn notify_events(config: &Config, event: &DeviceEvent) -> Vec<NotifyEvent> {
+    match event {
+        DeviceEvent::BatteryChanged { info, old_battery } => {
+            let low_battery = config.get_low_battery();
+            let mut events = Vec::new();
+            if *old_battery >= low_battery && info.battery < low_battery {
+                events.push(NotifyEvent::LowBattery);
+            }
+            if crossed_milestone(&config.get_milestone_steps(), *old_battery, info.battery)
+                .is_some()
+            {
+                events.push(NotifyEvent::Milestone);
+            }
+            events
+        }
+        DeviceEvent::ConnectionChanged { info } if info.status => vec![NotifyEvent::Reconnection],
+        DeviceEvent::ConnectionChanged { .. } => vec![NotifyEvent::Disconnection],
+        DeviceEvent::Added { .. } => vec![NotifyEvent::Added],
+        DeviceEvent::Removed { .. } => vec![NotifyEvent::Removed],
+        DeviceEvent::Renamed { .. } => vec![NotifyEvent::Renamed],
+    }
+}
+
+/// 根据事件过滤条件，在后台线程中调用所有匹配的 Webhook
+pub fn send_webhooks(webhooks: &[WebhookOptions], event: NotifyEvent, info: &BluetoothInfo) {
+    webhooks
+        .iter()
+        .filter(|webhook| webhook.events.is_empty() || webhook.events.contains(&event))
+        .for_each(|webhook| {
+            let url = webhook.url.clone();
+            let content_type = webhook
+                .content_type
+                .clone()
+                .unwrap_or_else(|| "application/json".to_owned());
+            let body = render_body(webhook.body.as_deref(), event, info);
+
+            std::thread::spawn(move || {
+                if let Err(e) = ureq::post(&url)
+                    .header("Content-Type", &content_type)
+                    .send(body)
+                {
+                    warn!("Failed to send webhook to {url}: {e}");
+                }
+            });
+        });
+}
+
+/// 模板支持 `{event}`、`{name}`、`{battery}`、`{address}`、`{status}`，未设置模板时发送 JSON
+fn render_body(template: Option<&str>, event: NotifyEvent, info: &BluetoothInfo) -> String {
+    let template = template.unwrap_or(
+        r#"{"event":"{event}","name":"{name}","battery":{battery},"address":"{address}","status":"{status}"}"#,
+    );
+
+    // 转义名称中的引号等字符，使其可安全嵌入 JSON 模板，去掉首尾各一个引号
+    let name = serde_json::to_string(&info.name)
+        .map(|s| s[1..s.len() - 1].to_owned())
+        .unwrap_or_else(|_| info.name.clone());
+    let status = if info.status {
+        "connected"
+    } else {
+        "disconnected"
+    };
+
+    // 单次扫描模板，替换后的值（如名称中的 `{status}`）不会再被替换
+    let mut body = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        body.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest.find('}').and_then(|end| {
+            let value = match &rest[1..end] {
+                "event" => event.as_str().to_owned(),
+                "name" => name.clone(),
+                "battery" => info.battery.to_string(),
+                "address" => format_address(info.address),
+                "status" => status.to_owned(),
+                _ => return None,
+            };
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                body.push_str(&value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                body.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    body.push_str(rest);
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bluetooth::provider::mock_device;
+
+    fn device(name: &str) -> BluetoothInfo {
+        mock_device(name, 0xAABB_CCDD_EEFF, 15)
+    }
+
+    #[test]
+    fn default_body_is_valid_json() {
+        let name = r#""Quoted" Buds {status}"#;
+        let body = render_body(None, NotifyEvent::LowBattery, &device(name));
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["event"], "low_battery");
+        assert_eq!(json["name"], name);
+        assert_eq!(json["battery"], 15);
+        assert_eq!(json["address"], "AA:BB:CC:DD:EE:FF");
+        assert_eq!(json["status"], "connected");
+    }
+
+    #[test]
+    fn custom_template_keeps_unknown_braces() {
+        let body = render_body(
+            Some("{name} at {battery}% {unknown} {{status}} {"),
+            NotifyEvent::Disconnection,
+            &device("Buds"),
+        );
+        assert_eq!(body, "Buds at 15% {unknown} {connected} {");
+    }
+}